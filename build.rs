@@ -3,18 +3,26 @@ use std::path::Path;
 
 fn main() {
     let out_dir = std::env::var("OUT_DIR").unwrap();
-    let target_dir = Path::new(&out_dir).parent().unwrap().parent().unwrap().parent().unwrap();
+    let target_dir = Path::new(&out_dir)
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap();
 
-    // Copy jcd_function.sh to target/release (or target/debug)
-    let src = "src/jcd_function.sh";
-    let dst = target_dir.join("jcd_function.sh");
+    // Copy the shell wrappers to target/release (or target/debug)
+    for name in ["jcd_function.sh", "jcd_function.ps1"] {
+        let src = format!("src/{}", name);
+        let dst = target_dir.join(name);
 
-    if let Err(e) = fs::copy(src, &dst) {
-        println!("cargo:warning=Failed to copy {}: {}", src, e);
-    } else {
-        println!("cargo:warning=Copied {} to {}", src, dst.display());
-    }
+        if let Err(e) = fs::copy(&src, &dst) {
+            println!("cargo:warning=Failed to copy {}: {}", src, e);
+        } else {
+            println!("cargo:warning=Copied {} to {}", src, dst.display());
+        }
 
-    // Tell cargo to rerun this script if jcd_function.sh changes
-    println!("cargo:rerun-if-changed=src/jcd_function.sh");
-}
\ No newline at end of file
+        // Tell cargo to rerun this script if the wrapper changes
+        println!("cargo:rerun-if-changed={}", src);
+    }
+}
@@ -0,0 +1,322 @@
+//! The `jcd bench` subcommand: times one pattern, searched from the current
+//! directory, by walking the tree and through the index, to show which
+//! settings pay off on a large filesystem.
+//!
+//! Cold runs start a new session for every search, loading the ignore files,
+//! history and index as each `jcd` invocation does; warm runs reuse one. The
+//! operating system's file cache cannot be dropped here, so after the first
+//! search every mode finds it warm. Parallel runs search from several threads
+//! at once, as several shells or a `jcd serve` client do.
+
+use jcd::{
+    config::Config, filesystem::RealFileSystem, history::History, ignore::load_ignore_patterns,
+    index::IndexSource, pin, prepare_search, ranking, selections::Selections, SearchFlags,
+    SearchSession, EXIT_USAGE,
+};
+use std::{
+    env,
+    io::{self, Write},
+    path::Path,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+pub const USAGE: &str = "Usage: jcd bench all|walk|index <pattern> [--runs <n>] [--threads <n>]";
+
+const DEFAULT_RUNS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    Walk,  // Walk the tree for every search
+    Index, // Answer downward searches from the daemon or index file
+}
+
+struct Options {
+    strategies: &'static [Strategy],
+    pattern: String,
+    runs: usize,
+    threads: usize,
+}
+
+/// The timings of one way of running the searches
+struct Row {
+    strategy: &'static str,
+    mode: &'static str,
+    threads: usize,
+    samples: Vec<Duration>, // Sorted
+    matches: usize,
+    directories: usize, // Listed by one search
+}
+
+fn count(option: &str, value: Option<&String>) -> Result<usize, String> {
+    match value.map(|v| v.parse::<usize>()) {
+        Some(Ok(n)) if n > 0 => Ok(n),
+        Some(_) => Err(format!("{} must be a positive number", option)),
+        None => Err(USAGE.to_string()),
+    }
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let strategies: &'static [Strategy] = match args.first().map(String::as_str) {
+        Some("all") => &[Strategy::Walk, Strategy::Index],
+        Some("walk") => &[Strategy::Walk],
+        Some("index") => &[Strategy::Index],
+        _ => return Err(USAGE.to_string()),
+    };
+    let mut options = Options {
+        strategies,
+        pattern: String::new(),
+        runs: DEFAULT_RUNS,
+        threads: thread::available_parallelism().map_or(1, usize::from),
+    };
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--runs" => options.runs = count("--runs", args.get(i + 1))?,
+            "--threads" => options.threads = count("--threads", args.get(i + 1))?,
+            pattern if options.pattern.is_empty() && !pattern.starts_with("--") => {
+                options.pattern = pattern.to_string();
+                i += 1;
+                continue;
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+        i += 2;
+    }
+    if options.pattern.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(options)
+}
+
+/// A session for `strategy`, or None when there is no index to use
+fn open<'a>(
+    flags: &'a SearchFlags,
+    settings: &'a Config,
+    strategy: Strategy,
+) -> Option<SearchSession<'a>> {
+    match strategy {
+        Strategy::Walk => Some(SearchSession {
+            flags,
+            settings,
+            rank_weights: ranking::RankWeights::configured(
+                settings,
+                flags.recency_boost,
+                flags.shortest_first.unwrap_or(settings.shortest_first),
+            ),
+            ignore_patterns: load_ignore_patterns(),
+            index: None,
+            history: History::load().with_decay(settings.decay),
+            selections: Selections::load_if(settings.learn),
+            pins: pin::load_all(&settings.pins),
+            fs: Arc::new(RealFileSystem),
+        }),
+        Strategy::Index => {
+            let session = SearchSession::new(flags, settings, None);
+            session.index.is_some().then_some(session)
+        }
+    }
+}
+
+/// Run one search; returns the number of matches and of directories listed
+fn search(session: &SearchSession, pattern: &str, dir: &Path) -> Result<(usize, usize), String> {
+    let search = prepare_search(pattern, session.flags, dir, session.settings)?;
+    let stats = Arc::clone(&search.config.stats);
+    let matches = session.find(search);
+    Ok((matches.len(), stats.report().directories))
+}
+
+fn bench(
+    flags: &SearchFlags,
+    settings: &Config,
+    strategy: Strategy,
+    options: &Options,
+    dir: &Path,
+) -> Result<Option<Vec<Row>>, String> {
+    let Some(session) = open(flags, settings, strategy) else {
+        return Ok(None);
+    };
+    let name = match (strategy, &session.index) {
+        (Strategy::Index, Some(index)) if matches!(**index, IndexSource::Daemon(_)) => "daemon",
+        (Strategy::Index, _) => "index",
+        (Strategy::Walk, _) => "walk",
+    };
+    let row = |mode, threads, mut samples: Vec<Duration>, (matches, directories)| {
+        samples.sort();
+        Row {
+            strategy: name,
+            mode,
+            threads,
+            samples,
+            matches,
+            directories,
+        }
+    };
+
+    // Cold: the session is part of what each search costs
+    let mut cold = Vec::with_capacity(options.runs);
+    let mut found = (0, 0);
+    for _ in 0..options.runs {
+        let started = Instant::now();
+        let session = open(flags, settings, strategy).ok_or("The index went away")?;
+        found = search(&session, &options.pattern, dir)?;
+        cold.push(started.elapsed());
+    }
+    let mut rows = vec![row("cold", 1, cold, found)];
+
+    // Warm: one session, with a search beforehand that is not counted
+    let timed = |runs| -> Result<Vec<Duration>, String> {
+        let mut samples = Vec::with_capacity(runs);
+        for _ in 0..runs {
+            let started = Instant::now();
+            search(&session, &options.pattern, dir)?;
+            samples.push(started.elapsed());
+        }
+        Ok(samples)
+    };
+    let found = search(&session, &options.pattern, dir)?;
+    rows.push(row("warm", 1, timed(options.runs)?, found));
+
+    if options.threads > 1 {
+        let results: Vec<Result<Vec<Duration>, String>> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..options.threads)
+                .map(|_| scope.spawn(|| timed(options.runs)))
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|_| Err("A search panicked".to_string()))
+                })
+                .collect()
+        });
+        let mut samples = Vec::with_capacity(options.runs * options.threads);
+        for result in results {
+            samples.extend(result?);
+        }
+        rows.push(row("warm", options.threads, samples, found));
+    }
+    Ok(Some(rows))
+}
+
+/// The sample below which `percent` of the sorted samples lie (nearest rank)
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+fn millis(d: Duration) -> String {
+    format!("{:.2} ms", d.as_secs_f64() * 1000.0)
+}
+
+fn run(args: &[String]) -> Result<String, String> {
+    let options = parse_options(args)?;
+    let dir = env::current_dir().map_err(|e| format!("Cannot get current directory: {}", e))?;
+    let settings = Config::load();
+    // The busy indicator would draw on stderr during slow searches
+    let flags = SearchFlags {
+        quiet: true,
+        ..SearchFlags::default()
+    };
+
+    let mut rows = Vec::new();
+    let mut notes = Vec::new();
+    for &strategy in options.strategies {
+        match bench(&flags, &settings, strategy, &options, &dir)? {
+            Some(found) => rows.extend(found),
+            None if options.strategies.len() == 1 => {
+                return Err(
+                    "No index to benchmark; run 'jcd index build' or 'jcd daemon start'".to_string(),
+                )
+            }
+            None => notes.push(
+                "index: skipped, no daemon is running and there is no fresh index (jcd index build)"
+                    .to_string(),
+            ),
+        }
+    }
+
+    // Compare the warm medians when both strategies ran
+    let median = |strategy: &str| {
+        rows.iter()
+            .find(|r| r.strategy == strategy && r.mode == "warm" && r.threads == 1)
+            .map(|r| percentile(&r.samples, 50))
+    };
+    let walk = median("walk");
+    let index = median("index")
+        .map(|d| ("index", d))
+        .or(median("daemon").map(|d| ("daemon", d)));
+    if let (Some(walk), Some((name, index))) = (walk, index) {
+        let (fast, slow) = if index <= walk {
+            (index, walk)
+        } else {
+            (walk, index)
+        };
+        notes.push(format!(
+            "Warm median: {} {} against walk {}, {:.1}x {}",
+            name,
+            millis(index),
+            millis(walk),
+            slow.as_secs_f64() / fast.as_secs_f64().max(f64::EPSILON),
+            if index <= walk { "faster" } else { "slower" }
+        ));
+    }
+
+    let mut report = format!(
+        "Benchmark of '{}' from {}, {} runs per thread\n\n{:<8} {:<5} {:>7} {:>10} {:>10} {:>10} {:>10} {:>8} {:>8}",
+        options.pattern,
+        dir.display(),
+        options.runs,
+        "strategy",
+        "mode",
+        "threads",
+        "p50",
+        "p90",
+        "p99",
+        "max",
+        "matches",
+        "dirs"
+    );
+    for row in &rows {
+        report.push_str(&format!(
+            "\n{:<8} {:<5} {:>7} {:>10} {:>10} {:>10} {:>10} {:>8} {:>8}",
+            row.strategy,
+            row.mode,
+            row.threads,
+            millis(percentile(&row.samples, 50)),
+            millis(percentile(&row.samples, 90)),
+            millis(percentile(&row.samples, 99)),
+            millis(percentile(&row.samples, 100)),
+            row.matches,
+            row.directories
+        ));
+    }
+    if !notes.is_empty() {
+        report.push('\n');
+        for note in notes {
+            report.push('\n');
+            report.push_str(&note);
+        }
+    }
+    Ok(report)
+}
+
+pub fn run_command(args: &[String]) -> i32 {
+    match run(args) {
+        Ok(report) => {
+            // A reader such as `head` may close the pipe before the end
+            let _ = writeln!(io::stdout(), "{}", report);
+            0
+        }
+        Err(e) if e == USAGE => {
+            eprintln!("{}", USAGE);
+            EXIT_USAGE
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Named bookmarks and the `jcd bookmark` subcommand. A search term starting
+//! with `@` is looked up among the bookmark names before anything else; when a
+//! bookmark matches, its directory is the answer and the filesystem is not
+//! searched. Only a term that names no bookmark falls back to an ordinary search
+//! for the literal text, so directories such as `node_modules/@types` still work.
+
+use crate::{
+    matcher::{NameMatcher, PatternSyntax},
+    platform, DirectoryMatch, MatchQuality, EXIT_USAGE,
+};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+const BOOKMARKS_HEADER: &str = "# jcd bookmarks v1";
+
+pub const USAGE: &str = "Usage: jcd bookmark add <name> [dir] | rm <name> | list";
+
+/// Location of the bookmark file, following the XDG Base Directory Specification
+pub fn bookmarks_file_path() -> PathBuf {
+    platform::data_home().join("jcd").join("bookmarks")
+}
+
+/// Bookmark names mapped to their directories
+#[derive(Debug, Default)]
+pub struct Bookmarks {
+    entries: BTreeMap<String, PathBuf>,
+}
+
+/// A bookmark name without its optional leading '@'
+fn bare_name(name: &str) -> &str {
+    name.strip_prefix('@').unwrap_or(name)
+}
+
+/// Names are single words so that `@name` can be typed without quoting
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Bookmark name must not be empty".to_string());
+    }
+    if name.contains(|c: char| c.is_whitespace() || c == '/' || c == '@') {
+        return Err(format!(
+            "Invalid bookmark name '{}': use letters, digits, '-', '_' or '.'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+impl Bookmarks {
+    /// Parse the bookmark file; malformed lines are skipped
+    pub fn parse(content: &str) -> Self {
+        let entries = content
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(name, path)| (name.to_string(), platform::str_to_path(path)))
+            .collect();
+        Self { entries }
+    }
+
+    /// Load the bookmarks, returning none if the file does not exist
+    pub fn load() -> Self {
+        let path = bookmarks_file_path();
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                let bookmarks = Self::parse(&content);
+                debug!(
+                    "Loaded {} bookmarks from {}",
+                    bookmarks.entries.len(),
+                    path.display()
+                );
+                bookmarks
+            }
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Write the file atomically by replacing it with a fully written temp file
+    pub fn save(&self) -> io::Result<()> {
+        let path = bookmarks_file_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut content = String::from(BOOKMARKS_HEADER);
+        content.push('\n');
+        for (name, dir) in &self.entries {
+            content.push_str(&format!("{}\t{}\n", name, platform::path_to_str(dir)));
+        }
+
+        let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        {
+            let mut file = fs::File::create(&tmp_path)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, &path)
+    }
+
+    /// Every bookmark name with its directory, in name order
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &PathBuf)> {
+        self.entries.iter()
+    }
+
+    /// Existing bookmarked directories whose names match `pattern` (the term
+    /// after '@'): exact names first, then prefixes, then any other match
+    pub fn matching(
+        &self,
+        pattern: &str,
+        case_sensitive: bool,
+        syntax: PatternSyntax,
+    ) -> Vec<DirectoryMatch> {
+        let Ok(matcher) = NameMatcher::new(pattern, case_sensitive, syntax) else {
+            return Vec::new();
+        };
+        let mut found: Vec<_> = self
+            .entries
+            .iter()
+            .filter_map(|(name, dir)| Some((matcher.classify(name)?, dir)))
+            .filter(|(_, dir)| dir.is_dir())
+            .collect();
+        // Stable, so names stay in alphabetical order within each tier
+        found.sort_by_key(|&(kind, _)| kind);
+
+        found
+            .into_iter()
+            .map(|(_, dir)| DirectoryMatch::new(dir.clone(), 0, MatchQuality::Bookmark))
+            .collect()
+    }
+}
+
+/// `jcd bookmark add`: bookmark `dir` (default: the current directory)
+pub fn add(name: &str, dir: Option<&str>) -> Result<String, String> {
+    let name = bare_name(name);
+    validate_name(name)?;
+    let cwd = env::current_dir().map_err(|e| format!("Cannot get current directory: {}", e))?;
+    let dir = match dir {
+        Some(dir) => cwd.join(crate::query::expand_home(dir)),
+        None => cwd,
+    };
+    let dir = fs::canonicalize(&dir)
+        .ok()
+        .filter(|d| d.is_dir())
+        .ok_or_else(|| format!("Not a directory: {}", dir.display()))?;
+
+    let mut bookmarks = Bookmarks::load();
+    let verb = if bookmarks.entries.contains_key(name) {
+        "Updated"
+    } else {
+        "Added"
+    };
+    bookmarks.entries.insert(name.to_string(), dir.clone());
+    save(&bookmarks)?;
+    Ok(format!("{} bookmark @{} -> {}", verb, name, dir.display()))
+}
+
+/// `jcd bookmark rm`: forget a bookmark
+pub fn remove(name: &str) -> Result<String, String> {
+    let name = bare_name(name);
+    let mut bookmarks = Bookmarks::load();
+    if bookmarks.entries.remove(name).is_none() {
+        return Err(format!("No bookmark named @{}", name));
+    }
+    save(&bookmarks)?;
+    Ok(format!("Removed bookmark @{}", name))
+}
+
+fn save(bookmarks: &Bookmarks) -> Result<(), String> {
+    bookmarks
+        .save()
+        .map_err(|e| format!("Cannot write {}: {}", bookmarks_file_path().display(), e))
+}
+
+/// `jcd bookmark list`: every bookmark, marking those whose directory is gone
+fn list() -> i32 {
+    let bookmarks = Bookmarks::load();
+    if bookmarks.entries.is_empty() {
+        println!("No bookmarks");
+        return 0;
+    }
+    let width = bookmarks.entries.keys().map(String::len).max().unwrap_or(0);
+    for (name, dir) in &bookmarks.entries {
+        let missing = if Path::new(dir).is_dir() {
+            ""
+        } else {
+            "  (missing)"
+        };
+        println!("@{:<width$}  {}{}", name, dir.display(), missing);
+    }
+    0
+}
+
+pub fn run_command(args: &[String]) -> i32 {
+    let result = match (args.first().map(String::as_str), args.len()) {
+        (Some("list"), 1) => return list(),
+        (Some("add"), 2) => add(&args[1], None),
+        (Some("add"), 3) => add(&args[1], Some(&args[2])),
+        (Some("rm"), 2) => remove(&args[1]),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(message) => {
+            println!("{}", message);
+            0
+        }
+        Err(e) if e == USAGE => {
+            eprintln!("{}", USAGE);
+            EXIT_USAGE
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
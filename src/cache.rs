@@ -0,0 +1,134 @@
+//! Result cache for repeated requests: tab completion asks for match 0, then
+//! 1, 2, ... with otherwise identical arguments, and each of those calls would
+//! walk the tree again. The ranked list of the last search is kept in the
+//! cache directory and reused while it is fresh: same directory, arguments,
+//! settings and `JCD_*`/`CDPATH` environment, the directory's mtime unchanged,
+//! and the entry younger than `search.cache_ttl_secs`.
+
+use crate::{
+    config::Config, history::now_secs, index::mtime_nanos, platform, DirectoryMatch, MatchQuality,
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    env, fs,
+    hash::{Hash, Hasher},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+const CACHE_HEADER: &str = "# jcd results v1";
+
+/// Location of the result cache, following the XDG Base Directory Specification
+pub fn cache_file_path() -> PathBuf {
+    platform::cache_home().join("jcd").join("results.db")
+}
+
+/// Identifies a search run in `dir` with `args` (everything but the match index)
+pub fn search_key(dir: &Path, args: &[String], settings: &Config) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    dir.hash(&mut hasher);
+    args.hash(&mut hasher);
+    format!("{:?}", settings).hash(&mut hasher);
+
+    // Variables such as JCD_RECENT or CDPATH change results without appearing in the settings
+    let mut vars: Vec<(String, String)> = env::vars()
+        .filter(|(name, _)| name.starts_with("JCD_") || name == "CDPATH")
+        .collect();
+    vars.sort();
+    vars.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Parse the cache file; None unless it holds an entry for `key` about `dir`
+/// that is at most `ttl_secs` old
+fn parse(content: &str, key: u64, dir: &Path, ttl_secs: u64) -> Option<Vec<DirectoryMatch>> {
+    let mut lines = content.lines();
+    if lines.next()? != CACHE_HEADER {
+        return None;
+    }
+
+    let mut field = |name: &str| {
+        let line = lines.next()?;
+        let (found, value) = line.split_once('\t')?;
+        (found == name)
+            .then_some(value)
+            .and_then(|v| v.parse::<u64>().ok())
+    };
+    let (stored_key, created, mtime) = (field("key")?, field("created")?, field("mtime")?);
+    if stored_key != key
+        || now_secs().saturating_sub(created) >= ttl_secs
+        || mtime_nanos(dir) != Some(mtime)
+    {
+        return None;
+    }
+
+    let mut matches = Vec::new();
+    for line in lines {
+        let mut fields = line.splitn(3, '\t');
+        let (Some(quality), Some(depth), Some(path)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return None;
+        };
+        matches.push(DirectoryMatch {
+            path: platform::str_to_path(path),
+            depth_from_current: depth.parse().ok()?,
+            match_quality: MatchQuality::from_name(quality)?,
+            last_access: None, // only needed for ranking, which is already done
+            root: 0,
+            project_root: false,
+            same_repo: false,
+        });
+    }
+    Some(matches)
+}
+
+/// The matches cached for `key`, if they are still fresh
+pub fn load(key: u64, dir: &Path, ttl_secs: u64) -> Option<Vec<DirectoryMatch>> {
+    let content = fs::read_to_string(cache_file_path()).ok()?;
+    let matches = parse(&content, key, dir, ttl_secs);
+    match &matches {
+        Some(matches) => debug!("Reusing {} cached matches", matches.len()),
+        None => debug!("Result cache is stale or for another search"),
+    }
+    matches
+}
+
+/// Replace the cache with the ranked `matches` of the search identified by `key`
+pub fn save(key: u64, dir: &Path, matches: &[DirectoryMatch]) -> io::Result<()> {
+    let Some(mtime) = mtime_nanos(dir) else {
+        return Ok(());
+    };
+    let mut content = format!(
+        "{}\nkey\t{}\ncreated\t{}\nmtime\t{}\n",
+        CACHE_HEADER,
+        key,
+        now_secs(),
+        mtime
+    );
+    for m in matches {
+        let path = platform::path_to_str(&m.path);
+        // Paths are stored one per line, so a name containing a newline cannot be kept
+        if path.contains('\n') {
+            return Ok(());
+        }
+        content.push_str(&format!(
+            "{}\t{}\t{}\n",
+            m.match_quality.name(),
+            m.depth_from_current,
+            path
+        ));
+    }
+
+    let path = cache_file_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension(format!("db.{}.tmp", std::process::id()));
+    {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(content.as_bytes())?;
+    }
+    fs::rename(&tmp_path, &path)
+}
@@ -0,0 +1,370 @@
+//! jcd's command line described once: the search flags and the management
+//! subcommands, with their help text. Shell completion and the man page are
+//! generated from these tables; `main` and the subcommand modules parse them.
+
+use crate::{bench, completions, doctor, import, init, serve};
+use jcd::{bookmark, config, daemon, history, ignore, index, pin};
+
+/// A search flag: its spellings, the value it takes and what it does
+pub struct Flag {
+    pub names: &'static [&'static str],
+    pub value: Option<&'static str>,
+    pub help: &'static str,
+}
+
+/// A management subcommand: its verbs, usage line(s) and what it does
+pub struct Subcommand {
+    pub name: &'static str,
+    pub verbs: &'static [&'static str],
+    pub usage: &'static str,
+    pub help: &'static str,
+}
+
+const fn flag(names: &'static [&'static str], help: &'static str) -> Flag {
+    Flag {
+        names,
+        value: None,
+        help,
+    }
+}
+
+const fn option(names: &'static [&'static str], value: &'static str, help: &'static str) -> Flag {
+    Flag {
+        names,
+        value: Some(value),
+        help,
+    }
+}
+
+/// Search flags, in the order they are documented
+pub const FLAGS: &[Flag] = &[
+    flag(
+        &["-i"],
+        "Case-insensitive matching (default: case-sensitive)",
+    ),
+    flag(
+        &["--smart-case"],
+        "Case-insensitive unless the pattern has an uppercase letter",
+    ),
+    flag(
+        &["--no-smart-case"],
+        "Plain case-sensitive matching (overrides search.smart_case)",
+    ),
+    flag(&["-x"], "Bypass ignore patterns (search all directories)"),
+    option(
+        &["--exclude"],
+        "glob",
+        "Also skip directories named like <glob> for this search (repeatable)",
+    ),
+    flag(
+        &["-E", "--regex"],
+        "Treat the pattern as a regular expression",
+    ),
+    flag(
+        &["--gitignore"],
+        "Skip directories ignored by git when searching down",
+    ),
+    flag(
+        &["--no-gitignore"],
+        "Search git-ignored directories (overrides search.gitignore)",
+    ),
+    flag(
+        &["--follow-symlinks"],
+        "Descend into symlinked directories when searching down",
+    ),
+    flag(
+        &["--same-filesystem"],
+        "Do not descend into other filesystems when searching down",
+    ),
+    flag(
+        &["--cross-filesystems"],
+        "Descend into other filesystems (overrides search.same_filesystem)",
+    ),
+    flag(
+        &["--typos"],
+        "Also match names a typo or two away from the pattern, below real matches",
+    ),
+    flag(
+        &["--no-typos"],
+        "Match the pattern as written (overrides search.typos)",
+    ),
+    flag(
+        &["--ignore-accents"],
+        "Match letters whatever their accents (resume finds résumé)",
+    ),
+    flag(
+        &["--match-accents"],
+        "Accented letters match only themselves (overrides search.ignore_accents)",
+    ),
+    flag(
+        &["--full-path"],
+        "Match the pattern against the path below the search directory, not just the name",
+    ),
+    flag(
+        &["--name-only"],
+        "Match directory names only (overrides search.full_path)",
+    ),
+    flag(
+        &["--siblings"],
+        "Also match directories next to the current one, before deeper ones",
+    ),
+    flag(
+        &["--no-siblings"],
+        "Leave directories next to the current one to ../ patterns (overrides search.siblings)",
+    ),
+    flag(
+        &["--cousins"],
+        "Like --siblings, and also search a little below each nearby ancestor",
+    ),
+    flag(
+        &["--no-cousins"],
+        "Search no further below ancestors than --siblings does (overrides search.cousins)",
+    ),
+    flag(
+        &["--up"],
+        "Search only the parent directories of the search directory",
+    ),
+    flag(
+        &["--down"],
+        "Search only below the search directory, skipping its parents",
+    ),
+    option(
+        &["--timeout"],
+        "ms",
+        "Time budget for each search (0 = unlimited, overrides search.timeout_ms)",
+    ),
+    option(
+        &["--max-matches"],
+        "n",
+        "Report at most n matches (overrides search.max_matches)",
+    ),
+    option(
+        &["--tier-limits"],
+        "list",
+        "Keep at most n matches of a quality, e.g. partial_down=5 (overrides search.tier_limits)",
+    ),
+    option(
+        &["--min-depth"],
+        "n",
+        "Skip matches fewer than n levels below the search directory (search.max_depth is the deepest)",
+    ),
+    option(
+        &["--from"],
+        "dir",
+        "Search from dir instead of the current directory",
+    ),
+    flag(
+        &["--recent"],
+        "Prefer recently used directories within the same priority level",
+    ),
+    flag(
+        &["--shortest-first"],
+        "Rank matches of the same kind by fewest path components, up or down (overrides ranking.shortest_first)",
+    ),
+    flag(
+        &["--record"],
+        "Record the printed match in the history database",
+    ),
+    option(
+        &["--chosen-for"],
+        "pattern",
+        "Remember the printed match as the one chosen for pattern; used by tab completion",
+    ),
+    option(
+        &["--add"],
+        "dir",
+        "Record a visit to dir (for shell prompt hooks)",
+    ),
+    flag(
+        &["--stdin"],
+        "Search for each line of standard input and print its best match, one line each",
+    ),
+    flag(
+        &["--live"],
+        "Stay running and list the matches of each query read from standard input",
+    ),
+    flag(&["--list"], "Print every match, best first, one per line"),
+    flag(
+        &["--complete"],
+        "Like --list --quiet for shell completion; no candidates is not an error",
+    ),
+    flag(
+        &["--fzf"],
+        "Choose among all matches with fzf (or $JCD_PICKER) and print the choice",
+    ),
+    flag(
+        &["--json"],
+        "Print matches as JSON objects (an array with --list)",
+    ),
+    flag(
+        &["-0", "--print0"],
+        "End each printed path with a NUL byte instead of a newline",
+    ),
+    flag(
+        &["--tsv"],
+        "Print path, display form, match quality and depth, separated by tabs, for each match",
+    ),
+    flag(
+        &["--stats"],
+        "Report directories visited, entries examined, ignore skips, limits hit and time taken on stderr",
+    ),
+    flag(
+        &["--report-errors"],
+        "List the directories that could not be read (permission denied, symlink loops) on stderr",
+    ),
+    flag(
+        &["--explain"],
+        "Print every match with its tier, depth, usage points, score and why it ranks there",
+    ),
+    option(
+        &["--rank"],
+        "weights",
+        "Override ranking weights for this search (e.g. exact=10,depth=-2)",
+    ),
+    option(
+        &["--color"],
+        "when",
+        "Highlight matches and show the busy indicator: auto (on terminals, unless NO_COLOR is set), always or never",
+    ),
+    flag(
+        &["--no-progress"],
+        "Search without the busy indicator, still reporting errors",
+    ),
+    flag(
+        &["--quiet"],
+        "Search without the busy indicator and without error messages",
+    ),
+    flag(
+        &["--selftest"],
+        "Run built-in search scenarios and report pass/fail",
+    ),
+    flag(&["--manpage"], "Print this manual page in roff format"),
+];
+
+/// Management subcommands, dispatched on their name and verb
+pub const SUBCOMMANDS: &[Subcommand] = &[
+    Subcommand {
+        name: "index",
+        verbs: &["build", "status", "clear"],
+        usage: index::USAGE,
+        help: "Record the directory trees under the configured roots, so downward searches need not walk them",
+    },
+    Subcommand {
+        name: "daemon",
+        verbs: &["start", "stop", "status", "run"],
+        usage: daemon::USAGE,
+        help: "Keep the directory index in memory and up to date, answering searches over a socket",
+    },
+    Subcommand {
+        name: "ignore",
+        verbs: &["add", "remove", "list", "test"],
+        usage: ignore::USAGE,
+        help: "Manage the patterns of directories that searches skip",
+    },
+    Subcommand {
+        name: "bookmark",
+        verbs: &["add", "rm", "list"],
+        usage: bookmark::USAGE,
+        help: "Manage named directories, reached with jcd @name",
+    },
+    Subcommand {
+        name: "pin",
+        verbs: &["add", "rm", "list"],
+        usage: pin::USAGE,
+        help: "Manage pinned directories, which come first whenever they match",
+    },
+    Subcommand {
+        name: "import",
+        verbs: &["zoxide", "autojump", "z"],
+        usage: import::USAGE,
+        help: "Seed the history database from another directory jumper",
+    },
+    Subcommand {
+        name: "history",
+        verbs: &["list", "prune"],
+        usage: history::USAGE,
+        help: "Show the recorded directories, or forget those that are gone",
+    },
+    Subcommand {
+        name: "init",
+        verbs: &["bash", "zsh"],
+        usage: init::USAGE,
+        help: "Print the shell function and its completion, ready for eval",
+    },
+    Subcommand {
+        name: "completions",
+        verbs: &["bash", "zsh"],
+        usage: completions::USAGE,
+        help: "Print completion of jcd's own flags, subcommands and bookmark names",
+    },
+    Subcommand {
+        name: "serve",
+        verbs: &["--stdio"],
+        usage: serve::USAGE,
+        help: "Answer JSON-RPC requests for searches, history and bookmarks on standard input",
+    },
+    Subcommand {
+        name: "config",
+        verbs: &["show", "get", "set", "path"],
+        usage: config::USAGE,
+        help: "Show the settings in effect and where each came from, or set one in the user's config.toml",
+    },
+    Subcommand {
+        name: "doctor",
+        verbs: &["all", "shell", "config", "ignore", "history", "index"],
+        usage: doctor::USAGE,
+        help: "Check the shell function, config, ignore files, history and index, and suggest fixes",
+    },
+    Subcommand {
+        name: "bench",
+        verbs: &["all", "walk", "index"],
+        usage: bench::USAGE,
+        help: "Time a pattern walking the tree and through the index, cold, warm and from several threads",
+    },
+];
+
+/// Forms of the directory pattern, with an example of each
+pub const PATTERNS: &[(&str, &str)] = &[
+    (
+        "jcd <substring>",
+        "Navigate to directory matching substring",
+    ),
+    (
+        "jcd .../<pattern>",
+        "Search from two levels up; each further dot is one more level",
+    ),
+    ("jcd <absolute_path>", "Navigate to absolute path"),
+    (
+        "jcd '~[user]/path'",
+        "Navigate to a path below a home directory",
+    ),
+    ("jcd <path/pattern>", "Navigate using path-like patterns"),
+    (
+        "jcd '<path>/**/<pattern>'",
+        "Match the pattern any number of levels below the path",
+    ),
+    (
+        "jcd <word>... <pattern>",
+        "Match the pattern below directories matching each word, in order",
+    ),
+    (
+        "jcd '<glob>'",
+        "Use * and ? wildcards (quote them from the shell)",
+    ),
+    (
+        "jcd '^<text>$'",
+        "Tie the pattern to the start (^) or end ($) of the name",
+    ),
+    (
+        "jcd '<pattern>|<pattern>'",
+        "Match names matching either pattern, at the best tier of the two",
+    ),
+    (
+        "jcd -E '<regex>'",
+        "Match directory names against a regular expression",
+    ),
+    (
+        "jcd @<bookmark>",
+        "Jump to a named bookmark (see jcd bookmark)",
+    ),
+];
@@ -0,0 +1,85 @@
+//! The `jcd completions` subcommand: prints completion for jcd's own flags,
+//! subcommands and bookmark names, ready for `eval`. It completes the command
+//! line of the binary, while `jcd init` sets up the directory-jumping function;
+//! directory patterns are still handed to the function's completion.
+
+use crate::{
+    cli::{FLAGS, SUBCOMMANDS},
+    init::{binary_path, shell_quote, valid_command_name},
+};
+use jcd::{config::config_keys, ranking::WEIGHT_NAMES, EXIT_USAGE};
+
+pub const USAGE: &str = "Usage: jcd completions bash|zsh [--cmd <name>]";
+
+const BASH_SCRIPT: &str = include_str!("jcd_completions.bash");
+const ZSH_SCRIPT: &str = include_str!("jcd_completions.zsh");
+
+/// One `case` branch per subcommand, offering its verbs
+fn verb_cases(shell: &str) -> String {
+    SUBCOMMANDS
+        .iter()
+        .map(|subcommand| {
+            let verbs = subcommand.verbs.join(" ");
+            let value = if shell == "bash" {
+                format!("words=\"{}\"", verbs)
+            } else {
+                format!("opts=({})", verbs)
+            };
+            format!("                        {}) {} ;;", subcommand.name, value)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn script(shell: &str, command: &str) -> Result<String, String> {
+    let template = match shell {
+        "bash" => BASH_SCRIPT,
+        "zsh" => ZSH_SCRIPT,
+        _ => return Err(USAGE.to_string()),
+    };
+    let flags: Vec<&str> = FLAGS.iter().flat_map(|flag| flag.names).copied().collect();
+    let names: Vec<&str> = SUBCOMMANDS
+        .iter()
+        .map(|subcommand| subcommand.name)
+        .collect();
+    let weights: Vec<String> = WEIGHT_NAMES.iter().map(|w| format!("{}=", w)).collect();
+    Ok(template
+        .replace("@JCD_BINARY@", &shell_quote(&binary_path()))
+        .replace("@JCD_CMD@", command)
+        .replace("@JCD_FLAGS@", &flags.join(" "))
+        .replace("@JCD_WEIGHTS@", &weights.join(" "))
+        .replace(
+            "@JCD_CONFIG_KEYS@",
+            &config_keys().collect::<Vec<_>>().join(" "),
+        )
+        .replace("@JCD_SUBCOMMANDS@", &names.join(" "))
+        .replace("@JCD_SUBCOMMAND_PATTERN@", &names.join("|"))
+        .replace("@JCD_VERBS@", &verb_cases(shell)))
+}
+
+pub fn run_command(args: &[String]) -> i32 {
+    let result = match args {
+        [shell] => script(shell, "jcd"),
+        [shell, flag, command] if flag == "--cmd" && valid_command_name(command) => {
+            script(shell, command)
+        }
+        [_, flag, command] if flag == "--cmd" => {
+            Err(format!("'{}' cannot be used as a command name", command))
+        }
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(script) => {
+            print!("{}", script);
+            0
+        }
+        Err(e) if e == USAGE => {
+            eprintln!("{}", USAGE);
+            EXIT_USAGE
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
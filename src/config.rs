@@ -0,0 +1,727 @@
+//! Settings from config.toml: the system-wide file, then the user's (or the
+//! one `JCD_CONFIG` names). Every setting has a default, so no file is needed.
+//! `jcd config` shows the settings in effect and where each came from, and
+//! edits single keys of the user's file.
+
+use crate::{
+    history::{Decay, DecayModel, PathPattern},
+    output::Style,
+    platform,
+    query::expand_home,
+    ranking::{PathBoost, RankWeights, TieBreak, TierLimits, FRECENCY_WEIGHT},
+    toml,
+    toml::Value,
+    ProgressStyle, EXIT_USAGE, MAX_MATCHES, MAX_SEARCH_DEPTH, MAX_SEARCH_TIME_MS,
+    PROGRESS_DELAY_MS,
+};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+/// Config keys and the environment variables that override them
+pub const SETTINGS: &[(&str, &str)] = &[
+    ("search.max_matches", "JCD_MAX_MATCHES"),
+    ("search.timeout_ms", "JCD_TIMEOUT_MS"),
+    ("search.max_depth", "JCD_MAX_DEPTH"),
+    ("search.gitignore", "JCD_GITIGNORE"),
+    ("search.same_filesystem", "JCD_SAME_FILESYSTEM"),
+    ("search.smart_case", "JCD_SMART_CASE"),
+    ("search.typos", "JCD_TYPOS"),
+    ("search.ignore_accents", "JCD_IGNORE_ACCENTS"),
+    ("search.full_path", "JCD_FULL_PATH"),
+    ("search.siblings", "JCD_SIBLINGS"),
+    ("search.cousins", "JCD_COUSINS"),
+    ("search.cdpath", "JCD_CDPATH"),
+    ("search.roots", "JCD_ROOTS"),
+    ("search.cache_ttl_secs", "JCD_CACHE_TTL_SECS"),
+    ("search.tier_limits", "JCD_TIER_LIMITS"),
+    ("ranking.frecency", "JCD_RANK_FRECENCY"),
+    ("ranking.tie_break", "JCD_RANK_TIE_BREAK"),
+    ("ranking.shortest_first", "JCD_RANK_SHORTEST_FIRST"),
+    ("ranking.learn", "JCD_RANK_LEARN"),
+    ("ranking.pins", "JCD_PINS"),
+    ("ranking.boost", "JCD_RANK_BOOST"),
+    ("ranking.weights", "JCD_RANK_WEIGHTS"),
+    ("history.decay", "JCD_HISTORY_DECAY"),
+    ("history.half_life_hours", "JCD_HISTORY_HALF_LIFE_HOURS"),
+    ("history.max_age", "JCD_HISTORY_MAX_AGE"),
+    ("history.exclude", "JCD_HISTORY_EXCLUDE"),
+    ("ui.progress_delay_ms", "JCD_PROGRESS_DELAY_MS"),
+    ("ui.progress_style", "JCD_PROGRESS_STYLE"),
+    ("ui.match_style", "JCD_MATCH_STYLE"),
+    ("ui.path_style", "JCD_PATH_STYLE"),
+    ("log.file", "JCD_LOG_FILE"),
+];
+
+/// Config keys without an environment variable
+const FILE_SETTINGS: &[&str] = &["index.roots", "index.max_depth", "index.max_age_secs"];
+
+pub const USAGE: &str =
+    "Usage: jcd config show | path\n       jcd config get <key>\n       jcd config set <key> <value>";
+
+/// Every config key, in the order `jcd config show` lists them
+pub fn config_keys() -> impl Iterator<Item = &'static str> {
+    SETTINGS
+        .iter()
+        .map(|(key, _)| *key)
+        .chain(FILE_SETTINGS.iter().copied())
+}
+
+/// Tunables read from config.toml, with the built-in constants as defaults
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub max_matches: usize, // Most matches reported; searches stop once they have enough
+    pub timeout_ms: u64,    // Time budget for each search
+    pub max_depth: usize,   // Deepest level explored when searching down
+    pub gitignore: bool,    // Skip directories ignored by git when searching down
+    pub same_filesystem: bool, // Stay on one filesystem when searching down
+    pub smart_case: bool,   // Lowercase patterns ignore case, others do not
+    pub typos: bool,        // Names a typo or two away match below real matches
+    pub ignore_accents: bool, // Plain patterns match names whatever their accents
+    pub full_path: bool,    // Patterns match the path below the search root, not the name
+    pub siblings: bool,     // Directories next to the current one match before deeper ones
+    pub cousins: bool,      // So do those just below each nearby ancestor
+    pub cdpath: bool,       // Search $CDPATH when nothing matches nearby
+    pub roots: Vec<PathBuf>, // Always searched after the current directory
+    pub cache_ttl_secs: u64, // How long results are reused for later matches; 0 = never
+    pub tier_limits: TierLimits, // Most matches kept of each quality
+    pub frecency_weight: f64, // Ranking points for a directory at full frecency
+    pub tie_break: TieBreak, // Order of matches that rank the same
+    pub shortest_first: bool, // Fewer path components win among matches of the same kind
+    pub learn: bool,        // The match last chosen for a pattern comes first next time
+    pub pins: Vec<PathBuf>, // Come first whenever their name matches
+    pub boosts: Vec<PathBoost>, // Score multipliers for the directories below path patterns
+    pub rank_weights: String, // Adjustments to the default ranking weights, as for --rank
+    pub decay: Decay,       // How history visits age
+    pub history_exclude: Vec<PathPattern>, // Directories never recorded in the history
+    pub progress_delay_ms: u64, // Delay before the busy indicator appears
+    pub progress_style: ProgressStyle, // How the busy indicator is drawn
+    pub match_style: Style, // Matched text of highlighted names
+    pub path_style: Style,  // Parent path of highlighted matches
+    pub log_file: Option<PathBuf>, // Traces are appended here instead of stderr
+    pub index_roots: Vec<PathBuf>, // Trees recorded by `jcd index build`
+    pub index_max_depth: usize, // Levels below each root that are indexed
+    pub index_max_age_secs: u64, // Older indexes are ignored
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_matches: MAX_MATCHES,
+            timeout_ms: MAX_SEARCH_TIME_MS,
+            max_depth: MAX_SEARCH_DEPTH,
+            gitignore: false,
+            same_filesystem: false,
+            smart_case: false,
+            typos: false,
+            ignore_accents: false,
+            full_path: false,
+            siblings: false,
+            cousins: false,
+            cdpath: true,
+            roots: Vec::new(),
+            cache_ttl_secs: 30,
+            tier_limits: TierLimits::default(),
+            frecency_weight: FRECENCY_WEIGHT,
+            tie_break: TieBreak::default(),
+            shortest_first: false,
+            learn: true,
+            pins: Vec::new(),
+            boosts: Vec::new(),
+            rank_weights: String::new(),
+            decay: Decay::default(),
+            history_exclude: Vec::new(),
+            progress_delay_ms: PROGRESS_DELAY_MS,
+            progress_style: ProgressStyle::default(),
+            match_style: Style::parse("bold red").expect("valid default style"),
+            path_style: Style::parse("dim").expect("valid default style"),
+            log_file: None,
+            index_roots: Vec::new(),
+            index_max_depth: 16,
+            index_max_age_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+/// The per-user config file, which `jcd config set` edits. JCD_CONFIG
+/// replaces it, e.g. for testing a configuration.
+pub fn user_config_path() -> PathBuf {
+    match env::var("JCD_CONFIG") {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => platform::config_home().join("jcd").join("config.toml"),
+    }
+}
+
+/// Config files in the order they are applied; later files override earlier ones
+pub fn config_file_paths() -> Vec<PathBuf> {
+    vec![
+        platform::system_config_dir().join("config.toml"),
+        user_config_path(),
+    ]
+}
+
+fn non_negative(key: &str, value: &Value) -> Result<u64, String> {
+    match value {
+        Value::Integer(i) if *i >= 0 => Ok(*i as u64),
+        Value::Integer(_) => Err(format!("'{}' must not be negative", key)),
+        Value::Float(f) if !f.is_finite() => {
+            Err(format!("'{}' must be an integer, found {:?}", key, f))
+        }
+        other => Err(format!(
+            "'{}' must be an integer, found {}",
+            key,
+            other.type_name()
+        )),
+    }
+}
+
+fn boolean(key: &str, value: &Value) -> Result<bool, String> {
+    match value {
+        Value::Boolean(b) => Ok(*b),
+        // Environment overrides are often written as 0/1
+        Value::Integer(0) => Ok(false),
+        Value::Integer(1) => Ok(true),
+        other => Err(format!(
+            "'{}' must be true or false, found {}",
+            key,
+            other.type_name()
+        )),
+    }
+}
+
+fn number(key: &str, value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Integer(i) => Ok(*i as f64),
+        Value::Float(f) if f.is_finite() => Ok(*f),
+        Value::Float(f) => Err(format!("'{}' must be a finite number, found {:?}", key, f)),
+        other => Err(format!(
+            "'{}' must be a number, found {}",
+            key,
+            other.type_name()
+        )),
+    }
+}
+
+fn positive(key: &str, value: &Value) -> Result<f64, String> {
+    let n = number(key, value)?;
+    if n <= 0.0 {
+        return Err(format!("'{}' must be greater than 0", key));
+    }
+    Ok(n)
+}
+
+fn path_list(key: &str, value: &Value) -> Result<Vec<PathBuf>, String> {
+    // Environment overrides spell a list like PATH, e.g. JCD_ROOTS=~/src:~/work
+    if let Value::String(list) = value {
+        return Ok(env::split_paths(list)
+            .filter(|path| !path.as_os_str().is_empty())
+            .map(|path| expand_home(&platform::path_to_str(&path)))
+            .collect());
+    }
+    let Value::Array(items) = value else {
+        return Err(format!(
+            "'{}' must be an array of strings, found {}",
+            key,
+            value.type_name()
+        ));
+    };
+    items
+        .iter()
+        .map(|item| match item {
+            Value::String(path) => Ok(expand_home(path)),
+            other => Err(format!(
+                "'{}' must contain strings, found {}",
+                key,
+                other.type_name()
+            )),
+        })
+        .collect()
+}
+
+/// A path, or none when empty so that an override can unset a file's setting
+fn optional_path(key: &str, value: &Value) -> Result<Option<PathBuf>, String> {
+    match value {
+        Value::String(path) if path.is_empty() => Ok(None),
+        Value::String(path) => Ok(Some(expand_home(path))),
+        other => Err(format!(
+            "'{}' must be a string, found {}",
+            key,
+            other.type_name()
+        )),
+    }
+}
+
+fn style(key: &str, value: &Value) -> Result<Style, String> {
+    match value {
+        Value::String(spec) => Style::parse(spec).map_err(|e| format!("'{}': {}", key, e)),
+        // An environment override such as JCD_MATCH_STYLE=1 reads as a number
+        Value::Integer(code) if *code >= 0 => Ok(Style::parse(&code.to_string())?),
+        other => Err(format!(
+            "'{}' must be a string, found {}",
+            key,
+            other.type_name()
+        )),
+    }
+}
+
+fn pattern_list(key: &str, value: &Value) -> Result<Vec<String>, String> {
+    // Environment overrides separate patterns like PATH, e.g. JCD_HISTORY_EXCLUDE=/tmp:~/Downloads,
+    // except that a regex may contain the separator and stands alone
+    Ok(match value {
+        Value::String(regex) if regex.starts_with("re:") => vec![regex.clone()],
+        Value::String(list) => env::split_paths(list)
+            .filter(|pattern| !pattern.as_os_str().is_empty())
+            .map(|pattern| platform::path_to_str(&pattern).into_owned())
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::String(pattern) => Ok(pattern.clone()),
+                other => Err(format!(
+                    "'{}' must contain strings, found {}",
+                    key,
+                    other.type_name()
+                )),
+            })
+            .collect::<Result<_, _>>()?,
+        other => {
+            return Err(format!(
+                "'{}' must be an array of strings, found {}",
+                key,
+                other.type_name()
+            ))
+        }
+    })
+}
+
+fn exclude_list(key: &str, value: &Value) -> Result<Vec<PathPattern>, String> {
+    pattern_list(key, value)?
+        .iter()
+        .map(|pattern| PathPattern::new(pattern).map_err(|e| format!("'{}': {}", key, e)))
+        .collect()
+}
+
+fn boost_list(key: &str, value: &Value) -> Result<Vec<PathBoost>, String> {
+    pattern_list(key, value)?
+        .iter()
+        .map(|spec| PathBoost::parse(spec).map_err(|e| format!("'{}': {}", key, e)))
+        .collect()
+}
+
+/// Something wrong with the configuration, found while loading it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigProblem {
+    Invalid(String), // A file that does not parse, or a value that is rejected
+    UnknownKey(String, PathBuf), // A key jcd does not know, e.g. a misspelled one
+}
+
+impl ConfigProblem {
+    /// Report the problem the way every command does: invalid settings on
+    /// stderr, unknown keys in the trace
+    fn warn(&self) {
+        match self {
+            ConfigProblem::Invalid(message) => eprintln!("Warning: {}", message),
+            ConfigProblem::UnknownKey(key, source) => {
+                debug!("Unknown config key '{}' in {}", key, source.display());
+            }
+        }
+    }
+}
+
+/// Where the value of a setting in effect came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    Default,
+    File(PathBuf),
+    Env(&'static str),
+}
+
+/// What loading the configuration came across besides the settings
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    pub problems: Vec<ConfigProblem>,
+    origins: BTreeMap<String, Origin>, // Settings that were set, by the last file or variable
+}
+
+impl LoadReport {
+    /// Where the value of `key` came from
+    pub fn origin(&self, key: &str) -> Origin {
+        self.origins.get(key).cloned().unwrap_or(Origin::Default)
+    }
+}
+
+impl Config {
+    /// Set one option by its dotted key. Returns Ok(false) for keys jcd does not know.
+    pub fn set(&mut self, key: &str, value: &Value) -> Result<bool, String> {
+        match key {
+            "search.max_matches" => {
+                let n = non_negative(key, value)?;
+                if n == 0 {
+                    return Err(format!("'{}' must be at least 1", key));
+                }
+                self.max_matches = n as usize;
+            }
+            "search.timeout_ms" => self.timeout_ms = non_negative(key, value)?,
+            "search.max_depth" => self.max_depth = non_negative(key, value)? as usize,
+            "search.gitignore" => self.gitignore = boolean(key, value)?,
+            "search.same_filesystem" => self.same_filesystem = boolean(key, value)?,
+            "search.smart_case" => self.smart_case = boolean(key, value)?,
+            "search.typos" => self.typos = boolean(key, value)?,
+            "search.ignore_accents" => self.ignore_accents = boolean(key, value)?,
+            "search.full_path" => self.full_path = boolean(key, value)?,
+            "search.siblings" => self.siblings = boolean(key, value)?,
+            "search.cousins" => self.cousins = boolean(key, value)?,
+            "search.cdpath" => self.cdpath = boolean(key, value)?,
+            "search.roots" => self.roots = path_list(key, value)?,
+            "search.cache_ttl_secs" => self.cache_ttl_secs = non_negative(key, value)?,
+            "search.tier_limits" => {
+                let Value::String(spec) = value else {
+                    return Err(format!(
+                        "'{}' must be a string, found {}",
+                        key,
+                        value.type_name()
+                    ));
+                };
+                self.tier_limits =
+                    TierLimits::parse(spec).map_err(|e| format!("'{}': {}", key, e))?;
+            }
+            "ranking.frecency" => self.frecency_weight = number(key, value)?,
+            "ranking.tie_break" => {
+                let Value::String(name) = value else {
+                    return Err(format!(
+                        "'{}' must be a string, found {}",
+                        key,
+                        value.type_name()
+                    ));
+                };
+                self.tie_break = TieBreak::from_name(name).ok_or_else(|| {
+                    format!(
+                        "'{}' must be alphabetical, length, components or recency, found '{}'",
+                        key, name
+                    )
+                })?;
+            }
+            "ranking.shortest_first" => self.shortest_first = boolean(key, value)?,
+            "ranking.learn" => self.learn = boolean(key, value)?,
+            "ranking.pins" => self.pins = path_list(key, value)?,
+            "ranking.boost" => self.boosts = boost_list(key, value)?,
+            "ranking.weights" => {
+                let Value::String(spec) = value else {
+                    return Err(format!(
+                        "'{}' must be a string, found {}",
+                        key,
+                        value.type_name()
+                    ));
+                };
+                RankWeights::default()
+                    .apply_overrides(spec)
+                    .map_err(|e| format!("'{}': {}", key, e))?;
+                self.rank_weights = spec.trim().to_string();
+            }
+            "history.decay" => {
+                let Value::String(name) = value else {
+                    return Err(format!(
+                        "'{}' must be a string, found {}",
+                        key,
+                        value.type_name()
+                    ));
+                };
+                self.decay.model = DecayModel::from_name(name).ok_or_else(|| {
+                    format!(
+                        "'{}' must be buckets, half-life, zoxide or count, found '{}'",
+                        key, name
+                    )
+                })?;
+            }
+            "history.half_life_hours" => self.decay.half_life_hours = positive(key, value)?,
+            "history.max_age" => self.decay.max_age = positive(key, value)?,
+            "history.exclude" => self.history_exclude = exclude_list(key, value)?,
+            "ui.progress_delay_ms" => self.progress_delay_ms = non_negative(key, value)?,
+            "ui.progress_style" => {
+                let Value::String(name) = value else {
+                    return Err(format!(
+                        "'{}' must be a string, found {}",
+                        key,
+                        value.type_name()
+                    ));
+                };
+                self.progress_style = ProgressStyle::from_name(name).ok_or_else(|| {
+                    format!(
+                        "'{}' must be counter, spinner, dots or none, found '{}'",
+                        key, name
+                    )
+                })?;
+            }
+            "ui.match_style" => self.match_style = style(key, value)?,
+            "ui.path_style" => self.path_style = style(key, value)?,
+            "log.file" => self.log_file = optional_path(key, value)?,
+            "index.roots" => self.index_roots = path_list(key, value)?,
+            "index.max_depth" => self.index_max_depth = non_negative(key, value)? as usize,
+            "index.max_age_secs" => self.index_max_age_secs = non_negative(key, value)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Apply every option of a parsed config file
+    fn apply_document(&mut self, doc: &toml::Document, source: &Path, report: &mut LoadReport) {
+        for (key, value) in &doc.values {
+            match self.set(key, value) {
+                Ok(true) => {
+                    report
+                        .origins
+                        .insert(key.clone(), Origin::File(source.to_path_buf()));
+                }
+                Ok(false) => report
+                    .problems
+                    .push(ConfigProblem::UnknownKey(key.clone(), source.to_path_buf())),
+                Err(e) => report.problems.push(ConfigProblem::Invalid(format!(
+                    "{}: {}",
+                    source.display(),
+                    e
+                ))),
+            }
+        }
+    }
+
+    /// Apply environment variable overrides, which take precedence over files
+    fn apply_env(&mut self, report: &mut LoadReport) {
+        for (key, var) in SETTINGS {
+            let Ok(raw) = env::var(var) else {
+                continue;
+            };
+            let value = toml::parse_scalar(raw.trim()).unwrap_or(Value::String(raw.clone()));
+            match self.set(key, &value) {
+                Ok(_) => {
+                    report.origins.insert(key.to_string(), Origin::Env(var));
+                }
+                Err(e) => report
+                    .problems
+                    .push(ConfigProblem::Invalid(format!("{}: {}", var, e))),
+            }
+        }
+    }
+
+    /// Load like `load`, returning the problems and where each setting came
+    /// from instead of printing the problems
+    pub fn load_report() -> (Self, LoadReport) {
+        let mut config = Self::default();
+        let mut report = LoadReport::default();
+
+        for path in config_file_paths() {
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            match toml::parse(&content) {
+                Ok(doc) => {
+                    debug!("Loaded config file {}", path.display());
+                    config.apply_document(&doc, &path, &mut report);
+                }
+                Err(e) => report.problems.push(ConfigProblem::Invalid(format!(
+                    "Ignoring config file {}: {}",
+                    path.display(),
+                    e
+                ))),
+            }
+        }
+
+        config.apply_env(&mut report);
+
+        debug!("Effective config {:?}", config);
+        (config, report)
+    }
+
+    /// Load the system and user config files, then the environment overrides.
+    /// Missing files are skipped; a file that fails to parse is ignored as a whole.
+    pub fn load() -> Self {
+        let (config, report) = Self::load_report();
+        for problem in &report.problems {
+            problem.warn();
+        }
+        config
+    }
+
+    /// The value in effect for a dotted key, as it would be written in config.toml
+    pub fn get(&self, key: &str) -> Option<Value> {
+        let path = |path: &Path| Value::String(platform::path_to_str(path).into_owned());
+        let paths = |paths: &[PathBuf]| Value::Array(paths.iter().map(|p| path(p)).collect());
+        let count = |n: u64| Value::Integer(i64::try_from(n).unwrap_or(i64::MAX));
+        Some(match key {
+            "search.max_matches" => count(self.max_matches as u64),
+            "search.timeout_ms" => count(self.timeout_ms),
+            "search.max_depth" => count(self.max_depth as u64),
+            "search.gitignore" => Value::Boolean(self.gitignore),
+            "search.same_filesystem" => Value::Boolean(self.same_filesystem),
+            "search.smart_case" => Value::Boolean(self.smart_case),
+            "search.typos" => Value::Boolean(self.typos),
+            "search.ignore_accents" => Value::Boolean(self.ignore_accents),
+            "search.full_path" => Value::Boolean(self.full_path),
+            "search.siblings" => Value::Boolean(self.siblings),
+            "search.cousins" => Value::Boolean(self.cousins),
+            "search.cdpath" => Value::Boolean(self.cdpath),
+            "search.roots" => paths(&self.roots),
+            "search.cache_ttl_secs" => count(self.cache_ttl_secs),
+            "search.tier_limits" => Value::String(self.tier_limits.spec()),
+            "ranking.frecency" => Value::Float(self.frecency_weight),
+            "ranking.tie_break" => Value::String(self.tie_break.name().to_string()),
+            "ranking.shortest_first" => Value::Boolean(self.shortest_first),
+            "ranking.learn" => Value::Boolean(self.learn),
+            "ranking.pins" => paths(&self.pins),
+            "ranking.weights" => Value::String(self.rank_weights.clone()),
+            "ranking.boost" => Value::Array(
+                self.boosts
+                    .iter()
+                    .map(|boost| Value::String(boost.spec()))
+                    .collect(),
+            ),
+            "history.decay" => Value::String(self.decay.model.name().to_string()),
+            "history.half_life_hours" => Value::Float(self.decay.half_life_hours),
+            "history.max_age" => Value::Float(self.decay.max_age),
+            "history.exclude" => Value::Array(
+                self.history_exclude
+                    .iter()
+                    .map(|pattern| Value::String(pattern.pattern().to_string()))
+                    .collect(),
+            ),
+            "ui.progress_delay_ms" => count(self.progress_delay_ms),
+            "ui.progress_style" => Value::String(self.progress_style.name().to_string()),
+            "ui.match_style" => Value::String(self.match_style.spec().to_string()),
+            "ui.path_style" => Value::String(self.path_style.spec().to_string()),
+            "log.file" => self
+                .log_file
+                .as_deref()
+                .map_or(Value::String(String::new()), path),
+            "index.roots" => paths(&self.index_roots),
+            "index.max_depth" => count(self.index_max_depth as u64),
+            "index.max_age_secs" => count(self.index_max_age_secs),
+            _ => return None,
+        })
+    }
+}
+
+fn unknown_key(key: &str) -> String {
+    format!("Unknown config key '{}' (see 'jcd config show')", key)
+}
+
+/// Every setting in effect, one `key = value` line each, with where it came from
+fn show() -> Result<String, String> {
+    let (config, report) = Config::load_report();
+    for problem in &report.problems {
+        problem.warn();
+    }
+    let lines: Vec<(String, String)> = config_keys()
+        .map(|key| {
+            let origin = match report.origin(key) {
+                Origin::Default => "default".to_string(),
+                Origin::File(path) => path.display().to_string(),
+                Origin::Env(var) => var.to_string(),
+            };
+            let value = config.get(key).unwrap_or(Value::String(String::new()));
+            (format!("{} = {}", key, value), origin)
+        })
+        .collect();
+    let width = lines.iter().map(|(line, _)| line.len()).max().unwrap_or(0);
+    Ok(lines
+        .iter()
+        .map(|(line, origin)| format!("{:<width$}  # {}", line, origin, width = width))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// The value in effect for `key`; a string is printed without quotes
+fn get(args: &[String]) -> Result<String, String> {
+    let [key] = args else {
+        return Err(USAGE.to_string());
+    };
+    match Config::load().get(key) {
+        Some(Value::String(text)) => Ok(text),
+        Some(value) => Ok(value.to_string()),
+        None => Err(unknown_key(key)),
+    }
+}
+
+/// A value from the command line: TOML, or else the text as a string so
+/// that `half-life` and `~/src` need no quotes
+fn command_line_value(text: &str) -> Result<Value, String> {
+    match toml::parse_value(text) {
+        Ok(value) => Ok(value),
+        Err(e) if text.starts_with(['[', '"', '\'']) => {
+            Err(format!("Invalid value '{}': {}", text, e))
+        }
+        Err(_) => Ok(Value::String(text.to_string())),
+    }
+}
+
+/// Write `key = value` into the user's config file, keeping the rest of it
+fn set(args: &[String]) -> Result<String, String> {
+    let [key, text] = args else {
+        return Err(USAGE.to_string());
+    };
+    if !config_keys().any(|k| k == key) {
+        return Err(unknown_key(key));
+    }
+    let value = command_line_value(text)?;
+    Config::default().set(key, &value)?;
+
+    let path = user_config_path();
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Cannot read {}: {}", path.display(), e)),
+    };
+    let edited = toml::set_value(&content, key, &value)
+        .map_err(|e| format!("Cannot edit {}: {}", path.display(), e))?;
+    write_atomically(&path, &edited)
+        .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+
+    if let Some((_, var)) = SETTINGS.iter().find(|(k, _)| k == key) {
+        if env::var_os(var).is_some() {
+            eprintln!("Warning: {} is set and overrides this setting", var);
+        }
+    }
+    Ok(format!("Set {} = {} in {}", key, value, path.display()))
+}
+
+/// Replace `path` with a fully written temp file
+fn write_atomically(path: &Path, content: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension(format!("toml.{}.tmp", std::process::id()));
+    {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp_path, path)
+}
+
+/// `jcd config show`, `jcd config get <key>`, `jcd config set <key> <value>`
+/// and `jcd config path`
+pub fn run_command(args: &[String]) -> i32 {
+    let result = match args.first().map(String::as_str) {
+        Some("show") if args.len() == 1 => show(),
+        Some("get") => get(&args[1..]),
+        Some("set") => set(&args[1..]),
+        Some("path") if args.len() == 1 => Ok(user_config_path().display().to_string()),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(message) => {
+            println!("{}", message);
+            0
+        }
+        Err(e) if e == USAGE => {
+            eprintln!("{}", USAGE);
+            EXIT_USAGE
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
@@ -0,0 +1,606 @@
+//! `jcd daemon`: keeps the directory index in memory, updates it from inotify
+//! events and answers downward searches over a Unix socket, so foreground
+//! queries neither walk the tree nor load the index file. Without a daemon
+//! answering on the socket, searches run in-process as before.
+
+use crate::{
+    index::{Candidate, Index},
+    matcher::{NameMatch, PatternSyntax},
+    platform, SearchConfig, EXIT_USAGE,
+};
+use std::{
+    borrow::Cow,
+    env,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+pub const USAGE: &str = "Usage: jcd daemon start | stop | status | run";
+
+/// Location of the daemon socket: `jcd.sock` in the per-user runtime directory
+/// if there is one
+pub fn socket_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime) if !runtime.is_empty() => PathBuf::from(runtime).join("jcd.sock"),
+        _ => platform::cache_home().join("jcd").join("jcd.sock"),
+    }
+}
+
+// Messages on the socket are a list of fields: the number of fields, then each
+// field's length and bytes, with lengths as 32-bit big-endian integers. No byte
+// is special inside a field, so paths and patterns may hold tabs and newlines.
+//
+//   ping                                       -> ok <dirs> <pid>
+//   stop                                       -> ok
+//   search <reach> <case> <syntax> <dir> <pattern>
+//                                              -> ok (<depth> <kind> <path>)* | miss | error
+
+/// Longest field accepted, so a stray client cannot make either side allocate
+/// without bound
+const MAX_FIELD_LEN: u32 = 1 << 20;
+/// Most fields accepted: a search answer has three per candidate
+const MAX_FIELDS: u32 = 1 << 24;
+
+/// Write one message, in a single write
+fn write_message<F: AsRef<[u8]>>(out: &mut impl Write, fields: &[F]) -> io::Result<()> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&(fields.len() as u32).to_be_bytes());
+    for field in fields {
+        let field = field.as_ref();
+        buf.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        buf.extend_from_slice(field);
+    }
+    out.write_all(&buf)
+}
+
+fn read_length(input: &mut impl Read, limit: u32) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    let length = u32::from_be_bytes(bytes);
+    if length > limit {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("length {} exceeds {}", length, limit),
+        ));
+    }
+    Ok(length)
+}
+
+/// Read one message written by `write_message`
+fn read_message(input: &mut impl Read) -> io::Result<Vec<Vec<u8>>> {
+    let count = read_length(input, MAX_FIELDS)?;
+    (0..count)
+        .map(|_| {
+            let mut field = vec![0; read_length(input, MAX_FIELD_LEN)? as usize];
+            input.read_exact(&mut field)?;
+            Ok(field)
+        })
+        .collect()
+}
+
+/// A field as text; paths were written by `platform::path_to_str`, so nothing is lost
+fn field_text(field: &[u8]) -> Cow<'_, str> {
+    String::from_utf8_lossy(field)
+}
+
+fn syntax_name(syntax: PatternSyntax) -> &'static str {
+    match syntax {
+        PatternSyntax::Text => "text",
+        PatternSyntax::Regex => "regex",
+    }
+}
+
+fn kind_name(kind: NameMatch) -> &'static str {
+    match kind {
+        NameMatch::Exact => "exact",
+        NameMatch::Prefix => "prefix",
+        NameMatch::Word => "word",
+        NameMatch::Acronym => "acronym",
+        NameMatch::Partial => "partial",
+        NameMatch::Typo => "typo",
+    }
+}
+
+/// The fields of a search request
+fn search_request(
+    current_dir: &Path,
+    reach: usize,
+    search_term: &str,
+    config: &SearchConfig,
+) -> Vec<Vec<u8>> {
+    vec![
+        b"search".to_vec(),
+        reach.to_string().into_bytes(),
+        if config.case_sensitive { b"1" } else { b"0" }.to_vec(),
+        syntax_name(config.syntax).as_bytes().to_vec(),
+        platform::path_to_str(current_dir).as_bytes().to_vec(),
+        search_term.as_bytes().to_vec(),
+    ]
+}
+
+/// Parse the daemon's answer to a search request; None for a miss or garbage
+fn parse_candidates(response: &[Vec<u8>]) -> Option<Vec<Candidate>> {
+    let (status, fields) = response.split_first()?;
+    if status != b"ok" || fields.len() % 3 != 0 {
+        return None;
+    }
+    fields
+        .chunks(3)
+        .map(|fields| {
+            let depth = field_text(&fields[0]).parse().ok()?;
+            let kind = match &fields[1][..] {
+                b"exact" => NameMatch::Exact,
+                b"prefix" => NameMatch::Prefix,
+                b"word" => NameMatch::Word,
+                b"acronym" => NameMatch::Acronym,
+                b"partial" => NameMatch::Partial,
+                b"typo" => NameMatch::Typo,
+                _ => return None,
+            };
+            let path = platform::str_to_path(&field_text(&fields[2]));
+            Some(Candidate { path, depth, kind })
+        })
+        .collect()
+}
+
+/// The daemon's answer to a search request
+fn format_candidates(candidates: &[Candidate]) -> Vec<Vec<u8>> {
+    let mut fields = vec![b"ok".to_vec()];
+    for c in candidates {
+        fields.push(c.depth.to_string().into_bytes());
+        fields.push(kind_name(c.kind).as_bytes().to_vec());
+        fields.push(platform::path_to_str(&c.path).as_bytes().to_vec());
+    }
+    fields
+}
+
+#[cfg(unix)]
+pub use unix::{query, run_command, running_socket};
+
+#[cfg(not(unix))]
+pub fn running_socket() -> Option<PathBuf> {
+    None
+}
+
+#[cfg(not(unix))]
+pub fn query(
+    _socket: &Path,
+    _current_dir: &Path,
+    _reach: usize,
+    _search_term: &str,
+    _config: &SearchConfig,
+) -> Option<Vec<Candidate>> {
+    None
+}
+
+#[cfg(not(unix))]
+pub fn run_command(_args: &[String]) -> i32 {
+    eprintln!("Error: jcd daemon is not supported on this platform");
+    1
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use crate::ignore::IgnorePattern;
+    use crate::{config::Config, ignore::load_ignore_patterns, matcher::NameMatcher};
+    use std::{
+        fs,
+        os::unix::{
+            fs::PermissionsExt,
+            net::{UnixListener, UnixStream},
+            process::CommandExt,
+        },
+        process::{self, Command, Stdio},
+        sync::{Arc, Mutex},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    const CLIENT_TIMEOUT: Duration = Duration::from_millis(250);
+    const START_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Send one request and read the response
+    fn request<F: AsRef<[u8]>>(socket: &Path, fields: &[F]) -> Option<Vec<Vec<u8>>> {
+        let mut stream = UnixStream::connect(socket).ok()?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+        write_message(&mut stream, fields).ok()?;
+        read_message(&mut stream).ok()
+    }
+
+    /// The answer to `ping`: directories indexed and pid, if the daemon answers
+    fn ping(socket: &Path) -> Option<(String, String)> {
+        match &request(socket, &["ping"])?[..] {
+            [status, dirs, pid] if status == b"ok" => {
+                Some((field_text(dirs).into_owned(), field_text(pid).into_owned()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Socket of a daemon that answers, if one is running
+    pub fn running_socket() -> Option<PathBuf> {
+        let socket = socket_path();
+        if !socket.exists() {
+            return None;
+        }
+        ping(&socket).map(|_| socket)
+    }
+
+    /// Ask the daemon for matching directories below `current_dir`
+    pub fn query(
+        socket: &Path,
+        current_dir: &Path,
+        reach: usize,
+        search_term: &str,
+        config: &SearchConfig,
+    ) -> Option<Vec<Candidate>> {
+        let response = request(
+            socket,
+            &search_request(current_dir, reach, search_term, config),
+        )?;
+        let candidates = parse_candidates(&response);
+        if candidates.is_none() {
+            debug!("Daemon cannot answer for {}", current_dir.display());
+        }
+        candidates
+    }
+
+    /// Answer one request; returns false when the daemon should stop
+    fn handle(mut stream: UnixStream, index: &Mutex<Index>) -> bool {
+        let Ok(request) = read_message(&mut stream) else {
+            return true;
+        };
+        let mut keep_running = true;
+
+        let response = match &request[..] {
+            [command] if command == b"ping" => vec![
+                b"ok".to_vec(),
+                index.lock().unwrap().dir_count().to_string().into_bytes(),
+                process::id().to_string().into_bytes(),
+            ],
+            [command] if command == b"stop" => {
+                keep_running = false;
+                vec![b"ok".to_vec()]
+            }
+            [command, reach, case, syntax, dir, pattern] if command == b"search" => {
+                let reach = field_text(reach).parse().ok();
+                let syntax = match &syntax[..] {
+                    b"regex" => PatternSyntax::Regex,
+                    _ => PatternSyntax::Text,
+                };
+                let dir = platform::str_to_path(&field_text(dir));
+                match (
+                    reach,
+                    NameMatcher::new(&field_text(pattern), case == b"1", syntax),
+                ) {
+                    (Some(reach), Ok(matcher)) => index
+                        .lock()
+                        .unwrap()
+                        .candidates(&dir, reach, &matcher, false)
+                        .map_or_else(|| vec![b"miss".to_vec()], |c| format_candidates(&c)),
+                    _ => vec![b"error".to_vec()],
+                }
+            }
+            _ => vec![b"error".to_vec()],
+        };
+
+        let _ = write_message(&mut stream, &response);
+        keep_running
+    }
+
+    /// Build the index, start watching it and serve requests until told to stop
+    fn serve() -> i32 {
+        let settings = Config::load();
+        if settings.index_roots.is_empty() {
+            eprintln!("Error: No index roots configured (set index.roots in config.toml)");
+            return 1;
+        }
+        if running_socket().is_some() {
+            eprintln!("Error: jcd daemon is already running");
+            return 1;
+        }
+
+        let socket = socket_path();
+        if let Some(parent) = socket.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                eprintln!("Error: Cannot create {}: {}", parent.display(), e);
+                return 1;
+            }
+        }
+
+        let ignore_patterns = load_ignore_patterns();
+        let index = Index::build(
+            &settings.index_roots,
+            settings.index_max_depth,
+            &ignore_patterns,
+        );
+        debug!("Daemon indexed {} directories", index.dir_count());
+        let index = Arc::new(Mutex::new(index));
+
+        #[cfg(target_os = "linux")]
+        {
+            let index = Arc::clone(&index);
+            thread::spawn(move || watch::run(&index, &ignore_patterns));
+        }
+
+        // A leftover socket from a daemon that died is not answering; replace it
+        let _ = fs::remove_file(&socket);
+        let listener = match UnixListener::bind(&socket) {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("Error: Cannot listen on {}: {}", socket.display(), e);
+                return 1;
+            }
+        };
+        let _ = fs::set_permissions(&socket, fs::Permissions::from_mode(0o600));
+
+        for stream in listener.incoming().flatten() {
+            if !handle(stream, &index) {
+                break;
+            }
+        }
+
+        let _ = fs::remove_file(&socket);
+        0
+    }
+
+    /// Launch `jcd daemon run` in the background and wait until it answers
+    fn start() -> i32 {
+        if running_socket().is_some() {
+            println!("jcd daemon is already running");
+            return 0;
+        }
+        let exe = match env::current_exe() {
+            Ok(exe) => exe,
+            Err(e) => {
+                eprintln!("Error: Cannot locate the jcd binary: {}", e);
+                return 1;
+            }
+        };
+        // A separate process group keeps terminal signals (Ctrl-C) away from the daemon
+        let mut child = match Command::new(exe)
+            .args(["daemon", "run"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::inherit())
+            .process_group(0)
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!("Error: Cannot start jcd daemon: {}", e);
+                return 1;
+            }
+        };
+
+        let deadline = Instant::now() + START_TIMEOUT;
+        while Instant::now() < deadline {
+            if running_socket().is_some() {
+                println!("jcd daemon started (pid {})", child.id());
+                return 0;
+            }
+            if let Ok(Some(status)) = child.try_wait() {
+                eprintln!("Error: jcd daemon exited during startup ({})", status);
+                return 1;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        println!("jcd daemon is still indexing (pid {})", child.id());
+        0
+    }
+
+    /// `jcd daemon start|stop|status|run`
+    pub fn run_command(args: &[String]) -> i32 {
+        let socket = socket_path();
+        match args.first().map(String::as_str) {
+            Some("run") => serve(),
+            Some("start") => start(),
+            Some("stop") => match running_socket() {
+                Some(socket) => {
+                    request(&socket, &["stop"]);
+                    println!("jcd daemon stopped");
+                    0
+                }
+                None => {
+                    println!("jcd daemon is not running");
+                    0
+                }
+            },
+            Some("status") => match ping(&socket) {
+                Some((dirs, pid)) => {
+                    println!(
+                        "jcd daemon running (pid {}), {} directories indexed, socket {}",
+                        pid,
+                        dirs,
+                        socket.display()
+                    );
+                    0
+                }
+                None => {
+                    println!("jcd daemon is not running");
+                    1
+                }
+            },
+            _ => {
+                eprintln!("{}", USAGE);
+                EXIT_USAGE
+            }
+        }
+    }
+
+    /// Keeps the in-memory index current from inotify events
+    #[cfg(target_os = "linux")]
+    mod watch {
+        use super::*;
+        use crate::ignore::should_ignore_directory;
+        use std::{
+            collections::HashMap,
+            ffi::{CString, OsStr, OsString},
+            fs::File,
+            io,
+            os::{
+                fd::{AsRawFd, FromRawFd},
+                raw::{c_char, c_int},
+                unix::ffi::OsStrExt,
+            },
+        };
+
+        // From <sys/inotify.h>
+        const IN_CLOEXEC: c_int = 0o2000000;
+        const IN_MOVED_FROM: u32 = 0x0000_0040;
+        const IN_MOVED_TO: u32 = 0x0000_0080;
+        const IN_CREATE: u32 = 0x0000_0100;
+        const IN_DELETE: u32 = 0x0000_0200;
+        const IN_Q_OVERFLOW: u32 = 0x0000_4000;
+        const IN_IGNORED: u32 = 0x0000_8000;
+        const IN_ONLYDIR: u32 = 0x0100_0000;
+        const IN_ISDIR: u32 = 0x4000_0000;
+        const WATCH_MASK: u32 = IN_CREATE | IN_DELETE | IN_MOVED_FROM | IN_MOVED_TO | IN_ONLYDIR;
+        const EVENT_HEADER: usize = 16; // wd, mask, cookie, len
+
+        extern "C" {
+            fn inotify_init1(flags: c_int) -> c_int;
+            fn inotify_add_watch(fd: c_int, pathname: *const c_char, mask: u32) -> c_int;
+        }
+
+        struct Event {
+            wd: i32,
+            mask: u32,
+            name: OsString,
+        }
+
+        struct Inotify {
+            file: File,
+            watches: HashMap<i32, PathBuf>,
+            limit_warned: bool,
+        }
+
+        impl Inotify {
+            fn new() -> io::Result<Self> {
+                // SAFETY: inotify_init1 takes no pointers; a negative result is an error
+                let fd = unsafe { inotify_init1(IN_CLOEXEC) };
+                if fd < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(Self {
+                    // SAFETY: fd is a freshly created descriptor owned by nothing else
+                    file: unsafe { File::from_raw_fd(fd) },
+                    watches: HashMap::new(),
+                    limit_warned: false,
+                })
+            }
+
+            fn watch(&mut self, dir: &Path) {
+                let Ok(path) = CString::new(dir.as_os_str().as_bytes()) else {
+                    return;
+                };
+                // SAFETY: path is a valid NUL-terminated string that outlives the call
+                let wd =
+                    unsafe { inotify_add_watch(self.file.as_raw_fd(), path.as_ptr(), WATCH_MASK) };
+                if wd >= 0 {
+                    self.watches.insert(wd, dir.to_path_buf());
+                } else if !self.limit_warned {
+                    self.limit_warned = true;
+                    eprintln!(
+                        "Warning: Cannot watch {}: {} (raise fs.inotify.max_user_watches)",
+                        dir.display(),
+                        io::Error::last_os_error()
+                    );
+                }
+            }
+
+            fn read_events(&mut self, buf: &mut [u8]) -> io::Result<Vec<Event>> {
+                let len = self.file.read(buf)?;
+                let mut events = Vec::new();
+                let mut offset = 0;
+                while offset + EVENT_HEADER <= len {
+                    let field = |i: usize| {
+                        let start = offset + 4 * i;
+                        u32::from_ne_bytes(buf[start..start + 4].try_into().unwrap())
+                    };
+                    let name_len = field(3) as usize;
+                    let name_end = (offset + EVENT_HEADER + name_len).min(len);
+                    let name = &buf[offset + EVENT_HEADER..name_end];
+                    let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(name.len())];
+                    events.push(Event {
+                        wd: field(0) as i32,
+                        mask: field(1),
+                        name: OsStr::from_bytes(name).to_os_string(),
+                    });
+                    offset = name_end;
+                }
+                Ok(events)
+            }
+        }
+
+        /// Watch every indexed directory and apply changes until reading fails
+        pub fn run(index: &Mutex<Index>, ignore_patterns: &[IgnorePattern]) {
+            let mut inotify = match Inotify::new() {
+                Ok(inotify) => inotify,
+                Err(e) => {
+                    eprintln!("Warning: inotify unavailable, index will not update: {}", e);
+                    return;
+                }
+            };
+            let dirs: Vec<PathBuf> = index.lock().unwrap().directories().cloned().collect();
+            for dir in &dirs {
+                inotify.watch(dir);
+            }
+
+            let mut buf = vec![0u8; 64 * 1024];
+            loop {
+                let events = match inotify.read_events(&mut buf) {
+                    Ok(events) => events,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        eprintln!("Warning: inotify read failed, index will not update: {}", e);
+                        return;
+                    }
+                };
+
+                for event in events {
+                    if event.mask & IN_Q_OVERFLOW != 0 {
+                        // Events were lost: start over from a fresh walk
+                        let mut index = index.lock().unwrap();
+                        let roots = index.roots().to_vec();
+                        *index = Index::build(&roots, index.depth(), ignore_patterns);
+                        inotify.watches.clear();
+                        for dir in index.directories() {
+                            inotify.watch(dir);
+                        }
+                        continue;
+                    }
+                    if event.mask & IN_IGNORED != 0 {
+                        inotify.watches.remove(&event.wd);
+                        continue;
+                    }
+                    if event.mask & IN_ISDIR == 0 {
+                        continue;
+                    }
+                    let Some(parent) = inotify.watches.get(&event.wd) else {
+                        continue;
+                    };
+                    if should_ignore_directory(&platform::os_to_str(&event.name), ignore_patterns) {
+                        continue;
+                    }
+                    let path = parent.join(&event.name);
+
+                    if event.mask & (IN_DELETE | IN_MOVED_FROM) != 0 {
+                        debug!("Daemon removing {}", path.display());
+                        index.lock().unwrap().remove_tree(&path);
+                    }
+                    if event.mask & (IN_CREATE | IN_MOVED_TO) != 0 {
+                        debug!("Daemon adding {}", path.display());
+                        let added = index.lock().unwrap().insert_tree(&path, ignore_patterns);
+                        for dir in &added {
+                            inotify.watch(dir);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
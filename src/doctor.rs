@@ -0,0 +1,505 @@
+//! The `jcd doctor` subcommand: checks the pieces a working jcd depends on
+//! (the shell function, the config and ignore files, the history database,
+//! the index and its daemon) and prints what is wrong with a command or edit
+//! that fixes it. It exits 1 if any check found a problem.
+//!
+//! The shell functions run `jcd doctor` with `_JCD_SHELL` and
+//! `_JCD_FUNCTION_REVISION` set, so a function loaded before jcd was upgraded
+//! can be told apart from the current one. Run directly, the doctor looks for
+//! the line in the shell's startup files instead.
+
+use crate::init;
+use jcd::{
+    config::{self, Config, ConfigProblem},
+    daemon,
+    history::{self, History},
+    ignore,
+    index::{self, Index},
+    platform, EXIT_USAGE,
+};
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+pub const USAGE: &str = "Usage: jcd doctor all | shell | config | ignore | history | index";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warning, // Works, but not as well as it could
+    Problem, // Something the user asked for has no effect
+}
+
+/// The outcome of one check
+#[derive(Debug)]
+struct Finding {
+    status: Status,
+    subject: &'static str,
+    detail: String,
+    fix: Option<String>,
+}
+
+impl Finding {
+    fn ok(subject: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            status: Status::Ok,
+            subject,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(subject: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: Status::Warning,
+            subject,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn problem(subject: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: Status::Problem,
+            subject,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        let status = match self.status {
+            Status::Ok => "ok",
+            Status::Warning => "warning",
+            Status::Problem => "PROBLEM",
+        };
+        writeln!(out, "{:<8} {}: {}", status, self.subject, self.detail)?;
+        if let Some(fix) = &self.fix {
+            writeln!(out, "{:<8} Fix: {}", "", fix)?;
+        }
+        Ok(())
+    }
+}
+
+/// The value the shell script sets `_JCD_FUNCTION_REVISION` to
+fn script_revision(script: &str) -> Option<&str> {
+    let (_, rest) = script.split_once("_JCD_FUNCTION_REVISION=")?;
+    rest.split_whitespace().next()
+}
+
+/// The line that loads jcd into `shell`
+fn init_line(shell: &str) -> String {
+    format!("eval \"$(jcd init {})\"", shell)
+}
+
+/// Startup files of `shell` that could load jcd
+fn startup_files(shell: &str) -> Vec<PathBuf> {
+    let home = platform::home_dir().unwrap_or_default();
+    if shell == "zsh" {
+        let dir = env::var_os("ZDOTDIR").map(PathBuf::from).unwrap_or(home);
+        return vec![dir.join(".zshrc")];
+    }
+    [".bashrc", ".bash_profile", ".profile"]
+        .iter()
+        .map(|name| home.join(name))
+        .collect()
+}
+
+/// The file a `source .../jcd_function.sh` line reads, with `~` and `$HOME` expanded
+fn sourced_path(line: &str) -> Option<PathBuf> {
+    let word = line
+        .split_whitespace()
+        .find(|word| word.contains("jcd_function.sh"))?
+        .trim_matches(|c| c == '"' || c == '\'');
+    let home = platform::home_dir().unwrap_or_default();
+    let home = platform::path_to_str(&home);
+    let expanded = match word.strip_prefix('~') {
+        Some(rest) => format!("{}{}", home, rest),
+        None => word.replace("${HOME}", &home).replace("$HOME", &home),
+    };
+    Some(platform::str_to_path(&expanded))
+}
+
+/// Whether the loaded function is the one this binary prints, when the doctor
+/// runs through it; otherwise whether a startup file loads jcd at all
+fn check_shell() -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    if let Ok(binary) = env::var("JCD_BINARY") {
+        if !binary.is_empty() && !Path::new(&binary).is_file() {
+            findings.push(Finding::problem(
+                "Shell",
+                format!("JCD_BINARY names {}, which does not exist", binary),
+                "point JCD_BINARY at the jcd binary, or unset it to use the one on PATH",
+            ));
+        }
+    }
+
+    if let Ok(shell) = env::var("_JCD_SHELL") {
+        let script = if shell == "zsh" {
+            init::ZSH_SCRIPT
+        } else {
+            init::BASH_SCRIPT
+        };
+        let expected = script_revision(script).unwrap_or_default();
+        let loaded = env::var("_JCD_FUNCTION_REVISION").unwrap_or_default();
+        findings.push(if loaded == expected {
+            Finding::ok(
+                "Shell",
+                format!("the {} function is loaded and current", shell),
+            )
+        } else {
+            Finding::warning(
+                "Shell",
+                format!(
+                    "the loaded {} function is revision {}, this jcd ships revision {}",
+                    shell, loaded, expected
+                ),
+                format!("open a new shell, or run: {}", init_line(&shell)),
+            )
+        });
+        return findings;
+    }
+
+    let shell = match env::var("SHELL") {
+        Ok(path) if path.ends_with("zsh") => "zsh",
+        _ => "bash",
+    };
+    let files = startup_files(shell);
+    let loader = files.iter().find_map(|file| {
+        let content = fs::read_to_string(file).ok()?;
+        let line = content.lines().map(str::trim).find(|line| {
+            !line.starts_with('#')
+                && (line.contains("jcd init")
+                    || line.contains("jcd_function.sh")
+                    || line.contains("jcd_init.zsh"))
+        })?;
+        Some((file.clone(), line.to_string()))
+    });
+
+    let Some((file, line)) = loader else {
+        let file = files.first().cloned().unwrap_or_default();
+        findings.push(Finding::problem(
+            "Shell",
+            format!("no {} startup file loads jcd", shell),
+            format!(
+                "echo '{}' >> {}, then open a new shell",
+                init_line(shell),
+                platform::path_to_str(&file)
+            ),
+        ));
+        return findings;
+    };
+
+    let sourced = line
+        .contains("jcd_function.sh")
+        .then(|| sourced_path(&line))
+        .flatten();
+    findings.push(match sourced {
+        Some(path) => match fs::read_to_string(&path) {
+            Err(e) => Finding::problem(
+                "Shell",
+                format!("{} sources {}: {}", file.display(), path.display(), e),
+                format!("replace that line with: {}", init_line(shell)),
+            ),
+            Ok(content) if content != init::BASH_SCRIPT => Finding::warning(
+                "Shell",
+                format!(
+                    "{} sources {}, which differs from the function of this jcd",
+                    file.display(),
+                    path.display()
+                ),
+                format!("replace that line with: {}", init_line(shell)),
+            ),
+            Ok(_) => Finding::ok(
+                "Shell",
+                format!("{} sources {}", file.display(), path.display()),
+            ),
+        },
+        None => Finding::ok(
+            "Shell",
+            format!("{} loads jcd with '{}'", file.display(), line),
+        ),
+    });
+    findings
+}
+
+/// Whether every config file parses and every key and value is accepted
+fn check_config() -> Vec<Finding> {
+    let (_, report) = Config::load_report();
+    let mut findings: Vec<Finding> = report
+        .problems
+        .into_iter()
+        .map(|problem| match problem {
+            ConfigProblem::Invalid(message) => Finding::problem(
+                "Config",
+                message,
+                "correct the file or variable; 'jcd --manpage' lists the settings",
+            ),
+            ConfigProblem::UnknownKey(key, source) => Finding::warning(
+                "Config",
+                format!("{}: unknown key '{}' has no effect", source.display(), key),
+                "correct or remove the key; 'jcd --manpage' lists the settings",
+            ),
+        })
+        .collect();
+
+    if findings.is_empty() {
+        let files: Vec<String> = config::config_file_paths()
+            .iter()
+            .filter(|path| path.is_file())
+            .map(|path| path.display().to_string())
+            .collect();
+        findings.push(Finding::ok(
+            "Config",
+            if files.is_empty() {
+                "no config file, using the defaults".to_string()
+            } else {
+                format!("{} read without problems", files.join(", "))
+            },
+        ));
+    }
+    findings
+}
+
+/// Whether every line of every ignore file takes effect
+fn check_ignore() -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut files = 0;
+    let mut patterns = 0;
+
+    for path in ignore::get_ignore_file_paths() {
+        if !path.exists() {
+            continue;
+        }
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                findings.push(Finding::problem(
+                    "Ignore",
+                    format!("cannot read {}: {}", path.display(), e),
+                    "fix the file's permissions, or remove it",
+                ));
+                continue;
+            }
+        };
+        files += 1;
+        patterns += ignore::parse_ignore_patterns(&content).len();
+        for problem in ignore::check_ignore_patterns(&content) {
+            findings.push(Finding::problem(
+                "Ignore",
+                format!(
+                    "{} line {} '{}': {}",
+                    path.display(),
+                    problem.line,
+                    problem.text,
+                    problem.message
+                ),
+                "correct or remove the line",
+            ));
+        }
+    }
+
+    if findings.is_empty() {
+        findings.push(Finding::ok(
+            "Ignore",
+            format!("{} patterns from {} files", patterns, files),
+        ));
+    }
+    findings
+}
+
+/// Whether the history database can be read and written and holds only
+/// well-formed entries for existing directories
+fn check_history() -> Vec<Finding> {
+    let path = history::history_file_path();
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) if !path.exists() => {
+            return vec![Finding::ok(
+                "History",
+                format!("no visits recorded yet ({})", path.display()),
+            )]
+        }
+        Err(e) => {
+            return vec![Finding::problem(
+                "History",
+                format!("cannot read {}: {}", path.display(), e),
+                "fix the file's permissions, or remove it to start over",
+            )]
+        }
+    };
+
+    let mut findings = Vec::new();
+    if fs::metadata(&path).is_ok_and(|meta| meta.permissions().readonly()) {
+        findings.push(Finding::problem(
+            "History",
+            format!(
+                "{} is read-only, so visits are not recorded",
+                path.display()
+            ),
+            format!("chmod u+w {}", platform::path_to_str(&path)),
+        ));
+    }
+    let malformed = History::malformed_lines(&content);
+    if !malformed.is_empty() {
+        let lines: Vec<String> = malformed.iter().map(usize::to_string).collect();
+        findings.push(Finding::warning(
+            "History",
+            format!(
+                "{} has malformed lines, which are skipped: {}",
+                path.display(),
+                lines.join(", ")
+            ),
+            "jcd history prune, which rewrites the file without them",
+        ));
+    }
+    let history = History::parse(&content);
+    let missing = history.entries().filter(|(dir, _)| !dir.is_dir()).count();
+    if missing > 0 {
+        findings.push(Finding::warning(
+            "History",
+            format!(
+                "{} of {} entries are for directories that no longer exist",
+                missing,
+                history.len()
+            ),
+            "jcd history prune",
+        ));
+    }
+
+    if findings.is_empty() {
+        findings.push(Finding::ok(
+            "History",
+            format!("{} entries in {}", history.len(), path.display()),
+        ));
+    }
+    findings
+}
+
+/// Whether the daemon answers and the index is readable and recent
+fn check_index() -> Vec<Finding> {
+    let (settings, _) = Config::load_report();
+    let mut findings = Vec::new();
+
+    let socket = daemon::socket_path();
+    findings.push(if let Some(socket) = daemon::running_socket() {
+        Finding::ok("Daemon", format!("answering on {}", socket.display()))
+    } else if socket.exists() {
+        Finding::warning(
+            "Daemon",
+            format!("{} exists but nothing answers on it", socket.display()),
+            "jcd daemon start, which replaces the stale socket",
+        )
+    } else {
+        Finding::ok("Daemon", "not running")
+    });
+
+    let path = index::index_file_path();
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) if !path.exists() && settings.index_roots.is_empty() => {
+            findings.push(Finding::ok("Index", "not used (index.roots is not set)"));
+            return findings;
+        }
+        Err(_) if !path.exists() => {
+            findings.push(Finding::warning(
+                "Index",
+                "index.roots is set, but no index has been built",
+                "jcd index build",
+            ));
+            return findings;
+        }
+        Err(e) => {
+            findings.push(Finding::problem(
+                "Index",
+                format!("cannot read {}: {}", path.display(), e),
+                "jcd index clear && jcd index build",
+            ));
+            return findings;
+        }
+    };
+
+    let Some(index) = Index::parse(&content) else {
+        findings.push(Finding::problem(
+            "Index",
+            format!("{} is not in the expected format", path.display()),
+            "jcd index clear && jcd index build",
+        ));
+        return findings;
+    };
+    let age = index.age_secs();
+    findings.push(if age > settings.index_max_age_secs {
+        Finding::warning(
+            "Index",
+            format!(
+                "{} is {}s old, older than index.max_age_secs ({}s), and is not used",
+                path.display(),
+                age,
+                settings.index_max_age_secs
+            ),
+            "jcd index build, or start the daemon to keep it current",
+        )
+    } else {
+        Finding::ok(
+            "Index",
+            format!(
+                "{} directories under {} roots, built {}s ago",
+                index.dir_count(),
+                index.roots().len(),
+                age
+            ),
+        )
+    });
+    findings
+}
+
+pub fn run_command(args: &[String]) -> i32 {
+    let checks: &[fn() -> Vec<Finding>] = match args.first().map(String::as_str) {
+        Some("all") => &[
+            check_shell,
+            check_config,
+            check_ignore,
+            check_history,
+            check_index,
+        ],
+        Some("shell") => &[check_shell],
+        Some("config") => &[check_config],
+        Some("ignore") => &[check_ignore],
+        Some("history") => &[check_history],
+        Some("index") => &[check_index],
+        _ => {
+            eprintln!("{}", USAGE);
+            return EXIT_USAGE;
+        }
+    };
+    if args.len() > 1 {
+        eprintln!("{}", USAGE);
+        return EXIT_USAGE;
+    }
+
+    let findings: Vec<Finding> = checks.iter().flat_map(|check| check()).collect();
+    let count = |status| findings.iter().filter(|f| f.status == status).count();
+    let (problems, warnings) = (count(Status::Problem), count(Status::Warning));
+    let summary = if problems == 0 && warnings == 0 {
+        "No problems found".to_string()
+    } else {
+        format!(
+            "{} problem{}, {} warning{}",
+            problems,
+            if problems == 1 { "" } else { "s" },
+            warnings,
+            if warnings == 1 { "" } else { "s" }
+        )
+    };
+
+    // A reader such as `grep -m1` may close the pipe before the end
+    let mut out = io::stdout().lock();
+    let _ = findings
+        .iter()
+        .try_for_each(|finding| finding.write(&mut out))
+        .and_then(|_| writeln!(out, "\n{}", summary));
+    i32::from(problems > 0)
+}
@@ -0,0 +1,289 @@
+//! What the search reads from the filesystem: directory listings and
+//! metadata, behind the `FileSystem` trait. `RealFileSystem` asks the OS;
+//! `MemoryFileSystem` holds a virtual tree, for embedders that search
+//! something other than the disk and for deterministic tests. Ignore files
+//! (.jcdignore, .gitignore) and the git repository of the starting directory
+//! are still read from disk.
+
+use crate::platform::{self, DirKey};
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fmt, fs, io,
+    path::{Component, Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Type of a directory entry itself; symlinks are not followed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Directory,
+    Symlink,
+    Other,
+}
+
+/// One entry of a directory listing
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub name: OsString,
+    pub path: PathBuf, // The listed directory joined with the name
+    pub kind: EntryKind,
+}
+
+/// What the search uses of a path's metadata, symlinks followed
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    pub is_dir: bool,
+    pub accessed: Option<SystemTime>,
+    pub device: Option<u64>, // None where devices are unknown; --same-filesystem then has no effect
+}
+
+/// The filesystem a search walks
+pub trait FileSystem: fmt::Debug + Send + Sync {
+    /// The entries of directory `dir`, in any order
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<Entry>>;
+
+    /// Metadata of `path`, following symlinks
+    fn metadata(&self, path: &Path) -> io::Result<Metadata>;
+
+    /// Identity of the directory `path` leads to, the same however it is
+    /// reached, so that --follow-symlinks does not search a directory twice
+    fn dir_key(&self, path: &Path) -> Option<DirKey>;
+
+    fn exists(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok_and(|m| m.is_dir)
+    }
+}
+
+/// The filesystem of the OS
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealFileSystem;
+
+impl FileSystem for RealFileSystem {
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+        Ok(fs::read_dir(dir)?
+            .filter_map(Result::ok)
+            .map(|entry| {
+                let kind = match entry.file_type() {
+                    Ok(t) if t.is_dir() => EntryKind::Directory,
+                    Ok(t) if t.is_symlink() => EntryKind::Symlink,
+                    _ => EntryKind::Other,
+                };
+                Entry {
+                    name: entry.file_name(),
+                    path: entry.path(),
+                    kind,
+                }
+            })
+            .collect())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let metadata = fs::metadata(path)?;
+        Ok(Metadata {
+            is_dir: metadata.is_dir(),
+            accessed: metadata.accessed().ok(),
+            device: device(&metadata),
+        })
+    }
+
+    fn dir_key(&self, path: &Path) -> Option<DirKey> {
+        platform::dir_key(path)
+    }
+}
+
+#[cfg(unix)]
+fn device(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn device(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Directory {
+        id: u64,
+        accessed: Option<SystemTime>,
+    },
+    File,
+    Symlink(PathBuf),
+}
+
+/// A directory tree held in memory. Paths are absolute; adding a path adds
+/// its missing parents as directories.
+///
+/// ```
+/// use jcd::filesystem::{FileSystem, MemoryFileSystem};
+/// use std::path::Path;
+///
+/// let tree = MemoryFileSystem::new()
+///     .with_dir("/work/src/app")
+///     .with_file("/work/src/app/Cargo.toml")
+///     .with_symlink("/work/app", "src/app");
+/// assert!(tree.is_dir(Path::new("/work/app")));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFileSystem {
+    nodes: BTreeMap<PathBuf, Node>,
+    next_id: u64,
+}
+
+/// Symlinks followed while resolving one path, as in Linux's MAXSYMLINKS
+const MAX_SYMLINK_HOPS: usize = 40;
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add directory `path`
+    pub fn with_dir(mut self, path: impl AsRef<Path>) -> Self {
+        self.insert_dir(path.as_ref());
+        self
+    }
+
+    /// Add an empty file, e.g. a project marker such as Cargo.toml
+    pub fn with_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.insert(path.as_ref(), Node::File);
+        self
+    }
+
+    /// Add a symlink to `target`, which is taken from the link's directory
+    /// when relative
+    pub fn with_symlink(mut self, path: impl AsRef<Path>, target: impl AsRef<Path>) -> Self {
+        self.insert(path.as_ref(), Node::Symlink(target.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Set the access time of directory `path`, which ranks with --recent
+    pub fn with_accessed(mut self, path: impl AsRef<Path>, time: SystemTime) -> Self {
+        self.insert_dir(path.as_ref());
+        if let Some(Node::Directory { accessed, .. }) = self.nodes.get_mut(path.as_ref()) {
+            *accessed = Some(time);
+        }
+        self
+    }
+
+    fn insert_dir(&mut self, path: &Path) {
+        if !matches!(self.nodes.get(path), Some(Node::Directory { .. })) {
+            self.next_id += 1;
+            let node = Node::Directory {
+                id: self.next_id,
+                accessed: None,
+            };
+            self.insert(path, node);
+        }
+    }
+
+    fn insert(&mut self, path: &Path, node: Node) {
+        if let Some(parent) = path.parent() {
+            if !self.nodes.contains_key(parent) {
+                self.insert_dir(parent);
+            }
+        }
+        self.nodes.insert(path.to_path_buf(), node);
+    }
+
+    /// `path` with every symlink in it replaced by its target
+    fn resolve(&self, path: &Path) -> io::Result<PathBuf> {
+        self.resolve_counting(path, &mut 0)
+    }
+
+    fn resolve_counting(&self, path: &Path, hops: &mut usize) -> io::Result<PathBuf> {
+        let mut resolved = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    resolved.pop();
+                }
+                _ => resolved.push(component),
+            }
+            while let Some(Node::Symlink(target)) = self.nodes.get(&resolved) {
+                *hops += 1;
+                if *hops > MAX_SYMLINK_HOPS {
+                    return Err(io::Error::other("too many levels of symbolic links"));
+                }
+                resolved.pop();
+                resolved = self.resolve_counting(&resolved.join(target), hops)?;
+            }
+        }
+        Ok(resolved)
+    }
+
+    fn node(&self, path: &Path) -> io::Result<(PathBuf, &Node)> {
+        let resolved = self.resolve(path)?;
+        match self.nodes.get(&resolved) {
+            Some(node) => Ok((resolved, node)),
+            None => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<Entry>> {
+        let (resolved, node) = self.node(dir)?;
+        if !matches!(node, Node::Directory { .. }) {
+            return Err(io::Error::other("not a directory"));
+        }
+        // Descendants sort right after the directory itself
+        Ok(self
+            .nodes
+            .range(resolved.clone()..)
+            .skip(1)
+            .take_while(|(path, _)| path.starts_with(&resolved))
+            .filter(|(path, _)| path.parent() == Some(resolved.as_path()))
+            .filter_map(|(path, node)| {
+                let name = path.file_name()?.to_os_string();
+                let kind = match node {
+                    Node::Directory { .. } => EntryKind::Directory,
+                    Node::Symlink(_) => EntryKind::Symlink,
+                    Node::File => EntryKind::Other,
+                };
+                Some(Entry {
+                    path: dir.join(&name),
+                    name,
+                    kind,
+                })
+            })
+            .collect())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let (_, node) = self.node(path)?;
+        Ok(match node {
+            Node::Directory { accessed, .. } => Metadata {
+                is_dir: true,
+                accessed: *accessed,
+                device: None,
+            },
+            _ => Metadata::default(),
+        })
+    }
+
+    fn dir_key(&self, path: &Path) -> Option<DirKey> {
+        match self.node(path).ok()? {
+            (resolved, Node::Directory { id, .. }) => Some(memory_key(*id, resolved)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn memory_key(id: u64, _resolved: PathBuf) -> DirKey {
+    (0, id)
+}
+
+#[cfg(not(unix))]
+fn memory_key(_id: u64, resolved: PathBuf) -> DirKey {
+    resolved
+}
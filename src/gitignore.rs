@@ -0,0 +1,219 @@
+//! `.gitignore` support for downward searches (`--gitignore`): directories that
+//! git ignores are skipped, so build output and dependency trees inside
+//! repositories do not use up the search budget.
+
+use crate::{filesystem::RealFileSystem, ignore::ScopedRules, project, MAX_COMPILED_REGEX_SIZE};
+use regex::{Regex, RegexBuilder};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::debug;
+
+/// One pattern line of an ignore file
+#[derive(Debug)]
+struct Rule {
+    regex: Regex, // Matches paths relative to the file's directory
+    negated: bool,
+}
+
+/// Rules read from one `.gitignore` (or `.git/info/exclude`)
+#[derive(Debug)]
+struct RuleFile {
+    base: PathBuf,
+    rules: Vec<Rule>,
+}
+
+/// Ignore rules in effect for one directory, outermost file first
+#[derive(Debug, Default)]
+pub struct GitIgnore {
+    files: Vec<Arc<RuleFile>>,
+}
+
+/// Translate a gitignore glob into a regex over '/'-separated relative paths
+pub fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                let at_start = i == 0 || chars[i - 1] == '/';
+                match chars.get(i + 2) {
+                    // "**/" matches zero or more leading directories
+                    Some('/') if at_start => {
+                        out.push_str("(?:.*/)?");
+                        i += 3;
+                    }
+                    // A trailing "/**" matches everything inside
+                    None if at_start => {
+                        out.push_str(".*");
+                        i += 2;
+                    }
+                    // Anywhere else "**" is an ordinary '*'
+                    _ => {
+                        out.push_str("[^/]*");
+                        i += 2;
+                    }
+                }
+            }
+            '*' => {
+                out.push_str("[^/]*");
+                i += 1;
+            }
+            '?' => {
+                out.push_str("[^/]");
+                i += 1;
+            }
+            '[' => match chars[i + 1..].iter().position(|&c| c == ']') {
+                Some(len) if len > 0 => {
+                    let class: String = chars[i + 1..i + 1 + len].iter().collect();
+                    let class = match class.strip_prefix('!') {
+                        Some(rest) => format!("^{}", rest),
+                        None => class,
+                    };
+                    out.push('[');
+                    out.push_str(&class.replace('\\', "\\\\").replace('[', "\\["));
+                    out.push(']');
+                    i += len + 2;
+                }
+                _ => {
+                    out.push_str("\\[");
+                    i += 1;
+                }
+            },
+            '\\' if i + 1 < chars.len() => {
+                out.push_str(&regex::escape(&chars[i + 1].to_string()));
+                i += 2;
+            }
+            c => {
+                out.push_str(&regex::escape(&c.to_string()));
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Parse one gitignore line; None for blank lines, comments and bad patterns
+fn parse_rule(line: &str) -> Option<Rule> {
+    let line = line.trim_end_matches(['\r', ' ']);
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    // Only directories are ever tested, so a trailing '/' changes nothing
+    let pattern = line.trim_end_matches('/');
+    if pattern.is_empty() {
+        return None;
+    }
+
+    // A '/' anywhere but the end anchors the pattern to the file's directory
+    let anchored = pattern.contains('/');
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+    let source = if anchored {
+        format!("^{}$", glob_to_regex(pattern))
+    } else {
+        format!("^(?:.*/)?{}$", glob_to_regex(pattern))
+    };
+
+    match RegexBuilder::new(&source)
+        .size_limit(MAX_COMPILED_REGEX_SIZE)
+        .build()
+    {
+        Ok(regex) => Some(Rule { regex, negated }),
+        Err(e) => {
+            debug!("Invalid gitignore pattern '{}': {}", line, e);
+            None
+        }
+    }
+}
+
+fn load_rule_file(path: &Path, base: &Path) -> Option<Arc<RuleFile>> {
+    let content = fs::read_to_string(path).ok()?;
+    let rules: Vec<Rule> = content.lines().filter_map(parse_rule).collect();
+    debug!(
+        "Loaded {} gitignore rules from {}",
+        rules.len(),
+        path.display()
+    );
+    Some(Arc::new(RuleFile {
+        base: base.to_path_buf(),
+        rules,
+    }))
+}
+
+impl GitIgnore {
+    /// Rules in effect inside `dir`: every `.gitignore` from the enclosing
+    /// repository's root down to `dir`, plus the repository's `info/exclude`
+    pub fn for_dir(dir: &Path) -> Arc<Self> {
+        let Some(repo_root) = project::repo_root(&RealFileSystem, dir) else {
+            return Arc::new(Self::default());
+        };
+        let mut rules = Arc::new(Self::default());
+        let mut path = repo_root.to_path_buf();
+        rules = rules.enter(&path);
+        if let Ok(relative) = dir.strip_prefix(repo_root) {
+            for component in relative.components() {
+                path.push(component);
+                rules = rules.enter(&path);
+            }
+        }
+        rules
+    }
+
+    /// Rules in effect inside `dir`, a child of the directory these rules
+    /// belong to. A nested repository starts over with its own rules.
+    pub fn enter(self: &Arc<Self>, dir: &Path) -> Arc<Self> {
+        let repo = dir.join(".git");
+        let gitignore = load_rule_file(&dir.join(".gitignore"), dir);
+        let mut files = if repo.exists() {
+            let exclude = load_rule_file(&repo.join("info").join("exclude"), dir);
+            exclude.into_iter().collect()
+        } else if gitignore.is_some() {
+            self.files.clone()
+        } else {
+            return Arc::clone(self);
+        };
+        files.extend(gitignore);
+        Arc::new(Self { files })
+    }
+
+    /// Whether git ignores the directory at `path`. The deepest file decides,
+    /// and within a file the last matching rule wins.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        if path.file_name().is_some_and(|name| name == ".git") {
+            return true;
+        }
+        for file in self.files.iter().rev() {
+            let Ok(relative) = path.strip_prefix(&file.base) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if let Some(rule) = file
+                .rules
+                .iter()
+                .rev()
+                .find(|r| r.regex.is_match(&relative))
+            {
+                return !rule.negated;
+            }
+        }
+        false
+    }
+}
+
+impl ScopedRules for GitIgnore {
+    fn enter(self: &Arc<Self>, dir: &Path) -> Arc<Self> {
+        GitIgnore::enter(self, dir)
+    }
+
+    fn ignores(&self, path: &Path) -> bool {
+        self.is_ignored(path)
+    }
+}
@@ -0,0 +1,561 @@
+//! The history database of visited directories, which ranks later searches
+//! by frecency, and the `jcd history` subcommand.
+
+use crate::{
+    config::Config,
+    gitignore::glob_to_regex,
+    matcher::{NameMatcher, PatternSyntax},
+    output::json_escape,
+    platform,
+    query::expand_home,
+    EXIT_USAGE,
+};
+use regex::Regex;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::debug;
+
+const HISTORY_HEADER: &str = "# jcd history v1";
+pub const USAGE: &str = "Usage: jcd history list [pattern] [--sort score|recent|visits] [--json]\n       jcd history prune [--days <n>] [--dry-run]";
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// Visit statistics for one directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub visits: f64,
+    pub last_visit: u64, // seconds since the Unix epoch
+}
+
+/// How visits lose weight as they age, chosen with `history.decay`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DecayModel {
+    #[default]
+    Buckets, // Visits count 4x within an hour, 2x within a day, 1/2 within a week, 1/4 after
+    HalfLife, // Visits lose half their weight every `history.half_life_hours`
+    Zoxide,   // Buckets, and all visits shrink once their total exceeds `history.max_age`
+    Count,    // Plain visit counts; age never matters
+}
+
+impl DecayModel {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "buckets" => Some(Self::Buckets),
+            "half-life" => Some(Self::HalfLife),
+            "zoxide" => Some(Self::Zoxide),
+            "count" => Some(Self::Count),
+            _ => None,
+        }
+    }
+
+    /// The name `history.decay` spells it with
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Buckets => "buckets",
+            Self::HalfLife => "half-life",
+            Self::Zoxide => "zoxide",
+            Self::Count => "count",
+        }
+    }
+}
+
+/// The aging model with its parameters, applied when recording and when ranking
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decay {
+    pub model: DecayModel,
+    pub half_life_hours: f64,
+    pub max_age: f64, // Total visits kept by zoxide-style aging
+}
+
+impl Default for Decay {
+    fn default() -> Self {
+        Self {
+            model: DecayModel::Buckets,
+            half_life_hours: 7.0 * 24.0,
+            max_age: 10_000.0, // zoxide's default _ZO_MAXAGE
+        }
+    }
+}
+
+impl Decay {
+    /// Factor by which a visit `age` seconds old has shrunk under exponential decay
+    fn half_life_factor(&self, age: u64) -> f64 {
+        0.5_f64.powf(age as f64 / (self.half_life_hours * HOUR as f64))
+    }
+}
+
+/// A `history.exclude` or `ranking.boost` pattern, naming directories and
+/// everything below them
+#[derive(Debug, Clone)]
+pub struct PathPattern {
+    pattern: String,
+    regex: Regex,
+}
+
+impl PartialEq for PathPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl PathPattern {
+    /// `re:` followed by a regex matched anywhere in the path, or else a path
+    /// or glob (`*`, `?`, `**`, `~` expanded) that matches the directories it
+    /// names and everything below them
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let source = match pattern.strip_prefix("re:") {
+            Some(regex) => regex.to_string(),
+            None => {
+                let path = expand_home(pattern.trim_end_matches('/'));
+                let glob =
+                    platform::normalize_separators(&platform::path_to_str(&path)).into_owned();
+                format!("^{}(?:/.*)?$", glob_to_regex(&glob))
+            }
+        };
+        let regex =
+            Regex::new(&source).map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?;
+        Ok(Self {
+            pattern: pattern.to_string(),
+            regex,
+        })
+    }
+
+    /// The entry as written in the config
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn matches(&self, dir: &Path) -> bool {
+        self.regex
+            .is_match(&platform::normalize_separators(&platform::path_to_str(dir)))
+    }
+}
+
+/// The first of `patterns` that excludes `dir` from the history
+pub fn excluded_by<'a>(dir: &Path, patterns: &'a [PathPattern]) -> Option<&'a str> {
+    patterns
+        .iter()
+        .find(|p| p.matches(dir))
+        .map(|p| p.pattern.as_str())
+}
+
+/// Directories the user has jumped to, persisted between runs
+#[derive(Debug, Default)]
+pub struct History {
+    entries: HashMap<PathBuf, HistoryEntry>,
+    decay: Decay,
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Location of the history database, following the XDG Base Directory Specification
+pub fn history_file_path() -> PathBuf {
+    platform::data_home().join("jcd").join("history.db")
+}
+
+impl History {
+    /// One `visits<TAB>last visit<TAB>path` line of the database
+    fn parse_line(line: &str) -> Option<(PathBuf, HistoryEntry)> {
+        let mut fields = line.splitn(3, '\t');
+        let (Some(visits), Some(last_visit), Some(path)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return None;
+        };
+        let entry = HistoryEntry {
+            visits: visits.parse().ok()?,
+            last_visit: last_visit.parse().ok()?,
+        };
+        Some((platform::str_to_path(path), entry))
+    }
+
+    /// Parse the database content; malformed lines are skipped
+    pub fn parse(content: &str) -> Self {
+        let entries = content
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(Self::parse_line)
+            .collect();
+
+        Self {
+            entries,
+            decay: Decay::default(),
+        }
+    }
+
+    /// 1-based numbers of the lines `parse` skips as malformed
+    pub fn malformed_lines(content: &str) -> Vec<usize> {
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .filter(|(_, line)| Self::parse_line(line).is_none())
+            .map(|(index, _)| index + 1)
+            .collect()
+    }
+
+    /// Use `decay` instead of the default aging model
+    pub fn with_decay(mut self, decay: Decay) -> Self {
+        self.decay = decay;
+        self
+    }
+
+    /// Load the history database, returning an empty history if it does not exist
+    pub fn load() -> Self {
+        let path = history_file_path();
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                let history = Self::parse(&content);
+                debug!(
+                    "Loaded {} history entries from {}",
+                    history.entries.len(),
+                    path.display()
+                );
+                history
+            }
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Write the database atomically by replacing it with a fully written temp file
+    pub fn save(&self) -> io::Result<()> {
+        let path = history_file_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut paths: Vec<&PathBuf> = self.entries.keys().collect();
+        paths.sort();
+
+        let mut content = String::from(HISTORY_HEADER);
+        content.push('\n');
+        for dir in paths {
+            let entry = &self.entries[dir];
+            content.push_str(&format!(
+                "{}\t{}\t{}\n",
+                entry.visits,
+                entry.last_visit,
+                platform::path_to_str(dir)
+            ));
+        }
+
+        let tmp_path = path.with_extension(format!("db.{}.tmp", std::process::id()));
+        {
+            let mut file = fs::File::create(&tmp_path)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, &path)
+    }
+
+    /// Count a visit to `dir` at time `now`
+    pub fn record(&mut self, dir: &Path, now: u64) {
+        // Paths are stored one per line, so names containing a newline cannot be kept
+        if platform::path_to_str(dir).contains('\n') {
+            return;
+        }
+        let entry = self
+            .entries
+            .entry(dir.to_path_buf())
+            .or_insert(HistoryEntry {
+                visits: 0.0,
+                last_visit: now,
+            });
+        if self.decay.model == DecayModel::HalfLife {
+            // Stored visits are weighed as of the last visit; bring them up to now
+            entry.visits *= self
+                .decay
+                .half_life_factor(now.saturating_sub(entry.last_visit));
+        }
+        entry.visits += 1.0;
+        entry.last_visit = now;
+        if self.decay.model == DecayModel::Zoxide {
+            self.age();
+        }
+    }
+
+    /// zoxide's aging: once the visits add up to more than `max_age`, scale
+    /// them all down to 90% of it and forget directories left below one visit
+    fn age(&mut self) {
+        let total: f64 = self.entries.values().map(|entry| entry.visits).sum();
+        if total <= self.decay.max_age {
+            return;
+        }
+        let factor = 0.9 * self.decay.max_age / total;
+        debug!("Aging history by {:.3}", factor);
+        self.entries.retain(|_, entry| {
+            entry.visits *= factor;
+            entry.visits >= 1.0
+        });
+    }
+
+    /// Add `visits` imported from another tool, keeping the later visit time
+    pub fn import(&mut self, dir: &Path, visits: f64, last_visit: u64) {
+        if platform::path_to_str(dir).contains('\n') {
+            return;
+        }
+        let entry = self
+            .entries
+            .entry(dir.to_path_buf())
+            .or_insert(HistoryEntry {
+                visits: 0.0,
+                last_visit,
+            });
+        entry.visits += visits;
+        entry.last_visit = entry.last_visit.max(last_visit);
+    }
+
+    /// Remove entries whose directory no longer exists and, with `max_age`,
+    /// those not visited for more than that many seconds. Returns the removed
+    /// paths, sorted.
+    pub fn prune(&mut self, max_age: Option<u64>, now: u64) -> Vec<PathBuf> {
+        let mut removed: Vec<PathBuf> = self
+            .entries
+            .iter()
+            .filter(|(dir, entry)| {
+                !dir.is_dir()
+                    || max_age.is_some_and(|age| now.saturating_sub(entry.last_visit) > age)
+            })
+            .map(|(dir, _)| dir.clone())
+            .collect();
+        removed.sort();
+        for dir in &removed {
+            self.entries.remove(dir);
+        }
+        removed
+    }
+
+    /// Every recorded directory with its statistics, in no particular order
+    pub fn entries(&self) -> impl Iterator<Item = (&PathBuf, &HistoryEntry)> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn get(&self, dir: &Path) -> Option<&HistoryEntry> {
+        self.entries.get(dir)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Frecency of a directory: visit count weighted by how recently it was
+    /// visited, as the aging model decides
+    pub fn frecency(&self, dir: &Path, now: u64) -> f64 {
+        self.get(dir).map_or(0.0, |entry| {
+            let age = now.saturating_sub(entry.last_visit);
+            match self.decay.model {
+                DecayModel::Count => entry.visits,
+                DecayModel::HalfLife => entry.visits * self.decay.half_life_factor(age),
+                DecayModel::Buckets | DecayModel::Zoxide => {
+                    entry.visits * Self::bucket_multiplier(age)
+                }
+            }
+        })
+    }
+
+    /// Weight of visits `age` seconds old under the bucketed model
+    fn bucket_multiplier(age: u64) -> f64 {
+        if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        }
+    }
+}
+
+/// Load the history, record one visit under the configured decay model and
+/// write it back. Directories matching `history.exclude` are left out.
+pub fn record_visit(dir: &Path, settings: &Config) -> io::Result<()> {
+    if let Some(pattern) = excluded_by(dir, &settings.history_exclude) {
+        debug!("Not recording {}, excluded by '{}'", dir.display(), pattern);
+        return Ok(());
+    }
+    let mut history = History::load().with_decay(settings.decay);
+    history.record(dir, now_secs());
+    history.save()
+}
+
+/// `secs` since the Unix epoch as a UTC date and time, e.g. `2024-05-01 13:45`
+fn format_utc(secs: u64) -> String {
+    // Civil date from a day count, after Howard Hinnant's days_from_civil inverse
+    let days = (secs / DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    let time = secs % DAY;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        time / HOUR,
+        time % HOUR / 60
+    )
+}
+
+/// `jcd history list`: recorded directories whose path matches `pattern`,
+/// best score first (or most recent, or most visited)
+pub fn list(args: &[String]) -> Result<String, String> {
+    let mut pattern = None;
+    let mut sort = "score";
+    let mut json = false;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => json = true,
+            "--sort" => {
+                sort = match args.get(i + 1).map(String::as_str) {
+                    Some(key @ ("score" | "recent" | "visits")) => key,
+                    _ => return Err("--sort requires score, recent or visits".to_string()),
+                };
+                i += 1;
+            }
+            arg if pattern.is_none() && !arg.starts_with("--") => {
+                pattern = Some(NameMatcher::new(arg, true, PatternSyntax::Text)?);
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+        i += 1;
+    }
+
+    let history = History::load().with_decay(Config::load().decay);
+    let now = now_secs();
+    let mut rows: Vec<(f64, &PathBuf, &HistoryEntry)> = history
+        .entries()
+        .filter(|(dir, _)| {
+            pattern
+                .as_ref()
+                .is_none_or(|p| p.classify(&platform::path_to_str(dir)).is_some())
+        })
+        .map(|(dir, entry)| (history.frecency(dir, now), dir, entry))
+        .collect();
+    rows.sort_by(|a, b| {
+        let order = match sort {
+            "recent" => b.2.last_visit.cmp(&a.2.last_visit),
+            "visits" => b.2.visits.total_cmp(&a.2.visits),
+            _ => b.0.total_cmp(&a.0),
+        };
+        order.then_with(|| a.1.cmp(b.1))
+    });
+
+    if json {
+        let items: Vec<String> = rows
+            .iter()
+            .map(|(score, dir, entry)| {
+                format!(
+                    "{{\"path\":\"{}\",\"visits\":{},\"last_visit\":{},\"score\":{}}}",
+                    json_escape(&platform::path_to_str(dir)),
+                    entry.visits,
+                    entry.last_visit,
+                    score
+                )
+            })
+            .collect();
+        return Ok(format!("[{}]", items.join(",")));
+    }
+    if rows.is_empty() {
+        return Ok("No history entries".to_string());
+    }
+    let mut lines = vec![format!(
+        "{:>8}  {:>7}  {:<16}  PATH",
+        "SCORE", "VISITS", "LAST VISIT (UTC)"
+    )];
+    for (score, dir, entry) in rows {
+        lines.push(format!(
+            "{:>8.2}  {:>7}  {:<16}  {}",
+            score,
+            entry.visits,
+            format_utc(entry.last_visit),
+            dir.display()
+        ));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// `jcd history prune`: forget directories that are gone (and, with `--days`,
+/// those not visited for that long). `--dry-run` only lists them.
+fn prune(args: &[String]) -> Result<String, String> {
+    let mut max_age = None;
+    let mut dry_run = false;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--dry-run" => dry_run = true,
+            "--days" => {
+                let days = args
+                    .get(i + 1)
+                    .and_then(|n| n.parse::<u64>().ok())
+                    .ok_or_else(|| "--days requires a number of days".to_string())?;
+                max_age = Some(days * DAY);
+                i += 1;
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+        i += 1;
+    }
+
+    let mut history = History::load();
+    let total = history.len();
+    let removed = history.prune(max_age, now_secs());
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    for dir in &removed {
+        println!("{} {}", verb, dir.display());
+    }
+    if !dry_run && !removed.is_empty() {
+        history
+            .save()
+            .map_err(|e| format!("Cannot write {}: {}", history_file_path().display(), e))?;
+    }
+    Ok(format!(
+        "{} {} of {} history entries",
+        verb,
+        removed.len(),
+        total
+    ))
+}
+
+pub fn run_command(args: &[String]) -> i32 {
+    let result = match args.first().map(String::as_str) {
+        Some("list") => list(&args[1..]),
+        Some("prune") => prune(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(message) => {
+            println!("{}", message);
+            0
+        }
+        Err(e) if e == USAGE => {
+            eprintln!("{}", USAGE);
+            EXIT_USAGE
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
@@ -0,0 +1,588 @@
+//! Ignore files (`.jcdignore` and friends): locating, parsing and matching
+//! them, and the `jcd ignore` subcommand for managing their patterns.
+
+use crate::{gitignore, platform, EXIT_USAGE, MAX_COMPILED_REGEX_SIZE, MAX_IGNORE_PATTERNS};
+use regex::{Regex, RegexBuilder};
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::debug;
+
+/// The per-user ignore file, `jcd ignore add` writes here by default
+fn user_ignore_file() -> PathBuf {
+    platform::config_home().join("jcd").join("ignore")
+}
+
+/// The ignore file of the current directory
+fn project_ignore_file() -> Option<PathBuf> {
+    env::current_dir().ok().map(|dir| dir.join(".jcdignore"))
+}
+
+/// Get ignore file paths in the order they are applied, lowest precedence first,
+/// following the XDG Base Directory Specification
+pub fn get_ignore_file_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    // 1. System-wide configuration (lowest precedence)
+    paths.push(platform::system_config_dir().join("ignore"));
+
+    // 2. Legacy dotfile for backward compatibility
+    if let Some(home) = platform::home_dir() {
+        paths.push(home.join(".jcdignore"));
+    }
+
+    // 3. User XDG config directory
+    paths.push(user_ignore_file());
+
+    // 4. Project-local ignore file (highest precedence)
+    if let Some(project) = project_ignore_file() {
+        // Searching from $HOME would otherwise read ~/.jcdignore twice
+        if !paths.contains(&project) {
+            paths.push(project);
+        }
+    }
+
+    paths
+}
+
+/// How the lines of an ignore file are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IgnoreSyntax {
+    Glob,  // gitignore-style globs matched against whole directory names (default)
+    Regex, // Unanchored regexes, the original format
+}
+
+impl IgnoreSyntax {
+    fn directive(self) -> &'static str {
+        match self {
+            IgnoreSyntax::Glob => "syntax: glob",
+            IgnoreSyntax::Regex => "syntax: regex",
+        }
+    }
+}
+
+/// Recognize a `syntax: glob` / `syntax: regex` line switching the syntax of the lines that follow
+fn parse_syntax_directive(line: &str) -> Option<IgnoreSyntax> {
+    let value = line.strip_prefix("syntax:")?.trim();
+    match value {
+        "glob" => Some(IgnoreSyntax::Glob),
+        "regex" | "regexp" => Some(IgnoreSyntax::Regex),
+        _ => None,
+    }
+}
+
+/// Syntax in effect after the last line of `content`
+fn final_syntax(content: &str) -> IgnoreSyntax {
+    content
+        .lines()
+        .rev()
+        .find_map(|line| parse_syntax_directive(line.trim()))
+        .unwrap_or(IgnoreSyntax::Glob)
+}
+
+/// Turn a glob line into a regex over a single directory name.
+/// A trailing '/' (directories only) and a leading '/' are accepted and dropped.
+fn ignore_glob_to_regex(line: &str) -> Option<String> {
+    let glob = line.trim_end_matches('/');
+    let glob = glob.strip_prefix('/').unwrap_or(glob);
+    if glob.is_empty() || glob.contains('/') {
+        return None;
+    }
+    Some(format!("^{}$", gitignore::glob_to_regex(glob)))
+}
+
+/// One line of an ignore file
+#[derive(Debug, Clone)]
+pub struct IgnorePattern {
+    regex: Regex,
+    negated: bool, // A `!pattern` line re-allows directories ignored by earlier lines
+    text: String,  // The line as written, for `jcd ignore list/test`
+    line: usize,   // 1-based line number in its file
+}
+
+/// A line of an ignore file that has no effect, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoreProblem {
+    pub line: usize, // 1-based line number
+    pub text: String,
+    pub message: String,
+}
+
+/// Parse ignore patterns from file content
+pub fn parse_ignore_patterns(content: &str) -> Vec<IgnorePattern> {
+    parse_ignore_file(content).0
+}
+
+/// The lines of an ignore file that are skipped when it is loaded: globs
+/// spanning directories, invalid regexes and patterns beyond the limit
+pub fn check_ignore_patterns(content: &str) -> Vec<IgnoreProblem> {
+    parse_ignore_file(content).1
+}
+
+/// The last line of a regex error, which names the problem; the lines
+/// before it quote the pattern
+fn regex_error_summary(e: &regex::Error) -> String {
+    let message = e.to_string();
+    let last = message.lines().last().unwrap_or_default();
+    last.trim_start_matches("error: ").to_string()
+}
+
+fn parse_ignore_file(content: &str) -> (Vec<IgnorePattern>, Vec<IgnoreProblem>) {
+    let mut patterns = Vec::new();
+    let mut problems = Vec::new();
+    let mut syntax = IgnoreSyntax::Glob;
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+
+        // Skip empty lines and comments
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(new_syntax) = parse_syntax_directive(line) {
+            syntax = new_syntax;
+            continue;
+        }
+
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, line),
+        };
+
+        let source = match syntax {
+            IgnoreSyntax::Regex => pattern.to_string(),
+            IgnoreSyntax::Glob => match ignore_glob_to_regex(pattern) {
+                Some(source) => source,
+                None => {
+                    debug!(
+                        "Ignore globs match single directory names, skipping '{}'",
+                        line
+                    );
+                    problems.push(IgnoreProblem {
+                        line: index + 1,
+                        text: line.to_string(),
+                        message: "globs match single directory names and cannot contain '/'"
+                            .to_string(),
+                    });
+                    continue;
+                }
+            },
+        };
+
+        // Try to compile the regex pattern
+        match RegexBuilder::new(&source)
+            .size_limit(MAX_COMPILED_REGEX_SIZE)
+            .build()
+        {
+            Ok(regex) => {
+                if patterns.len() < MAX_IGNORE_PATTERNS {
+                    patterns.push(IgnorePattern {
+                        regex,
+                        negated,
+                        text: line.to_string(),
+                        line: index + 1,
+                    });
+                } else {
+                    debug!("Ignored pattern due to max pattern count (100): '{}'", line);
+                    problems.push(IgnoreProblem {
+                        line: index + 1,
+                        text: line.to_string(),
+                        message: format!("beyond the limit of {} patterns", MAX_IGNORE_PATTERNS),
+                    });
+                }
+            }
+            Err(e) => {
+                debug!("Invalid regex pattern '{}': {}", line, e);
+                // Continue processing other patterns even if one is invalid
+                problems.push(IgnoreProblem {
+                    line: index + 1,
+                    text: line.to_string(),
+                    message: format!("invalid regex: {}", regex_error_summary(&e)),
+                });
+            }
+        }
+    }
+
+    (patterns, problems)
+}
+
+/// A `--exclude` glob, which skips directories for one search like a line
+/// of an ignore file
+pub fn exclude_pattern(glob: &str) -> Result<IgnorePattern, String> {
+    let source = ignore_glob_to_regex(glob).ok_or_else(|| {
+        format!(
+            "--exclude patterns match single directory names and cannot contain '/': '{}'",
+            glob
+        )
+    })?;
+    let regex = RegexBuilder::new(&source)
+        .size_limit(MAX_COMPILED_REGEX_SIZE)
+        .build()
+        .map_err(|e| {
+            format!(
+                "Invalid --exclude pattern '{}': {}",
+                glob,
+                regex_error_summary(&e)
+            )
+        })?;
+    Ok(IgnorePattern {
+        regex,
+        negated: false,
+        text: glob.to_string(),
+        line: 0,
+    })
+}
+
+/// Patterns of every ignore file that exists, in the order the files are applied
+fn load_ignore_files() -> Vec<(PathBuf, Vec<IgnorePattern>)> {
+    let mut files = Vec::new();
+
+    for file_path in get_ignore_file_paths() {
+        debug!("Checking ignore file: {}", file_path.display());
+
+        if let Ok(content) = fs::read_to_string(&file_path) {
+            debug!("Found ignore file: {}", file_path.display());
+            let patterns = parse_ignore_patterns(&content);
+            debug!("Loaded {} ignore patterns", patterns.len());
+            files.push((file_path, patterns));
+        }
+    }
+
+    files
+}
+
+/// Load and merge the ignore patterns of every standard location, in
+/// precedence order so that more specific files come last
+pub fn load_ignore_patterns() -> Vec<IgnorePattern> {
+    let files = load_ignore_files();
+    let found = !files.is_empty();
+    let patterns: Vec<IgnorePattern> = files
+        .into_iter()
+        .flat_map(|(_, patterns)| patterns)
+        .collect();
+
+    if found {
+        debug!("{} ignore patterns in effect", patterns.len());
+    } else {
+        debug!("No ignore file found");
+    }
+    patterns
+}
+
+/// Check if a directory should be ignored based on patterns.
+/// The last matching pattern decides, so `!pattern` lines can re-allow names.
+pub fn should_ignore_directory(dir_name: &str, ignore_patterns: &[IgnorePattern]) -> bool {
+    ignore_patterns
+        .iter()
+        .rev()
+        .find(|pattern| pattern.regex.is_match(dir_name))
+        .is_some_and(|pattern| !pattern.negated)
+}
+
+/// Name of the ignore files honored in the directories a search descends into
+pub const NESTED_IGNORE_FILE: &str = ".jcdignore";
+
+/// Ignore patterns in effect inside one directory: the global patterns plus
+/// every `.jcdignore` found on the way down from the search directory
+#[derive(Debug)]
+pub struct IgnoreScope {
+    global: Arc<[IgnorePattern]>,
+    nested: Vec<Arc<Vec<IgnorePattern>>>, // Outermost file first
+    enabled: bool,                        // Read nested files at all (off with -x)
+}
+
+impl IgnoreScope {
+    /// Scope of the search directory `root`. Its own `.jcdignore` is already
+    /// among the global patterns when it is the current directory.
+    pub fn new(global: &[IgnorePattern], root: &Path, enabled: bool) -> Arc<Self> {
+        let scope = Arc::new(Self {
+            global: global.into(),
+            nested: Vec::new(),
+            enabled,
+        });
+        if project_ignore_file().is_some_and(|project| project == root.join(NESTED_IGNORE_FILE)) {
+            scope
+        } else {
+            scope.enter(root)
+        }
+    }
+
+    /// Scope inside `dir`, a child of this scope's directory
+    pub fn enter(self: &Arc<Self>, dir: &Path) -> Arc<Self> {
+        if !self.enabled {
+            return Arc::clone(self);
+        }
+        let path = dir.join(NESTED_IGNORE_FILE);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Arc::clone(self);
+        };
+        let patterns = parse_ignore_patterns(&content);
+        debug!(
+            "Loaded {} ignore patterns from {}",
+            patterns.len(),
+            path.display()
+        );
+        let mut nested = self.nested.clone();
+        nested.push(Arc::new(patterns));
+        Arc::new(Self {
+            global: Arc::clone(&self.global),
+            nested,
+            enabled: true,
+        })
+    }
+
+    /// Whether a directory named `dir_name` is ignored here. The deepest file
+    /// with a matching pattern decides; the global patterns come last.
+    pub fn is_ignored(&self, dir_name: &str) -> bool {
+        for patterns in self.nested.iter().rev() {
+            if let Some(pattern) = patterns.iter().rev().find(|p| p.regex.is_match(dir_name)) {
+                return !pattern.negated;
+            }
+        }
+        should_ignore_directory(dir_name, &self.global)
+    }
+}
+
+/// Ignore rules that change from one directory to the next as nested files
+/// (`.jcdignore`, `.gitignore`) are picked up
+pub trait ScopedRules {
+    /// Rules in effect inside `dir`, a child of the directory these belong to
+    fn enter(self: &Arc<Self>, dir: &Path) -> Arc<Self>;
+    /// Whether the directory at `path`, a child of this scope, is ignored
+    fn ignores(&self, path: &Path) -> bool;
+}
+
+impl ScopedRules for IgnoreScope {
+    fn enter(self: &Arc<Self>, dir: &Path) -> Arc<Self> {
+        IgnoreScope::enter(self, dir)
+    }
+
+    fn ignores(&self, path: &Path) -> bool {
+        path.file_name()
+            .is_some_and(|name| self.is_ignored(&platform::os_to_str(name)))
+    }
+}
+
+/// Answers "is this path inside an ignored directory?" for arbitrary paths
+/// below a search directory, caching the rules of each directory visited
+pub struct SubtreeFilter<R> {
+    root: PathBuf,
+    dirs: HashMap<PathBuf, Option<Arc<R>>>, // None marks an ignored directory
+}
+
+impl<R: ScopedRules> SubtreeFilter<R> {
+    pub fn new(root: &Path, rules: Arc<R>) -> Self {
+        let mut dirs = HashMap::new();
+        dirs.insert(root.to_path_buf(), Some(rules));
+        Self {
+            root: root.to_path_buf(),
+            dirs,
+        }
+    }
+
+    /// Rules in effect inside `dir`, or None if `dir` or a parent is ignored
+    fn rules_for(&mut self, dir: &Path) -> Option<Arc<R>> {
+        if let Some(rules) = self.dirs.get(dir) {
+            return rules.clone();
+        }
+        let rules = match dir.parent() {
+            Some(parent) if dir.starts_with(&self.root) => self
+                .rules_for(parent)
+                .filter(|rules| !rules.ignores(dir))
+                .map(|rules| rules.enter(dir)),
+            _ => None,
+        };
+        self.dirs.insert(dir.to_path_buf(), rules.clone());
+        rules
+    }
+
+    /// Whether `path` or one of its parents below the root is ignored
+    pub fn is_ignored(&mut self, path: &Path) -> bool {
+        let Some(parent) = path.parent() else {
+            return false;
+        };
+        match self.rules_for(parent) {
+            Some(rules) => rules.ignores(path),
+            None => true,
+        }
+    }
+}
+
+pub const USAGE: &str = "Usage: jcd ignore add [--project] [--regex] <pattern> | remove [--project] <pattern> | list | test <name>";
+
+/// Options shared by `jcd ignore add` and `jcd ignore remove`
+struct EditArgs {
+    file: PathBuf,
+    syntax: IgnoreSyntax,
+    pattern: String,
+}
+
+fn parse_edit_args(args: &[String]) -> Result<EditArgs, String> {
+    let mut file = None;
+    let mut syntax = IgnoreSyntax::Glob;
+    let mut pattern = None;
+    for arg in args {
+        match arg.as_str() {
+            "--project" => {
+                file = Some(project_ignore_file().ok_or("Cannot get current directory")?);
+            }
+            "--regex" => syntax = IgnoreSyntax::Regex,
+            _ if pattern.is_none() => pattern = Some(arg.trim().to_string()),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let pattern = pattern.filter(|p| !p.is_empty()).ok_or(USAGE)?;
+    Ok(EditArgs {
+        file: file.unwrap_or_else(user_ignore_file),
+        syntax,
+        pattern,
+    })
+}
+
+/// `jcd ignore add`: append a pattern, switching the file's syntax if needed
+fn add(args: &EditArgs) -> Result<String, String> {
+    let line = args.pattern.as_str();
+    if parse_ignore_patterns(&format!("{}\n{}", args.syntax.directive(), line)).is_empty() {
+        return Err(format!("Invalid ignore pattern '{}'", line));
+    }
+
+    let content = fs::read_to_string(&args.file).unwrap_or_default();
+    if content.lines().any(|existing| existing.trim() == line) {
+        return Ok(format!("'{}' is already in {}", line, args.file.display()));
+    }
+
+    let mut updated = content.clone();
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    if final_syntax(&content) != args.syntax {
+        updated.push_str(args.syntax.directive());
+        updated.push('\n');
+    }
+    updated.push_str(line);
+    updated.push('\n');
+
+    if let Some(parent) = args.file.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Cannot create {}: {}", parent.display(), e))?;
+    }
+    fs::write(&args.file, updated)
+        .map_err(|e| format!("Cannot write {}: {}", args.file.display(), e))?;
+    Ok(format!("Added '{}' to {}", line, args.file.display()))
+}
+
+/// `jcd ignore remove`: drop every line equal to the pattern
+fn remove(args: &EditArgs) -> Result<String, String> {
+    let content = fs::read_to_string(&args.file)
+        .map_err(|e| format!("Cannot read {}: {}", args.file.display(), e))?;
+    let kept: Vec<&str> = content
+        .lines()
+        .filter(|existing| existing.trim() != args.pattern)
+        .collect();
+    if kept.len() == content.lines().count() {
+        return Err(format!(
+            "'{}' not found in {}",
+            args.pattern,
+            args.file.display()
+        ));
+    }
+
+    let mut updated = kept.join("\n");
+    if !updated.is_empty() {
+        updated.push('\n');
+    }
+    fs::write(&args.file, updated)
+        .map_err(|e| format!("Cannot write {}: {}", args.file.display(), e))?;
+    Ok(format!(
+        "Removed '{}' from {}",
+        args.pattern,
+        args.file.display()
+    ))
+}
+
+/// `jcd ignore list`: every pattern in effect, grouped by file
+fn list() -> i32 {
+    let files = load_ignore_files();
+    if files.is_empty() {
+        println!("No ignore files found");
+        return 0;
+    }
+    for (path, patterns) in files {
+        println!("{}:", path.display());
+        for pattern in patterns {
+            println!("  {:>4}  {}", pattern.line, pattern.text);
+        }
+    }
+    0
+}
+
+/// `jcd ignore test`: report which rule decides a directory name.
+/// Exits 0 when the name is ignored and 1 when it is not, like `git check-ignore`.
+fn test(name: &str) -> i32 {
+    let name = Path::new(name)
+        .file_name()
+        .map_or_else(|| name.to_string(), |n| n.to_string_lossy().to_string());
+    let files = load_ignore_files();
+
+    // Same rule as should_ignore_directory: the last matching pattern decides
+    let deciding = files
+        .iter()
+        .flat_map(|(path, patterns)| patterns.iter().map(move |p| (path, p)))
+        .rev()
+        .find(|(_, pattern)| pattern.regex.is_match(&name));
+    match deciding {
+        Some((path, pattern)) if !pattern.negated => {
+            println!(
+                "'{}' is ignored by '{}' ({}:{})",
+                name,
+                pattern.text,
+                path.display(),
+                pattern.line
+            );
+            0
+        }
+        Some((path, pattern)) => {
+            println!(
+                "'{}' is not ignored: re-allowed by '{}' ({}:{})",
+                name,
+                pattern.text,
+                path.display(),
+                pattern.line
+            );
+            1
+        }
+        None => {
+            println!("'{}' is not ignored", name);
+            1
+        }
+    }
+}
+
+/// `jcd ignore add|remove|list|test`
+pub fn run_command(args: &[String]) -> i32 {
+    let result = match args.first().map(String::as_str) {
+        Some("list") if args.len() == 1 => return list(),
+        Some("test") if args.len() == 2 => return test(&args[1]),
+        Some("add") => parse_edit_args(&args[1..]).and_then(|a| add(&a)),
+        Some("remove") => parse_edit_args(&args[1..]).and_then(|a| remove(&a)),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(message) => {
+            println!("{}", message);
+            0
+        }
+        Err(e) if e == USAGE => {
+            eprintln!("{}", USAGE);
+            EXIT_USAGE
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! The `jcd import` subcommand: seeds the history database from the data of
+//! other directory jumpers, so their learned rankings carry over. Visits are
+//! added to what jcd already recorded; directories that no longer exist are
+//! skipped.
+
+use jcd::{
+    config::Config,
+    history::{self, History},
+    platform, EXIT_USAGE,
+};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+use tracing::debug;
+
+pub const USAGE: &str = "Usage: jcd import zoxide|autojump|z [--file <path>]";
+
+/// Database format version written by zoxide 0.8 and later
+const ZOXIDE_VERSION: u32 = 3;
+
+/// One directory read from another tool's data
+#[derive(Debug)]
+struct Imported {
+    path: PathBuf,
+    visits: f64,
+    last_visit: u64, // seconds since the Unix epoch
+}
+
+/// zoxide's database: `$_ZO_DATA_DIR/db.zo`, by default in the data directory
+fn zoxide_file() -> PathBuf {
+    let dir = env::var_os("_ZO_DATA_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| platform::data_home().join("zoxide"));
+    dir.join("db.zo")
+}
+
+/// autojump's data file: `autojump.txt` in its data directory
+fn autojump_file() -> PathBuf {
+    let dir = if cfg!(target_os = "macos") && env::var_os("XDG_DATA_HOME").is_none() {
+        platform::home_dir()
+            .unwrap_or_default()
+            .join("Library")
+            .join("autojump")
+    } else {
+        platform::data_home().join("autojump")
+    };
+    dir.join("autojump.txt")
+}
+
+/// rupa/z's data file: `$_Z_DATA`, by default `~/.z`
+fn z_file() -> PathBuf {
+    env::var_os("_Z_DATA")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| platform::home_dir().unwrap_or_default().join(".z"))
+}
+
+/// Little-endian reader for the bincode encoding zoxide uses
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if self.data.len() < n {
+            return Err("unexpected end of file".to_string());
+        }
+        let (head, rest) = self.data.split_at(n);
+        self.data = rest;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn f64(&mut self) -> Result<f64, String> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<&'a str, String> {
+        let len = usize::try_from(self.u64()?).map_err(|_| "string too long".to_string())?;
+        std::str::from_utf8(self.take(len)?).map_err(|_| "path is not valid UTF-8".to_string())
+    }
+}
+
+/// Parse a zoxide database: a version number, then every directory with its
+/// rank (which grows by one per visit) and last access time
+fn parse_zoxide(data: &[u8]) -> Result<Vec<Imported>, String> {
+    let mut reader = Reader { data };
+    let version = reader.u32()?;
+    if version != ZOXIDE_VERSION {
+        return Err(format!("unsupported zoxide database version {}", version));
+    }
+    let count = reader.u64()?;
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let path = platform::str_to_path(reader.string()?);
+        let visits = reader.f64()?;
+        let last_visit = reader.u64()?;
+        entries.push(Imported {
+            path,
+            visits,
+            last_visit,
+        });
+    }
+    Ok(entries)
+}
+
+/// Parse autojump's `weight<TAB>path` lines. autojump gives a new directory a
+/// weight of 10 and grows it as sqrt(weight² + 10²) per visit, so weight/10 is
+/// about the square root of the visit count. It keeps no visit times; the file
+/// was last written on the latest visit, which stands in for every entry.
+fn parse_autojump(data: &[u8], last_visit: u64) -> Result<Vec<Imported>, String> {
+    let content = std::str::from_utf8(data).map_err(|_| "file is not valid UTF-8".to_string())?;
+    let mut entries = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let (weight, path) = line
+            .split_once('\t')
+            .ok_or_else(|| format!("malformed line '{}'", line))?;
+        let weight: f64 = weight
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid weight in line '{}'", line))?;
+        entries.push(Imported {
+            path: platform::str_to_path(path),
+            visits: (weight / 10.0).powi(2),
+            last_visit,
+        });
+    }
+    Ok(entries)
+}
+
+/// Parse z's `path|rank|timestamp` lines. The rank grows by one per visit, like
+/// jcd's visit count. Paths may contain '|', so the fields are split from the right.
+fn parse_z(data: &[u8]) -> Result<Vec<Imported>, String> {
+    let content = std::str::from_utf8(data).map_err(|_| "file is not valid UTF-8".to_string())?;
+    let mut entries = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let mut fields = line.rsplitn(3, '|');
+        let (Some(timestamp), Some(rank), Some(path)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(format!("malformed line '{}'", line));
+        };
+        let (Ok(visits), Ok(last_visit)) = (rank.parse::<f64>(), timestamp.parse::<u64>()) else {
+            return Err(format!("invalid rank or time in line '{}'", line));
+        };
+        entries.push(Imported {
+            path: platform::str_to_path(path),
+            visits,
+            last_visit,
+        });
+    }
+    Ok(entries)
+}
+
+/// Add the imported visits to the history database
+fn import(entries: Vec<Imported>, source: &Path) -> Result<String, String> {
+    let exclude = Config::load().history_exclude;
+    let mut history = History::load();
+    let mut imported = 0;
+    let mut missing = 0; // or never visited
+    let mut excluded = 0;
+    for entry in entries {
+        if history::excluded_by(&entry.path, &exclude).is_some() {
+            debug!("Not importing excluded {}", entry.path.display());
+            excluded += 1;
+            continue;
+        }
+        if !entry.path.is_dir() || !entry.visits.is_finite() || entry.visits <= 0.0 {
+            debug!("Not importing {}", entry.path.display());
+            missing += 1;
+            continue;
+        }
+        history.import(&entry.path, entry.visits, entry.last_visit);
+        imported += 1;
+    }
+    history.save().map_err(|e| {
+        format!(
+            "Cannot write {}: {}",
+            history::history_file_path().display(),
+            e
+        )
+    })?;
+
+    let mut message = format!(
+        "Imported {} directories from {}",
+        imported,
+        source.display()
+    );
+    if missing > 0 {
+        message.push_str(&format!(", skipped {} missing", missing));
+    }
+    if excluded > 0 {
+        message.push_str(&format!(", skipped {} excluded", excluded));
+    }
+    Ok(message)
+}
+
+/// When `file` was last written, in seconds since the Unix epoch
+fn modified_secs(file: &Path) -> u64 {
+    fs::metadata(file)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or_else(history::now_secs, |d| d.as_secs())
+}
+
+fn run(args: &[String]) -> Result<String, String> {
+    let (source, file) = match args {
+        [source] => (source.as_str(), None),
+        [source, flag, file] if flag == "--file" => {
+            (source.as_str(), Some(jcd::query::expand_home(file)))
+        }
+        _ => return Err(USAGE.to_string()),
+    };
+    let file = match source {
+        "zoxide" => file.unwrap_or_else(zoxide_file),
+        "autojump" => file.unwrap_or_else(autojump_file),
+        "z" => file.unwrap_or_else(z_file),
+        _ => return Err(USAGE.to_string()),
+    };
+
+    let data = fs::read(&file).map_err(|e| format!("Cannot read {}: {}", file.display(), e))?;
+    let entries = match source {
+        "zoxide" => parse_zoxide(&data),
+        "autojump" => parse_autojump(&data, modified_secs(&file)),
+        _ => parse_z(&data),
+    }
+    .map_err(|e| format!("{}: {}", file.display(), e))?;
+    import(entries, &file)
+}
+
+pub fn run_command(args: &[String]) -> i32 {
+    match run(args) {
+        Ok(message) => {
+            println!("{}", message);
+            0
+        }
+        Err(e) if e == USAGE => {
+            eprintln!("{}", USAGE);
+            EXIT_USAGE
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
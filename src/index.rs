@@ -0,0 +1,471 @@
+//! The directory index: a snapshot of the tree written by `jcd index build`,
+//! or the live one held by `jcd daemon`, answering downward searches
+//! without walking the tree.
+
+use crate::{
+    config::Config,
+    daemon, finalize_matches,
+    gitignore::GitIgnore,
+    history::now_secs,
+    ignore::{
+        load_ignore_patterns, should_ignore_directory, IgnorePattern, IgnoreScope, SubtreeFilter,
+    },
+    matcher::{NameMatch, NameMatcher},
+    platform, DirectoryMatch, MatchQuality, SearchConfig, EXIT_USAGE,
+};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    io::{self, Write},
+    ops::Bound,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+use tracing::{debug, debug_span};
+
+const INDEX_HEADER: &str = "# jcd index v1";
+pub const USAGE: &str = "Usage: jcd index build [root...] | status | clear";
+
+/// Location of the index, following the XDG Base Directory Specification
+pub fn index_file_path() -> PathBuf {
+    platform::cache_home().join("jcd").join("index.db")
+}
+
+/// Modification time in nanoseconds since the Unix epoch
+pub fn mtime_nanos(path: &Path) -> Option<u64> {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+}
+
+/// Snapshot of the directory trees under the configured roots
+#[derive(Debug, Default)]
+pub struct Index {
+    built: u64,   // seconds since the Unix epoch
+    depth: usize, // levels below each root that were recorded
+    roots: Vec<PathBuf>,
+    dirs: BTreeMap<PathBuf, u64>, // directory -> mtime when it was indexed
+}
+
+impl Index {
+    /// Walk every root breadth-first, skipping ignored directories like the live search
+    pub fn build(roots: &[PathBuf], depth: usize, ignore_patterns: &[IgnorePattern]) -> Self {
+        let mut index = Self {
+            built: now_secs(),
+            depth,
+            ..Self::default()
+        };
+
+        for root in roots {
+            let Ok(root) = fs::canonicalize(root) else {
+                eprintln!("Warning: Skipping missing index root {}", root.display());
+                continue;
+            };
+            index.roots.push(root.clone());
+            index.insert_tree(&root, ignore_patterns);
+        }
+
+        index
+    }
+
+    /// Record `dir` and everything below it that is within the index depth.
+    /// Returns the directories that were added.
+    pub fn insert_tree(&mut self, dir: &Path, ignore_patterns: &[IgnorePattern]) -> Vec<PathBuf> {
+        let mut added = Vec::new();
+        let Some(root) = self.roots.iter().find(|root| dir.starts_with(root)) else {
+            return added;
+        };
+        let start_level = dir.components().count() - root.components().count();
+
+        let mut queue = VecDeque::from([(dir.to_path_buf(), start_level)]);
+        while let Some((dir, level)) = queue.pop_front() {
+            let Some(mtime) = mtime_nanos(&dir) else {
+                continue;
+            };
+            self.dirs.insert(dir.clone(), mtime);
+            added.push(dir.clone());
+            if level >= self.depth {
+                continue;
+            }
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    continue;
+                }
+                if should_ignore_directory(
+                    &platform::os_to_str(&entry.file_name()),
+                    ignore_patterns,
+                ) {
+                    continue;
+                }
+                queue.push_back((entry.path(), level + 1));
+            }
+        }
+        added
+    }
+
+    /// Forget `dir` and everything below it
+    pub fn remove_tree(&mut self, dir: &Path) {
+        let doomed: Vec<PathBuf> = self.descendants(dir).cloned().collect();
+        for path in doomed {
+            self.dirs.remove(&path);
+        }
+        self.dirs.remove(dir);
+    }
+
+    pub fn dir_count(&self) -> usize {
+        self.dirs.len()
+    }
+
+    pub fn directories(&self) -> impl Iterator<Item = &PathBuf> {
+        self.dirs.keys()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Seconds since the index was built
+    pub fn age_secs(&self) -> u64 {
+        now_secs().saturating_sub(self.built)
+    }
+
+    /// Parse the index file; None if it is not in the expected format
+    pub fn parse(content: &str) -> Option<Self> {
+        let mut lines = content.lines();
+        if lines.next()? != INDEX_HEADER {
+            return None;
+        }
+
+        let mut index = Self::default();
+        for line in lines {
+            let mut fields = line.splitn(3, '\t');
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some("built"), Some(secs), None) => index.built = secs.parse().ok()?,
+                (Some("depth"), Some(depth), None) => index.depth = depth.parse().ok()?,
+                (Some("root"), Some(root), None) => index.roots.push(platform::str_to_path(root)),
+                (Some("dir"), Some(mtime), Some(path)) => {
+                    if let Ok(mtime) = mtime.parse() {
+                        index.dirs.insert(platform::str_to_path(path), mtime);
+                    }
+                }
+                _ => {} // Unknown or malformed lines are skipped
+            }
+        }
+        Some(index)
+    }
+
+    /// Load the index if it exists and is younger than `max_age_secs`
+    pub fn load_fresh(max_age_secs: u64) -> Option<Self> {
+        let path = index_file_path();
+        let index = Self::parse(&fs::read_to_string(&path).ok()?)?;
+        let age = index.age_secs();
+        if age > max_age_secs {
+            debug!(
+                "Index {} is {}s old, ignoring (run 'jcd index build')",
+                path.display(),
+                age
+            );
+            return None;
+        }
+        debug!(
+            "Loaded index with {} directories from {}",
+            index.dir_count(),
+            path.display()
+        );
+        Some(index)
+    }
+
+    /// Write the index atomically by replacing it with a fully written temp file
+    pub fn save(&self) -> io::Result<()> {
+        let path = index_file_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut content = format!(
+            "{}\nbuilt\t{}\ndepth\t{}\n",
+            INDEX_HEADER, self.built, self.depth
+        );
+        for root in &self.roots {
+            content.push_str(&format!("root\t{}\n", platform::path_to_str(root)));
+        }
+        for (dir, mtime) in &self.dirs {
+            // Paths are stored one per line, so names containing a newline cannot be kept
+            let dir = platform::path_to_str(dir);
+            if !dir.contains('\n') {
+                content.push_str(&format!("dir\t{}\t{}\n", mtime, dir));
+            }
+        }
+
+        let tmp_path = path.with_extension(format!("db.{}.tmp", std::process::id()));
+        {
+            let mut file = fs::File::create(&tmp_path)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, &path)
+    }
+
+    /// Matching directories up to `reach` levels below `current_dir`. Returns None
+    /// when the index cannot answer reliably: the directory is outside the roots,
+    /// deeper than the index reaches, or (with `check_mtime`) its contents changed
+    /// since it was indexed.
+    pub fn candidates(
+        &self,
+        current_dir: &Path,
+        reach: usize,
+        matcher: &NameMatcher,
+        check_mtime: bool,
+    ) -> Option<Vec<Candidate>> {
+        let root = self
+            .roots
+            .iter()
+            .find(|root| current_dir.starts_with(root))?;
+        let base_depth = current_dir.components().count() - root.components().count();
+        if base_depth + reach > self.depth {
+            debug!("Index does not reach {} levels below here", reach);
+            return None;
+        }
+        if check_mtime && self.dirs.get(current_dir) != mtime_nanos(current_dir).as_ref() {
+            debug!("{} changed since indexing", current_dir.display());
+            return None;
+        }
+
+        let mut candidates = Vec::new();
+        for path in self.descendants(current_dir) {
+            let depth = path.components().count() - current_dir.components().count();
+            if depth > reach {
+                continue;
+            }
+            let Some(name) = path.file_name() else {
+                continue;
+            };
+            if let Some(kind) = matcher.classify(&platform::os_to_str(name)) {
+                candidates.push(Candidate {
+                    path: path.clone(),
+                    depth,
+                    kind,
+                });
+            }
+        }
+        Some(candidates)
+    }
+
+    /// Every indexed directory strictly below `dir`
+    fn descendants<'a>(&'a self, dir: &'a Path) -> impl Iterator<Item = &'a PathBuf> + 'a {
+        // Descendants sort directly after their ancestor
+        self.dirs
+            .range::<Path, _>((Bound::Excluded(dir), Bound::Unbounded))
+            .map(|(path, _)| path)
+            .take_while(move |path| path.starts_with(dir))
+    }
+}
+
+/// A directory below the search directory whose name matches the pattern
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub path: PathBuf,
+    pub depth: usize,
+    pub kind: NameMatch,
+}
+
+/// Turn index candidates into the result of `search_down_breadth_first_all`:
+/// skip ignored subtrees (nested `.jcdignore` files too, and git-ignored ones
+/// with --gitignore) and, with --same-filesystem, other filesystems; verify each
+/// entry still exists and apply the same early stop on good immediate matches.
+fn finish_down_matches(
+    current_dir: &Path,
+    candidates: Vec<Candidate>,
+    config: &SearchConfig,
+    ignore_patterns: &[IgnorePattern],
+) -> Vec<DirectoryMatch> {
+    let mut matches = Vec::new();
+    let scope = IgnoreScope::new(ignore_patterns, current_dir, config.nested_ignore);
+    let mut ignored = SubtreeFilter::new(current_dir, scope);
+    let mut gitignore = config
+        .gitignore
+        .then(|| SubtreeFilter::new(current_dir, GitIgnore::for_dir(current_dir)));
+    // Like the walk, report mount points but nothing inside them
+    let device = config
+        .same_filesystem
+        .then(|| platform::device_of(current_dir))
+        .flatten();
+    config.stats.indexed(candidates.len());
+    for candidate in candidates {
+        if !candidate.path.starts_with(current_dir) {
+            continue;
+        }
+        if ignored.is_ignored(&candidate.path) {
+            config.stats.ignored();
+            continue;
+        }
+        if let Some(filter) = gitignore.as_mut() {
+            if filter.is_ignored(&candidate.path) {
+                config.stats.ignored();
+                continue;
+            }
+        }
+        if let Some(device) = device {
+            if candidate.path.parent().and_then(platform::device_of) != Some(device) {
+                continue;
+            }
+        }
+        if !candidate.path.is_dir() {
+            debug!("Dropping stale index entry {}", candidate.path.display());
+            continue;
+        }
+        matches.push(DirectoryMatch::new(
+            candidate.path,
+            candidate.depth as i32,
+            MatchQuality::down(candidate.kind),
+        ));
+    }
+
+    let has_good_immediate = matches.iter().any(|m| {
+        m.depth_from_current == 1
+            && matches!(
+                m.match_quality,
+                MatchQuality::ExactDown | MatchQuality::PrefixDown
+            )
+    });
+    if has_good_immediate && config.allows_early_stop() {
+        matches.retain(|m| m.depth_from_current == 1);
+    }
+
+    finalize_matches(matches)
+}
+
+/// Where downward searches can be answered without walking the tree
+#[derive(Debug)]
+pub enum IndexSource {
+    File(Index),     // Snapshot written by `jcd index build`
+    Daemon(PathBuf), // Live index held by `jcd daemon`, reached over this socket
+}
+
+impl IndexSource {
+    /// Prefer a running daemon, then a fresh index file
+    pub fn detect(settings: &Config) -> Option<Self> {
+        if let Some(socket) = daemon::running_socket() {
+            debug!("Using jcd daemon at {}", socket.display());
+            return Some(Self::Daemon(socket));
+        }
+        Index::load_fresh(settings.index_max_age_secs).map(Self::File)
+    }
+
+    /// Answer a downward search like `search_down_breadth_first_all`, or None
+    /// if the tree has to be walked
+    pub fn search_down(
+        &self,
+        current_dir: &Path,
+        search_term: &str,
+        config: &SearchConfig,
+        ignore_patterns: &[IgnorePattern],
+    ) -> Option<Vec<DirectoryMatch>> {
+        let _span = debug_span!("index").entered();
+        // The live search reports matches one level below its deepest queued directory
+        let reach = config.max_depth + 1;
+        // Both know names, not the paths --full-path matches against
+        if config.full_path {
+            return None;
+        }
+        let candidates = match self {
+            Self::File(index) => index.candidates(
+                current_dir,
+                reach,
+                &NameMatcher::or_never(search_term, config),
+                true,
+            )?,
+            // The daemon compares names with their accents
+            Self::Daemon(_) if config.ignore_accents => return None,
+            Self::Daemon(socket) => daemon::query(socket, current_dir, reach, search_term, config)?,
+        };
+        Some(finish_down_matches(
+            current_dir,
+            candidates,
+            config,
+            ignore_patterns,
+        ))
+    }
+}
+
+/// `jcd index build [root...]`, `jcd index status` and `jcd index clear`
+pub fn run_command(args: &[String]) -> i32 {
+    let settings = Config::load();
+    let path = index_file_path();
+
+    match args.first().map(String::as_str) {
+        Some("build") => {
+            let roots: Vec<PathBuf> = if args.len() > 1 {
+                args[1..]
+                    .iter()
+                    .map(|a| crate::query::expand_home(a))
+                    .collect()
+            } else {
+                settings.index_roots.clone()
+            };
+            if roots.is_empty() {
+                eprintln!("Error: No index roots configured (set index.roots in config.toml)");
+                return 1;
+            }
+            let index = Index::build(&roots, settings.index_max_depth, &load_ignore_patterns());
+            if let Err(e) = index.save() {
+                eprintln!("Error: Cannot write index {}: {}", path.display(), e);
+                return 1;
+            }
+            println!(
+                "Indexed {} directories under {} root(s) into {}",
+                index.dir_count(),
+                index.roots.len(),
+                path.display()
+            );
+            0
+        }
+        Some("status") => {
+            let Some(index) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| Index::parse(&content))
+            else {
+                println!("No index at {}", path.display());
+                return 1;
+            };
+            let age = index.age_secs();
+            println!("Index:       {}", path.display());
+            println!("Directories: {}", index.dir_count());
+            println!("Depth:       {}", index.depth);
+            for root in &index.roots {
+                println!("Root:        {}", root.display());
+            }
+            println!(
+                "Built:       {}s ago ({})",
+                age,
+                if age > settings.index_max_age_secs {
+                    "stale, not used"
+                } else {
+                    "fresh"
+                }
+            );
+            0
+        }
+        Some("clear") => match fs::remove_file(&path) {
+            Ok(()) => 0,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => {
+                eprintln!("Error: Cannot remove index {}: {}", path.display(), e);
+                1
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            EXIT_USAGE
+        }
+    }
+}
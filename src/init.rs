@@ -0,0 +1,107 @@
+//! The `jcd init` subcommand: prints the shell integration, ready for `eval`,
+//! with the path of the running binary filled in. Installing jcd then needs
+//! one line in the shell's startup file instead of a copied script. `--cmd`
+//! binds the function, and its completion, to another name; `--hook` adds a
+//! hook that records directories reached without jcd.
+
+use jcd::{platform, EXIT_USAGE};
+use std::env;
+
+pub const USAGE: &str = "Usage: jcd init bash|zsh [--cmd <name>] [--hook none|prompt|pwd]";
+
+pub const BASH_SCRIPT: &str = include_str!("jcd_function.sh");
+pub const ZSH_SCRIPT: &str = include_str!("jcd_init.zsh");
+
+/// `s` as a single-quoted shell word
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// The running binary, or plain `jcd` to be found on PATH when it is unknown
+pub fn binary_path() -> String {
+    env::current_exe()
+        .map(|exe| platform::path_to_str(&exe).into_owned())
+        .unwrap_or_else(|_| "jcd".to_string())
+}
+
+/// Whether `name` can name a shell function in both bash and zsh
+pub fn valid_command_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Options of `jcd init` after the shell name
+struct InitOptions {
+    command: String,
+    hook: &'static str,
+}
+
+fn parse_options(args: &[String]) -> Result<InitOptions, String> {
+    let mut options = InitOptions {
+        command: "jcd".to_string(),
+        hook: "none",
+    };
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1).ok_or_else(|| USAGE.to_string())?;
+        match args[i].as_str() {
+            "--cmd" if valid_command_name(value) => options.command = value.clone(),
+            "--cmd" => return Err(format!("'{}' cannot be used as a command name", value)),
+            "--hook" => {
+                options.hook = match value.as_str() {
+                    "none" => "none",
+                    "prompt" => "prompt",
+                    "pwd" => "pwd",
+                    _ => return Err("--hook must be none, prompt or pwd".to_string()),
+                }
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+        i += 2;
+    }
+    Ok(options)
+}
+
+fn script(shell: &str, options: &InitOptions) -> Result<String, String> {
+    let binary = shell_quote(&binary_path());
+    match shell {
+        // The sourced script finds the binary through JCD_BINARY and reads its
+        // name and hook from _JCD_CMD and _JCD_HOOK
+        "bash" => Ok(format!(
+            "if [[ -z \"${{JCD_BINARY:-}}\" ]]; then JCD_BINARY={}; fi\n_JCD_CMD={}\n_JCD_HOOK={}\n{}",
+            binary,
+            shell_quote(&options.command),
+            options.hook,
+            BASH_SCRIPT
+        )),
+        "zsh" => Ok(ZSH_SCRIPT
+            .replace("@JCD_BINARY@", &binary)
+            .replace("@JCD_CMD@", &options.command)
+            .replace("@JCD_HOOK@", options.hook)),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+pub fn run_command(args: &[String]) -> i32 {
+    let result = match args.split_first() {
+        Some((shell, rest)) => parse_options(rest).and_then(|options| script(shell, &options)),
+        None => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(script) => {
+            print!("{}", script);
+            0
+        }
+        Err(e) if e == USAGE => {
+            eprintln!("{}", USAGE);
+            EXIT_USAGE
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
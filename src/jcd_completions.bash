@@ -0,0 +1,99 @@
+# Completion of jcd's own flags and subcommands, printed by
+# `jcd completions bash`. Load it after the jcd shell function:
+#
+#   eval "$(jcd completions bash)"
+#
+# Flags, subcommands, their options and bookmark names are completed here;
+# directory patterns are still handed to the shell function's completion.
+
+_jcd_cli_binary() {
+    echo "${JCD_BINARY:-@JCD_BINARY@}"
+}
+
+# Names of the saved bookmarks, one per line
+_jcd_cli_bookmarks() {
+    "$(_jcd_cli_binary)" bookmark list 2>/dev/null | sed -n 's/^@\([^ ]*\).*/\1/p'
+}
+
+_jcd_cli_complete() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    local command="${COMP_WORDS[1]}"
+    local words=""
+    COMPREPLY=()
+
+    # Values of options
+    case "$prev" in
+        --add|--from|--file)
+            COMPREPLY=($(compgen -d -- "$cur"))
+            return
+            ;;
+        --rank)
+            compopt -o nospace
+            COMPREPLY=($(compgen -W "@JCD_WEIGHTS@" -- "$cur"))
+            return
+            ;;
+        --timeout|--max-matches|--min-depth|--tier-limits|--chosen-for|--days|--cmd)
+            return
+            ;;
+        --sort) words="score recent visits" ;;
+        --hook) words="none prompt pwd" ;;
+        --color) words="auto always never" ;;
+    esac
+
+    # Subcommands: their verbs, then the options and arguments of each verb
+    if [[ -z "$words" ]] && (( COMP_CWORD >= 2 )); then
+        case "$command" in
+            @JCD_SUBCOMMAND_PATTERN@)
+                if (( COMP_CWORD == 2 )); then
+                    case "$command" in
+@JCD_VERBS@
+                    esac
+                else
+                    case "$command ${COMP_WORDS[2]}" in
+                        "bookmark rm")
+                            (( COMP_CWORD == 3 )) && words="$(_jcd_cli_bookmarks)"
+                            ;;
+                        "bookmark add")
+                            (( COMP_CWORD == 4 )) && COMPREPLY=($(compgen -d -- "$cur"))
+                            return
+                            ;;
+                        "pin add" | "pin rm")
+                            (( COMP_CWORD == 3 )) && COMPREPLY=($(compgen -d -- "$cur"))
+                            return
+                            ;;
+                        "ignore add" | "ignore remove") words="--project --regex" ;;
+                        "history list") words="--sort --json" ;;
+                        "history prune") words="--days --dry-run" ;;
+                        "config get" | "config set")
+                            (( COMP_CWORD == 3 )) && words="@JCD_CONFIG_KEYS@"
+                            ;;
+                        "import "*) words="--file" ;;
+                        "init "*) words="--cmd --hook" ;;
+                        "completions "*) words="--cmd" ;;
+                    esac
+                fi
+                COMPREPLY=($(compgen -W "$words" -- "$cur"))
+                return
+                ;;
+        esac
+    fi
+
+    if [[ -n "$words" ]]; then
+        COMPREPLY=($(compgen -W "$words" -- "$cur"))
+    elif [[ "$cur" == -* ]]; then
+        COMPREPLY=($(compgen -W "@JCD_FLAGS@" -- "$cur"))
+    elif [[ "$cur" == @* ]]; then
+        COMPREPLY=($(compgen -P @ -W "$(_jcd_cli_bookmarks)" -- "${cur#@}"))
+    elif (( COMP_CWORD == 1 )) && [[ -n "$cur" ]]; then
+        COMPREPLY=($(compgen -W "@JCD_SUBCOMMANDS@" -- "$cur"))
+    fi
+
+    # Anything else is a directory pattern for the shell function to complete
+    if (( ${#COMPREPLY[@]} == 0 )) && declare -F _jcd_tab_complete >/dev/null; then
+        compopt -o nospace
+        _jcd_tab_complete
+    fi
+}
+
+complete -F _jcd_cli_complete @JCD_CMD@
@@ -0,0 +1,72 @@
+# jcd for PowerShell. Dot-source this file from your $PROFILE:
+#
+#   . C:\path\to\jcd_function.ps1
+#
+# A program cannot change the directory of the shell that started it, so this
+# function runs jcd.exe and moves to the directory it prints.
+
+# Path of the jcd binary: $env:JCD_BINARY, else jcd.exe from PATH
+function Get-JcdBinary {
+    if ($env:JCD_BINARY) {
+        return $env:JCD_BINARY
+    }
+    $command = Get-Command jcd.exe -CommandType Application -ErrorAction SilentlyContinue |
+        Select-Object -First 1
+    if ($command) {
+        return $command.Source
+    }
+    return $null
+}
+
+function jcd {
+    $binary = Get-JcdBinary
+    if (-not $binary) {
+        Write-Error "JCD binary not found; put jcd.exe on PATH or set `$env:JCD_BINARY"
+        return
+    }
+
+    if ($args.Count -eq 0) {
+        Write-Output "Usage: jcd [-i] [-E] [-x] [--gitignore] [--fzf] <pattern>"
+        Write-Output "       jcd index|daemon|ignore|bookmark|pin|import|history|init|completions|serve|doctor|bench|config <command> ..."
+        return
+    }
+
+    # Management subcommands (e.g. "jcd index build") go straight to the binary
+    if ($args.Count -ge 2 -and $args[0] -in 'index', 'daemon', 'ignore', 'bookmark', 'pin', 'import', 'history', 'init', 'completions', 'serve', 'doctor', 'bench', 'config') {
+        & $binary @args
+        return
+    }
+
+    # --explain prints the ranking rather than a directory to change to
+    if ($args -contains '--explain') {
+        & $binary @args
+        return
+    }
+
+    # The last argument is the pattern; anything before it is flags
+    $pattern = $args[-1]
+    $flags = @()
+    if ($args.Count -gt 1) {
+        $flags = $args[0..($args.Count - 2)]
+    }
+
+    # Get the best match (index 0) and record the jump in the history database
+    $dest = & $binary --record @flags $pattern 0
+    switch ($LASTEXITCODE) {
+        0 { }
+        3 {
+            # A limit cut the search short and jcd said which; go to the best match found
+            if (-not $dest) {
+                Write-Output "No directories found matching '$pattern' before the search stopped"
+                return
+            }
+        }
+        # Usage and I/O errors were reported by the binary
+        { $_ -in 2, 4 } { return }
+        default {
+            Write-Output "No directories found matching '$pattern'"
+            return
+        }
+    }
+    Set-Location -LiteralPath $dest
+}
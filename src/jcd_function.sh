@@ -5,19 +5,75 @@
 
 _jcd_print_usage() {
     echo "Usage:"
-    echo "  jcd [-i] <directory_pattern>        - Changes directory according to the pattern"
+    echo "  jcd [-i] [-E] <directory_pattern>   - Changes directory according to the pattern"
+    echo "  jcd [-i] [-E] <word>... <pattern>   - The words must match directories above the match, in order"
     echo "  jcd -h|--help                       - Display this help message"
     echo
     echo "directory_pattern:"
     echo "  jcd <substring>        # Navigate to directory matching substring"
     echo "  jcd <absolute_path>    # Navigate to absolute path"
     echo "  jcd <path/pattern>     # Navigate using path-like patterns"
+    echo "  jcd -E <regex>         # Match directory names against a regular expression"
+    echo "  jcd --fzf <pattern>    # Choose among all matches with fzf (or \$JCD_PICKER)"
 }
 
+# Name the jcd function is defined under ("jcd init bash --cmd <name>" sets it)
+_JCD_CMD="${_JCD_CMD:-jcd}"
+
+# Path of the jcd binary: $JCD_BINARY, else the packaged location
+_jcd_binary_path() {
+    if [[ -n "${JCD_BINARY:-}" ]]; then
+        echo "$JCD_BINARY"
+    elif [[ "$(uname)" == "Darwin" ]]; then
+        echo "$(brew --prefix 2>/dev/null || true)/bin/jcd"
+    elif [[ "$_JCD_WINDOWS" == 1 ]]; then
+        # Git Bash / MSYS2: jcd.exe from PATH
+        command -v jcd.exe 2>/dev/null || echo "/usr/bin/jcd.exe"
+    else
+        echo "/usr/bin/jcd"
+    fi
+}
+
+# Git Bash, MSYS2 and Cygwin run a native Windows binary, which prints
+# C:\Users\me where the shell wants /c/Users/me (see cygpath)
+case "$(uname -s)" in
+    MINGW* | MSYS* | CYGWIN*) _JCD_WINDOWS=1 ;;
+    *) _JCD_WINDOWS=0 ;;
+esac
+
 jcd() {
+    # Management subcommands (e.g. "jcd index build", "jcd ignore list") go straight to the binary.
+    # A single word is always a search, so "jcd index" still finds a directory.
+    if [[ $# -ge 2 ]]; then
+        case "$1" in
+            doctor)
+                # Tells the doctor which function is loaded; bump the revision
+                # whenever this script changes
+                _JCD_SHELL=bash _JCD_FUNCTION_REVISION=19 "$(_jcd_binary_path)" "$@"
+                return $?
+                ;;
+            index|daemon|ignore|bookmark|pin|import|history|init|completions|serve|bench|config)
+                "$(_jcd_binary_path)" "$@"
+                return $?
+                ;;
+        esac
+    fi
+
+    # --explain prints the ranking rather than a directory to change to
+    local arg
+    for arg in "$@"; do
+        if [[ "$arg" == "--explain" ]]; then
+            "$(_jcd_binary_path)" "$@"
+            return $?
+        fi
+    done
+
     # Parse arguments to handle flags
     local case_insensitive=false
+    local regex_mode=false
+    local walk_flags=()  # Passed through to the binary unchanged
     local search_term=""
+    local path_words=()  # Words before the pattern of a multi-word query
 
     while [[ $# -gt 0 ]]; do
         case $1 in
@@ -25,17 +81,31 @@ jcd() {
                 case_insensitive=true
                 shift
                 ;;
+            -E|--regex)
+                regex_mode=true
+                shift
+                ;;
+            --smart-case|--no-smart-case|--gitignore|--no-gitignore|--follow-symlinks|--same-filesystem|--cross-filesystems|--fzf)
+                walk_flags+=("$1")
+                shift
+                ;;
+            --timeout|--max-matches|--min-depth|--tier-limits|--from|--exclude|--rank|--color|--chosen-for)
+                walk_flags+=("$1" "$2")
+                shift 2
+                ;;
             -h|--help)
                 _jcd_print_usage
                 return 0
                 ;;
+            -x|--?*)
+                # Other flags go to the binary rather than becoming words of the query
+                walk_flags+=("$1")
+                shift
+                ;;
             *)
-                if [ -z "$search_term" ]; then
-                    search_term="$1"
-                else
-                    _jcd_print_usage
-                    return 1
-                fi
+                # The last word is the pattern; earlier ones must appear above the match
+                [ -n "$search_term" ] && path_words+=("$search_term")
+                search_term="$1"
                 shift
                 ;;
         esac
@@ -46,17 +116,8 @@ jcd() {
         return 1
     fi
 
-    local jcd_binary=""
-    if [[ -n "${JCD_BINARY:-}" ]]; then
-        jcd_binary="$JCD_BINARY"
-    else
-        if [[ "$(uname)" == "Darwin" ]]; then
-            brew_prefix="$(brew --prefix 2>/dev/null || true)"
-            jcd_binary="$brew_prefix/bin/jcd"
-        else
-            jcd_binary="/usr/bin/jcd"
-        fi
-    fi
+    local jcd_binary
+    jcd_binary="$(_jcd_binary_path)"
 
     # Ensure binary exists
     if [ ! -x "$jcd_binary" ]; then
@@ -98,17 +159,51 @@ jcd() {
         # If directory doesn't exist, fall through to search logic
     fi
 
-    # Get the best match (index 0)
+    # Get the best match (index 0) and record the jump in the history database,
+    # unless a visit hook records every directory anyway
     local dest
-    if [ "$case_insensitive" = true ]; then
-        dest=$("$jcd_binary" -i "$search_term" 0)
-    else
-        dest=$("$jcd_binary" "$search_term" 0)
-    fi
-    if [ $? -ne 0 ] || [ -z "$dest" ]; then
-        echo "No directories found matching '$search_term'"
-        return 1
+    local flags=()
+    [[ "${_JCD_HOOK:-none}" == none ]] && flags+=(--record)
+    [ "$case_insensitive" = true ] && flags+=(-i)
+    [ "$regex_mode" = true ] && flags+=(-E)
+    # A match reached by pressing Tab is remembered for the pattern it completed,
+    # which then leads straight to it
+    if [[ "$_JCD_IS_RELATIVE_PATTERN" == true && -n "$_JCD_ORIGINAL_PATTERN" ]] &&
+        [[ "$(_jcd_find_current_index "$search_term")" -ge 0 ]]; then
+        flags+=(--chosen-for "$_JCD_ORIGINAL_PATTERN")
     fi
+    flags+=("${walk_flags[@]}")
+    # NUL-terminated (-0) so that names ending in a newline survive the read;
+    # the exit status follows on a line of its own
+    local status
+    {
+        if IFS= read -r -d '' dest; then
+            read -r status
+        else
+            # Nothing was printed, so all there is is the status
+            status="${dest%$'\n'}"
+            dest=""
+        fi
+    } < <("$jcd_binary" -0 "${flags[@]}" "${path_words[@]}" "$search_term" 0; echo $?)
+    case "$status" in
+        0) ;;
+        1)
+            echo "No directories found matching '$search_term'"
+            return 1
+            ;;
+        3)
+            # A limit cut the search short and jcd said which; go to the best match found
+            if [ -z "$dest" ]; then
+                echo "No directories found matching '$search_term' before the search stopped"
+                return 3
+            fi
+            ;;
+        *)
+            # Usage and I/O errors were reported by the binary
+            return "$status"
+            ;;
+    esac
+    [[ "$_JCD_WINDOWS" == 1 ]] && dest="$(cygpath -u "$dest")"
     cd "$dest"
 }
 
@@ -123,9 +218,16 @@ _JCD_IS_LEAF_DIR=false
 _JCD_LEAF_COMPLETION_COUNT=0
 _JCD_CYCLING_DIRECTION=1  # 1 for forward, -1 for backward
 
-# Debug flag - set to 1 to enable debug output
+# Debug flag - set to 1 to enable debug output, appended to $JCD_LOG_FILE when set
 _JCD_DEBUG="${JCD_DEBUG:-0}"
-_jcd_debug() { [[ "$_JCD_DEBUG" == "1" ]] && echo "DEBUG: $*" >&2; }
+_jcd_debug() {
+    [[ "$_JCD_DEBUG" == "1" ]] || return 0
+    if [[ -n "$JCD_LOG_FILE" ]]; then
+        echo "DEBUG: $*" >>"$JCD_LOG_FILE"
+    else
+        echo "DEBUG: $*" >&2
+    fi
+}
 
 _jcd_reset_state() {
     _jcd_debug "=== RESETTING STATE ==="
@@ -330,6 +432,12 @@ _jcd_show_tab_busy_indicator() {
 # -----------------------------------------------------------------------------
 _jcd_run_with_animation() {
 
+    # No animation without a terminal, or when NO_COLOR asks for plain output
+    if [[ -n "$NO_COLOR" || ! -t 2 ]]; then
+        "$@" 2>/dev/null
+        return
+    fi
+
     # Save cursor position
     tput sc >&2
 
@@ -360,6 +468,24 @@ _jcd_run_with_animation() {
     return $exit_code
 }
 
+# Every completion candidate for a pattern, best first, one per line. A single
+# jcd --complete call returns them all; the caller cycles through them locally.
+# Names containing a newline cannot be completed on one line and are left out.
+_jcd_complete_candidates() {
+    local jcd_binary="$1"
+    local pattern="$2"
+    local case_insensitive="$3"  # true/false
+    local flags=(--complete -0)
+    [ "$case_insensitive" = "true" ] && flags+=(-i)
+
+    local match
+    while IFS= read -r -d '' match; do
+        [[ -z "$match" || "$match" == *$'\n'* ]] && continue
+        [[ "$_JCD_WINDOWS" == 1 ]] && match="$(cygpath -u "$match")"
+        printf '%s\n' "$match"
+    done < <("$jcd_binary" "${flags[@]}" "$pattern" 2>/dev/null)
+}
+
 # Get all matches for a relative pattern
 _jcd_get_relative_matches() {
     local pattern="$1"
@@ -368,17 +494,8 @@ _jcd_get_relative_matches() {
     local idx=0
     local match
 
-    local jcd_binary=""
-    if [[ -n "${JCD_BINARY:-}" ]]; then
-        jcd_binary="$JCD_BINARY"
-    else
-        if [[ "$(uname)" == "Darwin" ]]; then
-            brew_prefix="$(brew --prefix 2>/dev/null || true)"
-            jcd_binary="$brew_prefix/bin/jcd"
-        else
-            jcd_binary="/usr/bin/jcd"
-        fi
-    fi
+    local jcd_binary
+    jcd_binary="$(_jcd_binary_path)"
 
     _jcd_debug "getting relative matches for pattern '$pattern' (case_insensitive=$case_insensitive)"
 
@@ -482,23 +599,11 @@ _jcd_get_relative_matches() {
                 fi
             else
                 # Use the jcd binary directly, no per-call animation
-                while true; do
-                    if [ "$case_insensitive" = "true" ]; then
-                        match=$("$jcd_binary" -i "$pattern" "$idx" --quiet 2>/dev/null)
-                    else
-                        match=$("$jcd_binary" "$pattern" "$idx" --quiet 2>/dev/null)
-                    fi
-                    if [ $? -ne 0 ] || [ -z "$match" ]; then
-                        break
-                    fi
+                while IFS= read -r match; do
                     _jcd_debug "  relative match #$idx: '$match'"
                     matches+=("$match")
                     idx=$((idx + 1))
-                    # Safety limit to prevent infinite loops
-                    if [ $idx -gt 100 ]; then
-                        break
-                    fi
-                done
+                done < <(_jcd_complete_candidates "$jcd_binary" "$pattern" "$case_insensitive")
             fi
             ;;
     esac
@@ -517,17 +622,8 @@ _jcd_get_absolute_matches() {
     local case_insensitive="$2"  # true/false
     local matches=()
 
-    local jcd_binary=""
-    if [[ -n "${JCD_BINARY:-}" ]]; then
-        jcd_binary="$JCD_BINARY"
-    else
-        if [[ "$(uname)" == "Darwin" ]]; then
-            brew_prefix="$(brew --prefix 2>/dev/null || true)"
-            jcd_binary="$brew_prefix/bin/jcd"
-        else
-            jcd_binary="/usr/bin/jcd"
-        fi
-    fi
+    local jcd_binary
+    jcd_binary="$(_jcd_binary_path)"
 
     _jcd_debug "getting absolute matches for pattern '$pattern' (case_insensitive=$case_insensitive)"
     # Handle relative path patterns that start with ../
@@ -557,23 +653,11 @@ _jcd_get_absolute_matches() {
 
     _jcd_debug "using jcd binary for absolute pattern '$pattern'"
 
-    while true; do
-        if [ "$case_insensitive" = "true" ]; then
-            match=$("$jcd_binary" -i "$pattern" "$idx" --quiet 2>/dev/null)
-        else
-            match=$("$jcd_binary" "$pattern" "$idx" --quiet 2>/dev/null)
-        fi
-        if [ $? -ne 0 ] || [ -z "$match" ]; then
-            break
-        fi
+    while IFS= read -r match; do
         _jcd_debug "  absolute match #$idx: '$match'"
         matches+=("$match")
         idx=$((idx + 1))
-        # Safety limit to prevent infinite loops
-        if [ $idx -gt 100 ]; then
-            break
-        fi
-    done
+    done < <(_jcd_complete_candidates "$jcd_binary" "$pattern" "$case_insensitive")
 
     _jcd_debug "found ${#matches[@]} absolute matches via binary"
     if [ ${#matches[@]} -eq 0 ]; then
@@ -917,7 +1001,7 @@ _jcd_tab_complete_internal() {
 # Clear jcd completion state when command is executed or changed
 _jcd_clear_on_execute() {
     # Only clear if we're not in the middle of completing an jcd command
-    if [[ "${BASH_COMMAND}" != *"jcd "* ]] && [[ "${BASH_COMMAND}" != *"_jcd_"* ]]; then
+    if [[ "${BASH_COMMAND}" != *"$_JCD_CMD "* ]] && [[ "${BASH_COMMAND}" != *"_jcd_"* ]]; then
         _jcd_debug "clearing state on command execute: '${BASH_COMMAND}'"
         _jcd_reset_state
     fi
@@ -929,7 +1013,7 @@ _jcd_shift_tab_handler() {
     local point="$READLINE_POINT"
 
     # Check if we're at the end of a jcd command
-    if [[ "$line" =~ ^jcd[[:space:]]+((-i[[:space:]]+)?[^[:space:]]*)?$ ]]; then
+    if [[ "$line" =~ ^"$_JCD_CMD"[[:space:]]+((-i[[:space:]]+)?[^[:space:]]*)?$ ]]; then
         # Extract the current word being completed
         local words
         read -ra words <<< "$line"
@@ -959,18 +1043,48 @@ _jcd_shift_tab_handler() {
         if [[ ${#COMPREPLY[@]} -gt 0 ]]; then
             local completion="${COMPREPLY[0]}"
             if [[ "${words[1]}" == "-i" ]]; then
-                READLINE_LINE="jcd -i $completion"
+                READLINE_LINE="$_JCD_CMD -i $completion"
             else
-                READLINE_LINE="jcd $completion"
+                READLINE_LINE="$_JCD_CMD $completion"
             fi
             READLINE_POINT=${#READLINE_LINE}
         fi
     fi
 }
 
+# Directory-visit hook ("jcd init bash --hook prompt|pwd" sets _JCD_HOOK):
+# records directories reached with plain cd too, not only jcd's jumps.
+# "prompt" counts every prompt, "pwd" only prompts after the directory changed.
+_jcd_hook() {
+    if [[ "$_JCD_HOOK" == pwd ]]; then
+        [[ "${_JCD_HOOK_PWD:-}" == "$PWD" ]] && return
+        _JCD_HOOK_PWD="$PWD"
+    fi
+    "$(_jcd_binary_path)" --add "$PWD" 2>/dev/null
+}
+
+case "${_JCD_HOOK:-none}" in
+    prompt|pwd)
+        if [[ -n "${BASH_VERSION:-}" ]]; then
+            if [[ ";${PROMPT_COMMAND:-};" != *";_jcd_hook;"* ]]; then
+                PROMPT_COMMAND="_jcd_hook${PROMPT_COMMAND:+;$PROMPT_COMMAND}"
+            fi
+        elif [[ -n "${ZSH_VERSION:-}" ]]; then
+            autoload -Uz add-zsh-hook
+            add-zsh-hook precmd _jcd_hook
+        fi
+        ;;
+esac
+
+# Under a custom name, the function moves there and jcd is left alone
+if [[ "$_JCD_CMD" != "jcd" ]]; then
+    eval "$(declare -f jcd | sed "1s/^jcd /$_JCD_CMD /")"
+    unset -f jcd
+fi
+
 if [[ -n "${BASH_VERSION:-}" ]]; then
     # Register the completion function
-    complete -o nospace -F _jcd_tab_complete jcd
+    complete -o nospace -F _jcd_tab_complete "$_JCD_CMD"
 
     # Bind Shift+Tab to backward completion for jcd (only in interactive shells)
     if [[ $- == *i* ]]; then
@@ -981,7 +1095,7 @@ if [[ -n "${BASH_VERSION:-}" ]]; then
     trap '_jcd_clear_on_execute' DEBUG
 
     # Export the function
-    export -f jcd
+    export -f "$_JCD_CMD"
 
     # Clear any existing state when script is loaded to ensure clean start
     _jcd_reset_state
@@ -1019,8 +1133,8 @@ elif [[ -n "${ZSH_VERSION:-}" ]]; then
                 done
 
                 # If the command name is missing (e.g., COMP_WORDS[0] is the pattern), insert it
-                if [[ "${COMP_WORDS[0]}" != "jcd" ]]; then
-                    COMP_WORDS=("jcd" "${COMP_WORDS[@]}")
+                if [[ "${COMP_WORDS[0]}" != "$_JCD_CMD" ]]; then
+                    COMP_WORDS=("$_JCD_CMD" "${COMP_WORDS[@]}")
                 fi
 
                 for i in {0..$#COMP_WORDS}; do
@@ -1054,13 +1168,13 @@ elif [[ -n "${ZSH_VERSION:-}" ]]; then
     }
 
     # Register zsh completion
-    compdef _jcd_zsh_complete jcd
+    compdef _jcd_zsh_complete "$_JCD_CMD"
 
     # In zsh, use preexec hook instead of DEBUG trap for better compatibility
     autoload -Uz add-zsh-hook
     _jcd_zsh_preexec() {
         # Clear state when executing any command that's not jcd-related
-        if [[ "$1" != *"jcd "* ]] && [[ "$1" != *"_jcd_"* ]] && [[ -n "$1" ]]; then
+        if [[ "$1" != *"$_JCD_CMD "* ]] && [[ "$1" != *"_jcd_"* ]] && [[ -n "$1" ]]; then
             _jcd_debug "clearing state on zsh command execute: '$1'"
             _jcd_reset_state
         fi
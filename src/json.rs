@@ -0,0 +1,247 @@
+//! Minimal JSON reader and writer for the requests of `jcd serve`: objects,
+//! arrays, strings, numbers, booleans and null. Numbers are read as f64.
+
+use jcd::output::json_escape;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// The member `key` of an object
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// The value written back as JSON text
+    pub fn to_json(&self) -> String {
+        match self {
+            Value::Null => "null".to_string(),
+            Value::Boolean(b) => b.to_string(),
+            // Integral ids such as 7 are echoed as 7, not 7.0
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", *n as i64),
+            Value::Number(n) => n.to_string(),
+            Value::String(s) => format!("\"{}\"", json_escape(s)),
+            Value::Array(items) => {
+                let items: Vec<String> = items.iter().map(Value::to_json).collect();
+                format!("[{}]", items.join(","))
+            }
+            Value::Object(members) => {
+                let members: Vec<String> = members
+                    .iter()
+                    .map(|(key, value)| format!("\"{}\":{}", json_escape(key), value.to_json()))
+                    .collect();
+                format!("{{{}}}", members.join(","))
+            }
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at offset {}", c, self.pos))
+        }
+    }
+
+    fn parse_literal(&mut self, word: &str, value: Value) -> Result<Value, String> {
+        let word: Vec<char> = word.chars().collect();
+        if self.chars.get(self.pos..self.pos + word.len()) == Some(&word[..]) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(format!("invalid literal at offset {}", self.pos))
+        }
+    }
+
+    /// Four hex digits of a `\u` escape
+    fn parse_hex4(&mut self) -> Result<u32, String> {
+        let hex: String = self.chars.iter().skip(self.pos).take(4).collect();
+        self.pos += 4;
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .filter(|_| hex.len() == 4)
+            .ok_or_else(|| format!("invalid unicode escape '\\u{}'", hex))
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                None => return Err("unterminated string".to_string()),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    let escaped = self.peek().ok_or("unterminated escape sequence")?;
+                    self.pos += 1;
+                    match escaped {
+                        '"' => out.push('"'),
+                        '\\' => out.push('\\'),
+                        '/' => out.push('/'),
+                        'b' => out.push('\u{8}'),
+                        'f' => out.push('\u{c}'),
+                        'n' => out.push('\n'),
+                        'r' => out.push('\r'),
+                        't' => out.push('\t'),
+                        'u' => {
+                            let mut code = self.parse_hex4()?;
+                            // A surrogate pair spells one character outside the BMP
+                            if (0xD800..0xDC00).contains(&code)
+                                && self.chars.get(self.pos..self.pos + 2) == Some(&['\\', 'u'])
+                            {
+                                self.pos += 2;
+                                let low = self.parse_hex4()?;
+                                if (0xDC00..0xE000).contains(&low) {
+                                    code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                                }
+                            }
+                            out.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+                        }
+                        other => return Err(format!("invalid escape sequence '\\{}'", other)),
+                    }
+                }
+                Some(c) => {
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while matches!(self.peek(), Some('-' | '+' | '.' | 'e' | 'E' | '0'..='9')) {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse()
+            .map(Value::Number)
+            .map_err(|_| format!("invalid number '{}'", text))
+    }
+
+    fn parse_value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err("unexpected end of input".to_string()),
+            Some('{') => {
+                self.pos += 1;
+                let mut members = BTreeMap::new();
+                self.skip_whitespace();
+                if self.peek() == Some('}') {
+                    self.pos += 1;
+                    return Ok(Value::Object(members));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.parse_string()?;
+                    self.expect(':')?;
+                    members.insert(key, self.parse_value()?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some('}') => {
+                            self.pos += 1;
+                            return Ok(Value::Object(members));
+                        }
+                        _ => return Err(format!("expected ',' or '}}' at offset {}", self.pos)),
+                    }
+                }
+            }
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(']') {
+                    self.pos += 1;
+                    return Ok(Value::Array(items));
+                }
+                loop {
+                    items.push(self.parse_value()?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(']') => {
+                            self.pos += 1;
+                            return Ok(Value::Array(items));
+                        }
+                        _ => return Err(format!("expected ',' or ']' at offset {}", self.pos)),
+                    }
+                }
+            }
+            Some('"') => self.parse_string().map(Value::String),
+            Some('t') => self.parse_literal("true", Value::Boolean(true)),
+            Some('f') => self.parse_literal("false", Value::Boolean(false)),
+            Some('n') => self.parse_literal("null", Value::Null),
+            Some('-' | '0'..='9') => self.parse_number(),
+            Some(c) => Err(format!("unexpected '{}' at offset {}", c, self.pos)),
+        }
+    }
+}
+
+/// Parse one JSON document
+pub fn parse(text: &str) -> Result<Value, String> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+    };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.peek().is_some() {
+        return Err(format!("trailing characters at offset {}", parser.pos));
+    }
+    Ok(value)
+}
@@ -0,0 +1,2532 @@
+//! The jcd search engine: finds directories by name around a starting
+//! directory, up towards the root and down into the tree, and ranks them by
+//! match quality, depth and usage. The `jcd` binary is a command line around
+//! this library; file managers, prompts and other tools can embed the same
+//! matcher without starting a process.
+//!
+//! A search is prepared from a term, then run by a [`SearchSession`] that holds
+//! what all searches share: ignore patterns, the directory index and the
+//! history used for ranking.
+//!
+//! ```no_run
+//! use jcd::{config::Config, prepare_search, SearchFlags, SearchSession};
+//!
+//! let settings = Config::load();
+//! let flags = SearchFlags {
+//!     quiet: true,
+//!     ..SearchFlags::default()
+//! };
+//! let session = SearchSession::new(&flags, &settings, None);
+//! let current_dir = std::env::current_dir().unwrap();
+//! let search = prepare_search("proj", &flags, &current_dir, &settings).unwrap();
+//! for m in session.find(search) {
+//!     println!("{} ({:?})", m.path.display(), m.match_quality);
+//! }
+//! ```
+
+pub mod bookmark;
+pub mod cache;
+pub mod config;
+pub mod daemon;
+pub mod filesystem;
+mod gitignore;
+pub mod history;
+pub mod ignore;
+pub mod index;
+pub mod matcher;
+pub mod output;
+pub mod pin;
+pub mod platform;
+mod project;
+pub mod query;
+pub mod ranking;
+mod roots;
+pub mod selections;
+pub mod selftest;
+pub mod stats;
+#[cfg(feature = "async")]
+pub mod stream;
+mod toml;
+
+use config::Config;
+use filesystem::{EntryKind, FileSystem, RealFileSystem};
+use gitignore::GitIgnore;
+use history::History;
+use ignore::{
+    load_ignore_patterns, should_ignore_directory, IgnorePattern, IgnoreScope, NESTED_IGNORE_FILE,
+};
+use matcher::{has_uppercase, NameMatch, NameMatcher, PatternSyntax};
+use query::QueryFilters;
+use selections::Selections;
+use stats::SearchStats;
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    env, fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, debug_span, trace};
+
+// Configuration constants for performance tuning
+// (defaults for the settings that config.toml can override)
+const MAX_MATCHES: usize = 20; // Stop after finding enough matches
+const MAX_SEARCH_TIME_MS: u64 = 500; // Max time to spend searching (milliseconds)
+const MAX_IGNORE_PATTERNS: usize = 100; // Upper bound on loaded ignore patterns
+const MAX_COMPILED_REGEX_SIZE: usize = 1_000_000; // 1MB compiled regex size limit
+const MAX_SEARCH_DEPTH: usize = 8; // Deepest level explored when searching down
+const COUSIN_ANCESTORS: usize = 3; // Nearest ancestors searched below with --cousins
+const COUSIN_LEVELS: usize = 2; // Levels searched below each of them
+const PROGRESS_DELAY_MS: u64 = 20; // Grace period before the busy indicator appears
+const BUSY_INDICATOR_INTERVAL: Duration = Duration::from_millis(100); // Between redraws
+pub const RECENCY_WEIGHT: f64 = 49.0; // --recent weight under --rank; with frecency stays below one category
+
+// Exit statuses of the jcd command, which the shell functions and scripts
+// tell apart. 0 is a match found, or a command that succeeded.
+pub const EXIT_NO_MATCH: i32 = 1; // Nothing matched, nothing was picked, or a command failed
+pub const EXIT_USAGE: i32 = 2; // Bad flags, pattern or arguments; nothing was searched
+pub const EXIT_PARTIAL: i32 = 3; // A time or match limit cut the search short; what was found is printed
+pub const EXIT_IO: i32 = 4; // The current directory, history or picker could not be used
+
+/// How a directory matched and where it lies, best first
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchQuality {
+    Bookmark,       // Named bookmark (@name) - always first
+    Pinned,         // Pinned directory (jcd pin) whose name matches - always first
+    ExactUp,        // Exact match up the path - highest priority
+    ExactSibling,   // Exact match next to the current directory (--siblings)
+    ExactCousin,    // Exact match a little below a nearby ancestor (--cousins)
+    PartialUp,      // Partial match up the path - second priority
+    PartialSibling, // Partial match next to the current directory (--siblings)
+    PartialCousin,  // Partial match a little below a nearby ancestor (--cousins)
+    ExactDown,      // Exact match down the path - third priority
+    PrefixDown,     // Prefix match down the path - fourth priority
+    WordDown,       // Match at a word inside the name down the path - below prefix matches
+    AcronymDown,    // Initials match down the path - below word matches
+    PartialDown,    // Partial match down the path - lowest real match
+    TypoUp,         // A typo or two away from a name up the path (--typos)
+    TypoDown,       // A typo or two away from a name down the path (--typos)
+}
+
+impl MatchQuality {
+    /// Quality of a match found below the search directory
+    fn down(kind: NameMatch) -> Self {
+        match kind {
+            NameMatch::Exact => MatchQuality::ExactDown,
+            NameMatch::Prefix => MatchQuality::PrefixDown,
+            NameMatch::Word => MatchQuality::WordDown,
+            NameMatch::Acronym => MatchQuality::AcronymDown,
+            NameMatch::Partial => MatchQuality::PartialDown,
+            NameMatch::Typo => MatchQuality::TypoDown,
+        }
+    }
+
+    /// Whether the match lies below the search directory
+    fn is_down(&self) -> bool {
+        matches!(
+            self,
+            MatchQuality::ExactDown
+                | MatchQuality::PrefixDown
+                | MatchQuality::WordDown
+                | MatchQuality::AcronymDown
+                | MatchQuality::PartialDown
+                | MatchQuality::TypoDown
+        )
+    }
+}
+
+/// A directory found by a search
+#[derive(Debug, Clone)]
+pub struct DirectoryMatch {
+    pub path: PathBuf,
+    pub depth_from_current: i32, // negative for parents, positive for children
+    pub match_quality: MatchQuality,
+    pub last_access: Option<SystemTime>, // atime captured before the search descends into it
+    pub root: usize, // 0 below the search directory, n below the n-th extra root
+    pub project_root: bool, // Holds a project marker such as .git or Cargo.toml
+    pub same_repo: bool, // In the git repository of the current directory
+}
+
+impl DirectoryMatch {
+    pub fn new(path: PathBuf, depth_from_current: i32, match_quality: MatchQuality) -> Self {
+        Self::new_in(&RealFileSystem, path, depth_from_current, match_quality)
+    }
+
+    /// A match for a directory of `fs`
+    pub fn new_in(
+        fs: &dyn FileSystem,
+        path: PathBuf,
+        depth_from_current: i32,
+        match_quality: MatchQuality,
+    ) -> Self {
+        // Read atime now: descending into the directory later would refresh it
+        let last_access = fs.metadata(&path).ok().and_then(|m| m.accessed);
+        let project_root = project::is_project_root(fs, &path);
+        Self {
+            path,
+            depth_from_current,
+            match_quality,
+            last_access,
+            root: 0,
+            project_root,
+            same_repo: false,
+        }
+    }
+}
+
+/// Which way from the search directory a search looks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    #[default]
+    Both,
+    Up,   // Parent directories only (--up)
+    Down, // Below the search directory only (--down)
+}
+
+impl Direction {
+    fn searches_up(self) -> bool {
+        self != Direction::Down
+    }
+
+    fn searches_down(self) -> bool {
+        self != Direction::Up
+    }
+}
+
+/// Options that shape a single search, assembled from flags and query filters
+#[derive(Debug, Clone)]
+pub struct SearchConfig {
+    pub case_sensitive: bool,
+    pub syntax: PatternSyntax,
+    pub max_depth: usize,
+    pub gitignore: bool, // Skip directories ignored by git when searching down
+    pub nested_ignore: bool, // Honor .jcdignore files found while searching down
+    pub follow_symlinks: bool, // Descend into symlinked directories when searching down
+    pub same_filesystem: bool, // Stay on the starting filesystem when searching down
+    pub typos: bool,     // Also match names a typo or two away from a plain pattern
+    pub ignore_accents: bool, // Compare plain patterns and names without their accents
+    pub full_path: bool, // Match downward against the path below the search root, not the name
+    pub siblings: bool,  // Also match the other subdirectories of the parent, before deeper ones
+    pub cousins: bool,   // Also match a little below each nearby ancestor, after the siblings
+    pub max_matches: usize, // Most matches reported; searches stop once they have enough
+    pub min_depth: usize, // Matches below the search directory nearer than this are dropped
+    pub direction: Direction, // Skips the traversal --up or --down leaves out
+    pub tier_limits: ranking::TierLimits, // Most matches each quality keeps after ranking
+    pub max_time: Duration, // Duration::MAX when unlimited
+    pub deadline: Option<Instant>, // When max_time runs out; set as the search starts
+    pub progress_delay: Duration,
+    pub progress_style: ProgressStyle,
+    pub filters: QueryFilters,
+    pub path_words: Vec<NameMatcher>, // Earlier words of a query, found in order above each match
+    pub index: Option<Arc<index::IndexSource>>, // Answers downward searches without walking
+    pub roots: Vec<PathBuf>,          // Searched in order after the local search
+    pub fallback_roots: Vec<PathBuf>, // Searched in order when nothing else matches
+    pub repo_root: Option<PathBuf>,   // Git repository of the current directory
+    pub fs: Arc<dyn FileSystem>,      // What the search walks; the disk by default
+    pub on_match: Option<MatchSink>,  // Told of each match as the walk finds it
+    pub stats: Arc<SearchStats>,      // Counts what the search does
+}
+
+/// Receives each match as soon as the walk finds it, before the matches are
+/// deduplicated and ranked. The callback returns false once nobody listens
+/// any more, which ends the walk early.
+#[derive(Clone)]
+pub struct MatchSink {
+    send: Arc<dyn Fn(&DirectoryMatch) -> bool + Send + Sync>,
+    closed: Arc<AtomicBool>,
+}
+
+impl MatchSink {
+    pub fn new(send: impl Fn(&DirectoryMatch) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            send: Arc::new(send),
+            closed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn send(&self, m: &DirectoryMatch) {
+        if !self.is_closed() && !(self.send)(m) {
+            self.closed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Whether the callback has declined a match
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Debug for MatchSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MatchSink")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+/// The search time budget for a timeout in milliseconds; 0 means unlimited
+fn time_budget(timeout_ms: u64) -> Duration {
+    if timeout_ms == 0 {
+        Duration::MAX
+    } else {
+        Duration::from_millis(timeout_ms)
+    }
+}
+
+impl SearchConfig {
+    pub fn new(settings: &Config, case_sensitive: bool, filters: QueryFilters) -> Self {
+        // A depth filter also bounds how far the downward search has to go
+        let max_depth = filters
+            .depth
+            .map_or(settings.max_depth, |d| d.max.min(settings.max_depth));
+        Self {
+            case_sensitive,
+            syntax: PatternSyntax::Text,
+            max_depth,
+            gitignore: settings.gitignore,
+            nested_ignore: true,
+            follow_symlinks: false,
+            same_filesystem: settings.same_filesystem,
+            typos: settings.typos,
+            ignore_accents: settings.ignore_accents,
+            full_path: settings.full_path,
+            siblings: settings.siblings,
+            cousins: settings.cousins,
+            max_matches: settings.max_matches,
+            min_depth: 0,
+            direction: Direction::Both,
+            tier_limits: settings.tier_limits.clone(),
+            max_time: time_budget(settings.timeout_ms),
+            deadline: None,
+            progress_delay: Duration::from_millis(settings.progress_delay_ms),
+            progress_style: settings.progress_style,
+            filters,
+            path_words: Vec::new(),
+            index: None,
+            roots: Vec::new(),
+            fallback_roots: Vec::new(),
+            repo_root: None,
+            fs: Arc::new(RealFileSystem),
+            on_match: None,
+            stats: Arc::default(),
+        }
+    }
+
+    /// Whether matches right below the search directory can end the search early.
+    /// Filters that may reject those matches require looking deeper.
+    fn allows_early_stop(&self) -> bool {
+        self.filters.dir_type.is_none()
+            && self.filters.depth.is_none_or(|d| d.min <= 1)
+            && self.min_depth <= 1
+            && self.path_words.is_empty()
+    }
+
+    /// Whether the query filters accept a match
+    fn keeps(&self, m: &DirectoryMatch) -> bool {
+        self.filters
+            .depth
+            .is_none_or(|d| d.contains(m.depth_from_current))
+            && self.filters.dir_type.is_none_or(|t| t.matches(&m.path))
+            && self.deep_enough(m)
+            && self.has_path_words(&m.path)
+    }
+
+    /// Whether a match below the search directory is at least `min_depth`
+    /// levels down. A directory named outright (`..`, an absolute path) is
+    /// at depth 0 and always kept.
+    fn deep_enough(&self, m: &DirectoryMatch) -> bool {
+        !m.match_quality.is_down()
+            || m.depth_from_current == 0
+            || m.depth_from_current >= self.min_depth as i32
+    }
+
+    /// Whether the earlier words of a multi-word query (`jcd api handlers`)
+    /// match, in order, the names of directories above `path`
+    fn has_path_words(&self, path: &Path) -> bool {
+        let mut words = self.path_words.iter().peekable();
+        for name in path.parent().into_iter().flat_map(Path::iter) {
+            if words.peek().is_none() {
+                break;
+            }
+            words.next_if(|word| word.classify(&platform::os_to_str(name)).is_some());
+        }
+        words.peek().is_none()
+    }
+
+    /// Drop matches rejected by the query filters
+    fn apply_filters(&self, matches: Vec<DirectoryMatch>) -> Vec<DirectoryMatch> {
+        if self.filters.is_empty() && self.min_depth == 0 && self.path_words.is_empty() {
+            return matches;
+        }
+        matches
+            .into_iter()
+            .filter(|m| {
+                let keep = self.keeps(m);
+                if !keep {
+                    trace!("Filtered out by query: {}", m.path.display());
+                }
+                keep
+            })
+            .collect()
+    }
+
+    /// Hand matches just found to `on_match`, unless the query filters reject them
+    fn report(&self, matches: &[DirectoryMatch]) {
+        if let Some(sink) = &self.on_match {
+            for m in matches.iter().filter(|m| self.keeps(m)) {
+                sink.send(m);
+            }
+        }
+    }
+
+    /// Whether whoever follows the matches has stopped listening
+    fn abandoned(&self) -> bool {
+        self.on_match.as_ref().is_some_and(MatchSink::is_closed)
+    }
+
+    /// Whether the time budget of the search has run out
+    fn out_of_time(&self) -> bool {
+        let late = self.deadline.is_some_and(|d| Instant::now() >= d);
+        if late {
+            self.stats.timed_out();
+        }
+        late
+    }
+}
+
+#[derive(Debug)]
+struct SearchContext {
+    deadline: Option<Instant>,
+    max_matches: usize,
+    max_depth: usize, // How far below the previous component a part after `**` may be
+    found: ranking::TierCount, // Matches so far, less those over their tier limit
+    stats: Arc<SearchStats>,
+}
+
+impl SearchContext {
+    fn new(config: &SearchConfig) -> Self {
+        Self {
+            deadline: config
+                .deadline
+                .or_else(|| Instant::now().checked_add(config.max_time)),
+            max_matches: config.max_matches,
+            max_depth: config.max_depth,
+            found: ranking::TierCount::new(&config.tier_limits),
+            stats: Arc::clone(&config.stats),
+        }
+    }
+
+    fn should_continue(&self) -> bool {
+        if self.found.kept() >= self.max_matches {
+            self.stats.stopped_early();
+            return false;
+        }
+        if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            self.stats.timed_out();
+            return false;
+        }
+        true
+    }
+
+    fn add_match(&mut self, quality: &MatchQuality) {
+        self.found.add(quality);
+    }
+}
+
+/// Resolves the search context by handling relative paths and directory navigation patterns.
+/// Returns (search_directory, pattern) where search_directory is the resolved starting point
+/// and pattern is the remaining search term after resolving relative components.
+fn resolve_search_context(current_dir: &Path, search_term: &str) -> (PathBuf, String) {
+    debug!(
+        "resolve_search_context: current_dir={}, search_term='{}'",
+        current_dir.display(),
+        search_term
+    );
+
+    // Handle empty search term
+    if search_term.is_empty() {
+        debug!("Empty search term, returning current directory");
+        return (current_dir.to_path_buf(), String::new());
+    }
+
+    // `...` is `../..`, `....` is `../../..` and so on
+    let expanded = expand_parent_dots(search_term);
+    if expanded != search_term {
+        debug!("Expanded '{}' to '{}'", search_term, expanded);
+    }
+    let search_term = expanded.as_ref();
+
+    // Handle pure directory navigation without search pattern
+    if search_term == ".." {
+        if let Some(parent) = current_dir.parent() {
+            debug!("Parent directory navigation to {}", parent.display());
+            return (parent.to_path_buf(), String::new());
+        } else {
+            debug!("Already at root, staying in current directory");
+            return (current_dir.to_path_buf(), String::new());
+        }
+    }
+
+    if search_term == "." {
+        debug!("Current directory navigation, staying put");
+        return (current_dir.to_path_buf(), String::new());
+    }
+
+    // Handle relative paths with patterns like "../foo", "../../bar", etc.
+    if search_term.starts_with("../") || search_term.starts_with("./") {
+        let path = platform::str_to_path(search_term);
+        let mut resolved_dir = current_dir.to_path_buf();
+        let mut remaining_pattern = String::new();
+
+        debug!("Processing relative path pattern");
+
+        for component in path.components() {
+            match component {
+                std::path::Component::CurDir => {
+                    debug!(
+                        "Current dir component, staying in {}",
+                        resolved_dir.display()
+                    );
+                    continue;
+                }
+                std::path::Component::ParentDir => {
+                    if let Some(parent) = resolved_dir.parent() {
+                        debug!(
+                            "Parent dir component, moving from {} to {}",
+                            resolved_dir.display(),
+                            parent.display()
+                        );
+                        resolved_dir = parent.to_path_buf();
+                    }
+                }
+                std::path::Component::Normal(name) => {
+                    remaining_pattern = platform::os_to_str(name).into_owned();
+                    debug!(
+                        "Found search pattern '{}' in relative path",
+                        remaining_pattern
+                    );
+                    break;
+                }
+                _ => {
+                    debug!("Other path component encountered");
+                    break;
+                }
+            }
+        }
+
+        debug!(
+            "Resolved relative path: search_dir={}, pattern='{}'",
+            resolved_dir.display(),
+            remaining_pattern
+        );
+        return (resolved_dir, remaining_pattern);
+    }
+
+    // Handle multiple levels of parent directory navigation like "../../", "../../../"
+    if search_term.chars().all(|c| c == '.' || c == '/') && search_term.contains("..") {
+        let mut resolved_dir = current_dir.to_path_buf();
+        let path = platform::str_to_path(search_term);
+
+        debug!("Processing multiple parent directory navigation");
+
+        for component in path.components() {
+            match component {
+                std::path::Component::ParentDir => {
+                    if let Some(parent) = resolved_dir.parent() {
+                        debug!(
+                            "Moving up from {} to {}",
+                            resolved_dir.display(),
+                            parent.display()
+                        );
+                        resolved_dir = parent.to_path_buf();
+                    }
+                }
+                std::path::Component::CurDir => {
+                    debug!("Staying in current directory");
+                }
+                _ => {
+                    break;
+                }
+            }
+        }
+
+        debug!("Final resolved directory: {}", resolved_dir.display());
+        return (resolved_dir, String::new());
+    }
+
+    // For absolute paths and regular patterns, use the original behavior
+    debug!(
+        "Using current directory for search with pattern '{}'",
+        search_term
+    );
+    (current_dir.to_path_buf(), search_term.to_string())
+}
+
+/// Spell out a leading run of three or more dots as parent directories, one
+/// level for each dot after the first, as zsh does: `.../infra` is `../../infra`
+fn expand_parent_dots(search_term: &str) -> Cow<'_, str> {
+    let (head, tail) = match search_term.split_once('/') {
+        Some((head, tail)) => (head, Some(tail)),
+        None => (search_term, None),
+    };
+    if head.len() < 3 || !head.bytes().all(|b| b == b'.') {
+        return Cow::Borrowed(search_term);
+    }
+    let mut expanded = vec![".."; head.len() - 1].join("/");
+    if let Some(tail) = tail {
+        expanded.push('/');
+        expanded.push_str(tail);
+    }
+    Cow::Owned(expanded)
+}
+
+/// How the busy indicator of a slow search is drawn on stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProgressStyle {
+    #[default]
+    Counter, // "searching… 14,203 dirs, depth 5"
+    Spinner, // The counter behind a turning bar
+    Dots,    // " ." to " ...", without figures
+    None,    // Nothing; for prompts that misdraw a line rewritten with \r
+}
+
+impl ProgressStyle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "counter" => Some(ProgressStyle::Counter),
+            "spinner" => Some(ProgressStyle::Spinner),
+            "dots" => Some(ProgressStyle::Dots),
+            "none" => Some(ProgressStyle::None),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ProgressStyle::Counter => "counter",
+            ProgressStyle::Spinner => "spinner",
+            ProgressStyle::Dots => "dots",
+            ProgressStyle::None => "none",
+        }
+    }
+}
+
+/// Search flags from the command line, applied to every pattern searched.
+/// `None` leaves the setting from config.toml in effect.
+#[derive(Clone)]
+pub struct SearchFlags {
+    pub case_sensitive: bool,
+    pub smart_case: Option<bool>, // --smart-case/--no-smart-case override the config
+    pub syntax: PatternSyntax,
+    pub bypass_ignore: bool,
+    pub exclude: Vec<IgnorePattern>, // --exclude globs, applied after the ignore files
+    pub gitignore: Option<bool>,
+    pub follow_symlinks: bool,
+    pub same_filesystem: Option<bool>,
+    pub typos: Option<bool>, // --typos/--no-typos override the config
+    pub ignore_accents: Option<bool>, // --ignore-accents/--match-accents override the config
+    pub full_path: Option<bool>, // --full-path/--name-only override the config
+    pub siblings: Option<bool>, // --siblings/--no-siblings override the config
+    pub cousins: Option<bool>, // --cousins/--no-cousins override the config
+    pub path_words: Vec<String>, // Words before the last of a multi-word query
+    pub timeout_ms: Option<u64>,
+    pub max_matches: Option<usize>,
+    pub min_depth: usize, // --min-depth: nearer matches below the search directory are skipped
+    pub direction: Direction, // --up/--down
+    pub tier_limits: Option<ranking::TierLimits>, // --tier-limits overrides search.tier_limits
+    pub recency_boost: bool,
+    pub shortest_first: Option<bool>, // --shortest-first overrides ranking.shortest_first
+    pub quiet: bool,                  // No busy indicator
+}
+
+impl Default for SearchFlags {
+    fn default() -> Self {
+        Self {
+            case_sensitive: true,
+            smart_case: None,
+            syntax: PatternSyntax::Text,
+            bypass_ignore: false,
+            exclude: Vec::new(),
+            gitignore: None,
+            follow_symlinks: false,
+            same_filesystem: None,
+            typos: None,
+            ignore_accents: None,
+            full_path: None,
+            siblings: None,
+            cousins: None,
+            path_words: Vec::new(),
+            timeout_ms: None,
+            max_matches: None,
+            min_depth: 0,
+            direction: Direction::Both,
+            tier_limits: None,
+            recency_boost: false,
+            shortest_first: None,
+            quiet: false,
+        }
+    }
+}
+
+/// A search term resolved against the starting directory, ready to run
+pub struct PreparedSearch {
+    pub search_dir: PathBuf,
+    pub pattern: String,
+    pub config: SearchConfig,
+    pub bookmarked: Vec<DirectoryMatch>, // Bookmarks named by an '@' term; they replace the search
+}
+
+impl PreparedSearch {
+    /// The bookmarks named by the term, as the search's answer
+    pub fn bookmark_matches(self) -> Vec<DirectoryMatch> {
+        debug!("Found {} matching bookmarks", self.bookmarked.len());
+        let mut matches = self.config.apply_filters(self.bookmarked);
+        matches.truncate(self.config.max_matches);
+        matches
+    }
+}
+
+/// Parse a search term and work out where to search and how
+pub fn prepare_search(
+    search_term: &str,
+    flags: &SearchFlags,
+    current_dir: &Path,
+    settings: &Config,
+) -> Result<PreparedSearch, String> {
+    let _span = debug_span!("prepare", term = search_term).entered();
+    // Pull structured filters (depth:, root:, type:) out of the query
+    let (search_term, filters) = query::parse_query(search_term)?;
+
+    if search_term.is_empty() {
+        return Err("Query filters need a directory pattern".to_string());
+    }
+
+    // Windows paths may use '\'; in a regex it stays an escape
+    let syntax = flags.syntax;
+    let search_term = if syntax == PatternSyntax::Regex {
+        search_term
+    } else {
+        platform::normalize_separators(&search_term).into_owned()
+    };
+
+    // `~/proj` and `~user/proj` are paths from a home directory, not names containing '~'
+    let search_term = match query::expand_home_term(&search_term) {
+        Some(expanded) if syntax != PatternSyntax::Regex => expanded,
+        _ => search_term,
+    };
+
+    // A root: filter replaces the current directory as the starting point
+    let base_dir = match &filters.root {
+        Some(root) if root.is_dir() => root.clone(),
+        Some(root) => {
+            return Err(format!("Search root does not exist: {}", root.display()));
+        }
+        None => current_dir.to_path_buf(),
+    };
+
+    // -i always wins; smart case only relaxes patterns without uppercase letters
+    let smart_case = flags.smart_case.unwrap_or(settings.smart_case);
+    let case_sensitive =
+        flags.case_sensitive && (!smart_case || has_uppercase(&search_term, syntax));
+    let mut config = SearchConfig::new(settings, case_sensitive, filters);
+    config.syntax = syntax;
+    if let Some(gitignore) = flags.gitignore {
+        config.gitignore = gitignore;
+    }
+    config.nested_ignore = !flags.bypass_ignore;
+    config.follow_symlinks = flags.follow_symlinks;
+    if let Some(same_filesystem) = flags.same_filesystem {
+        config.same_filesystem = same_filesystem;
+    }
+    if let Some(typos) = flags.typos {
+        config.typos = typos;
+    }
+    if let Some(ignore_accents) = flags.ignore_accents {
+        config.ignore_accents = ignore_accents;
+    }
+    if let Some(full_path) = flags.full_path {
+        config.full_path = full_path;
+    }
+    if let Some(siblings) = flags.siblings {
+        config.siblings = siblings;
+    }
+    if let Some(cousins) = flags.cousins {
+        config.cousins = cousins;
+    }
+    if let Some(timeout_ms) = flags.timeout_ms {
+        config.max_time = time_budget(timeout_ms);
+    }
+    if let Some(max_matches) = flags.max_matches {
+        config.max_matches = max_matches;
+    }
+    config.min_depth = flags.min_depth;
+    config.direction = flags.direction;
+    if let Some(tier_limits) = &flags.tier_limits {
+        config.tier_limits = tier_limits.clone();
+    }
+
+    // A term starting with '@' names a bookmark; only when none matches is the
+    // literal text searched for
+    let bookmarked = match search_term.strip_prefix('@') {
+        Some(name) if !name.is_empty() => {
+            bookmark::Bookmarks::load().matching(name, case_sensitive, syntax)
+        }
+        _ => Vec::new(),
+    };
+
+    // Handle relative paths and standard directory navigation
+    let (search_dir, pattern) = resolve_search_context(&base_dir, &search_term);
+
+    // Plain patterns are also searched below the configured roots, and fall
+    // back to the CDPATH directories like `cd` itself; `backend/` lists the
+    // subdirectories of one match, found from here alone. Roots are searched
+    // downward, so --up leaves them out.
+    if pattern == search_term
+        && !platform::is_absolute_term(&pattern)
+        && !pattern.ends_with('/')
+        && config.direction.searches_down()
+    {
+        config.roots = roots::configured_roots(&settings.roots, &base_dir);
+        if settings.cdpath {
+            config.fallback_roots = roots::cdpath_roots(&base_dir);
+        }
+    }
+
+    // Inside a git repository, its matches come before those of other checkouts
+    if !platform::is_absolute_term(&pattern) {
+        config.repo_root = project::repo_root(&RealFileSystem, &base_dir).map(Path::to_path_buf);
+    }
+
+    // The earlier words of `jcd api handlers` must appear above each match;
+    // smart case decides for each word on its own
+    config.path_words = flags
+        .path_words
+        .iter()
+        .map(|word| {
+            let case_sensitive =
+                flags.case_sensitive && (!smart_case || has_uppercase(word, syntax));
+            NameMatcher::new(word, case_sensitive, syntax)
+                .map(|matcher| matcher.with_accents_ignored(config.ignore_accents))
+        })
+        .collect::<Result<_, _>>()?;
+
+    // Report a malformed pattern now rather than silently matching nothing
+    if config.full_path {
+        NameMatcher::new(&pattern, case_sensitive, syntax)?;
+    } else {
+        for part in pattern.split('/').filter(|p| !p.is_empty() && *p != "**") {
+            NameMatcher::new(part, case_sensitive, syntax)?;
+        }
+    }
+
+    debug!("Searching for '{}' from {}", pattern, search_dir.display());
+
+    Ok(PreparedSearch {
+        search_dir,
+        pattern,
+        config,
+        bookmarked,
+    })
+}
+
+/// What the searches of one run share, loaded once however many patterns
+/// are searched: ignore patterns, the index, and the history, earlier
+/// choices and pins used for ranking
+pub struct SearchSession<'a> {
+    pub flags: &'a SearchFlags,
+    pub settings: &'a Config,
+    pub rank_weights: ranking::RankWeights, // The defaults, adjusted by ranking.weights or --rank
+    pub ignore_patterns: Vec<IgnorePattern>,
+    pub index: Option<Arc<index::IndexSource>>,
+    pub history: History,
+    pub selections: Selections, // Matches chosen before for a pattern; empty unless ranking.learn
+    pub pins: Vec<PathBuf>,     // ranking.pins and the pin file
+    pub fs: Arc<dyn FileSystem>,
+}
+
+impl<'a> SearchSession<'a> {
+    pub fn new(
+        flags: &'a SearchFlags,
+        settings: &'a Config,
+        rank_weights: Option<&ranking::RankWeights>,
+    ) -> Self {
+        // Load ignore patterns unless bypassed
+        let mut ignore_patterns = if flags.bypass_ignore {
+            debug!("Bypassing ignore patterns (-x flag)");
+            Vec::new()
+        } else {
+            load_ignore_patterns()
+        };
+        // --exclude comes last, so no `!pattern` line of an ignore file re-allows what it skips
+        ignore_patterns.extend(flags.exclude.iter().cloned());
+
+        // The index skips ignored directories and symlinks, so -x and
+        // --follow-symlinks always walk the tree
+        let index = (!flags.bypass_ignore && !flags.follow_symlinks)
+            .then(|| index::IndexSource::detect(settings).map(Arc::new))
+            .flatten();
+
+        Self {
+            flags,
+            settings,
+            rank_weights: rank_weights.cloned().unwrap_or_else(|| {
+                let shortest_first = flags.shortest_first.unwrap_or(settings.shortest_first);
+                ranking::RankWeights::configured(settings, flags.recency_boost, shortest_first)
+            }),
+            ignore_patterns,
+            index,
+            history: History::load().with_decay(settings.decay),
+            selections: Selections::load_if(settings.learn),
+            pins: pin::load_all(&settings.pins),
+            fs: Arc::new(RealFileSystem),
+        }
+    }
+
+    /// Search `fs` instead of the disk. The index describes the disk, so it
+    /// is no longer consulted.
+    pub fn with_file_system(mut self, fs: Arc<dyn FileSystem>) -> Self {
+        self.fs = fs;
+        self.index = None;
+        self
+    }
+
+    /// Run a prepared search and rank its matches
+    pub fn find(&self, search: PreparedSearch) -> Vec<DirectoryMatch> {
+        if !search.bookmarked.is_empty() {
+            return search.bookmark_matches();
+        }
+        let PreparedSearch {
+            search_dir,
+            pattern,
+            mut config,
+            ..
+        } = search;
+        config.index = self.index.clone();
+        config.fs = Arc::clone(&self.fs);
+
+        // Use threaded search with busy indicator (unless in quiet mode)
+        let mut matches = if self.flags.quiet || config.progress_style == ProgressStyle::None {
+            find_matching_directories(&search_dir, &pattern, &config, &self.ignore_patterns)
+        } else {
+            search_with_progress(&search_dir, &pattern, &config, &self.ignore_patterns)
+        };
+
+        debug!("Found {} matches", matches.len());
+
+        ranking::break_ties(
+            &mut matches,
+            self.settings.tie_break,
+            self.shortest_first(),
+            &self.history,
+        );
+        ranking::rank_by_weights(
+            &mut matches,
+            &self.rank_weights,
+            &self.settings.boosts,
+            &self.history,
+        );
+        // A match chosen for this pattern before, from the same area, goes first
+        if let Some(chosen) = self
+            .selections
+            .chosen(&selections::area(&search_dir), &pattern)
+        {
+            if let Some(i) = matches.iter().position(|m| m.path == chosen) {
+                debug!("Moving {} first, chosen before", chosen.display());
+                let m = matches.remove(i);
+                matches.insert(0, m);
+            }
+        }
+        self.put_pins_first(&mut matches, &pattern, &config);
+        config.tier_limits.apply(&mut matches);
+        // Path patterns stop at the limit while searching; every search reports at most that many
+        if matches.len() > config.max_matches {
+            config.stats.match_limited();
+            matches.truncate(config.max_matches);
+        }
+        matches
+    }
+
+    /// Move pinned matches to the front, adding pins whose name matches a
+    /// name pattern wherever they are; among themselves they keep their order
+    fn put_pins_first(
+        &self,
+        matches: &mut Vec<DirectoryMatch>,
+        pattern: &str,
+        config: &SearchConfig,
+    ) {
+        if self.pins.is_empty() {
+            return;
+        }
+        if !pattern.contains('/') && !config.full_path {
+            let matcher = NameMatcher::or_never(pattern, config);
+            let added: Vec<_> = self
+                .pins
+                .iter()
+                .filter(|pin| !matches.iter().any(|m| &m.path == *pin))
+                .filter(|pin| {
+                    pin.file_name().is_some_and(|name| {
+                        let name = matcher.classify(&platform::os_to_str(name));
+                        name.is_some_and(|name| name != NameMatch::Typo)
+                    }) && config.fs.is_dir(pin)
+                })
+                .map(|pin| {
+                    DirectoryMatch::new_in(config.fs.as_ref(), pin.clone(), 0, MatchQuality::Pinned)
+                })
+                .collect();
+            matches.extend(config.apply_filters(added));
+        }
+        for m in matches.iter_mut() {
+            if self.pins.contains(&m.path) {
+                m.match_quality = MatchQuality::Pinned;
+            }
+        }
+        matches.sort_by_key(|m| m.match_quality != MatchQuality::Pinned);
+    }
+
+    /// How `find` scored and ordered the `matches` it returned
+    pub fn explain(&self, matches: &[DirectoryMatch]) -> Vec<ranking::Explanation> {
+        ranking::explain(
+            matches,
+            &self.rank_weights,
+            self.settings.tie_break,
+            self.shortest_first(),
+            &self.settings.boosts,
+            &self.history,
+        )
+    }
+
+    /// Whether fewer path components win among matches of the same kind
+    fn shortest_first(&self) -> bool {
+        self.flags
+            .shortest_first
+            .unwrap_or(self.settings.shortest_first)
+    }
+}
+
+/// The directory named by `--from`, searched from as if it were the current
+/// directory. A relative path is taken from the current directory.
+pub fn search_origin(dir: &str) -> Result<PathBuf, String> {
+    let path = query::expand_home(dir);
+    let path = if path.is_absolute() {
+        path
+    } else {
+        env::current_dir()
+            .or_else(|e| pwd_fallback().ok_or(e))
+            .map_err(|e| format!("Cannot get current directory: {}", e))?
+            .join(path)
+    };
+    fs::canonicalize(&path)
+        .ok()
+        .filter(|d| d.is_dir())
+        .ok_or_else(|| format!("--from needs an existing directory: {}", path.display()))
+}
+
+/// Where to search from when the current directory has been deleted, as
+/// happens after a branch switch: `$PWD` if it has been created again, else
+/// its nearest ancestor that still exists
+pub fn pwd_fallback() -> Option<PathBuf> {
+    let pwd = PathBuf::from(env::var_os("PWD")?);
+    if !pwd.is_absolute() {
+        return None;
+    }
+    let dir = pwd.ancestors().find(|dir| dir.is_dir())?.to_path_buf();
+    debug!(
+        "Searching from {} in place of {}",
+        dir.display(),
+        pwd.display()
+    );
+    Some(dir)
+}
+
+fn search_with_progress(
+    current_dir: &Path,
+    search_term: &str,
+    config: &SearchConfig,
+    ignore_patterns: &[IgnorePattern],
+) -> Vec<DirectoryMatch> {
+    let current_dir = current_dir.to_path_buf();
+    let search_term = search_term.to_string();
+    let progress_delay = config.progress_delay;
+    let style = config.progress_style;
+    let stats = Arc::clone(&config.stats); // Counted by the search, shown by the indicator
+    let config = config.clone(); // Clone for thread
+    let ignore_patterns = ignore_patterns.to_vec(); // Clone for thread
+
+    // The search stores its matches here and wakes the waiting thread
+    let done = Arc::new((Mutex::new(None), Condvar::new()));
+    let done_clone = Arc::clone(&done);
+
+    // Start the search in a background thread
+    let search_handle = thread::spawn(move || {
+        let matches =
+            find_matching_directories(&current_dir, &search_term, &config, &ignore_patterns);
+        let (result, finished) = &*done_clone;
+        *result.lock().unwrap() = Some(matches);
+        finished.notify_all();
+    });
+
+    // Wait up to the delay before showing the indicator; a quick search
+    // ends the wait as soon as it is done
+    let (result, finished) = &*done;
+    let (mut guard, _) = finished
+        .wait_timeout_while(result.lock().unwrap(), progress_delay, |r| r.is_none())
+        .unwrap();
+    let mut frame = 0;
+    // A search that panicked never stores matches; joining it passes the panic on
+    while guard.is_none() && !search_handle.is_finished() {
+        draw_busy_indicator(&stats, style, frame);
+        frame += 1;
+        guard = finished
+            .wait_timeout_while(guard, BUSY_INDICATOR_INTERVAL, |r| r.is_none())
+            .unwrap()
+            .0;
+    }
+    let matches = guard.take();
+    drop(guard);
+    search_handle.join().unwrap();
+
+    if frame > 0 {
+        // Clear the progress line
+        eprint!("\r\x1b[K");
+        io::stderr().flush().unwrap();
+    }
+    matches.unwrap_or_default()
+}
+
+/// `n` with its digits grouped in threes, e.g. 14,203
+fn grouped(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+/// Draw the `frame`th frame of the busy indicator. The counter shows how many
+/// directories the search has listed so far, and the deepest level it
+/// reached, so that a slow search can be told from a hung one.
+fn draw_busy_indicator(stats: &SearchStats, style: ProgressStyle, frame: usize) {
+    let report = stats.report();
+    let counter = format!(
+        "searching\u{2026} {} dirs, depth {}",
+        grouped(report.directories),
+        report.deepest
+    );
+    match style {
+        ProgressStyle::Counter => eprint!("\r\x1b[K{}", counter),
+        ProgressStyle::Spinner => {
+            let bars = ['|', '/', '-', '\\'];
+            eprint!("\r\x1b[K{} {}", bars[frame % bars.len()], counter);
+        }
+        // Two redraws per dot, the pace of the old animation
+        ProgressStyle::Dots => eprint!("\r\x1b[K {}", ".".repeat(frame / 2 % 3 + 1)),
+        ProgressStyle::None => return,
+    }
+    io::stderr().flush().unwrap();
+}
+
+/// Every directory matching `search_term` around `current_dir`, in priority
+/// order but not yet ranked by usage
+pub fn find_matching_directories(
+    current_dir: &Path,
+    search_term: &str,
+    config: &SearchConfig,
+    ignore_patterns: &[IgnorePattern],
+) -> Vec<DirectoryMatch> {
+    let _span = debug_span!("search", term = search_term, dir = %current_dir.display()).entered();
+    let started = Instant::now();
+    // The time budget covers the whole search, the extra roots included
+    let config = &SearchConfig {
+        deadline: config.deadline.or(started.checked_add(config.max_time)),
+        ..config.clone()
+    };
+    let matches = collect_matching_directories(current_dir, search_term, config, ignore_patterns);
+    let mut matches = config.apply_filters(matches);
+
+    // Matches below an extra root rank after those of the roots before it
+    let mut root = 0;
+    let mut search_root = |dir: &Path, matches: &mut Vec<DirectoryMatch>| {
+        root += 1;
+        debug!("Searching extra root {}", dir.display());
+        for mut m in search_extra_root(dir, search_term, config, ignore_patterns) {
+            m.root = root;
+            matches.push(m);
+        }
+    };
+    for dir in &config.roots {
+        search_root(dir, &mut matches);
+    }
+    // Fallback roots are searched only while nothing has matched so far
+    for dir in &config.fallback_roots {
+        if !matches.is_empty() {
+            break;
+        }
+        search_root(dir, &mut matches);
+    }
+
+    // Roots may overlap the search directory or each other; the first find counts
+    if root > 0 {
+        let mut seen = HashSet::new();
+        matches.retain(|m| seen.insert(m.path.clone()));
+    }
+
+    // Within a priority level, matches in the current repository move ahead of
+    // those in other checkouts; the sort is stable, so the order is otherwise kept
+    if let Some(repo) = &config.repo_root {
+        for m in &mut matches {
+            m.same_repo = project::repo_root(config.fs.as_ref(), &m.path) == Some(repo.as_path());
+        }
+        if matches.iter().any(|m| !m.same_repo) {
+            matches.sort_by_key(|m| (m.root, match_priority(m), !m.same_repo));
+        }
+    }
+    config.stats.finished(started.elapsed());
+    matches
+}
+
+/// Matches below an extra search root. Only downward: the root's parents have
+/// nothing to do with where the user is.
+fn search_extra_root(
+    root: &Path,
+    search_term: &str,
+    config: &SearchConfig,
+    ignore_patterns: &[IgnorePattern],
+) -> Vec<DirectoryMatch> {
+    let _span = debug_span!("root", dir = %root.display()).entered();
+    let matches = if search_term.contains('/') && !config.full_path {
+        let parts = path_parts(search_term, config);
+        let mut matches = Vec::new();
+        let mut context = SearchContext::new(config);
+        let reach = parts[0].reach(4, &context);
+        search_pattern_recursive_fast(
+            config.fs.as_ref(),
+            root,
+            &parts[0],
+            &parts[1..],
+            &mut matches,
+            &mut context,
+            0,
+            reach,
+        );
+        config.report(&matches);
+        finalize_matches(matches)
+    } else {
+        config
+            .index
+            .as_ref()
+            .filter(|_| !config.typos)
+            .and_then(|index| index.search_down(root, search_term, config, ignore_patterns))
+            .inspect(|found| config.report(found))
+            .unwrap_or_else(|| {
+                search_down_breadth_first_all(root, search_term, config, ignore_patterns)
+            })
+    };
+    config.apply_filters(matches)
+}
+
+fn collect_matching_directories(
+    current_dir: &Path,
+    search_term: &str,
+    config: &SearchConfig,
+    ignore_patterns: &[IgnorePattern],
+) -> Vec<DirectoryMatch> {
+    let case_sensitive = config.case_sensitive;
+    let fs = config.fs.as_ref();
+    debug!(
+        "find_matching_directories: current_dir={}, search_term='{}', case_sensitive={}",
+        current_dir.display(),
+        search_term,
+        case_sensitive
+    );
+
+    let mut matches = Vec::new();
+
+    // Handle empty search term (pure directory navigation like "..", "../../")
+    if search_term.is_empty() {
+        debug!("Empty search term, returning current directory as match");
+        matches.push(DirectoryMatch::new_in(
+            fs,
+            current_dir.to_path_buf(),
+            0,
+            MatchQuality::ExactDown,
+        ));
+        config.report(&matches);
+        return matches;
+    }
+
+    // Handle absolute paths
+    if platform::is_absolute_term(search_term) {
+        debug!("Processing absolute path: {}", search_term);
+        let path = platform::str_to_path(search_term);
+
+        if search_term.ends_with('/') {
+            debug!("Absolute path ends with slash - exploring subdirectories");
+            let dir_path = &platform::str_to_path(platform::strip_trailing_separator(search_term));
+            if fs.is_dir(dir_path) {
+                list_subdirectories(dir_path, &mut matches, config);
+            } else {
+                debug!("Directory doesn't exist, treating as pattern search");
+                let search_term_no_slash = platform::strip_trailing_separator(search_term);
+                let (search_root, search_pattern) =
+                    find_search_root_and_pattern(fs, search_term_no_slash);
+                if let Some(root) = search_root {
+                    search_absolute_pattern(&root, &search_pattern, &mut matches, config);
+                }
+            }
+        } else if fs.is_dir(&path) {
+            debug!("Absolute path exists exactly, returning it directly");
+            matches.push(DirectoryMatch::new_in(
+                fs,
+                path.to_path_buf(),
+                0,
+                MatchQuality::ExactDown,
+            ));
+            config.report(&matches);
+        } else {
+            debug!("Absolute path doesn't exist, finding search root and pattern");
+            let (search_root, search_pattern) = find_search_root_and_pattern(fs, search_term);
+            if let Some(root) = search_root {
+                debug!(
+                    "Searching from root {} for pattern '{}'",
+                    root.display(),
+                    search_pattern
+                );
+                search_absolute_pattern(&root, &search_pattern, &mut matches, config);
+            }
+        }
+        return finalize_matches(matches);
+    }
+
+    // `backend/` lists the subdirectories of the best match for `backend`, as
+    // an absolute path ending in '/' does for that directory
+    if let Some(parent_term) = search_term.strip_suffix('/').filter(|t| !t.is_empty()) {
+        debug!("Pattern ends with slash - exploring subdirectories of its best match");
+        // Query filters are for the subdirectories, and the parent is not a match to report
+        let parent_config = SearchConfig {
+            filters: QueryFilters::default(),
+            on_match: None,
+            ..config.clone()
+        };
+        let parents =
+            collect_matching_directories(current_dir, parent_term, &parent_config, ignore_patterns);
+        if let Some(best) = parent_config.apply_filters(parents).into_iter().next() {
+            debug!(
+                "Best match for '{}' is {}",
+                parent_term,
+                best.path.display()
+            );
+            list_subdirectories(&best.path, &mut matches, config);
+        }
+        return finalize_matches(matches);
+    }
+
+    // Handle path-like patterns (contains '/'); --full-path matches them whole below
+    if search_term.contains('/') && !config.full_path {
+        debug!("Processing path-like pattern with '/'");
+        let mut context = SearchContext::new(config);
+        search_path_pattern_fast(current_dir, search_term, &mut matches, &mut context, config);
+        config.report(&matches);
+        if !matches.is_empty() {
+            debug!("Found {} matches for path pattern", matches.len());
+            return finalize_matches(matches);
+        }
+    }
+
+    debug!("Starting comprehensive search - up tree then down tree");
+
+    // 1. Search up for exact matches, then partial matches (direct path to root only)
+    if config.direction.searches_up() {
+        let up_matches =
+            search_up_tree_with_priority(current_dir, search_term, config, ignore_patterns);
+        config.report(&up_matches);
+        debug!("Found {} matches searching up tree", up_matches.len());
+        matches.extend(up_matches);
+    }
+
+    // 2. With --siblings, the other subdirectories of the parent, and with
+    // --cousins those a little below each nearby ancestor, before any deeper match.
+    // These are neither above nor below, so --up and --down both skip them.
+    let beside = if config.direction != Direction::Both {
+        None
+    } else if config.cousins {
+        Some((COUSIN_ANCESTORS, COUSIN_LEVELS))
+    } else {
+        config.siblings.then_some((1, 1))
+    };
+    if let Some((ancestors, levels)) = beside {
+        let beside_matches = search_beside(
+            current_dir,
+            search_term,
+            config,
+            ignore_patterns,
+            ancestors,
+            levels,
+        );
+        config.report(&beside_matches);
+        debug!(
+            "Found {} matches beside the current directory",
+            beside_matches.len()
+        );
+        matches.extend(beside_matches);
+    }
+
+    // 3. Search down for all matches (exact and partial) from current directory only,
+    // answered from the index when it covers this directory
+    // The index classifies names without typos, so --typos walks the tree
+    if config.direction.searches_down() {
+        let indexed = config
+            .index
+            .as_ref()
+            .filter(|_| !config.typos)
+            .and_then(|index| index.search_down(current_dir, search_term, config, ignore_patterns));
+        let down_matches = match indexed {
+            Some(found) => {
+                debug!("Downward search answered from the index");
+                config.report(&found);
+                found
+            }
+            None => {
+                search_down_breadth_first_all(current_dir, search_term, config, ignore_patterns)
+            }
+        };
+        debug!("Found {} matches searching down tree", down_matches.len());
+        matches.extend(down_matches);
+    }
+
+    // Return all matches sorted by priority
+    if !matches.is_empty() {
+        debug!("Total {} matches found, finalizing", matches.len());
+        return finalize_matches(matches);
+    }
+
+    debug!("No matches found");
+    Vec::new()
+}
+
+fn search_up_tree_with_priority(
+    current_dir: &Path,
+    search_term: &str,
+    config: &SearchConfig,
+    ignore_patterns: &[IgnorePattern],
+) -> Vec<DirectoryMatch> {
+    let _span = debug_span!("up").entered();
+    let case_sensitive = config.case_sensitive;
+    debug!(
+        "search_up_tree_with_priority: searching for '{}', case_sensitive={}",
+        search_term, case_sensitive
+    );
+
+    let mut exact_matches = Vec::new();
+    let mut partial_matches = Vec::new();
+    let mut typo_matches = Vec::new();
+    let mut current = current_dir;
+    let mut depth = -1;
+    let matcher = NameMatcher::or_never(search_term, config).with_typos(config.typos);
+
+    while let Some(parent) = current.parent() {
+        if let Some(name) = parent.file_name() {
+            let name_str = platform::os_to_str(name);
+
+            // Check if this directory should be ignored
+            if should_ignore_directory(&name_str, ignore_patterns) {
+                trace!("Ignoring parent directory: {}", name_str);
+                config.stats.ignored();
+                current = parent;
+                depth -= 1;
+                continue;
+            }
+
+            trace!("Checking parent '{}' at depth {}", name_str, depth);
+
+            // Up the tree there are no prefix, word or acronym tiers: they count as partial matches
+            match matcher.classify(&name_str) {
+                Some(NameMatch::Exact) => {
+                    debug!("Exact match found: {}", parent.display());
+                    exact_matches.push(DirectoryMatch::new_in(
+                        config.fs.as_ref(),
+                        parent.to_path_buf(),
+                        depth,
+                        MatchQuality::ExactUp,
+                    ));
+                }
+                Some(
+                    NameMatch::Prefix | NameMatch::Word | NameMatch::Acronym | NameMatch::Partial,
+                ) => {
+                    debug!("Partial match found: {}", parent.display());
+                    partial_matches.push(DirectoryMatch::new_in(
+                        config.fs.as_ref(),
+                        parent.to_path_buf(),
+                        depth,
+                        MatchQuality::PartialUp,
+                    ));
+                }
+                Some(NameMatch::Typo) => {
+                    debug!("Typo match found: {}", parent.display());
+                    typo_matches.push(DirectoryMatch::new_in(
+                        config.fs.as_ref(),
+                        parent.to_path_buf(),
+                        depth,
+                        MatchQuality::TypoUp,
+                    ));
+                }
+                None => {}
+            }
+        }
+        current = parent;
+        depth -= 1;
+    }
+
+    let mut result = exact_matches;
+    result.extend(partial_matches);
+    result.extend(typo_matches);
+
+    debug!(
+        "search_up_tree_with_priority completed with {} matches",
+        result.len()
+    );
+
+    result
+}
+
+/// Matches beside `current_dir`: down to `levels` levels below each of its
+/// nearest `ancestors` ancestors, leaving out the branch it is in, which the
+/// other phases search. The parent's other subdirectories are siblings, the
+/// rest cousins. The depth of a match counts the levels up to the ancestor and
+/// down from it, less one, so siblings are 1 away. As up the tree, names match
+/// exactly or partially.
+fn search_beside(
+    current_dir: &Path,
+    search_term: &str,
+    config: &SearchConfig,
+    ignore_patterns: &[IgnorePattern],
+    ancestors: usize,
+    levels: usize,
+) -> Vec<DirectoryMatch> {
+    let _span = debug_span!("beside").entered();
+    use std::collections::VecDeque;
+
+    let fs = config.fs.as_ref();
+    let mut exact_matches = Vec::new();
+    let mut partial_matches = Vec::new();
+    let matcher = NameMatcher::or_never(search_term, config);
+    let nearest = current_dir.ancestors().skip(1).zip(current_dir.ancestors());
+    for (up, (ancestor, came_from)) in nearest.take(ancestors).enumerate() {
+        trace!(
+            "Searching beside {} from {}",
+            came_from.display(),
+            ancestor.display()
+        );
+        let mut queue = VecDeque::from([(ancestor.to_path_buf(), 0)]);
+        while let Some((dir, level)) = queue.pop_front() {
+            if config.abandoned() || config.out_of_time() {
+                break;
+            }
+            let Some(mut entries) = list_dir(fs, &dir, &config.stats) else {
+                continue;
+            };
+            config.stats.listed(entries.len(), level);
+            entries.sort_by(|a, b| ranking::natural_name_cmp(&a.name, &b.name));
+
+            for entry in entries {
+                if entry.kind != EntryKind::Directory || entry.path == came_from {
+                    continue;
+                }
+                let name_str = platform::os_to_str(&entry.name);
+                if should_ignore_directory(&name_str, ignore_patterns) {
+                    trace!("Ignoring directory beside the current one: {}", name_str);
+                    config.stats.ignored();
+                    continue;
+                }
+                let depth = (up + level + 1) as i32;
+                let sibling = up == 0 && level == 0;
+                match matcher.classify(&name_str) {
+                    Some(NameMatch::Exact) => {
+                        debug!("Exact match beside: {}", entry.path.display());
+                        let quality = if sibling {
+                            MatchQuality::ExactSibling
+                        } else {
+                            MatchQuality::ExactCousin
+                        };
+                        exact_matches.push(DirectoryMatch::new_in(
+                            fs,
+                            entry.path.clone(),
+                            depth,
+                            quality,
+                        ));
+                    }
+                    Some(_) => {
+                        debug!("Partial match beside: {}", entry.path.display());
+                        let quality = if sibling {
+                            MatchQuality::PartialSibling
+                        } else {
+                            MatchQuality::PartialCousin
+                        };
+                        partial_matches.push(DirectoryMatch::new_in(
+                            fs,
+                            entry.path.clone(),
+                            depth,
+                            quality,
+                        ));
+                    }
+                    None => {}
+                }
+                if level + 1 < levels {
+                    queue.push_back((entry.path, level + 1));
+                }
+            }
+        }
+    }
+
+    let mut result = exact_matches;
+    result.extend(partial_matches);
+    result
+}
+
+/// The entries of `dir`, or None when it cannot be read, noting why in `stats`
+fn list_dir(
+    fs: &dyn FileSystem,
+    dir: &Path,
+    stats: &SearchStats,
+) -> Option<Vec<filesystem::Entry>> {
+    fs.read_dir(dir).map_err(|e| stats.unreadable(dir, &e)).ok()
+}
+
+/// Decides which directories the downward search descends into. With
+/// --follow-symlinks it remembers the directories entered, since a link back to
+/// an ancestor would otherwise loop; with --same-filesystem it stays on the
+/// filesystem the search started on.
+struct Descent<'a> {
+    fs: &'a dyn FileSystem,
+    stats: &'a SearchStats,
+    seen: Option<HashSet<platform::DirKey>>, // Only tracked with --follow-symlinks
+    device: Option<u64>,                     // Only set with --same-filesystem
+}
+
+impl<'a> Descent<'a> {
+    fn new(root: &Path, config: &'a SearchConfig) -> Self {
+        let fs = config.fs.as_ref();
+        Self {
+            fs,
+            stats: &config.stats,
+            seen: config
+                .follow_symlinks
+                .then(|| fs.dir_key(root).into_iter().collect()),
+            device: config
+                .same_filesystem
+                .then(|| fs.metadata(root).ok()?.device)
+                .flatten(),
+        }
+    }
+
+    /// Whether `entry` is searched as a directory: a real one, or with
+    /// --follow-symlinks a link to one. Links that cannot be followed, such
+    /// as loops, are noted in the statistics.
+    fn is_dir(&self, entry: &filesystem::Entry) -> bool {
+        match entry.kind {
+            EntryKind::Directory => true,
+            EntryKind::Symlink if self.seen.is_some() => match self.fs.metadata(&entry.path) {
+                Ok(metadata) => metadata.is_dir,
+                Err(e) => {
+                    self.stats.unreadable(&entry.path, &e);
+                    false
+                }
+            },
+            EntryKind::Symlink | EntryKind::Other => false,
+        }
+    }
+
+    /// Whether to descend into `path`. Directories that are skipped can still
+    /// match: a mount point is reported, only its contents are not searched.
+    fn enters(&mut self, path: &Path) -> bool {
+        if let Some(device) = self.device {
+            if self.fs.metadata(path).ok().and_then(|m| m.device) != Some(device) {
+                trace!(
+                    "{} is on another filesystem, not descending",
+                    path.display()
+                );
+                return false;
+            }
+        }
+        let Some(seen) = &mut self.seen else {
+            return true;
+        };
+        match self.fs.dir_key(path) {
+            Some(key) if seen.insert(key) => true,
+            _ => {
+                trace!("Already searched {}, not descending", path.display());
+                false
+            }
+        }
+    }
+}
+
+/// `path` relative to `root` with '/' between its components: what --full-path
+/// matches a pattern against
+fn path_below(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative
+        .iter()
+        .map(platform::os_to_str)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether the walk below has found `max_matches` matches that no deeper one
+/// can outrank: immediate matches and exact ones, less those beyond their tier
+/// limit. Filters may drop some of them, so then the walk goes on.
+fn has_enough_top_matches(matches: &[DirectoryMatch], config: &SearchConfig) -> bool {
+    let top = |m: &&DirectoryMatch| {
+        m.match_quality == MatchQuality::ExactDown
+            || (m.depth_from_current == 1 && m.match_quality != MatchQuality::TypoDown)
+    };
+    if !config.allows_early_stop() {
+        return false;
+    }
+    let mut found = ranking::TierCount::new(&config.tier_limits);
+    for m in matches.iter().filter(top) {
+        found.add(&m.match_quality);
+    }
+    found.kept() >= config.max_matches
+}
+
+fn search_down_breadth_first_all(
+    current_dir: &Path,
+    search_term: &str,
+    config: &SearchConfig,
+    ignore_patterns: &[IgnorePattern],
+) -> Vec<DirectoryMatch> {
+    let _span = debug_span!("down").entered();
+    let case_sensitive = config.case_sensitive;
+    debug!(
+        "search_down_breadth_first_all: searching for '{}', case_sensitive={}",
+        search_term, case_sensitive
+    );
+
+    use std::collections::VecDeque;
+
+    let mut queue = VecDeque::new();
+    let mut all_matches = Vec::new();
+    let matcher = NameMatcher::or_never(search_term, config).with_typos(config.typos);
+    // Each queued directory carries the gitignore rules and ignore scope of its parent
+    let root_rules = if config.gitignore {
+        GitIgnore::for_dir(current_dir)
+    } else {
+        Arc::new(GitIgnore::default())
+    };
+    let root_scope = IgnoreScope::new(ignore_patterns, current_dir, config.nested_ignore);
+    let mut descent = Descent::new(current_dir, config);
+    let max_depth = config.max_depth as i32;
+
+    // First, search immediate subdirectories (depth 1) to check for early stopping
+    let mut immediate_matches = Vec::new();
+
+    debug!(
+        "Processing immediate subdirectories in {}",
+        current_dir.display()
+    );
+
+    // Process current directory (depth 0) first
+    if let Some(mut entries) = list_dir(config.fs.as_ref(), current_dir, &config.stats) {
+        config.stats.listed(entries.len(), 0);
+        entries.sort_by(|a, b| ranking::natural_name_cmp(&a.name, &b.name));
+
+        for entry in &entries {
+            if descent.is_dir(entry) {
+                let path = entry.path.clone();
+                if let Some(name) = path.file_name() {
+                    let name_str = platform::os_to_str(name);
+
+                    // Check if this directory should be ignored
+                    if root_scope.is_ignored(&name_str) {
+                        trace!("Ignoring directory: {}", name_str);
+                        config.stats.ignored();
+                        continue;
+                    }
+                    if config.gitignore && root_rules.is_ignored(&path) {
+                        trace!("Ignoring git-ignored directory: {}", name_str);
+                        config.stats.ignored();
+                        continue;
+                    }
+
+                    // Check for any match in immediate subdirectories
+                    if let Some(kind) = matcher.classify(&name_str) {
+                        debug!("Immediate {:?} match: {}", kind, path.display());
+                        let dir_match = DirectoryMatch::new_in(
+                            config.fs.as_ref(),
+                            path.clone(),
+                            1,
+                            MatchQuality::down(kind),
+                        );
+                        config.report(std::slice::from_ref(&dir_match));
+                        immediate_matches.push(dir_match.clone());
+                        all_matches.push(dir_match);
+                    }
+
+                    // Add subdirectories to queue for potential deeper search
+                    if descent.enters(&path) {
+                        queue.push_back((
+                            path.clone(),
+                            1,
+                            Arc::clone(&root_rules),
+                            Arc::clone(&root_scope),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    // If there are exact or prefix matches in immediate subdirectories, return early to avoid deep search
+    let has_good_immediate = immediate_matches.iter().any(|m| {
+        matches!(
+            m.match_quality,
+            MatchQuality::ExactDown | MatchQuality::PrefixDown
+        )
+    });
+    if has_good_immediate && config.allows_early_stop() {
+        debug!("Found good immediate matches, skipping deep search");
+        return finalize_matches(all_matches);
+    }
+
+    debug!("No good immediate matches, continuing with deep search");
+
+    // Otherwise, continue with breadth-first search for deeper levels
+    let mut level = 1;
+    while let Some((current_path, depth, parent_rules, parent_scope)) = queue.pop_front() {
+        if depth == 0 || depth > max_depth {
+            continue; // Skip depth 0 (already processed) and beyond max depth
+        }
+        if config.abandoned() || config.out_of_time() {
+            break;
+        }
+        // Matches from the next level rank after those found so far
+        if depth > level {
+            level = depth;
+            if has_enough_top_matches(&all_matches, config) {
+                debug!(
+                    "Found enough top matches, skipping depth {} and below",
+                    depth + 1
+                );
+                config.stats.match_limited();
+                break;
+            }
+        }
+        let rules = if config.gitignore {
+            parent_rules.enter(&current_path)
+        } else {
+            parent_rules
+        };
+        trace!("Searching depth {} in {}", depth, current_path.display());
+
+        let mut level_matches = Vec::new();
+        let mut level_subdirs = Vec::new();
+        let mut scope = parent_scope;
+
+        if let Some(mut entries) = list_dir(config.fs.as_ref(), &current_path, &config.stats) {
+            config.stats.listed(entries.len(), depth as usize);
+            // Sort entries for deterministic order
+            entries.sort_by(|a, b| ranking::natural_name_cmp(&a.name, &b.name));
+            // Only read a nested ignore file when the listing shows one
+            if entries.iter().any(|e| e.name == NESTED_IGNORE_FILE) {
+                scope = scope.enter(&current_path);
+            }
+
+            // Process all entries at this level
+            for entry in &entries {
+                if descent.is_dir(entry) {
+                    let path = entry.path.clone();
+                    if let Some(name) = path.file_name() {
+                        let name_str = platform::os_to_str(name);
+
+                        // Check if this directory should be ignored
+                        if scope.is_ignored(&name_str) {
+                            trace!("Ignoring directory at depth {}: {}", depth + 1, name_str);
+                            config.stats.ignored();
+                            continue;
+                        }
+                        if config.gitignore && rules.is_ignored(&path) {
+                            trace!(
+                                "Ignoring git-ignored directory at depth {}: {}",
+                                depth + 1,
+                                name_str
+                            );
+                            config.stats.ignored();
+                            continue;
+                        }
+
+                        // Check for any match (exact, prefix, or partial)
+                        let subject = if config.full_path {
+                            Cow::Owned(path_below(current_dir, &path))
+                        } else {
+                            name_str.clone()
+                        };
+                        if let Some(kind) = matcher.classify(&subject) {
+                            debug!(
+                                "Deep {:?} match at depth {}: {}",
+                                kind,
+                                depth + 1,
+                                path.display()
+                            );
+                            level_matches.push(DirectoryMatch::new_in(
+                                config.fs.as_ref(),
+                                path.clone(),
+                                depth + 1,
+                                MatchQuality::down(kind),
+                            ));
+                        }
+
+                        // Collect subdirectories for next level
+                        if depth >= max_depth {
+                            config.stats.depth_limited();
+                        } else if descent.enters(&path) {
+                            level_subdirs.push((path.clone(), depth + 1));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Add matches from this level
+        config.report(&level_matches);
+        all_matches.extend(level_matches);
+
+        // Add subdirectories to queue for next level search
+        for (subdir, next_depth) in level_subdirs {
+            queue.push_back((subdir, next_depth, Arc::clone(&rules), Arc::clone(&scope)));
+        }
+    }
+
+    debug!(
+        "search_down_breadth_first_all completed with {} total matches",
+        all_matches.len()
+    );
+
+    finalize_matches(all_matches)
+}
+
+/// Priority category of a match; lower values rank first
+fn match_priority(m: &DirectoryMatch) -> u32 {
+    match (m.depth_from_current, &m.match_quality) {
+        // Bookmarks were asked for by name, and pins are meant to win - above everything else
+        (_, MatchQuality::Bookmark | MatchQuality::Pinned) => 0,
+        // Immediate subdirectory exact matches - highest priority
+        (1, MatchQuality::ExactDown) => 1,
+        // Immediate subdirectory prefix matches - very high priority
+        (1, MatchQuality::PrefixDown) => 2,
+        // Immediate subdirectory word matches - high priority
+        (1, MatchQuality::WordDown) => 3,
+        // Immediate subdirectory acronym matches - high priority
+        (1, MatchQuality::AcronymDown) => 4,
+        // Immediate subdirectory partial matches - high priority
+        (1, MatchQuality::PartialDown) => 5,
+        // Up tree exact matches - medium-high priority
+        (_, MatchQuality::ExactUp) => 6,
+        // Exact matches next to the current directory (--siblings) - medium-high priority
+        (_, MatchQuality::ExactSibling) => 7,
+        // Exact matches below nearby ancestors (--cousins) - medium-high priority
+        (_, MatchQuality::ExactCousin) => 8,
+        // Up tree partial matches - medium priority
+        (_, MatchQuality::PartialUp) => 9,
+        // Partial matches next to the current directory (--siblings) - medium priority
+        (_, MatchQuality::PartialSibling) => 10,
+        // Partial matches below nearby ancestors (--cousins) - medium priority
+        (_, MatchQuality::PartialCousin) => 11,
+        // Deeper exact matches - lower priority
+        (_, MatchQuality::ExactDown) => 12,
+        // Deeper prefix matches - lower priority
+        (_, MatchQuality::PrefixDown) => 13,
+        // Deeper word matches - lower priority
+        (_, MatchQuality::WordDown) => 14,
+        // Deeper acronym matches - lower priority
+        (_, MatchQuality::AcronymDown) => 15,
+        // Deeper partial matches - lowest priority
+        (_, MatchQuality::PartialDown) => 16,
+        // Names a typo or two away (--typos) - below every real match
+        (_, MatchQuality::TypoUp) => 17,
+        (_, MatchQuality::TypoDown) => 18,
+    }
+}
+
+/// The finalized order of matches: search root, tier, current repository,
+/// project root, then distance (closest first up the tree, shallowest first
+/// down it). Matches equal in all of these are tied.
+fn finalized_order(a: &DirectoryMatch, b: &DirectoryMatch) -> std::cmp::Ordering {
+    a.root
+        .cmp(&b.root)
+        .then_with(|| match_priority(a).cmp(&match_priority(b)))
+        .then_with(|| b.same_repo.cmp(&a.same_repo))
+        .then_with(|| b.project_root.cmp(&a.project_root))
+        .then_with(|| match a.match_quality {
+            MatchQuality::ExactUp | MatchQuality::PartialUp | MatchQuality::TypoUp => {
+                b.depth_from_current.cmp(&a.depth_from_current)
+            }
+            _ => a.depth_from_current.cmp(&b.depth_from_current),
+        })
+}
+
+fn finalize_matches(mut matches: Vec<DirectoryMatch>) -> Vec<DirectoryMatch> {
+    debug!("finalize_matches: input {} matches", matches.len());
+    trace_matches(&matches);
+
+    // Remove duplicates based on path
+    matches.sort_by(|a, b| ranking::natural_path_cmp(&a.path, &b.path));
+    matches.dedup_by(|a, b| a.path == b.path);
+
+    debug!("After dedup: {} matches", matches.len());
+
+    // Sort by priority; the sort is stable, so ties stay in path order
+    matches.sort_by(finalized_order);
+
+    debug!("After sorting: {} matches", matches.len());
+    trace_matches(&matches);
+
+    matches
+}
+
+/// Each match on its own line, at trace level
+fn trace_matches(matches: &[DirectoryMatch]) {
+    for (i, m) in matches.iter().enumerate() {
+        trace!(
+            index = i,
+            quality = ?m.match_quality,
+            depth = m.depth_from_current,
+            path = %m.path.display(),
+        );
+    }
+}
+
+/// Mount point and whether it keeps access times up to date
+struct MountEntry {
+    mount_point: PathBuf,
+    atime_reliable: bool,
+}
+
+/// Decode the octal escapes (`\040` for space, etc.) used in /proc/self/mounts
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 3 < bytes.len() {
+            if let Ok(code) = u8::from_str_radix(&field[i + 1..i + 4], 8) {
+                out.push(code);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Load the mount table so atime reliability can be decided per match
+fn load_mount_table() -> Vec<MountEntry> {
+    let content = match fs::read_to_string("/proc/self/mounts") {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 4 {
+                return None;
+            }
+            // noatime/nodiratime mounts never refresh directory access times
+            let atime_reliable = !fields[3]
+                .split(',')
+                .any(|opt| opt == "noatime" || opt == "nodiratime");
+            Some(MountEntry {
+                mount_point: PathBuf::from(unescape_mount_field(fields[1])),
+                atime_reliable,
+            })
+        })
+        .collect()
+}
+
+/// Check whether the filesystem holding `path` records access times.
+/// Without a mount table (non-Linux systems) atime is assumed to be usable.
+fn is_atime_reliable(path: &Path, mounts: &[MountEntry]) -> bool {
+    mounts
+        .iter()
+        .filter(|mount| path.starts_with(&mount.mount_point))
+        .max_by_key(|mount| mount.mount_point.as_os_str().len())
+        .is_none_or(|mount| mount.atime_reliable)
+}
+
+/// Get the last time a directory was used, or None when no trustworthy signal exists.
+/// Falls back to jcd's own history when the filesystem does not track atime.
+fn directory_last_access(
+    m: &DirectoryMatch,
+    mounts: &[MountEntry],
+    history: &History,
+) -> Option<SystemTime> {
+    if is_atime_reliable(&m.path, mounts) {
+        return m.last_access;
+    }
+    trace!("atime not reliable for {}, using history", m.path.display());
+    history
+        .get(&m.path)
+        .map(|entry| UNIX_EPOCH + Duration::from_secs(entry.last_visit))
+}
+
+/// One component of a path pattern
+struct PathPart {
+    matcher: NameMatcher,
+    deep: bool, // After `**`: any number of directories may come between it and the previous part
+}
+
+impl PathPart {
+    /// How many levels down this part is looked for: `near` below the
+    /// previous part, or as deep as the search goes after `**`
+    fn reach(&self, near: usize, context: &SearchContext) -> usize {
+        if self.deep {
+            context.max_depth
+        } else {
+            near
+        }
+    }
+}
+
+/// The components of a path pattern. `**` is not a component of its own: it
+/// lets the next one match any number of levels further down, and a trailing
+/// `**` stands for every directory below.
+fn path_parts(search_term: &str, config: &SearchConfig) -> Vec<PathPart> {
+    let mut parts = Vec::new();
+    let mut deep = false;
+    for part in search_term.split('/') {
+        if part == "**" {
+            deep = true;
+            continue;
+        }
+        parts.push(PathPart {
+            matcher: NameMatcher::or_never(part, config),
+            deep,
+        });
+        deep = false;
+    }
+    if deep {
+        parts.push(PathPart {
+            matcher: NameMatcher::any(),
+            deep,
+        });
+    }
+    parts
+}
+
+fn search_path_pattern_fast(
+    current_dir: &Path,
+    search_term: &str,
+    matches: &mut Vec<DirectoryMatch>,
+    context: &mut SearchContext,
+    config: &SearchConfig,
+) {
+    let _span = debug_span!("path_pattern").entered();
+    let case_sensitive = config.case_sensitive;
+    debug!(
+        "search_path_pattern_fast: current_dir={}, search_term='{}', case_sensitive={}",
+        current_dir.display(),
+        search_term,
+        case_sensitive
+    );
+
+    let parts = path_parts(search_term, config);
+    if parts.is_empty() || !context.should_continue() {
+        debug!("search_path_pattern_fast: early exit - parts empty or context expired");
+        return;
+    }
+
+    let first_part = &parts[0];
+    let remaining_parts = &parts[1..];
+
+    debug!(
+        "search_path_pattern_fast: split into first_part='{}', remaining_parts={:?}",
+        first_part.matcher.pattern(),
+        remaining_parts
+            .iter()
+            .map(|part| part.matcher.pattern())
+            .collect::<Vec<_>>()
+    );
+
+    // Search for the first part in current directory and subdirectories
+    if config.direction.searches_down() {
+        debug!("search_path_pattern_fast: starting recursive search down from current dir");
+        let reach = first_part.reach(4, context);
+        search_pattern_recursive_fast(
+            config.fs.as_ref(),
+            current_dir,
+            first_part,
+            remaining_parts,
+            matches,
+            context,
+            0,
+            reach,
+        );
+    }
+
+    // Also search up the tree for the first part (but limit this to avoid slowdown);
+    // with --up the first part must name a parent directory
+    if !config.direction.searches_up() {
+        return;
+    }
+    debug!("search_path_pattern_fast: starting search up the tree");
+    let mut current = current_dir;
+    let mut depth = -1;
+    let mut up_count = 0;
+
+    while let Some(parent) = current.parent() {
+        if !context.should_continue() || up_count >= 10 {
+            debug!("search_path_pattern_fast: stopping up search - context expired or max up count reached");
+            break;
+        }
+
+        if let Some(name) = parent.file_name() {
+            let name_str = platform::os_to_str(name);
+            trace!(
+                "search_path_pattern_fast: checking parent '{}' at depth {}",
+                name_str,
+                depth
+            );
+
+            if let Some(kind) = first_part.matcher.classify(&name_str) {
+                trace!(
+                    "search_path_pattern_fast: parent '{}' contains pattern '{}'",
+                    name_str,
+                    first_part.matcher.pattern()
+                );
+
+                if remaining_parts.is_empty() {
+                    let match_quality = if kind == NameMatch::Exact {
+                        MatchQuality::ExactUp
+                    } else {
+                        MatchQuality::PartialUp
+                    };
+
+                    debug!(
+                        "search_path_pattern_fast: adding up match {:?} for {}",
+                        match_quality,
+                        parent.display()
+                    );
+
+                    matches.push(DirectoryMatch::new_in(
+                        config.fs.as_ref(),
+                        parent.to_path_buf(),
+                        depth,
+                        match_quality.clone(),
+                    ));
+                    context.add_match(&match_quality);
+                } else {
+                    debug!(
+                        "search_path_pattern_fast: recursing from parent for remaining patterns"
+                    );
+                    let reach = remaining_parts[0].reach(3, context);
+                    search_pattern_recursive_fast(
+                        config.fs.as_ref(),
+                        parent,
+                        &remaining_parts[0],
+                        &remaining_parts[1..],
+                        matches,
+                        context,
+                        depth,
+                        reach,
+                    );
+                }
+            }
+        }
+        current = parent;
+        depth -= 1;
+        up_count += 1;
+    }
+
+    debug!(
+        "search_path_pattern_fast: completed with {} total matches",
+        matches.len()
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn search_pattern_recursive_fast(
+    fs: &dyn FileSystem,
+    current_dir: &Path,
+    pattern: &PathPart,
+    remaining_patterns: &[PathPart],
+    matches: &mut Vec<DirectoryMatch>,
+    context: &mut SearchContext,
+    base_depth: i32,
+    max_depth: usize,
+) {
+    trace!("search_pattern_recursive_fast: dir={}, pattern='{}', remaining={:?}, base_depth={}, max_depth={}",
+                 current_dir.display(), pattern.matcher.pattern(), remaining_patterns.iter().map(|part| part.matcher.pattern()).collect::<Vec<_>>(), base_depth, max_depth);
+
+    if max_depth == 0 {
+        trace!("search_pattern_recursive_fast: early exit - max_depth=0");
+        context.stats.depth_limited();
+        return;
+    }
+    if !context.should_continue() {
+        trace!("search_pattern_recursive_fast: early exit - context expired");
+        return;
+    }
+
+    if let Some(entries) = list_dir(fs, current_dir, &context.stats) {
+        context
+            .stats
+            .listed(entries.len(), base_depth.max(0) as usize);
+        let mut entry_count = 0;
+        let mut match_count = 0;
+
+        for entry in entries {
+            entry_count += 1;
+
+            if !context.should_continue() {
+                debug!("search_pattern_recursive_fast: breaking due to context timeout");
+                break;
+            }
+
+            if entry.kind == EntryKind::Directory {
+                let path = entry.path;
+                if let Some(name) = path.file_name() {
+                    let name_str = platform::os_to_str(name);
+                    if let Some(kind) = pattern.matcher.classify(&name_str) {
+                        match_count += 1;
+
+                        trace!("search_pattern_recursive_fast: found matching dir '{}' for pattern '{}'", name_str, pattern.matcher.pattern());
+
+                        if remaining_patterns.is_empty() {
+                            let is_exact = kind == NameMatch::Exact;
+
+                            let match_quality = if is_exact {
+                                if base_depth < 0 {
+                                    MatchQuality::ExactUp
+                                } else {
+                                    MatchQuality::ExactDown
+                                }
+                            } else {
+                                if base_depth < 0 {
+                                    MatchQuality::PartialUp
+                                } else {
+                                    MatchQuality::PartialDown
+                                }
+                            };
+
+                            debug!(
+                                "search_pattern_recursive_fast: adding final match {:?} for {}",
+                                match_quality,
+                                path.display()
+                            );
+
+                            matches.push(DirectoryMatch::new_in(
+                                fs,
+                                path.clone(),
+                                base_depth + 1,
+                                match_quality.clone(),
+                            ));
+                            context.add_match(&match_quality);
+                        } else {
+                            trace!("search_pattern_recursive_fast: recursing deeper for remaining patterns");
+                            let reach = remaining_patterns[0].reach(max_depth - 1, context);
+                            search_pattern_recursive_fast(
+                                fs,
+                                &path,
+                                &remaining_patterns[0],
+                                &remaining_patterns[1..],
+                                matches,
+                                context,
+                                base_depth + 1,
+                                reach,
+                            );
+                        }
+                    }
+
+                    // Also recurse into subdirectories to find pattern deeper
+                    if context.should_continue() {
+                        search_pattern_recursive_fast(
+                            fs,
+                            &path,
+                            pattern,
+                            remaining_patterns,
+                            matches,
+                            context,
+                            base_depth + 1,
+                            max_depth - 1,
+                        );
+                    }
+                }
+            }
+        }
+
+        trace!(
+            "search_pattern_recursive_fast: processed {} entries, found {} pattern matches in {}",
+            entry_count,
+            match_count,
+            current_dir.display()
+        );
+    } else {
+        trace!(
+            "search_pattern_recursive_fast: failed to read directory {}",
+            current_dir.display()
+        );
+    }
+}
+
+/// The subdirectories of `dir`, nearest first, or `dir` itself when it has none
+fn list_subdirectories(dir: &Path, matches: &mut Vec<DirectoryMatch>, config: &SearchConfig) {
+    let mut subdir_matches = Vec::new();
+    search_absolute_pattern(dir, "", &mut subdir_matches, config);
+
+    if !subdir_matches.is_empty() {
+        debug!(
+            "Found {} subdirectories in {}",
+            subdir_matches.len(),
+            dir.display()
+        );
+        matches.extend(subdir_matches);
+    } else {
+        debug!("No subdirectories found, returning directory itself");
+        let dir_match = DirectoryMatch::new_in(
+            config.fs.as_ref(),
+            dir.to_path_buf(),
+            0,
+            MatchQuality::ExactDown,
+        );
+        config.report(std::slice::from_ref(&dir_match));
+        matches.push(dir_match);
+    }
+}
+
+fn search_absolute_pattern(
+    parent_dir: &Path,
+    pattern: &str,
+    matches: &mut Vec<DirectoryMatch>,
+    config: &SearchConfig,
+) {
+    let _span = debug_span!("absolute", dir = %parent_dir.display()).entered();
+    use std::collections::VecDeque;
+
+    let mut queue = VecDeque::new();
+    let mut immediate_matches: Vec<DirectoryMatch> = Vec::new();
+    queue.push_back((parent_dir.to_path_buf(), 0));
+    let matcher = NameMatcher::or_never(pattern, config);
+    let max_depth = config.max_depth as i32;
+    let fs = config.fs.as_ref();
+
+    // First, search immediate subdirectories (depth 1) to check for early stopping
+    if let Some(mut entries) = list_dir(fs, parent_dir, &config.stats) {
+        config.stats.listed(entries.len(), 0);
+        entries.sort_by(|a, b| ranking::natural_name_cmp(&a.name, &b.name));
+
+        for entry in entries {
+            if entry.kind == EntryKind::Directory {
+                let path = entry.path;
+                if let Some(name) = path.file_name() {
+                    let name_str = platform::os_to_str(name);
+                    // Check for immediate matches
+                    if let Some(kind) = matcher.classify(&name_str) {
+                        let dir_match =
+                            DirectoryMatch::new_in(fs, path.clone(), 1, MatchQuality::down(kind));
+                        config.report(std::slice::from_ref(&dir_match));
+                        immediate_matches.push(dir_match.clone());
+                        matches.push(dir_match);
+                    }
+
+                    // Add subdirectories to queue for potential deeper search
+                    queue.push_back((path.clone(), 1));
+                }
+            }
+        }
+    }
+
+    // If there are any matches in immediate subdirectories, return early to avoid deep search
+    // This prioritizes local matches over distant ones (same logic as relative paths)
+    if !immediate_matches.is_empty() && config.allows_early_stop() {
+        return;
+    }
+
+    // Otherwise, continue with breadth-first search for deeper levels
+    while let Some((current_dir, depth)) = queue.pop_front() {
+        if depth == 0 || depth > max_depth {
+            continue; // Skip depth 0 (already processed) and beyond max depth
+        }
+        if config.abandoned() || config.out_of_time() {
+            break;
+        }
+
+        if let Some(mut entries) = list_dir(fs, &current_dir, &config.stats) {
+            config.stats.listed(entries.len(), depth as usize);
+            entries.sort_by(|a, b| ranking::natural_name_cmp(&a.name, &b.name));
+
+            for entry in entries {
+                if entry.kind == EntryKind::Directory {
+                    let path = entry.path;
+                    if let Some(name) = path.file_name() {
+                        let name_str = platform::os_to_str(name);
+                        // Check for pattern match at deeper levels
+                        if let Some(kind) = matcher.classify(&name_str) {
+                            let dir_match = DirectoryMatch::new_in(
+                                fs,
+                                path.clone(),
+                                depth,
+                                MatchQuality::down(kind),
+                            );
+                            config.report(std::slice::from_ref(&dir_match));
+                            matches.push(dir_match);
+                        }
+
+                        // Add subdirectories to queue for next level search
+                        if depth < max_depth {
+                            queue.push_back((path, depth + 1));
+                        } else {
+                            config.stats.depth_limited();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn find_search_root_and_pattern(
+    fs: &dyn FileSystem,
+    search_term: &str,
+) -> (Option<PathBuf>, String) {
+    let path_buf = platform::str_to_path(search_term);
+    let path = path_buf.as_path();
+    let mut current = path;
+
+    // Walk up the path to find the longest existing prefix
+    while let Some(parent) = current.parent() {
+        if fs.is_dir(parent) {
+            // Found existing parent directory
+            // The search pattern is the first component after this parent
+            let remaining = path.strip_prefix(parent).unwrap();
+            let mut components = remaining.components();
+            if let Some(first_component) = components.next() {
+                let pattern = platform::os_to_str(first_component.as_os_str()).into_owned();
+                return (Some(parent.to_path_buf()), pattern);
+            }
+        }
+        current = parent;
+    }
+
+    // If we get here, even the root doesn't exist (an unmounted drive or share)
+    // Fall back to searching from the root with the first component as pattern
+    let root = path.ancestors().last().unwrap_or(Path::new("/"));
+    let first_component = path
+        .components()
+        .find(|c| matches!(c, std::path::Component::Normal(_))) // Skip "/", "C:" and "\\server\share"
+        .map(|c| platform::os_to_str(c.as_os_str()).into_owned())
+        .unwrap_or_else(|| search_term.trim_start_matches('/').to_string());
+    (Some(root.to_path_buf()), first_component)
+}
@@ -0,0 +1,59 @@
+//! Where the library's tracing events go. `JCD_LOG` takes env-filter
+//! directives such as `debug` or `jcd::ranking=trace`, and `JCD_DEBUG=1` is
+//! short for `JCD_LOG=debug`. Without either, nothing is traced.
+//!
+//! Traces go to stderr unless `log.file` (`JCD_LOG_FILE`) names a file to
+//! append them to, which keeps them out of completion output and the busy
+//! indicator.
+
+use jcd::config::Config;
+use std::{
+    env,
+    fs::{File, OpenOptions},
+    io,
+    sync::Mutex,
+};
+use tracing_subscriber::EnvFilter;
+
+/// The filter directives asked for, if any
+fn directives() -> Option<String> {
+    match env::var("JCD_LOG") {
+        Ok(directives) if !directives.is_empty() => Some(directives),
+        _ if env::var("JCD_DEBUG").unwrap_or_default() == "1" => Some("debug".to_string()),
+        _ => None,
+    }
+}
+
+/// The log file of the configuration, opened for appending
+fn log_file() -> Option<File> {
+    let path = Config::load().log_file?;
+    match OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            eprintln!(
+                "Warning: Cannot open log file {}, tracing to stderr: {}",
+                path.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Send tracing events to the log file or stderr, one line each with the
+/// spans they occur in
+pub fn init() {
+    let Some(directives) = directives() else {
+        return;
+    };
+    let filter = EnvFilter::try_new(&directives).unwrap_or_else(|e| {
+        eprintln!("Warning: Invalid JCD_LOG '{}': {}", directives, e);
+        EnvFilter::new("debug")
+    });
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    match log_file() {
+        // Runs from several shells append to one file; the times order them
+        Some(file) => subscriber.with_writer(Mutex::new(file)).init(),
+        None => subscriber.with_writer(io::stderr).without_time().init(),
+    }
+}
@@ -1,341 +1,99 @@
-use regex::{Regex, RegexBuilder};
+//! The `jcd` command line: parses flags, runs the management subcommands
+//! and prints the matches the jcd library finds.
+
+mod bench;
+mod cli;
+mod completions;
+mod doctor;
+mod import;
+mod init;
+mod json;
+mod logging;
+mod manpage;
+mod picker;
+mod serve;
+
+use jcd::{
+    bookmark, cache,
+    config::{self, Config},
+    daemon, history, ignore, index,
+    matcher::PatternSyntax,
+    output,
+    output::{ColorChoice, Highlighter, OutputFormat},
+    pin, platform, prepare_search, pwd_fallback, ranking, search_origin, selections, selftest,
+    stats::SearchStats,
+    Direction, SearchFlags, SearchSession, EXIT_IO, EXIT_NO_MATCH, EXIT_PARTIAL, EXIT_USAGE,
+};
 use std::{
-    env, fs,
-    io::{self, Write},
-    path::{Path, PathBuf},
+    env,
+    io::{self, BufRead, IsTerminal, Write},
+    path::Path,
     process,
-    sync::{Arc, Mutex},
+    sync::Arc,
     thread,
-    time::{Duration, Instant},
+    time::Duration,
 };
-
-// Configuration constants for performance tuning
-const MAX_MATCHES: usize = 20; // Stop after finding enough matches
-const MAX_SEARCH_TIME_MS: u64 = 500; // Max time to spend searching (milliseconds)
-const MAX_IGNORE_PATTERNS: usize = 100; // Upper bound on loaded ignore patterns
-const MAX_COMPILED_REGEX_SIZE: usize = 1_000_000; // 1MB compiled regex size limit
-
-/// Get ignore file paths in priority order following XDG Base Directory Specification
-fn get_ignore_file_paths() -> Vec<PathBuf> {
-    let mut paths = Vec::new();
-
-    // 1. Project-local ignore file (highest precedence)
-    if let Ok(current_dir) = env::current_dir() {
-        paths.push(current_dir.join(".jcdignore"));
-    }
-
-    // 2. User XDG config directory
-    let config_home = env::var("XDG_CONFIG_HOME")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| {
-            env::var("HOME")
-                .map(|home| PathBuf::from(home).join(".config"))
-                .unwrap_or_else(|_| PathBuf::from(".config"))
-        });
-    paths.push(config_home.join("jcd").join("ignore"));
-
-    // 3. Legacy dotfile for backward compatibility
-    if let Ok(home) = env::var("HOME") {
-        paths.push(PathBuf::from(home).join(".jcdignore"));
-    }
-
-    // 4. System-wide configuration
-    paths.push(PathBuf::from("/etc/jcd/ignore"));
-
-    paths
-}
-
-/// Parse ignore patterns from file content
-fn parse_ignore_patterns(content: &str) -> Vec<Regex> {
-    let mut patterns = Vec::new();
-
-    for line in content.lines() {
-        let line = line.trim();
-
-        // Skip empty lines and comments
-        if line.is_empty() || line.starts_with('#') {
-            continue;
-        }
-
-        // Try to compile the regex pattern
-        match RegexBuilder::new(line)
-            .size_limit(MAX_COMPILED_REGEX_SIZE)
-            .build()
-        {
-            Ok(regex) => {
-                if patterns.len() < MAX_IGNORE_PATTERNS {
-                    patterns.push(regex);
-                } else if is_debug_enabled() {
-                    eprintln!(
-                        "DEBUG: Ignored pattern due to max pattern count (100): '{}'",
-                        line
-                    );
-                }
-            }
-            Err(e) => {
-                if is_debug_enabled() {
-                    eprintln!("DEBUG: Invalid regex pattern '{}': {}", line, e);
-                }
-                // Continue processing other patterns even if one is invalid
-            }
-        }
-    }
-
-    patterns
-}
-
-/// Load ignore patterns from standard locations
-fn load_ignore_patterns() -> Vec<Regex> {
-    let ignore_files = get_ignore_file_paths();
-
-    for file_path in ignore_files {
-        if is_debug_enabled() {
-            eprintln!("DEBUG: Checking ignore file: {}", file_path.display());
-        }
-
-        if let Ok(content) = fs::read_to_string(&file_path) {
-            if is_debug_enabled() {
-                eprintln!("DEBUG: Found ignore file: {}", file_path.display());
-            }
-            let patterns = parse_ignore_patterns(&content);
-            if is_debug_enabled() {
-                eprintln!("DEBUG: Loaded {} ignore patterns", patterns.len());
-            }
-            return patterns;
-        }
-    }
-
-    if is_debug_enabled() {
-        eprintln!("DEBUG: No ignore file found");
-    }
-    Vec::new()
-}
-
-/// Check if a directory should be ignored based on patterns
-fn should_ignore_directory(dir_name: &str, ignore_patterns: &[Regex]) -> bool {
-    ignore_patterns
-        .iter()
-        .any(|pattern| pattern.is_match(dir_name))
-}
-
-fn is_debug_enabled() -> bool {
-    env::var("JCD_DEBUG").unwrap_or_default() == "1"
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-enum MatchQuality {
-    ExactUp,     // Exact match up the path - highest priority
-    PartialUp,   // Partial match up the path - second priority
-    ExactDown,   // Exact match down the path - third priority
-    PrefixDown,  // Prefix match down the path - fourth priority
-    PartialDown, // Partial match down the path - lowest priority
-}
-
-#[derive(Debug, Clone)]
-struct DirectoryMatch {
-    path: PathBuf,
-    depth_from_current: i32, // negative for parents, positive for children
-    match_quality: MatchQuality,
-}
-
-#[derive(Debug)]
-struct SearchContext {
-    start_time: Instant,
-    max_matches: usize,
-    max_time: Duration,
-    current_matches: usize,
-}
-
-impl SearchContext {
-    fn new() -> Self {
-        Self {
-            start_time: Instant::now(),
-            max_matches: MAX_MATCHES,
-            max_time: Duration::from_millis(MAX_SEARCH_TIME_MS),
-            current_matches: 0,
-        }
-    }
-
-    fn should_continue(&self) -> bool {
-        self.current_matches < self.max_matches && self.start_time.elapsed() < self.max_time
-    }
-
-    fn add_match(&mut self) {
-        self.current_matches += 1;
-    }
-}
-
-/// Resolves the search context by handling relative paths and directory navigation patterns.
-/// Returns (search_directory, pattern) where search_directory is the resolved starting point
-/// and pattern is the remaining search term after resolving relative components.
-fn resolve_search_context(current_dir: &Path, search_term: &str) -> (PathBuf, String) {
-    if is_debug_enabled() {
-        eprintln!(
-            "DEBUG: resolve_search_context: current_dir={}, search_term='{}'",
-            current_dir.display(),
-            search_term
-        );
-    }
-
-    // Handle empty search term
-    if search_term.is_empty() {
-        if is_debug_enabled() {
-            eprintln!("DEBUG: Empty search term, returning current directory");
-        }
-        return (current_dir.to_path_buf(), String::new());
-    }
-
-    // Handle pure directory navigation without search pattern
-    if search_term == ".." {
-        if let Some(parent) = current_dir.parent() {
-            if is_debug_enabled() {
-                eprintln!("DEBUG: Parent directory navigation to {}", parent.display());
-            }
-            return (parent.to_path_buf(), String::new());
-        } else {
-            if is_debug_enabled() {
-                eprintln!("DEBUG: Already at root, staying in current directory");
-            }
-            return (current_dir.to_path_buf(), String::new());
-        }
-    }
-
-    if search_term == "." {
-        if is_debug_enabled() {
-            eprintln!("DEBUG: Current directory navigation, staying put");
-        }
-        return (current_dir.to_path_buf(), String::new());
-    }
-
-    // Handle relative paths with patterns like "../foo", "../../bar", etc.
-    if search_term.starts_with("../") || search_term.starts_with("./") {
-        let path = Path::new(search_term);
-        let mut resolved_dir = current_dir.to_path_buf();
-        let mut remaining_pattern = String::new();
-
-        if is_debug_enabled() {
-            eprintln!("DEBUG: Processing relative path pattern");
-        }
-
-        for component in path.components() {
-            match component {
-                std::path::Component::CurDir => {
-                    if is_debug_enabled() {
-                        eprintln!(
-                            "DEBUG: Current dir component, staying in {}",
-                            resolved_dir.display()
-                        );
-                    }
-                    continue;
-                }
-                std::path::Component::ParentDir => {
-                    if let Some(parent) = resolved_dir.parent() {
-                        if is_debug_enabled() {
-                            eprintln!(
-                                "DEBUG: Parent dir component, moving from {} to {}",
-                                resolved_dir.display(),
-                                parent.display()
-                            );
-                        }
-                        resolved_dir = parent.to_path_buf();
-                    }
-                }
-                std::path::Component::Normal(name) => {
-                    remaining_pattern = name.to_string_lossy().to_string();
-                    if is_debug_enabled() {
-                        eprintln!(
-                            "DEBUG: Found search pattern '{}' in relative path",
-                            remaining_pattern
-                        );
-                    }
-                    break;
-                }
-                _ => {
-                    if is_debug_enabled() {
-                        eprintln!("DEBUG: Other path component encountered");
-                    }
-                    break;
-                }
-            }
-        }
-
-        if is_debug_enabled() {
-            eprintln!(
-                "DEBUG: Resolved relative path: search_dir={}, pattern='{}'",
-                resolved_dir.display(),
-                remaining_pattern
-            );
-        }
-        return (resolved_dir, remaining_pattern);
-    }
-
-    // Handle multiple levels of parent directory navigation like "../../", "../../../"
-    if search_term.chars().all(|c| c == '.' || c == '/') && search_term.contains("..") {
-        let mut resolved_dir = current_dir.to_path_buf();
-        let path = Path::new(search_term);
-
-        if is_debug_enabled() {
-            eprintln!("DEBUG: Processing multiple parent directory navigation");
-        }
-
-        for component in path.components() {
-            match component {
-                std::path::Component::ParentDir => {
-                    if let Some(parent) = resolved_dir.parent() {
-                        if is_debug_enabled() {
-                            eprintln!(
-                                "DEBUG: Moving up from {} to {}",
-                                resolved_dir.display(),
-                                parent.display()
-                            );
-                        }
-                        resolved_dir = parent.to_path_buf();
-                    }
-                }
-                std::path::Component::CurDir => {
-                    if is_debug_enabled() {
-                        eprintln!("DEBUG: Staying in current directory");
-                    }
-                }
-                _ => {
-                    break;
-                }
-            }
-        }
-
-        if is_debug_enabled() {
-            eprintln!(
-                "DEBUG: Final resolved directory: {}",
-                resolved_dir.display()
-            );
-        }
-        return (resolved_dir, String::new());
-    }
-
-    // For absolute paths and regular patterns, use the original behavior
-    if is_debug_enabled() {
-        eprintln!(
-            "DEBUG: Using current directory for search with pattern '{}'",
-            search_term
-        );
-    }
-    (current_dir.to_path_buf(), search_term.to_string())
-}
+use tracing::debug;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    logging::init();
+
+    // Arguments that are not valid UTF-8 are kept byte for byte, see platform::os_to_str
+    let args: Vec<String> = env::args_os()
+        .map(|arg| platform::os_to_str(&arg).into_owned())
+        .collect();
 
     if args.len() < 2 {
         eprintln!("Error: No search term provided");
-        process::exit(1);
+        process::exit(EXIT_USAGE);
+    }
+
+    if let Some(code) = run_subcommand(&args[1..]) {
+        process::exit(code);
     }
 
     // Parse command line arguments for flags
     let mut case_sensitive = true; // Default to case sensitive
+    let mut smart_case: Option<bool> = None; // --smart-case/--no-smart-case override the config
     let mut search_term = String::new();
+    let mut path_words = Vec::new(); // Words before the last of `jcd api handlers`
     let mut tab_index = 0;
     let mut quiet_mode = false;
+    let mut no_progress = false; // --no-progress drops the busy indicator but not the errors
     let mut bypass_ignore = false; // -x flag to bypass ignore patterns
+    let mut exclude = Vec::new(); // --exclude skips more directories for this search
+    let mut recency_boost = env::var("JCD_RECENT").unwrap_or_default() == "1";
+    let mut rank_spec: Option<String> = None; // --rank weight overrides
+    let mut timeout_ms: Option<u64> = None; // --timeout overrides search.timeout_ms
+    let mut max_matches: Option<usize> = None; // --max-matches overrides search.max_matches
+    let mut min_depth = 0; // --min-depth skips matches right below the search directory
+    let mut up_only = false; // --up searches the parent directories alone
+    let mut down_only = false; // --down searches below the search directory alone
+    let mut tier_limits = None; // --tier-limits overrides search.tier_limits
+    let mut from_dir: Option<String> = None; // --from replaces the current directory
+    let mut index_arg = None; // Position of the match index, the one argument the cache ignores
+    let mut record_jump = false; // --record stores the printed match in history
+    let mut chosen_for: Option<String> = None; // --chosen-for: the match was picked among those of a pattern
+    let mut list_mode = false; // --list prints every match instead of one
+    let mut complete_mode = false; // --complete lists candidates for shell completion
+    let mut pick_mode = false; // --fzf lets the user choose among all candidates
+    let mut stdin_mode = false; // --stdin searches for each line of standard input
+    let mut live_mode = false; // --live lists the matches of each query typed so far
+    let mut explain_mode = false; // --explain shows how every match was ranked
+    let mut show_stats = false; // --stats reports what the search did on stderr
+    let mut report_errors = false; // --report-errors lists directories that could not be read
+    let mut output_format = OutputFormat::Plain;
+    let mut color = ColorChoice::Auto; // --color decides on highlighting and the busy indicator
+    let mut syntax = PatternSyntax::Text; // -E treats the search term as a regex
+    let mut gitignore: Option<bool> = None; // --gitignore/--no-gitignore override the config
+    let mut follow_symlinks = false;
+    let mut same_filesystem: Option<bool> = None; // --same-filesystem/--cross-filesystems override the config
+    let mut typos: Option<bool> = None; // --typos/--no-typos override the config
+    let mut ignore_accents: Option<bool> = None; // --ignore-accents/--match-accents override the config
+    let mut full_path: Option<bool> = None; // --full-path/--name-only override the config
+    let mut siblings: Option<bool> = None; // --siblings/--no-siblings override the config
+    let mut cousins: Option<bool> = None; // --cousins/--no-cousins override the config
+    let mut shortest_first: Option<bool> = None; // --shortest-first overrides ranking.shortest_first
 
     let mut i = 1;
     while i < args.len() {
@@ -344,1186 +102,836 @@ fn main() {
                 case_sensitive = false; // -i flag makes it case insensitive
                 i += 1;
             }
+            "--smart-case" => {
+                smart_case = Some(true);
+                i += 1;
+            }
+            "--no-smart-case" => {
+                smart_case = Some(false);
+                i += 1;
+            }
+            "-E" | "--regex" => {
+                syntax = PatternSyntax::Regex;
+                i += 1;
+            }
             "-x" => {
                 bypass_ignore = true; // -x flag bypasses ignore patterns
                 i += 1;
             }
+            "--exclude" => {
+                match args.get(i + 1).map(|glob| ignore::exclude_pattern(glob)) {
+                    Some(Ok(pattern)) => exclude.push(pattern),
+                    Some(Err(e)) => {
+                        eprintln!("Error: {}", e);
+                        process::exit(EXIT_USAGE);
+                    }
+                    None => {
+                        eprintln!("Error: --exclude requires a directory name pattern");
+                        process::exit(EXIT_USAGE);
+                    }
+                }
+                i += 2;
+            }
+            arg if arg.starts_with("--exclude=") => {
+                match ignore::exclude_pattern(&arg["--exclude=".len()..]) {
+                    Ok(pattern) => exclude.push(pattern),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        process::exit(EXIT_USAGE);
+                    }
+                }
+                i += 1;
+            }
+            "--gitignore" => {
+                gitignore = Some(true);
+                i += 1;
+            }
+            "--no-gitignore" => {
+                gitignore = Some(false);
+                i += 1;
+            }
+            "--follow-symlinks" => {
+                follow_symlinks = true;
+                i += 1;
+            }
+            "--same-filesystem" => {
+                same_filesystem = Some(true);
+                i += 1;
+            }
+            "--cross-filesystems" => {
+                same_filesystem = Some(false);
+                i += 1;
+            }
+            "--typos" => {
+                typos = Some(true);
+                i += 1;
+            }
+            "--no-typos" => {
+                typos = Some(false);
+                i += 1;
+            }
+            "--ignore-accents" => {
+                ignore_accents = Some(true);
+                i += 1;
+            }
+            "--match-accents" => {
+                ignore_accents = Some(false);
+                i += 1;
+            }
+            "--full-path" => {
+                full_path = Some(true);
+                i += 1;
+            }
+            "--name-only" => {
+                full_path = Some(false);
+                i += 1;
+            }
+            "--siblings" => {
+                siblings = Some(true);
+                i += 1;
+            }
+            "--no-siblings" => {
+                siblings = Some(false);
+                i += 1;
+            }
+            "--up" => {
+                up_only = true;
+                i += 1;
+            }
+            "--down" => {
+                down_only = true;
+                i += 1;
+            }
+            "--cousins" => {
+                cousins = Some(true);
+                i += 1;
+            }
+            "--no-cousins" => {
+                cousins = Some(false);
+                i += 1;
+            }
             "--quiet" => {
                 quiet_mode = true;
                 i += 1;
             }
-            arg => {
-                if search_term.is_empty() {
-                    search_term = arg.to_string();
-                } else if tab_index == 0 {
-                    tab_index = arg.parse::<usize>().unwrap_or(0);
-                }
+            "--no-progress" => {
+                no_progress = true;
                 i += 1;
             }
-        }
-    }
-
-    if search_term.is_empty() {
-        eprintln!("Error: No search term provided");
-        process::exit(1);
-    }
-
-    let current_dir = match env::current_dir() {
-        Ok(dir) => dir,
-        Err(e) => {
-            eprintln!("Error: Cannot get current directory: {}", e);
-            process::exit(1);
-        }
-    };
-
-    // Handle relative paths and standard directory navigation
-    let (search_dir, pattern) = resolve_search_context(&current_dir, &search_term);
-
-    if is_debug_enabled() {
-        eprintln!(
-            "DEBUG: Searching for '{}' from {}",
-            pattern,
-            search_dir.display()
-        );
-    }
-
-    // Load ignore patterns unless bypassed
-    let ignore_patterns = if bypass_ignore {
-        if is_debug_enabled() {
-            eprintln!("DEBUG: Bypassing ignore patterns (-x flag)");
-        }
-        Vec::new()
-    } else {
-        load_ignore_patterns()
-    };
-
-    // Use threaded search with busy indicator (unless in quiet mode)
-    let matches = if quiet_mode {
-        find_matching_directories(&search_dir, &pattern, case_sensitive, &ignore_patterns)
-    } else {
-        search_with_progress(&search_dir, &pattern, case_sensitive, &ignore_patterns)
-    };
-
-    if is_debug_enabled() {
-        eprintln!("DEBUG: Found {} matches", matches.len());
-    }
-
-    if matches.is_empty() || tab_index >= matches.len() {
-        if is_debug_enabled() {
-            eprintln!("DEBUG: No matches or index out of range");
-        }
-        process::exit(1);
-    }
-
-    println!("{}", matches[tab_index].path.display());
-}
-
-fn search_with_progress(
-    current_dir: &Path,
-    search_term: &str,
-    case_sensitive: bool,
-    ignore_patterns: &[Regex],
-) -> Vec<DirectoryMatch> {
-    let current_dir = current_dir.to_path_buf();
-    let search_term = search_term.to_string();
-    let ignore_patterns = ignore_patterns.to_vec(); // Clone for thread
-
-    // Shared state for the search result
-    let result = Arc::new(Mutex::new(None));
-    let result_clone = Arc::clone(&result);
-
-    // Shared flag to indicate when search is complete
-    let search_complete = Arc::new(Mutex::new(false));
-    let search_complete_clone = Arc::clone(&search_complete);
-
-    // Start the search in a background thread
-    let search_handle = thread::spawn(move || {
-        let matches =
-            find_matching_directories(&current_dir, &search_term, case_sensitive, &ignore_patterns);
-
-        // Store the result
-        {
-            let mut result_guard = result_clone.lock().unwrap();
-            *result_guard = Some(matches);
-        }
-
-        // Mark search as complete
-        {
-            let mut complete_guard = search_complete_clone.lock().unwrap();
-            *complete_guard = true;
-        }
-    });
-
-    // Give search a brief moment to complete (20ms)
-    thread::sleep(Duration::from_millis(20));
-
-    // Check if search is still running
-    let show_progress = {
-        let complete_guard = search_complete.lock().unwrap();
-        !*complete_guard
-    };
-
-    if show_progress {
-        // Start the busy indicator in a separate thread
-        let search_complete_clone = Arc::clone(&search_complete);
-        let indicator_handle = thread::spawn(move || {
-            show_busy_indicator(&search_complete_clone);
-        });
-
-        // Wait for the search to complete
-        search_handle.join().unwrap();
-
-        // Wait for indicator to finish
-        indicator_handle.join().unwrap();
-
-        // Clear the progress line
-        eprint!("\r\x1b[K");
-        io::stderr().flush().unwrap();
-    } else {
-        // Search completed quickly, just wait for it
-        search_handle.join().unwrap();
-    }
-
-    // Return the result
-    let result_guard = result.lock().unwrap();
-    result_guard.as_ref().unwrap().clone()
-}
-
-fn show_busy_indicator(search_complete: &Arc<Mutex<bool>>) {
-    let dots = [" .", " ..", " ..."];
-    let mut dot_index = 0;
-
-    loop {
-        // Check if search is complete
-        {
-            let complete_guard = search_complete.lock().unwrap();
-            if *complete_guard {
-                break;
+            "--selftest" => {
+                process::exit(selftest::run());
             }
-        }
-
-        // Show the dots animation with carriage return
-        eprint!("\r{}", dots[dot_index]);
-        io::stderr().flush().unwrap();
-
-        // Update dot index
-        dot_index = (dot_index + 1) % dots.len();
-
-        // Wait before next update
-        thread::sleep(Duration::from_millis(200));
-    }
-}
-
-fn find_matching_directories(
-    current_dir: &Path,
-    search_term: &str,
-    case_sensitive: bool,
-    ignore_patterns: &[Regex],
-) -> Vec<DirectoryMatch> {
-    if is_debug_enabled() {
-        eprintln!(
-            "DEBUG: find_matching_directories: current_dir={}, search_term='{}', case_sensitive={}",
-            current_dir.display(),
-            search_term,
-            case_sensitive
-        );
-    }
-
-    let mut matches = Vec::new();
-
-    // Handle empty search term (pure directory navigation like "..", "../../")
-    if search_term.is_empty() {
-        if is_debug_enabled() {
-            eprintln!("DEBUG: Empty search term, returning current directory as match");
-        }
-        matches.push(DirectoryMatch {
-            path: current_dir.to_path_buf(),
-            depth_from_current: 0,
-            match_quality: MatchQuality::ExactDown,
-        });
-        return matches;
-    }
-
-    // Handle absolute paths
-    if search_term.starts_with('/') {
-        if is_debug_enabled() {
-            eprintln!("DEBUG: Processing absolute path: {}", search_term);
-        }
-        let path = Path::new(search_term);
-
-        if search_term.ends_with('/') {
-            if is_debug_enabled() {
-                eprintln!("DEBUG: Absolute path ends with slash - exploring subdirectories");
+            "--manpage" => {
+                print!("{}", manpage::render());
+                process::exit(0);
             }
-            let dir_path = Path::new(&search_term[..search_term.len() - 1]);
-            if dir_path.exists() && dir_path.is_dir() {
-                let mut subdir_matches = Vec::new();
-                search_absolute_pattern(dir_path, "", &mut subdir_matches, case_sensitive);
-
-                if !subdir_matches.is_empty() {
-                    if is_debug_enabled() {
+            "--recent" => {
+                recency_boost = true; // Prefer recently used directories
+                i += 1;
+            }
+            "--shortest-first" => {
+                shortest_first = Some(true); // Fewer path components win within a match kind
+                i += 1;
+            }
+            "--record" => {
+                record_jump = true;
+                i += 1;
+            }
+            "--list" => {
+                list_mode = true;
+                i += 1;
+            }
+            "--complete" => {
+                // One call yields every candidate; the shell cycles through them itself
+                complete_mode = true;
+                list_mode = true;
+                quiet_mode = true;
+                i += 1;
+            }
+            "--fzf" => {
+                pick_mode = true;
+                i += 1;
+            }
+            "--stdin" => {
+                stdin_mode = true;
+                i += 1;
+            }
+            "--live" => {
+                live_mode = true;
+                i += 1;
+            }
+            "--explain" => {
+                explain_mode = true;
+                i += 1;
+            }
+            "--stats" => {
+                show_stats = true;
+                i += 1;
+            }
+            "--report-errors" => {
+                report_errors = true;
+                i += 1;
+            }
+            "--json" => {
+                output_format = OutputFormat::Json;
+                i += 1;
+            }
+            "-0" | "--print0" => {
+                output_format = OutputFormat::Null;
+                i += 1;
+            }
+            "--tsv" => {
+                output_format = OutputFormat::Tsv;
+                i += 1;
+            }
+            "--add" => {
+                if i + 1 >= args.len() {
+                    eprintln!("Error: --add requires a directory path");
+                    process::exit(EXIT_USAGE);
+                }
+                process::exit(add_to_history(&args[i + 1]));
+            }
+            "--rank" => {
+                if i + 1 >= args.len() {
+                    eprintln!("Error: --rank requires a weight list, e.g. exact=10,depth=-2");
+                    process::exit(EXIT_USAGE);
+                }
+                rank_spec = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "--timeout" => {
+                match args.get(i + 1).map(|ms| ms.parse::<u64>()) {
+                    Some(Ok(ms)) => timeout_ms = Some(ms),
+                    _ => {
                         eprintln!(
-                            "DEBUG: Found {} subdirectories in {}",
-                            subdir_matches.len(),
-                            dir_path.display()
+                            "Error: --timeout requires a time in milliseconds (0 = unlimited)"
                         );
+                        process::exit(EXIT_USAGE);
                     }
-                    matches.extend(subdir_matches);
-                } else {
-                    if is_debug_enabled() {
-                        eprintln!("DEBUG: No subdirectories found, returning directory itself");
+                }
+                i += 2;
+            }
+            "--max-matches" => {
+                match args.get(i + 1).map(|n| n.parse::<usize>()) {
+                    Some(Ok(n)) if n > 0 => max_matches = Some(n),
+                    _ => {
+                        eprintln!("Error: --max-matches requires a number of at least 1");
+                        process::exit(EXIT_USAGE);
+                    }
+                }
+                i += 2;
+            }
+            "--tier-limits" => {
+                match args.get(i + 1).map(|spec| ranking::TierLimits::parse(spec)) {
+                    Some(Ok(limits)) => tier_limits = Some(limits),
+                    Some(Err(e)) => {
+                        eprintln!("Error: {}", e);
+                        process::exit(EXIT_USAGE);
+                    }
+                    None => {
+                        eprintln!("Error: --tier-limits requires a list such as partial_down=5");
+                        process::exit(EXIT_USAGE);
+                    }
+                }
+                i += 2;
+            }
+            "--min-depth" => {
+                match args.get(i + 1).map(|n| n.parse::<usize>()) {
+                    Some(Ok(n)) => min_depth = n,
+                    _ => {
+                        eprintln!("Error: --min-depth requires a number of levels");
+                        process::exit(EXIT_USAGE);
                     }
-                    matches.push(DirectoryMatch {
-                        path: dir_path.to_path_buf(),
-                        depth_from_current: 0,
-                        match_quality: MatchQuality::ExactDown,
-                    });
                 }
-            } else {
-                if is_debug_enabled() {
-                    eprintln!("DEBUG: Directory doesn't exist, treating as pattern search");
+                i += 2;
+            }
+            "--color" => {
+                match args
+                    .get(i + 1)
+                    .and_then(|when| ColorChoice::from_name(when))
+                {
+                    Some(when) => color = when,
+                    None => {
+                        eprintln!("Error: --color requires auto, always or never");
+                        process::exit(EXIT_USAGE);
+                    }
                 }
-                let search_term_no_slash = &search_term[..search_term.len() - 1];
-                let (search_root, search_pattern) =
-                    find_search_root_and_pattern(search_term_no_slash);
-                if let Some(root) = search_root {
-                    search_absolute_pattern(&root, &search_pattern, &mut matches, case_sensitive);
+                i += 2;
+            }
+            arg if arg.starts_with("--color=") => {
+                match ColorChoice::from_name(&arg["--color=".len()..]) {
+                    Some(when) => color = when,
+                    None => {
+                        eprintln!("Error: --color requires auto, always or never");
+                        process::exit(EXIT_USAGE);
+                    }
                 }
+                i += 1;
             }
-        } else if path.exists() && path.is_dir() {
-            if is_debug_enabled() {
-                eprintln!("DEBUG: Absolute path exists exactly, returning it directly");
+            "--chosen-for" => {
+                if i + 1 >= args.len() {
+                    eprintln!("Error: --chosen-for requires the pattern the match was chosen for");
+                    process::exit(EXIT_USAGE);
+                }
+                chosen_for = Some(args[i + 1].clone());
+                i += 2;
             }
-            matches.push(DirectoryMatch {
-                path: path.to_path_buf(),
-                depth_from_current: 0,
-                match_quality: MatchQuality::ExactDown,
-            });
-        } else {
-            if is_debug_enabled() {
-                eprintln!("DEBUG: Absolute path doesn't exist, finding search root and pattern");
+            "--from" => {
+                if i + 1 >= args.len() {
+                    eprintln!("Error: --from requires a directory path");
+                    process::exit(EXIT_USAGE);
+                }
+                from_dir = Some(args[i + 1].clone());
+                i += 2;
             }
-            let (search_root, search_pattern) = find_search_root_and_pattern(search_term);
-            if let Some(root) = search_root {
-                if is_debug_enabled() {
-                    eprintln!(
-                        "DEBUG: Searching from root {} for pattern '{}'",
-                        root.display(),
-                        search_pattern
-                    );
+            arg => {
+                // The first number after the pattern is the match index; other
+                // words make a multi-word query whose last word is the pattern
+                if search_term.is_empty() {
+                    search_term = arg.to_string();
+                } else if let (None, Ok(index)) = (index_arg, arg.parse::<usize>()) {
+                    tab_index = index;
+                    index_arg = Some(i);
+                } else {
+                    path_words.push(std::mem::replace(&mut search_term, arg.to_string()));
                 }
-                search_absolute_pattern(&root, &search_pattern, &mut matches, case_sensitive);
+                i += 1;
             }
         }
-        return finalize_matches(matches);
     }
 
-    // Handle path-like patterns (contains '/')
-    if search_term.contains('/') {
-        if is_debug_enabled() {
-            eprintln!("DEBUG: Processing path-like pattern with '/'");
-        }
-        let mut context = SearchContext::new();
-        search_path_pattern_fast(
-            current_dir,
-            search_term,
-            &mut matches,
-            &mut context,
-            case_sensitive,
-        );
-        if !matches.is_empty() {
-            if is_debug_enabled() {
-                eprintln!("DEBUG: Found {} matches for path pattern", matches.len());
-            }
-            return finalize_matches(matches);
-        }
+    if stdin_mode && (list_mode || pick_mode || live_mode) {
+        eprintln!("Error: --stdin prints one match per pattern; it cannot be combined with --list, --complete, --fzf or --live");
+        process::exit(EXIT_USAGE);
     }
-
-    if is_debug_enabled() {
-        eprintln!("DEBUG: Starting comprehensive search - up tree then down tree");
+    if live_mode && (list_mode || pick_mode) {
+        eprintln!("Error: --live lists every match itself; it cannot be combined with --list, --complete or --fzf");
+        process::exit(EXIT_USAGE);
     }
-
-    // 1. Search up for exact matches, then partial matches (direct path to root only)
-    let up_matches =
-        search_up_tree_with_priority(current_dir, search_term, case_sensitive, ignore_patterns);
-    if is_debug_enabled() {
+    if explain_mode && (list_mode || pick_mode || stdin_mode || live_mode) {
+        eprintln!("Error: --explain lists every match itself; it cannot be combined with --list, --complete, --fzf, --stdin or --live");
+        process::exit(EXIT_USAGE);
+    }
+    if show_stats && (stdin_mode || live_mode) {
         eprintln!(
-            "DEBUG: Found {} matches searching up tree",
-            up_matches.len()
+            "Error: --stats reports a single search; it cannot be combined with --stdin or --live"
         );
+        process::exit(EXIT_USAGE);
     }
-    matches.extend(up_matches);
-
-    // 2. Search down for all matches (exact and partial) from current directory only
-    let down_matches =
-        search_down_breadth_first_all(current_dir, search_term, case_sensitive, ignore_patterns);
-    if is_debug_enabled() {
+    if report_errors && (stdin_mode || live_mode) {
         eprintln!(
-            "DEBUG: Found {} matches searching down tree",
-            down_matches.len()
+            "Error: --report-errors reports a single search; it cannot be combined with --stdin or --live"
         );
+        process::exit(EXIT_USAGE);
     }
-    matches.extend(down_matches);
-
-    // Return all matches sorted by priority
-    if !matches.is_empty() {
-        if is_debug_enabled() {
-            eprintln!("DEBUG: Total {} matches found, finalizing", matches.len());
-        }
-        return finalize_matches(matches);
+    if chosen_for.is_some() && (list_mode || stdin_mode || live_mode || explain_mode) {
+        eprintln!("Error: --chosen-for remembers the single match printed; it cannot be combined with --list, --complete, --stdin, --live or --explain");
+        process::exit(EXIT_USAGE);
     }
-
-    if is_debug_enabled() {
-        eprintln!("DEBUG: No matches found");
+    if up_only && down_only {
+        eprintln!("Error: --up and --down cannot be combined; leave both out to search both ways");
+        process::exit(EXIT_USAGE);
     }
-    Vec::new()
-}
-
-fn search_up_tree_with_priority(
-    current_dir: &Path,
-    search_term: &str,
-    case_sensitive: bool,
-    ignore_patterns: &[Regex],
-) -> Vec<DirectoryMatch> {
-    if is_debug_enabled() {
+    let read_stdin = stdin_mode || live_mode;
+    if read_stdin && !search_term.is_empty() {
         eprintln!(
-            "DEBUG: search_up_tree_with_priority: searching for '{}', case_sensitive={}",
-            search_term, case_sensitive
+            "Error: --stdin and --live read the patterns from standard input, not the command line"
         );
+        process::exit(EXIT_USAGE);
+    }
+    if search_term.is_empty() && !read_stdin {
+        eprintln!("Error: No search term provided");
+        process::exit(EXIT_USAGE);
     }
 
-    let mut exact_matches = Vec::new();
-    let mut partial_matches = Vec::new();
-    let mut current = current_dir;
-    let mut depth = -1;
-
-    let search_lower = if case_sensitive {
-        search_term.to_string()
-    } else {
-        search_term.to_lowercase()
-    };
-
-    while let Some(parent) = current.parent() {
-        if let Some(name) = parent.file_name() {
-            let name_str = name.to_string_lossy();
-
-            // Check if this directory should be ignored
-            if should_ignore_directory(&name_str, ignore_patterns) {
-                if is_debug_enabled() {
-                    eprintln!("DEBUG: Ignoring parent directory: {}", name_str);
-                }
-                current = parent;
-                depth -= 1;
-                continue;
-            }
-
-            let (name_compare, search_compare) = if case_sensitive {
-                (name_str.to_string(), search_term.to_string())
-            } else {
-                (name_str.to_lowercase(), search_lower.clone())
-            };
-
-            if is_debug_enabled() {
-                eprintln!("DEBUG: Checking parent '{}' at depth {}", name_str, depth);
-            }
-
-            if name_compare == search_compare {
-                if is_debug_enabled() {
-                    eprintln!("DEBUG: Exact match found: {}", parent.display());
+    let current_dir = match from_dir {
+        Some(dir) => match search_origin(&dir) {
+            Ok(dir) => dir,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(EXIT_USAGE);
+            }
+        },
+        None => match env::current_dir() {
+            Ok(dir) => dir,
+            Err(e) => match pwd_fallback() {
+                Some(dir) => {
+                    if !quiet_mode {
+                        eprintln!(
+                            "Warning: the current directory no longer exists; searching from {}",
+                            dir.display()
+                        );
+                    }
+                    dir
                 }
-                exact_matches.push(DirectoryMatch {
-                    path: parent.to_path_buf(),
-                    depth_from_current: depth,
-                    match_quality: MatchQuality::ExactUp,
-                });
-            } else if name_compare.contains(&search_compare) {
-                if is_debug_enabled() {
-                    eprintln!("DEBUG: Partial match found: {}", parent.display());
+                None => {
+                    eprintln!("Error: Cannot get current directory: {}", e);
+                    process::exit(EXIT_IO);
                 }
-                partial_matches.push(DirectoryMatch {
-                    path: parent.to_path_buf(),
-                    depth_from_current: depth,
-                    match_quality: MatchQuality::PartialUp,
-                });
-            }
-        }
-        current = parent;
-        depth -= 1;
-    }
+            },
+        },
+    };
 
-    let mut result = exact_matches;
-    result.extend(partial_matches);
+    let settings = Config::load();
 
-    if is_debug_enabled() {
-        eprintln!(
-            "DEBUG: search_up_tree_with_priority completed with {} matches",
-            result.len()
-        );
-    }
+    // Validate ranking overrides before spending time on the search
+    let rank_weights = rank_spec.map(|spec| {
+        let shortest_first = shortest_first.unwrap_or(settings.shortest_first);
+        ranking::RankWeights::for_search(&settings, recency_boost, shortest_first, &spec)
+            .unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                process::exit(EXIT_USAGE);
+            })
+    });
 
-    result
-}
+    let flags = SearchFlags {
+        case_sensitive,
+        smart_case,
+        syntax,
+        bypass_ignore,
+        exclude,
+        gitignore,
+        follow_symlinks,
+        same_filesystem,
+        typos,
+        ignore_accents,
+        full_path,
+        siblings,
+        cousins,
+        path_words,
+        timeout_ms,
+        max_matches,
+        min_depth,
+        direction: if up_only {
+            Direction::Up
+        } else if down_only {
+            Direction::Down
+        } else {
+            Direction::Both
+        },
+        tier_limits,
+        recency_boost,
+        shortest_first,
+        // Batch searches run unattended; there is no one to show progress to
+        quiet: quiet_mode
+            || no_progress
+            || read_stdin
+            || !color.decorate(io::stderr().is_terminal()),
+    };
 
-fn search_down_breadth_first_all(
-    current_dir: &Path,
-    search_term: &str,
-    case_sensitive: bool,
-    ignore_patterns: &[Regex],
-) -> Vec<DirectoryMatch> {
-    if is_debug_enabled() {
-        eprintln!(
-            "DEBUG: search_down_breadth_first_all: searching for '{}', case_sensitive={}",
-            search_term, case_sensitive
-        );
+    if live_mode {
+        let mut session = SearchSession::new(&flags, &settings, rank_weights.as_ref());
+        process::exit(run_live(&mut session, &current_dir, output_format));
     }
 
-    use std::collections::VecDeque;
+    if stdin_mode {
+        let session = SearchSession::new(&flags, &settings, rank_weights.as_ref());
+        process::exit(run_batch(
+            &session,
+            &current_dir,
+            output_format,
+            record_jump,
+        ));
+    }
 
-    let mut queue = VecDeque::new();
-    let mut all_matches = Vec::new();
-    queue.push_back((current_dir.to_path_buf(), 0));
-    let search_lower = if case_sensitive {
-        search_term.to_string()
-    } else {
-        search_term.to_lowercase()
+    let search = match prepare_search(&search_term, &flags, &current_dir, &settings) {
+        Ok(search) => search,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(EXIT_USAGE);
+        }
     };
-    let max_depth = 8;
-
-    // First, search immediate subdirectories (depth 1) to check for early stopping
-    let mut immediate_matches = Vec::new();
 
-    if is_debug_enabled() {
-        eprintln!(
-            "DEBUG: Processing immediate subdirectories in {}",
-            current_dir.display()
-        );
+    // Explaining the ranking always searches afresh, so it shows what ranking does now
+    let stats = Arc::clone(&search.config.stats);
+    let (time_budget, match_limit) = (search.config.max_time, search.config.max_matches);
+    if explain_mode {
+        let session = SearchSession::new(&flags, &settings, rank_weights.as_ref());
+        let matches = session.find(search);
+        if show_stats {
+            print_stats(&stats, output_format);
+        }
+        if report_errors {
+            print_unreadable(&stats);
+        }
+        let partial = stats.report().partial();
+        if partial && !quiet_mode {
+            warn_partial(&stats, time_budget, match_limit);
+        }
+        let explanations = session.explain(&matches);
+        output::print_explanations(&matches, &explanations, output_format, partial);
+        process::exit(match (matches.is_empty(), partial) {
+            (_, true) => EXIT_PARTIAL,
+            (true, false) => EXIT_NO_MATCH,
+            (false, false) => 0,
+        });
     }
 
-    // Process current directory (depth 0) first
-    if let Ok(entries) = fs::read_dir(current_dir) {
-        let mut entries: Vec<_> = entries.filter_map(|e| e.ok()).collect();
-        entries.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
-
-        for entry in &entries {
-            if let Ok(file_type) = entry.file_type() {
-                if file_type.is_dir() {
-                    let path = entry.path();
-                    if let Some(name) = path.file_name() {
-                        let name_str = name.to_string_lossy();
-
-                        // Check if this directory should be ignored
-                        if should_ignore_directory(&name_str, ignore_patterns) {
-                            if is_debug_enabled() {
-                                eprintln!("DEBUG: Ignoring directory: {}", name_str);
-                            }
-                            continue;
-                        }
-
-                        let (name_compare, search_compare) = if case_sensitive {
-                            (name_str.to_string(), search_term.to_string())
-                        } else {
-                            (name_str.to_lowercase(), search_lower.clone())
-                        };
-
-                        // Check for any match in immediate subdirectories
-                        if name_compare == search_compare {
-                            if is_debug_enabled() {
-                                eprintln!("DEBUG: Immediate exact match: {}", path.display());
-                            }
-                            let dir_match = DirectoryMatch {
-                                path: path.clone(),
-                                depth_from_current: 1,
-                                match_quality: MatchQuality::ExactDown,
-                            };
-                            immediate_matches.push(dir_match.clone());
-                            all_matches.push(dir_match);
-                        } else if name_compare.starts_with(&search_compare) {
-                            if is_debug_enabled() {
-                                eprintln!("DEBUG: Immediate prefix match: {}", path.display());
-                            }
-                            let dir_match = DirectoryMatch {
-                                path: path.clone(),
-                                depth_from_current: 1,
-                                match_quality: MatchQuality::PrefixDown,
-                            };
-                            immediate_matches.push(dir_match.clone());
-                            all_matches.push(dir_match);
-                        } else if name_compare.contains(&search_compare) {
-                            if is_debug_enabled() {
-                                eprintln!("DEBUG: Immediate partial match: {}", path.display());
-                            }
-                            let dir_match = DirectoryMatch {
-                                path: path.clone(),
-                                depth_from_current: 1,
-                                match_quality: MatchQuality::PartialDown,
-                            };
-                            immediate_matches.push(dir_match.clone());
-                            all_matches.push(dir_match);
-                        }
+    // Asking for a later match (the next tab press) reuses the previous search
+    let key_args: Vec<String> = args
+        .iter()
+        .enumerate()
+        .filter(|&(pos, _)| Some(pos) != index_arg)
+        .map(|(_, arg)| arg.clone())
+        .collect();
+    let cache_key = (!list_mode && settings.cache_ttl_secs > 0)
+        .then(|| cache::search_key(&search.search_dir, &key_args, &settings));
+    // Statistics describe a search, so --stats always runs one
+    let cached = cache_key
+        .filter(|_| tab_index > 0 && !show_stats && !report_errors)
+        .and_then(|key| cache::load(key, &search.search_dir, settings.cache_ttl_secs));
+    let highlighter = Highlighter::new(&search, &settings);
+
+    let matches = if !search.bookmarked.is_empty() {
+        search.bookmark_matches()
+    } else if let Some(matches) = cached {
+        matches
+    } else {
+        let search_dir = search.search_dir.clone();
+        let session = SearchSession::new(&flags, &settings, rank_weights.as_ref());
+        let matches = session.find(search);
 
-                        // Add subdirectories to queue for potential deeper search
-                        queue.push_back((path.clone(), 1));
-                    }
+        if let Some(key) = cache_key {
+            if let Err(e) = cache::save(key, &search_dir, &matches) {
+                debug!("Failed to cache results: {}", e);
+            }
+        }
+        matches
+    };
+    if show_stats {
+        print_stats(&stats, output_format);
+    }
+    if report_errors {
+        print_unreadable(&stats);
+    }
+    // A search cut short by a limit may have missed better matches
+    let partial = stats.report().partial();
+    if partial && !quiet_mode {
+        warn_partial(&stats, time_budget, match_limit);
+    }
+    let found = if partial { EXIT_PARTIAL } else { 0 };
+    let no_match = if partial { EXIT_PARTIAL } else { EXIT_NO_MATCH };
+
+    if pick_mode {
+        if matches.is_empty() {
+            debug!("No matches to pick from");
+            process::exit(no_match);
+        }
+        // The picker draws on the terminal and prints its choice without colors
+        let highlighter = color.decorate(true).then_some(&highlighter);
+        let chosen = picker::pick(&matches, highlighter).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            process::exit(EXIT_IO);
+        });
+        // Nothing chosen means the picker was cancelled
+        if chosen.is_empty() {
+            process::exit(EXIT_NO_MATCH);
+        }
+        output::print_list(&chosen, output_format, partial);
+        if record_jump {
+            for m in &chosen {
+                if let Err(e) = history::record_visit(&m.path, &settings) {
+                    debug!("Failed to record history: {}", e);
                 }
             }
         }
-    }
-
-    // If there are exact or prefix matches in immediate subdirectories, return early to avoid deep search
-    let has_good_immediate = immediate_matches.iter().any(|m| {
-        matches!(
-            m.match_quality,
-            MatchQuality::ExactDown | MatchQuality::PrefixDown
-        )
-    });
-    if has_good_immediate {
-        if is_debug_enabled() {
-            eprintln!("DEBUG: Found good immediate matches, skipping deep search");
+        // A jump picked from the list leads straight there next time
+        let pattern = chosen_for
+            .as_deref()
+            .or(record_jump.then_some(search_term.as_str()));
+        if let (Some(pattern), [m]) = (pattern, chosen.as_slice()) {
+            learn_choice(pattern, &m.path, &flags, &current_dir, &settings);
         }
-        return finalize_matches(all_matches);
-    }
-
-    if is_debug_enabled() {
-        eprintln!("DEBUG: No good immediate matches, continuing with deep search");
+        process::exit(found);
     }
 
-    // Otherwise, continue with breadth-first search for deeper levels
-    while let Some((current_path, depth)) = queue.pop_front() {
-        if depth == 0 || depth > max_depth {
-            continue; // Skip depth 0 (already processed) and beyond max depth
-        }
-        if is_debug_enabled() {
-            eprintln!(
-                "DEBUG: Searching depth {} in {}",
-                depth,
-                current_path.display()
-            );
+    if list_mode {
+        // An empty JSON list is still valid output for scripts to parse
+        // Highlighting is for reading in a terminal; completion and pipes get bare paths
+        if output_format == OutputFormat::Plain
+            && !complete_mode
+            && color.decorate(io::stdout().is_terminal())
+        {
+            output::print_highlighted(&matches, &highlighter);
+        } else if output_format == OutputFormat::Json || !matches.is_empty() {
+            output::print_list(&matches, output_format, partial);
         }
-
-        let mut level_matches = Vec::new();
-        let mut level_subdirs = Vec::new();
-
-        if let Ok(entries) = fs::read_dir(&current_path) {
-            // Collect and sort entries for deterministic order
-            let mut entries: Vec<_> = entries.filter_map(|e| e.ok()).collect();
-            entries.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
-
-            // Process all entries at this level
-            for entry in &entries {
-                if let Ok(file_type) = entry.file_type() {
-                    if file_type.is_dir() {
-                        let path = entry.path();
-                        if let Some(name) = path.file_name() {
-                            let name_str = name.to_string_lossy();
-
-                            // Check if this directory should be ignored
-                            if should_ignore_directory(&name_str, ignore_patterns) {
-                                if is_debug_enabled() {
-                                    eprintln!(
-                                        "DEBUG: Ignoring directory at depth {}: {}",
-                                        depth + 1,
-                                        name_str
-                                    );
-                                }
-                                continue;
-                            }
-
-                            let (name_compare, search_compare) = if case_sensitive {
-                                (name_str.to_string(), search_term.to_string())
-                            } else {
-                                (name_str.to_lowercase(), search_lower.clone())
-                            };
-
-                            // Check for any match (exact, prefix, or partial)
-                            if name_compare == search_compare {
-                                if is_debug_enabled() {
-                                    eprintln!(
-                                        "DEBUG: Deep exact match at depth {}: {}",
-                                        depth + 1,
-                                        path.display()
-                                    );
-                                }
-                                level_matches.push(DirectoryMatch {
-                                    path: path.clone(),
-                                    depth_from_current: (depth + 1) as i32,
-                                    match_quality: MatchQuality::ExactDown,
-                                });
-                            } else if name_compare.starts_with(&search_compare) {
-                                if is_debug_enabled() {
-                                    eprintln!(
-                                        "DEBUG: Deep prefix match at depth {}: {}",
-                                        depth + 1,
-                                        path.display()
-                                    );
-                                }
-                                level_matches.push(DirectoryMatch {
-                                    path: path.clone(),
-                                    depth_from_current: (depth + 1) as i32,
-                                    match_quality: MatchQuality::PrefixDown,
-                                });
-                            } else if name_compare.contains(&search_compare) {
-                                if is_debug_enabled() {
-                                    eprintln!(
-                                        "DEBUG: Deep partial match at depth {}: {}",
-                                        depth + 1,
-                                        path.display()
-                                    );
-                                }
-                                level_matches.push(DirectoryMatch {
-                                    path: path.clone(),
-                                    depth_from_current: (depth + 1) as i32,
-                                    match_quality: MatchQuality::PartialDown,
-                                });
-                            }
-
-                            // Collect subdirectories for next level
-                            if depth < max_depth {
-                                level_subdirs.push((path.clone(), depth + 1));
-                            }
-                        }
-                    }
-                }
-            }
+        // For completion, no candidates is an answer rather than a failure
+        if complete_mode {
+            return;
         }
-
-        // Add matches from this level
-        all_matches.extend(level_matches);
-
-        // Add subdirectories to queue for next level search
-        for (subdir, next_depth) in level_subdirs {
-            queue.push_back((subdir, next_depth));
+        if matches.is_empty() {
+            debug!("No matches");
+            process::exit(no_match);
         }
+        process::exit(found);
     }
 
-    if is_debug_enabled() {
-        eprintln!(
-            "DEBUG: search_down_breadth_first_all completed with {} total matches",
-            all_matches.len()
-        );
+    if matches.is_empty() || tab_index >= matches.len() {
+        debug!("No matches or index out of range");
+        process::exit(no_match);
     }
 
-    finalize_matches(all_matches)
-}
+    output::print_match(&matches[tab_index], tab_index, output_format, partial);
 
-fn finalize_matches(mut matches: Vec<DirectoryMatch>) -> Vec<DirectoryMatch> {
-    if is_debug_enabled() {
-        eprintln!("DEBUG: finalize_matches: input {} matches", matches.len());
-        for (i, m) in matches.iter().enumerate() {
-            eprintln!(
-                "DEBUG:   [{}] {:?} depth={} path={}",
-                i,
-                m.match_quality,
-                m.depth_from_current,
-                m.path.display()
-            );
+    if record_jump {
+        if let Err(e) = history::record_visit(&matches[tab_index].path, &settings) {
+            debug!("Failed to record history: {}", e);
         }
     }
-
-    // Remove duplicates based on path
-    matches.sort_by(|a, b| a.path.cmp(&b.path));
-    matches.dedup_by(|a, b| a.path == b.path);
-
-    if is_debug_enabled() {
-        eprintln!("DEBUG: After dedup: {} matches", matches.len());
+    // A match reached with Tab leads straight there next time
+    if let Some(pattern) = &chosen_for {
+        learn_choice(
+            pattern,
+            &matches[tab_index].path,
+            &flags,
+            &current_dir,
+            &settings,
+        );
     }
+    process::exit(found);
+}
 
-    // Sort by priority with clear prioritization
-    matches.sort_by(|a, b| {
-        // Define priority categories
-        let get_priority = |m: &DirectoryMatch| -> u32 {
-            match (m.depth_from_current, &m.match_quality) {
-                // Immediate subdirectory exact matches - highest priority
-                (1, MatchQuality::ExactDown) => 0,
-                // Immediate subdirectory prefix matches - very high priority
-                (1, MatchQuality::PrefixDown) => 1,
-                // Immediate subdirectory partial matches - high priority
-                (1, MatchQuality::PartialDown) => 2,
-                // Up tree exact matches - medium-high priority
-                (_, MatchQuality::ExactUp) => 3,
-                // Up tree partial matches - medium priority
-                (_, MatchQuality::PartialUp) => 4,
-                // Deeper exact matches - lower priority
-                (_, MatchQuality::ExactDown) => 5,
-                // Deeper prefix matches - lower priority
-                (_, MatchQuality::PrefixDown) => 6,
-                // Deeper partial matches - lowest priority
-                (_, MatchQuality::PartialDown) => 7,
-            }
-        };
-
-        let a_priority = get_priority(a);
-        let b_priority = get_priority(b);
-
-        // First sort by priority
-        let priority_cmp = a_priority.cmp(&b_priority);
-        if priority_cmp != std::cmp::Ordering::Equal {
-            return priority_cmp;
-        }
-
-        // Within same priority, sort by depth (shallower first for down matches, closer first for up matches)
-        match a.match_quality {
-            MatchQuality::ExactUp | MatchQuality::PartialUp => {
-                // For up matches, closer to current (higher depth) comes first
-                b.depth_from_current.cmp(&a.depth_from_current)
-            }
-            _ => {
-                // For down matches, shallower (lower depth) comes first
-                a.depth_from_current.cmp(&b.depth_from_current)
+/// Remember `dir` as the match chosen for `pattern` searched from
+/// `current_dir`, unless ranking.learn is off
+fn learn_choice(
+    pattern: &str,
+    dir: &Path,
+    flags: &SearchFlags,
+    current_dir: &Path,
+    settings: &Config,
+) {
+    if !settings.learn {
+        return;
+    }
+    // The choice is found again by the resolved pattern and its search directory
+    match prepare_search(pattern, flags, current_dir, settings) {
+        Ok(search) => {
+            if let Err(e) = selections::record_choice(&search.search_dir, &search.pattern, dir) {
+                debug!("Failed to record choice: {}", e);
             }
         }
-    });
-
-    if is_debug_enabled() {
-        eprintln!("DEBUG: After sorting: {} matches", matches.len());
-        for (i, m) in matches.iter().enumerate() {
-            eprintln!(
-                "DEBUG:   [{}] {:?} depth={} path={}",
-                i,
-                m.match_quality,
-                m.depth_from_current,
-                m.path.display()
-            );
-        }
+        Err(e) => debug!("Not recording choice for '{}': {}", pattern, e),
     }
-
-    matches
 }
 
-fn search_path_pattern_fast(
-    current_dir: &Path,
-    search_term: &str,
-    matches: &mut Vec<DirectoryMatch>,
-    context: &mut SearchContext,
-    case_sensitive: bool,
-) {
-    if is_debug_enabled() {
-        eprintln!(
-            "DEBUG: search_path_pattern_fast: current_dir={}, search_term='{}', case_sensitive={}",
-            current_dir.display(),
-            search_term,
-            case_sensitive
-        );
+/// `--stats`: report on stderr what the search did, as a JSON object with --json
+fn print_stats(stats: &SearchStats, format: OutputFormat) {
+    let report = stats.report();
+    if format == OutputFormat::Json {
+        eprintln!("{}", report.json());
+    } else {
+        eprintln!("{}", report.text());
     }
+}
 
-    let parts: Vec<&str> = search_term.split('/').collect();
-    if parts.is_empty() || !context.should_continue() {
-        if is_debug_enabled() {
-            eprintln!(
-                "DEBUG: search_path_pattern_fast: early exit - parts empty or context expired"
-            );
-        }
+/// `--report-errors`: list on stderr the directories the search could not read
+/// or follow, so that a match that never appears can be explained
+fn print_unreadable(stats: &SearchStats) {
+    let dirs = stats.unreadable_dirs();
+    if dirs.is_empty() {
+        eprintln!("Every directory searched could be read");
         return;
     }
+    let mut reasons: Vec<(&str, usize)> = Vec::new();
+    for dir in &dirs {
+        match reasons.iter_mut().find(|(reason, _)| *reason == dir.reason) {
+            Some((_, count)) => *count += 1,
+            None => reasons.push((&dir.reason, 1)),
+        }
+    }
+    let counts: Vec<String> = reasons
+        .iter()
+        .map(|(reason, count)| format!("{}: {}", reason, count))
+        .collect();
+    eprintln!(
+        "{} {} could not be read ({}):",
+        dirs.len(),
+        if dirs.len() == 1 {
+            "directory"
+        } else {
+            "directories"
+        },
+        counts.join(", ")
+    );
+    for dir in &dirs {
+        eprintln!("  {}: {}", dir.path.display(), dir.reason);
+    }
+}
 
-    let first_part = parts[0];
-    let remaining_parts = &parts[1..];
-
-    if is_debug_enabled() {
+/// Say on stderr which limit cut the search short, and how to lift it
+fn warn_partial(stats: &SearchStats, time_budget: Duration, match_limit: usize) {
+    let report = stats.report();
+    if report.timed_out {
         eprintln!(
-            "DEBUG: search_path_pattern_fast: split into first_part='{}', remaining_parts={:?}",
-            first_part, remaining_parts
+            "Warning: the search stopped after {} ms and may have missed matches (--timeout 0 removes the limit)",
+            time_budget.as_millis()
         );
-    }
-
-    // Search for the first part in current directory and subdirectories
-    if is_debug_enabled() {
+    } else {
         eprintln!(
-            "DEBUG: search_path_pattern_fast: starting recursive search down from current dir"
+            "Warning: the search stopped at {} matches and may have missed better ones (--max-matches raises the limit)",
+            match_limit
         );
     }
-    search_pattern_recursive_fast(
-        current_dir,
-        first_part,
-        remaining_parts,
-        matches,
-        context,
-        0,
-        4,
-        case_sensitive,
-    );
-
-    // Also search up the tree for the first part (but limit this to avoid slowdown)
-    if is_debug_enabled() {
-        eprintln!("DEBUG: search_path_pattern_fast: starting search up the tree");
-    }
-    let mut current = current_dir;
-    let mut depth = -1;
-    let mut up_count = 0;
+}
 
-    while let Some(parent) = current.parent() {
-        if !context.should_continue() || up_count >= 10 {
-            if is_debug_enabled() {
-                eprintln!("DEBUG: search_path_pattern_fast: stopping up search - context expired or max up count reached");
-            }
+/// `--stdin`: search for each line of standard input and print its best match,
+/// or an empty line (`null` with --json) when there is none, so that output
+/// lines pair up with input lines. Exits 1 when any pattern found nothing.
+fn run_batch(
+    session: &SearchSession,
+    current_dir: &Path,
+    output_format: OutputFormat,
+    record_jump: bool,
+) -> i32 {
+    let mut status = 0;
+    for line in io::stdin().lock().split(b'\n') {
+        let Ok(line) = line else {
             break;
-        }
-
-        if let Some(name) = parent.file_name() {
-            let name_str = name.to_string_lossy();
-            if is_debug_enabled() {
-                eprintln!(
-                    "DEBUG: search_path_pattern_fast: checking parent '{}' at depth {}",
-                    name_str, depth
-                );
-            }
-
-            let matches_pattern = if case_sensitive {
-                name_str.contains(first_part)
-            } else {
-                name_str.to_lowercase().contains(&first_part.to_lowercase())
-            };
-
-            if matches_pattern {
-                if is_debug_enabled() {
-                    eprintln!(
-                        "DEBUG: search_path_pattern_fast: parent '{}' contains pattern '{}'",
-                        name_str, first_part
-                    );
+        };
+        // Like arguments, patterns that are not valid UTF-8 are kept byte for byte
+        let line = platform::bytes_to_str(&line);
+        let search_term = line.trim_end_matches('\r');
+        let mut partial = false;
+        let best = if search_term.is_empty() {
+            None
+        } else {
+            match prepare_search(search_term, session.flags, current_dir, session.settings) {
+                Ok(search) => {
+                    let stats = Arc::clone(&search.config.stats);
+                    let best = session.find(search).into_iter().next();
+                    partial = stats.report().partial();
+                    best
                 }
-
-                if remaining_parts.is_empty() {
-                    let match_quality = if name_str.to_lowercase() == first_part.to_lowercase() {
-                        MatchQuality::ExactUp
-                    } else {
-                        MatchQuality::PartialUp
-                    };
-
-                    if is_debug_enabled() {
-                        eprintln!(
-                            "DEBUG: search_path_pattern_fast: adding up match {:?} for {}",
-                            match_quality,
-                            parent.display()
-                        );
-                    }
-
-                    matches.push(DirectoryMatch {
-                        path: parent.to_path_buf(),
-                        depth_from_current: depth,
-                        match_quality,
-                    });
-                    context.add_match();
-                } else {
-                    if is_debug_enabled() {
-                        eprintln!("DEBUG: search_path_pattern_fast: recursing from parent for remaining patterns");
-                    }
-                    search_pattern_recursive_fast(
-                        parent,
-                        &remaining_parts[0],
-                        &remaining_parts[1..],
-                        matches,
-                        context,
-                        depth,
-                        3,
-                        case_sensitive,
-                    );
+                Err(e) => {
+                    eprintln!("Error: {}: {}", search_term, e);
+                    None
                 }
             }
+        };
+
+        match &best {
+            Some(m) => output::print_match(m, 0, output_format, partial),
+            None => {
+                output::print_no_match(output_format);
+                status = EXIT_NO_MATCH;
+            }
         }
-        current = parent;
-        depth -= 1;
-        up_count += 1;
-    }
+        // A caller feeding one pattern at a time waits for each answer
+        let _ = io::stdout().flush();
 
-    if is_debug_enabled() {
-        eprintln!(
-            "DEBUG: search_path_pattern_fast: completed with {} total matches",
-            matches.len()
-        );
+        if let (Some(m), true) = (&best, record_jump) {
+            if let Err(e) = history::record_visit(&m.path, session.settings) {
+                debug!("Failed to record history: {}", e);
+            }
+        }
     }
+    status
 }
 
-fn search_pattern_recursive_fast(
-    current_dir: &Path,
-    pattern: &str,
-    remaining_patterns: &[&str],
-    matches: &mut Vec<DirectoryMatch>,
-    context: &mut SearchContext,
-    base_depth: i32,
-    max_depth: usize,
-    case_sensitive: bool,
-) {
-    if is_debug_enabled() {
-        eprintln!("DEBUG: search_pattern_recursive_fast: dir={}, pattern='{}', remaining={:?}, base_depth={}, max_depth={}, case_sensitive={}",
-                 current_dir.display(), pattern, remaining_patterns, base_depth, max_depth, case_sensitive);
-    }
+/// `--live`: stays running behind a filter-as-you-type picker. Each line of
+/// standard input is the whole query typed so far; every match for it is
+/// printed, best first, and then an empty line (with --json, one array per
+/// line). The tree below the starting directory is read into memory in the
+/// background, so once it is there later keystrokes do not walk it again.
+fn run_live(session: &mut SearchSession, current_dir: &Path, output_format: OutputFormat) -> i32 {
+    // The snapshot records no symlinked directories, so --follow-symlinks keeps walking
+    let mut snapshot = (!session.flags.follow_symlinks).then(|| {
+        let roots = vec![current_dir.to_path_buf()];
+        let depth = session.settings.max_depth + 1;
+        let ignore_patterns = session.ignore_patterns.clone();
+        thread::spawn(move || index::Index::build(&roots, depth, &ignore_patterns))
+    });
 
-    if max_depth == 0 || !context.should_continue() {
-        if is_debug_enabled() {
-            eprintln!(
-                "DEBUG: search_pattern_recursive_fast: early exit - max_depth=0 or context expired"
-            );
+    for line in io::stdin().lock().split(b'\n') {
+        let Ok(line) = line else {
+            break;
+        };
+        if snapshot.as_ref().is_some_and(|build| build.is_finished()) {
+            if let Some(Ok(tree)) = snapshot.take().map(thread::JoinHandle::join) {
+                debug!("Read {} directories into memory", tree.dir_count());
+                session.index = Some(Arc::new(index::IndexSource::File(tree)));
+            }
         }
-        return;
-    }
-
-    if let Ok(entries) = fs::read_dir(current_dir) {
-        let mut entry_count = 0;
-        let mut match_count = 0;
-
-        for entry in entries.flatten() {
-            entry_count += 1;
 
-            if !context.should_continue() {
-                if is_debug_enabled() {
-                    eprintln!(
-                        "DEBUG: search_pattern_recursive_fast: breaking due to context timeout"
-                    );
+        let line = platform::bytes_to_str(&line);
+        let search_term = line.trim_end_matches('\r');
+        let mut partial = false;
+        let matches = if search_term.is_empty() {
+            Vec::new()
+        } else {
+            match prepare_search(search_term, session.flags, current_dir, session.settings) {
+                Ok(search) => {
+                    let stats = Arc::clone(&search.config.stats);
+                    let matches = session.find(search);
+                    partial = stats.report().partial();
+                    matches
                 }
-                break;
-            }
-
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_dir() {
-                    let path = entry.path();
-                    if let Some(name) = path.file_name() {
-                        let name_str = name.to_string_lossy();
-                        let matches_pattern = if case_sensitive {
-                            name_str.contains(pattern)
-                        } else {
-                            name_str.to_lowercase().contains(&pattern.to_lowercase())
-                        };
-
-                        if matches_pattern {
-                            match_count += 1;
-
-                            if is_debug_enabled() {
-                                eprintln!("DEBUG: search_pattern_recursive_fast: found matching dir '{}' for pattern '{}'", name_str, pattern);
-                            }
-
-                            if remaining_patterns.is_empty() {
-                                let is_exact = if case_sensitive {
-                                    name_str == pattern
-                                } else {
-                                    name_str.to_lowercase() == pattern.to_lowercase()
-                                };
-
-                                let match_quality = if is_exact {
-                                    if base_depth < 0 {
-                                        MatchQuality::ExactUp
-                                    } else {
-                                        MatchQuality::ExactDown
-                                    }
-                                } else {
-                                    if base_depth < 0 {
-                                        MatchQuality::PartialUp
-                                    } else {
-                                        MatchQuality::PartialDown
-                                    }
-                                };
-
-                                if is_debug_enabled() {
-                                    eprintln!("DEBUG: search_pattern_recursive_fast: adding final match {:?} for {}", match_quality, path.display());
-                                }
-
-                                matches.push(DirectoryMatch {
-                                    path: path.clone(),
-                                    depth_from_current: base_depth + 1,
-                                    match_quality,
-                                });
-                                context.add_match();
-                            } else {
-                                if is_debug_enabled() {
-                                    eprintln!("DEBUG: search_pattern_recursive_fast: recursing deeper for remaining patterns");
-                                }
-                                search_pattern_recursive_fast(
-                                    &path,
-                                    remaining_patterns[0],
-                                    &remaining_patterns[1..],
-                                    matches,
-                                    context,
-                                    base_depth + 1,
-                                    max_depth - 1,
-                                    case_sensitive,
-                                );
-                            }
-                        }
-
-                        // Also recurse into subdirectories to find pattern deeper
-                        if context.should_continue() {
-                            search_pattern_recursive_fast(
-                                &path,
-                                pattern,
-                                remaining_patterns,
-                                matches,
-                                context,
-                                base_depth + 1,
-                                max_depth - 1,
-                                case_sensitive,
-                            );
-                        }
-                    }
+                Err(e) => {
+                    // Half-typed queries are often malformed (e.g. an open regex group)
+                    debug!("{}: {}", search_term, e);
+                    Vec::new()
                 }
             }
-        }
+        };
 
-        if is_debug_enabled() {
-            eprintln!("DEBUG: search_pattern_recursive_fast: processed {} entries, found {} pattern matches in {}",
-                     entry_count, match_count, current_dir.display());
-        }
-    } else if is_debug_enabled() {
-        eprintln!(
-            "DEBUG: search_pattern_recursive_fast: failed to read directory {}",
-            current_dir.display()
-        );
+        output::print_list(&matches, output_format, partial);
+        output::print_list_end(output_format);
+        // The picker redraws as soon as the list is complete
+        let _ = io::stdout().flush();
     }
+    0
 }
 
-fn search_absolute_pattern(
-    parent_dir: &Path,
-    pattern: &str,
-    matches: &mut Vec<DirectoryMatch>,
-    case_sensitive: bool,
-) {
-    use std::collections::VecDeque;
+/// Management subcommands such as `jcd index build`. They are only recognized
+/// with a known verb, so `jcd index 0` still searches for a directory named "index".
+fn run_subcommand(args: &[String]) -> Option<i32> {
+    match (args.first()?.as_str(), args.get(1)?.as_str()) {
+        ("index", "build" | "status" | "clear") => Some(index::run_command(&args[1..])),
+        ("daemon", "start" | "stop" | "status" | "run") => Some(daemon::run_command(&args[1..])),
+        ("ignore", "add" | "remove" | "list" | "test") => Some(ignore::run_command(&args[1..])),
+        ("bookmark", "add" | "rm" | "list") => Some(bookmark::run_command(&args[1..])),
+        ("pin", "add" | "rm" | "list") => Some(pin::run_command(&args[1..])),
+        ("import", "zoxide" | "autojump" | "z") => Some(import::run_command(&args[1..])),
+        ("history", "prune" | "list") => Some(history::run_command(&args[1..])),
+        ("init", "bash" | "zsh") => Some(init::run_command(&args[1..])),
+        ("completions", "bash" | "zsh") => Some(completions::run_command(&args[1..])),
+        ("serve", "--stdio") => Some(serve::run_command(&args[1..])),
+        ("config", "show" | "get" | "set" | "path") => Some(config::run_command(&args[1..])),
+        ("bench", "all" | "walk" | "index") => Some(bench::run_command(&args[1..])),
+        ("doctor", "all" | "shell" | "config" | "ignore" | "history" | "index") => {
+            Some(doctor::run_command(&args[1..]))
+        }
+        _ => None,
+    }
+}
 
-    let mut queue = VecDeque::new();
-    let mut immediate_matches: Vec<DirectoryMatch> = Vec::new();
-    queue.push_back((parent_dir.to_path_buf(), 0));
-    let search_lower = if case_sensitive {
-        pattern.to_string()
+/// Record a directory visit reported by a shell hook (`jcd --add "$PWD"`).
+/// Kept minimal since it runs on every prompt: no ignore files, no searching.
+fn add_to_history(dir: &str) -> i32 {
+    let path = platform::str_to_path(dir);
+    let path = if path.is_absolute() {
+        path
     } else {
-        pattern.to_lowercase()
-    };
-    let max_depth = 8;
-
-    // First, search immediate subdirectories (depth 1) to check for early stopping
-    if let Ok(entries) = fs::read_dir(parent_dir) {
-        let mut entries: Vec<_> = entries.filter_map(|e| e.ok()).collect();
-        entries.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
-
-        for entry in &entries {
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_dir() {
-                    let path = entry.path();
-                    if let Some(name) = path.file_name() {
-                        let name_str = name.to_string_lossy();
-                        let (name_compare, search_compare) = if case_sensitive {
-                            (name_str.to_string(), pattern.to_string())
-                        } else {
-                            (name_str.to_lowercase(), search_lower.clone())
-                        };
-
-                        // Check for immediate matches
-                        if name_compare == search_compare {
-                            let dir_match = DirectoryMatch {
-                                path: path.clone(),
-                                depth_from_current: 1,
-                                match_quality: MatchQuality::ExactDown,
-                            };
-                            immediate_matches.push(dir_match.clone());
-                            matches.push(dir_match);
-                        } else if name_compare.starts_with(&search_compare) {
-                            let dir_match = DirectoryMatch {
-                                path: path.clone(),
-                                depth_from_current: 1,
-                                match_quality: MatchQuality::PrefixDown,
-                            };
-                            immediate_matches.push(dir_match.clone());
-                            matches.push(dir_match);
-                        } else if name_compare.contains(&search_compare) {
-                            let dir_match = DirectoryMatch {
-                                path: path.clone(),
-                                depth_from_current: 1,
-                                match_quality: MatchQuality::PartialDown,
-                            };
-                            immediate_matches.push(dir_match.clone());
-                            matches.push(dir_match);
-                        }
-
-                        // Add subdirectories to queue for potential deeper search
-                        queue.push_back((path.clone(), 1));
-                    }
-                }
+        match env::current_dir() {
+            Ok(cwd) => cwd.join(path),
+            Err(e) => {
+                eprintln!("Error: Cannot get current directory: {}", e);
+                return EXIT_IO;
             }
         }
-    }
-
-    // If there are any matches in immediate subdirectories, return early to avoid deep search
-    // This prioritizes local matches over distant ones (same logic as relative paths)
-    if !immediate_matches.is_empty() {
-        return;
-    }
-
-    // Otherwise, continue with breadth-first search for deeper levels
-    while let Some((current_dir, depth)) = queue.pop_front() {
-        if depth == 0 || depth > max_depth {
-            continue; // Skip depth 0 (already processed) and beyond max depth
-        }
-
-        if let Ok(entries) = fs::read_dir(&current_dir) {
-            let mut entries: Vec<_> = entries.filter_map(|e| e.ok()).collect();
-            entries.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
-
-            for entry in &entries {
-                if let Ok(metadata) = entry.metadata() {
-                    if metadata.is_dir() {
-                        let path = entry.path();
-                        if let Some(name) = path.file_name() {
-                            let name_str = name.to_string_lossy();
-                            let (name_compare, search_compare) = if case_sensitive {
-                                (name_str.to_string(), pattern.to_string())
-                            } else {
-                                (name_str.to_lowercase(), search_lower.clone())
-                            };
-
-                            // Check for pattern match at deeper levels
-                            if name_compare == search_compare {
-                                matches.push(DirectoryMatch {
-                                    path: path.clone(),
-                                    depth_from_current: depth as i32,
-                                    match_quality: MatchQuality::ExactDown,
-                                });
-                            } else if name_compare.starts_with(&search_compare) {
-                                matches.push(DirectoryMatch {
-                                    path: path.clone(),
-                                    depth_from_current: depth as i32,
-                                    match_quality: MatchQuality::PrefixDown,
-                                });
-                            } else if name_compare.contains(&search_compare) {
-                                matches.push(DirectoryMatch {
-                                    path: path.clone(),
-                                    depth_from_current: depth as i32,
-                                    match_quality: MatchQuality::PartialDown,
-                                });
-                            }
+    };
 
-                            // Add subdirectories to queue for next level search
-                            if depth < max_depth {
-                                queue.push_back((path, depth + 1));
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    if !path.is_dir() {
+        debug!("Not recording missing directory {}", path.display());
+        return 0;
     }
-}
-
-fn find_search_root_and_pattern(search_term: &str) -> (Option<PathBuf>, String) {
-    let path = Path::new(search_term);
-    let mut current = path;
 
-    // Walk up the path to find the longest existing prefix
-    while let Some(parent) = current.parent() {
-        if parent.exists() && parent.is_dir() {
-            // Found existing parent directory
-            // The search pattern is the first component after this parent
-            let remaining = path.strip_prefix(parent).unwrap();
-            let mut components = remaining.components();
-            if let Some(first_component) = components.next() {
-                let pattern = first_component.as_os_str().to_string_lossy().to_string();
-                return (Some(parent.to_path_buf()), pattern);
-            }
+    match history::record_visit(&path, &Config::load()) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: Cannot update history: {}", e);
+            EXIT_IO
         }
-        current = parent;
     }
-
-    // If we get here, even root doesn't exist (shouldn't happen on Unix)
-    // Fall back to searching from root with the first component as pattern
-    let first_component = Path::new(search_term)
-        .components()
-        .nth(1) // Skip the root component "/"
-        .map(|c| c.as_os_str().to_string_lossy().to_string())
-        .unwrap_or_else(|| search_term.trim_start_matches('/').to_string());
-    (Some(PathBuf::from("/")), first_component)
 }
@@ -0,0 +1,173 @@
+//! `--manpage`: renders jcd(1) in roff from the command-line tables in `cli`
+//! and the settings in `config`, so the page packagers ship lists exactly the
+//! flags, subcommands and environment variables the binary accepts.
+
+use crate::cli::{Flag, FLAGS, PATTERNS, SUBCOMMANDS};
+use jcd::config::SETTINGS;
+
+/// Environment variables read outside the config settings
+const ENVIRONMENT: &[(&str, &str)] = &[
+    (
+        "JCD_CONFIG",
+        "Config file read instead of ~/.config/jcd/config.toml",
+    ),
+    (
+        "JCD_PICKER",
+        "Command run by --fzf instead of fzf, split on whitespace",
+    ),
+    ("JCD_BINARY", "The jcd binary called by the shell function"),
+    (
+        "NO_COLOR",
+        "When set and not empty, --color=auto draws no highlighting or busy indicator",
+    ),
+    ("JCD_DEBUG", "Set to 1 to trace searches on standard error"),
+    (
+        "JCD_LOG",
+        "Tracing filter overriding JCD_DEBUG, e.g. debug or jcd::ranking=trace",
+    ),
+];
+
+/// `text` with the characters roff treats specially escaped
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    // A line starting with a dot or quote would be read as a request
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+/// The tag of an option paragraph: its spellings in bold, its value in italics
+fn flag_tag(flag: &Flag) -> String {
+    let names: Vec<String> = flag
+        .names
+        .iter()
+        .map(|name| format!("\\fB{}\\fR", escape(name)))
+        .collect();
+    match flag.value {
+        Some(value) => format!("{} \\fI{}\\fR", names.join(", "), escape(value)),
+        None => names.join(", "),
+    }
+}
+
+/// A tagged paragraph
+fn paragraph(page: &mut String, tag: &str, text: &str) {
+    page.push_str(&format!(".TP\n{}\n{}\n", tag, escape(text)));
+}
+
+/// The whole page
+pub fn render() -> String {
+    let mut page = String::new();
+    page.push_str(&format!(
+        ".TH JCD 1 \"\" \"jcd {}\" \"User Commands\"\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+    page.push_str(".SH NAME\njcd \\- ");
+    page.push_str(&escape(env!("CARGO_PKG_DESCRIPTION")).to_lowercase());
+    page.push('\n');
+
+    page.push_str(".SH SYNOPSIS\n");
+    page.push_str(".B jcd\n[\\fIflags\\fR] [\\fIword\\fR...] \\fIdirectory_pattern\\fR [\\fIindex\\fR]\n.br\n");
+    page.push_str(".B jcd\n\\fIsubcommand\\fR \\fIverb\\fR [\\fIarguments\\fR]\n");
+
+    page.push_str(".SH DESCRIPTION\n");
+    page.push_str(
+        "jcd finds the directory matching a pattern, searching up and down from the \
+         current directory, and prints it.\n",
+    );
+    page.push_str(
+        "The shell function installed by \\fBjcd init\\fR changes to that directory, \
+         and its completion cycles through the matches, best first.\n",
+    );
+    page.push_str(
+        ".PP\nWith an \\fIindex\\fR, jcd prints the match at that position in the ranking; 0 is the best.\n",
+    );
+
+    page.push_str(".SH OPTIONS\n");
+    for flag in FLAGS {
+        paragraph(&mut page, &flag_tag(flag), flag.help);
+    }
+
+    page.push_str(".SH PATTERNS\n");
+    for (form, help) in PATTERNS {
+        paragraph(&mut page, &format!("\\fB{}\\fR", escape(form)), help);
+    }
+
+    page.push_str(".SH SUBCOMMANDS\n");
+    page.push_str("A subcommand is only recognized with one of its verbs; a single word is always a search.\n");
+    for subcommand in SUBCOMMANDS {
+        let usage: Vec<String> = subcommand
+            .usage
+            .lines()
+            .map(|line| {
+                format!(
+                    "\\fB{}\\fR",
+                    escape(line.trim_start_matches("Usage:").trim())
+                )
+            })
+            .collect();
+        paragraph(&mut page, &usage.join("\n.br\n"), subcommand.help);
+    }
+
+    page.push_str(".SH ENVIRONMENT\n");
+    for (var, help) in ENVIRONMENT {
+        paragraph(&mut page, &format!("\\fB{}\\fR", var), help);
+    }
+    for (key, var) in SETTINGS {
+        paragraph(
+            &mut page,
+            &format!("\\fB{}\\fR", var),
+            &format!("Overrides the {} setting of the config file", key),
+        );
+    }
+
+    page.push_str(".SH FILES\n");
+    paragraph(
+        &mut page,
+        "\\fI~/.config/jcd/config.toml\\fR",
+        "Per-user settings, read after the system-wide config.toml",
+    );
+    paragraph(
+        &mut page,
+        "\\fI~/.local/share/jcd/history.db\\fR",
+        "Directories visited, ranking later searches",
+    );
+    paragraph(
+        &mut page,
+        "\\fI~/.local/share/jcd/bookmarks\\fR",
+        "Named bookmarks",
+    );
+    paragraph(
+        &mut page,
+        "\\fI~/.local/share/jcd/pins\\fR",
+        "Pinned directories, added with jcd pin add",
+    );
+    paragraph(
+        &mut page,
+        "\\fI$XDG_RUNTIME_DIR/jcd.sock\\fR",
+        "Socket of a running jcd daemon, used for downward searches when it answers",
+    );
+
+    page.push_str(".SH EXIT STATUS\n");
+    for (status, meaning) in [
+        ("0", "A directory was found, or a subcommand succeeded"),
+        (
+            "1",
+            "Nothing matched, nothing was picked, or a subcommand found a problem",
+        ),
+        ("2", "Bad flags, pattern or arguments; nothing was searched"),
+        (
+            "3",
+            "A limit cut the search short; what was found is printed",
+        ),
+        (
+            "4",
+            "The current directory, the history or the picker could not be used",
+        ),
+    ] {
+        paragraph(&mut page, status, meaning);
+    }
+    page.push_str(".SH SEE ALSO\n\\fBfzf\\fR(1)\n");
+    page
+}
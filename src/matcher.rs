@@ -0,0 +1,543 @@
+//! Matching one directory name against one pattern component, as text or
+//! as a regex, classifying the match as exact, prefix, word, acronym,
+//! partial or (when asked for) a typo away, and finding the matched text for
+//! highlighting. Names and patterns are compared in Unicode NFC, so a name
+//! stored decomposed (as macOS does) matches its composed spelling.
+
+use crate::{SearchConfig, MAX_COMPILED_REGEX_SIZE};
+use regex::{Regex, RegexBuilder};
+use std::{borrow::Cow, ops::Range};
+use tracing::debug;
+use unicode_normalization::{
+    char::is_combining_mark, is_nfc_quick, IsNormalized, UnicodeNormalization,
+};
+
+/// How well a directory name matches the search pattern, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NameMatch {
+    Exact,   // The whole name matches
+    Prefix,  // The name starts with a match
+    Word,    // A word inside the name starts with a match (api: my-api-server)
+    Acronym, // The pattern spells the starts of all the name's words (fbt: foo-bar-tools, dserv: DataServices)
+    Partial, // A match appears somewhere in the name
+    Typo,    // No match, but the name is a typo or two away from the pattern
+}
+
+/// How the search term is interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PatternSyntax {
+    #[default]
+    Text, // Literal text, or a glob when it contains * or ?
+    Regex, // Regular expression (-E)
+}
+
+#[derive(Debug, Clone)]
+enum Kind {
+    /// Plain text, folded up front like the names it is compared with
+    Literal(String),
+    /// Glob or regex compiled three ways, one per match tier
+    Pattern {
+        full: Regex,
+        prefix: Regex,
+        anywhere: Regex,
+    },
+    /// Text patterns separated by `|`; a name matches as well as the best of them
+    Either(Vec<NameMatcher>),
+    /// Pattern that could not be compiled; matches nothing
+    Never,
+}
+
+/// How names and text patterns are brought to one form before they are compared
+#[derive(Debug, Clone, Copy)]
+struct Folding {
+    case_sensitive: bool,
+    ignore_accents: bool, // é, è and e are the same letter
+}
+
+impl Folding {
+    /// `s` in NFC, without accents and lowercased as asked
+    fn apply(self, s: &str) -> Cow<'_, str> {
+        let mut folded = nfc(s);
+        if self.ignore_accents && !folded.is_ascii() {
+            folded = Cow::Owned(strip_accents(&folded));
+        }
+        if !self.case_sensitive {
+            folded = Cow::Owned(folded.to_lowercase());
+        }
+        folded
+    }
+}
+
+/// `s` in Unicode normalization form C, composing what is decomposed
+pub fn nfc(s: &str) -> Cow<'_, str> {
+    if is_nfc_quick(s.chars()) == IsNormalized::Yes {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(s.nfc().collect())
+    }
+}
+
+/// The byte range of `name` whose NFC form is `range` of the NFC form of the
+/// whole name, widened to whole characters of `name`
+fn original_range(name: &str, range: Range<usize>) -> Range<usize> {
+    let boundaries = || name.char_indices().map(|(i, _)| i).chain([name.len()]);
+    let composed_len = |end: usize| nfc(&name[..end]).len();
+    let start = boundaries()
+        .take_while(|&i| composed_len(i) <= range.start)
+        .last()
+        .unwrap_or(0);
+    let end = boundaries()
+        .find(|&i| composed_len(i) >= range.end)
+        .unwrap_or(name.len());
+    start..end
+}
+
+/// `s` without the accents and other marks combined with its letters
+fn strip_accents(s: &str) -> String {
+    s.nfd().filter(|&c| !is_combining_mark(c)).nfc().collect()
+}
+
+/// A leading `^` and trailing `$` of a text pattern, which tie it to the
+/// start or the end of the name (`^api`, `api$`, `^api$`)
+#[derive(Debug, Clone, Copy, Default)]
+struct Anchors {
+    start: bool,
+    end: bool,
+}
+
+impl Anchors {
+    /// The anchors of a text pattern and the text between them. A pattern
+    /// that is nothing but anchors is taken as written.
+    fn split(pattern: &str) -> (Self, &str) {
+        let (start, rest) = match pattern.strip_prefix('^') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        let (end, text) = match rest.strip_suffix('$') {
+            Some(text) => (true, text),
+            None => (false, rest),
+        };
+        if text.is_empty() {
+            (Self::default(), pattern)
+        } else {
+            (Self { start, end }, text)
+        }
+    }
+
+    fn any(self) -> bool {
+        self.start || self.end
+    }
+}
+
+/// Matches directory names against one search pattern
+#[derive(Debug, Clone)]
+pub struct NameMatcher {
+    pattern: String,
+    folding: Folding,
+    anchors: Anchors, // Only text patterns have them; regexes spell their own
+    kind: Kind,
+    max_typos: usize, // Edits a name may be away from a text pattern; 0 = none
+}
+
+/// Whether the pattern uses glob wildcards (`*` or `?`)
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Whether the pattern contains an uppercase letter, which makes a smart-case
+/// search case-sensitive. Regex escapes such as `\S` or `\W` do not count.
+pub fn has_uppercase(pattern: &str, syntax: PatternSyntax) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' && syntax == PatternSyntax::Regex {
+            chars.next();
+        } else if c.is_uppercase() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Translate a glob into an unanchored regex: `*` matches any run of
+/// characters, `?` exactly one, everything else matches literally
+fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::new();
+    for c in glob.chars() {
+        match c {
+            '*' => out.push_str(".*"),
+            '?' => out.push('.'),
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    out
+}
+
+/// Where the words of a directory name start, as byte offsets. Words are
+/// separated by anything but letters and digits (`foo-bar_tools`, `v1.2`),
+/// and a new word starts at an uppercase letter after a lowercase one
+/// (`dataServices`) or before one in a run of capitals (`HTTPServer`).
+pub fn word_starts(name: &str) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut chars = name.char_indices().peekable();
+    let mut previous: Option<char> = None;
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, next)| next);
+        let starts_word = c.is_alphanumeric()
+            && match previous {
+                None => true,
+                Some(p) if !p.is_alphanumeric() => true,
+                Some(p) => {
+                    c.is_uppercase()
+                        && (p.is_lowercase()
+                            || (p.is_uppercase() && next.is_some_and(char::is_lowercase)))
+                }
+            };
+        if starts_word {
+            starts.push(i);
+        }
+        previous = Some(c);
+    }
+    starts
+}
+
+/// Whether the folded `pattern` starts a word of `name` other than the first
+fn starts_inner_word(name: &str, pattern: &str, folding: Folding) -> bool {
+    word_starts(name)
+        .into_iter()
+        .filter(|&start| start > 0)
+        .any(|start| folding.apply(&name[start..]).starts_with(pattern))
+}
+
+/// Whether the folded `pattern` is a word of `name` other than the first
+/// and everything after it
+fn ends_inner_word(name: &str, pattern: &str, folding: Folding) -> bool {
+    word_starts(name)
+        .into_iter()
+        .filter(|&start| start > 0)
+        .any(|start| folding.apply(&name[start..]) == pattern)
+}
+
+/// Whether `pattern` is made of the start of every word, in order: at least
+/// the initial of each, so `fbt` spells `foo-bar-tools` and `dserv`
+/// `DataServices`. `words` are the letters and digits of each word.
+fn spells_word_starts(words: &[Vec<char>], pattern: &[char]) -> bool {
+    let Some((word, rest)) = words.split_first() else {
+        return pattern.is_empty();
+    };
+    let shared = word.iter().zip(pattern).take_while(|(w, p)| w == p).count();
+    (1..=shared).any(|taken| spells_word_starts(rest, &pattern[taken..]))
+}
+
+/// Whether the folded `pattern` spells the starts of the words of `name`,
+/// which must have two words or more
+fn is_acronym(name: &str, pattern: &str, folding: Folding) -> bool {
+    let starts = word_starts(name);
+    if starts.len() < 2 {
+        return false;
+    }
+    let ends = starts.iter().skip(1).copied().chain([name.len()]);
+    let words: Vec<Vec<char>> = starts
+        .iter()
+        .zip(ends)
+        .map(|(&start, end)| {
+            let word = &name[start..end];
+            let word = word.trim_end_matches(|c: char| !c.is_alphanumeric());
+            folding.apply(word).chars().collect()
+        })
+        .collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    spells_word_starts(&words, &pattern)
+}
+
+/// The most edits a name may be from `pattern` to count as a typo: none for
+/// patterns too short to tell a typo from another name, one for short ones
+/// and two from six characters on
+fn typo_allowance(pattern: &str) -> usize {
+    match pattern.chars().count() {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// Whether `a` can be turned into `b` with at most `max` edits: a character
+/// inserted, deleted or replaced, or two neighbours swapped (`srevices`)
+fn within_edits(a: &str, b: &str, max: usize) -> bool {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    if a.len().abs_diff(b.len()) > max {
+        return false;
+    }
+    // Optimal string alignment distance, keeping the last three rows
+    let mut before: Vec<usize> = Vec::new();
+    let mut above: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (above[j] + 1).min(row[j - 1] + 1).min(above[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(before[j - 2] + 1);
+            }
+        }
+        if row.iter().min().is_some_and(|&least| least > max) {
+            return false;
+        }
+        before = std::mem::replace(&mut above, row);
+    }
+    above[b.len()] <= max
+}
+
+fn compile(source: &str, case_sensitive: bool) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(source)
+        .case_insensitive(!case_sensitive)
+        .size_limit(MAX_COMPILED_REGEX_SIZE)
+        .build()
+}
+
+fn compile_tiers(source: &str, case_sensitive: bool) -> Result<Kind, regex::Error> {
+    // Compile the bare pattern first so errors point at what the user wrote
+    let anywhere = compile(source, case_sensitive)?;
+    Ok(Kind::Pattern {
+        full: compile(&format!("^(?:{})$", source), case_sensitive)?,
+        prefix: compile(&format!("^(?:{})", source), case_sensitive)?,
+        anywhere,
+    })
+}
+
+impl NameMatcher {
+    /// Build a matcher for `pattern`. In text syntax, `|` separates
+    /// alternatives, patterns containing `*` or `?` are globs, and `^` and `$`
+    /// around the text anchor it; in regex syntax the pattern is a regular
+    /// expression.
+    pub fn new(pattern: &str, case_sensitive: bool, syntax: PatternSyntax) -> Result<Self, String> {
+        let folding = Folding {
+            case_sensitive,
+            ignore_accents: false,
+        };
+        if syntax == PatternSyntax::Text && pattern.contains('|') {
+            // Empty alternatives are dropped; a pattern of bars alone is taken as written
+            let mut alternatives: Vec<&str> =
+                pattern.split('|').filter(|a| !a.is_empty()).collect();
+            match alternatives.len() {
+                0 => {}
+                1 => return Self::new(alternatives.remove(0), case_sensitive, syntax),
+                _ => {
+                    let alternatives = alternatives
+                        .into_iter()
+                        .map(|alternative| Self::new(alternative, case_sensitive, syntax))
+                        .collect::<Result<_, _>>()?;
+                    return Ok(Self {
+                        pattern: pattern.to_string(),
+                        folding,
+                        anchors: Anchors::default(),
+                        kind: Kind::Either(alternatives),
+                        max_typos: 0,
+                    });
+                }
+            }
+        }
+        let (anchors, text) = match syntax {
+            PatternSyntax::Text => Anchors::split(pattern),
+            PatternSyntax::Regex => (Anchors::default(), pattern),
+        };
+        let source = nfc(text);
+        let kind = if syntax == PatternSyntax::Regex {
+            compile_tiers(&source, case_sensitive)
+                .map_err(|e| format!("Invalid regex '{}': {}", pattern, e))?
+        } else if is_glob(text) {
+            let source = format!(
+                "{}(?:{}){}",
+                if anchors.start { "^" } else { "" },
+                glob_to_regex(&source),
+                if anchors.end { "$" } else { "" }
+            );
+            compile_tiers(&source, case_sensitive)
+                .map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?
+        } else {
+            Kind::Literal(folding.apply(text).into_owned())
+        };
+        Ok(Self {
+            pattern: pattern.to_string(),
+            folding,
+            anchors,
+            kind,
+            max_typos: 0,
+        })
+    }
+
+    /// Compare text patterns with names without their accents, so `resume`
+    /// matches `résumé` and `Résumé` matches `resume`. Globs and regexes
+    /// keep them.
+    pub fn with_accents_ignored(mut self, ignore_accents: bool) -> Self {
+        self.folding.ignore_accents = ignore_accents;
+        if let Kind::Literal(pattern) = &mut self.kind {
+            let (_, text) = Anchors::split(&self.pattern);
+            *pattern = self.folding.apply(text).into_owned();
+        }
+        self.each_alternative(|alternative| alternative.with_accents_ignored(ignore_accents))
+    }
+
+    /// Also match names a typo or two away from a text pattern, as
+    /// `NameMatch::Typo`, when they match no other way. Globs, regexes and
+    /// anchored patterns are left as they are.
+    pub fn with_typos(mut self, typos: bool) -> Self {
+        self.max_typos = match &self.kind {
+            Kind::Literal(pattern) if typos && !self.anchors.any() => typo_allowance(pattern),
+            _ => 0,
+        };
+        self.each_alternative(|alternative| alternative.with_typos(typos))
+    }
+
+    /// Apply a builder to each alternative of a `foo|bar` pattern
+    fn each_alternative(mut self, f: impl Fn(Self) -> Self) -> Self {
+        if let Kind::Either(alternatives) = &mut self.kind {
+            *alternatives = std::mem::take(alternatives).into_iter().map(f).collect();
+        }
+        self
+    }
+
+    /// Matcher for `pattern` under the search's options. A pattern that fails
+    /// to compile matches nothing; main validates patterns before searching.
+    pub fn or_never(pattern: &str, config: &SearchConfig) -> Self {
+        let case_sensitive = config.case_sensitive;
+        Self::new(pattern, case_sensitive, config.syntax)
+            .map(|matcher| matcher.with_accents_ignored(config.ignore_accents))
+            .unwrap_or_else(|e| {
+                debug!("{}", e);
+                Self {
+                    pattern: pattern.to_string(),
+                    folding: Folding {
+                        case_sensitive,
+                        ignore_accents: false,
+                    },
+                    anchors: Anchors::default(),
+                    kind: Kind::Never,
+                    max_typos: 0,
+                }
+            })
+    }
+
+    /// Matcher for every name, as an exact match
+    pub fn any() -> Self {
+        Self::new("*", true, PatternSyntax::Text).expect("'*' is a valid glob")
+    }
+
+    /// The pattern as given by the user
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Classify a directory name, or None if it does not match
+    pub fn classify(&self, name: &str) -> Option<NameMatch> {
+        match &self.kind {
+            Kind::Literal(pattern) => {
+                // Words are found in the NFC name, whose case is still there
+                let composed = nfc(name);
+                let name = self.folding.apply(&composed);
+                if name == pattern.as_str() {
+                    Some(NameMatch::Exact)
+                } else if self.anchors.end {
+                    // Tied to the end of the name: a word or any other suffix
+                    if self.anchors.start || !name.ends_with(pattern.as_str()) {
+                        None
+                    } else if ends_inner_word(&composed, pattern, self.folding) {
+                        Some(NameMatch::Word)
+                    } else {
+                        Some(NameMatch::Partial)
+                    }
+                } else if name.starts_with(pattern.as_str()) {
+                    Some(NameMatch::Prefix)
+                } else if self.anchors.start {
+                    None
+                } else if starts_inner_word(&composed, pattern, self.folding) {
+                    Some(NameMatch::Word)
+                } else if is_acronym(&composed, pattern, self.folding) {
+                    Some(NameMatch::Acronym)
+                } else if name.contains(pattern.as_str()) {
+                    Some(NameMatch::Partial)
+                } else if self.max_typos > 0 && within_edits(&name, pattern, self.max_typos) {
+                    Some(NameMatch::Typo)
+                } else {
+                    None
+                }
+            }
+            Kind::Pattern {
+                full,
+                prefix,
+                anywhere,
+            } => {
+                let name = nfc(name);
+                let name = name.as_ref();
+                if full.is_match(name) {
+                    Some(NameMatch::Exact)
+                } else if prefix.is_match(name) {
+                    Some(NameMatch::Prefix)
+                } else if anywhere.is_match(name) {
+                    Some(NameMatch::Partial)
+                } else {
+                    None
+                }
+            }
+            Kind::Either(alternatives) => alternatives
+                .iter()
+                .filter_map(|alternative| alternative.classify(name))
+                .min(),
+            Kind::Never => None,
+        }
+    }
+
+    /// The byte range of `name` that a text pattern matches from `start`
+    fn literal_at(&self, name: &str, start: usize, pattern: &str) -> Option<Range<usize>> {
+        let rest = &name[start..];
+        if !self.folding.apply(rest).starts_with(pattern) {
+            return None;
+        }
+        // Folding can change lengths, so compare each prefix of the original
+        rest.char_indices()
+            .map(|(offset, c)| offset + c.len_utf8())
+            .find(|&end| self.folding.apply(&rest[..end]) == pattern)
+            .map(|end| start..start + end)
+    }
+
+    /// The byte range of `name` that the pattern matched, for highlighting:
+    /// the whole name for an exact match, else the first match at the start
+    /// of a word, else the first match, where its anchors allow
+    pub fn find(&self, name: &str) -> Option<Range<usize>> {
+        match &self.kind {
+            Kind::Literal(pattern) => word_starts(name)
+                .into_iter()
+                .chain(name.char_indices().map(|(start, _)| start))
+                .filter(|&start| !self.anchors.start || start == 0)
+                .filter_map(|start| self.literal_at(name, start, pattern))
+                .find(|range| !self.anchors.end || range.end == name.len()),
+            Kind::Pattern {
+                full,
+                prefix,
+                anywhere,
+            } => {
+                // Matched against the NFC name, as `classify` does
+                let composed = nfc(name);
+                if full.is_match(&composed) {
+                    return Some(0..name.len());
+                }
+                let range = prefix
+                    .find(&composed)
+                    .or_else(|| anywhere.find(&composed))
+                    .map(|m| m.range())?;
+                match composed {
+                    Cow::Borrowed(_) => Some(range),
+                    Cow::Owned(_) => Some(original_range(name, range)),
+                }
+            }
+            // The alternative that matched best, else the first found
+            Kind::Either(alternatives) => alternatives
+                .iter()
+                .filter_map(|alternative| {
+                    Some((alternative.classify(name), alternative.find(name)?))
+                })
+                .min_by_key(|(kind, _)| kind.unwrap_or(NameMatch::Typo))
+                .map(|(_, range)| range),
+            Kind::Never => None,
+        }
+    }
+}
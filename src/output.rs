@@ -0,0 +1,456 @@
+//! Writing matches to stdout: plain paths, NUL-terminated paths, JSON or
+//! tab-separated completion candidates, and the ranking explained. Paths for
+//! a terminal can be highlighted: the parent path dimmed and the part of the
+//! name that matched the pattern colored, unless `--color` or `NO_COLOR`
+//! says otherwise.
+
+use crate::{
+    config::Config, matcher::NameMatcher, platform, ranking::Explanation, DirectoryMatch,
+    MatchQuality, PreparedSearch,
+};
+use std::{
+    env,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// When output is decorated with colors and the busy indicator (`--color`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    #[default]
+    Auto, // On terminals, unless NO_COLOR is set
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(ColorChoice::Auto),
+            "always" => Some(ColorChoice::Always),
+            "never" => Some(ColorChoice::Never),
+            _ => None,
+        }
+    }
+
+    /// Whether to decorate a stream, given whether it is a terminal. A
+    /// non-empty NO_COLOR turns decorations off (https://no-color.org).
+    pub fn decorate(self, terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => terminal && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+        }
+    }
+}
+
+const COLORS: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// The SGR parameter for one word of a style, e.g. `bold`, `red`,
+/// `bright_blue`, `on_yellow` or a number such as `38;5;208`
+fn sgr_code(word: &str) -> Option<String> {
+    let attribute = match word {
+        "bold" => Some(1),
+        "dim" => Some(2),
+        "italic" => Some(3),
+        "underline" => Some(4),
+        "reverse" => Some(7),
+        _ => None,
+    };
+    if let Some(code) = attribute {
+        return Some(code.to_string());
+    }
+    let (background, color) = match word.strip_prefix("on_") {
+        Some(color) => (10, color),
+        None => (0, word),
+    };
+    let (bright, color) = match color.strip_prefix("bright_") {
+        Some(color) => (60, color),
+        None => (0, color),
+    };
+    if let Some(n) = COLORS.iter().position(|c| *c == color) {
+        return Some((30 + background + bright + n).to_string());
+    }
+    (!word.is_empty() && word.chars().all(|c| c.is_ascii_digit() || c == ';'))
+        .then(|| word.to_string())
+}
+
+/// How a piece of text is drawn on a terminal, written as words such as
+/// `bold red` or `dim`; `none` draws it plainly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Style {
+    spec: String,
+    sgr: String, // SGR parameters, e.g. "1;31"; empty for none
+}
+
+impl Style {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut codes = Vec::new();
+        for word in spec.split_whitespace() {
+            if word == "none" {
+                continue;
+            }
+            codes.push(sgr_code(word).ok_or_else(|| {
+                format!(
+                    "Unknown style '{}'; use bold, dim, italic, underline, reverse, a color such as red, bright_red or on_red, or SGR numbers",
+                    word
+                )
+            })?);
+        }
+        Ok(Self {
+            spec: spec.trim().to_string(),
+            sgr: codes.join(";"),
+        })
+    }
+
+    /// The style as it was written
+    pub fn spec(&self) -> &str {
+        &self.spec
+    }
+
+    /// `text` with the escape sequences that draw it in this style
+    pub fn paint(&self, text: &str) -> String {
+        if self.sgr.is_empty() || text.is_empty() {
+            text.to_string()
+        } else {
+            format!("\x1b[{}m{}\x1b[0m", self.sgr, text)
+        }
+    }
+}
+
+/// Draws matches for a terminal: the parent path in `ui.path_style` and the
+/// matched part of the name in `ui.match_style`
+#[derive(Debug, Clone)]
+pub struct Highlighter {
+    matcher: Option<NameMatcher>, // The last component of the pattern; all of it with --full-path
+    below: Option<PathBuf>, // With --full-path, the search directory the pattern matched below
+    matched: Style,
+    path: Style,
+}
+
+impl Highlighter {
+    /// The highlighter for the matches of `search`, before it is run
+    pub fn new(search: &PreparedSearch, settings: &Config) -> Self {
+        let full_path = search.config.full_path;
+        let last = if full_path {
+            Some(search.pattern.as_str())
+        } else {
+            search
+                .pattern
+                .rsplit('/')
+                .find(|part| !part.is_empty() && *part != "**")
+        };
+        Self {
+            matcher: last.and_then(|part| {
+                NameMatcher::new(part, search.config.case_sensitive, search.config.syntax)
+                    .ok()
+                    .map(|matcher| matcher.with_accents_ignored(search.config.ignore_accents))
+            }),
+            below: full_path.then(|| search.search_dir.clone()),
+            matched: settings.match_style.clone(),
+            path: settings.path_style.clone(),
+        }
+    }
+
+    /// The path of `m` with its parent path and matched text styled
+    pub fn paint(&self, m: &DirectoryMatch) -> String {
+        let shown = platform::path_to_str(&m.path);
+        let split = shown
+            .rfind(std::path::is_separator)
+            .map_or(0, |separator| separator + 1);
+        // The matched text is looked for in the name, or with --full-path in
+        // the path below the search directory
+        let searched = match &self.below {
+            Some(dir) => m.path.strip_prefix(dir).map_or(0, |relative| {
+                shown.len() - platform::path_to_str(relative).len()
+            }),
+            None => split,
+        };
+        let found = self
+            .matcher
+            .as_ref()
+            .and_then(|matcher| matcher.find(&shown[searched..]))
+            .map(|range| searched + range.start..searched + range.end);
+        match found {
+            Some(range) => format!(
+                "{}{}{}",
+                self.unmatched(&shown[..range.start], split),
+                self.matched.paint(&shown[range.clone()]),
+                self.unmatched(&shown[range.end..], split.saturating_sub(range.end))
+            ),
+            None => self.unmatched(&shown, split),
+        }
+    }
+
+    /// `text` with its first `parent` bytes in the path style
+    fn unmatched(&self, text: &str, parent: usize) -> String {
+        let parent = parent.min(text.len());
+        format!("{}{}", self.path.paint(&text[..parent]), &text[parent..])
+    }
+}
+
+/// How matches are written to stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Plain, // Bare path, one per line
+    Null, // Bare path followed by a NUL byte (-0), safe for any name
+    Json, // One JSON object per match; an array in list mode
+    Tsv,  // path<TAB>display<TAB>quality<TAB>depth, for pickers and described completion
+}
+
+impl OutputFormat {
+    /// Byte written after each bare path
+    fn terminator(self) -> u8 {
+        if self == OutputFormat::Null {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+impl MatchQuality {
+    /// Stable name used in structured output
+    pub fn name(&self) -> &'static str {
+        match self {
+            MatchQuality::Bookmark => "bookmark",
+            MatchQuality::Pinned => "pinned",
+            MatchQuality::ExactUp => "exact_up",
+            MatchQuality::ExactSibling => "exact_sibling",
+            MatchQuality::ExactCousin => "exact_cousin",
+            MatchQuality::PartialUp => "partial_up",
+            MatchQuality::PartialSibling => "partial_sibling",
+            MatchQuality::PartialCousin => "partial_cousin",
+            MatchQuality::ExactDown => "exact_down",
+            MatchQuality::PrefixDown => "prefix_down",
+            MatchQuality::WordDown => "word_down",
+            MatchQuality::AcronymDown => "acronym_down",
+            MatchQuality::PartialDown => "partial_down",
+            MatchQuality::TypoUp => "typo_up",
+            MatchQuality::TypoDown => "typo_down",
+        }
+    }
+
+    /// The quality called `name` in structured output
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bookmark" => Some(MatchQuality::Bookmark),
+            "pinned" => Some(MatchQuality::Pinned),
+            "exact_up" => Some(MatchQuality::ExactUp),
+            "exact_sibling" => Some(MatchQuality::ExactSibling),
+            "exact_cousin" => Some(MatchQuality::ExactCousin),
+            "partial_up" => Some(MatchQuality::PartialUp),
+            "partial_sibling" => Some(MatchQuality::PartialSibling),
+            "partial_cousin" => Some(MatchQuality::PartialCousin),
+            "exact_down" => Some(MatchQuality::ExactDown),
+            "prefix_down" => Some(MatchQuality::PrefixDown),
+            "word_down" => Some(MatchQuality::WordDown),
+            "acronym_down" => Some(MatchQuality::AcronymDown),
+            "partial_down" => Some(MatchQuality::PartialDown),
+            "typo_up" => Some(MatchQuality::TypoUp),
+            "typo_down" => Some(MatchQuality::TypoDown),
+            _ => None,
+        }
+    }
+}
+
+/// Escape a string for use inside a JSON string literal
+pub fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// JSON object describing one match; `rank` is its index in the ranked list.
+/// Matches of a `partial` search, which a limit cut short, say so.
+pub fn match_json(m: &DirectoryMatch, rank: usize, partial: bool) -> String {
+    format!(
+        "{{\"path\":\"{}\",\"quality\":\"{}\",\"depth\":{},\"rank\":{}{}}}",
+        json_escape(&m.path.to_string_lossy()),
+        m.match_quality.name(),
+        m.depth_from_current,
+        rank,
+        if partial { ",\"partial\":true" } else { "" }
+    )
+}
+
+/// The path as shown to people: the home directory spelled `~`, and tabs and
+/// line breaks replaced so they cannot split the fields
+fn display_path(path: &Path) -> String {
+    let shown = match platform::home_dir().and_then(|home| {
+        path.strip_prefix(&home)
+            .ok()
+            .map(|rest| Path::new("~").join(rest))
+    }) {
+        Some(short) => platform::path_to_str(&short).into_owned(),
+        None => platform::path_to_str(path).into_owned(),
+    };
+    shown.replace(['\t', '\n', '\r'], "?")
+}
+
+/// Tab-separated line describing one match: raw path, display form, quality
+/// and depth (negative for parent directories)
+pub fn match_tsv(m: &DirectoryMatch) -> String {
+    format!(
+        "{}\t{}\t{}\t{}",
+        platform::path_to_str(&m.path),
+        display_path(&m.path),
+        m.match_quality.name(),
+        m.depth_from_current
+    )
+}
+
+/// Print the match at `rank` of a search, `partial` if a limit cut it short
+pub fn print_match(m: &DirectoryMatch, rank: usize, format: OutputFormat, partial: bool) {
+    match format {
+        OutputFormat::Plain | OutputFormat::Null => {
+            let _ = platform::write_path(&mut io::stdout().lock(), &m.path, format.terminator());
+        }
+        OutputFormat::Json => println!("{}", match_json(m, rank, partial)),
+        OutputFormat::Tsv => println!("{}", match_tsv(m)),
+    }
+}
+
+/// Print the answer for a search that found nothing, where one line is due
+pub fn print_no_match(format: OutputFormat) {
+    match format {
+        OutputFormat::Plain | OutputFormat::Tsv => println!(),
+        OutputFormat::Null => print!("\0"),
+        OutputFormat::Json => println!("null"),
+    }
+}
+
+/// Mark the end of a list when several are printed in a row: an empty line
+/// (a NUL byte with -0). A JSON list is one line and needs no marker.
+pub fn print_list_end(format: OutputFormat) {
+    match format {
+        OutputFormat::Plain | OutputFormat::Tsv => println!(),
+        OutputFormat::Null => print!("\0"),
+        OutputFormat::Json => {}
+    }
+}
+
+/// Print every match in ranked order, `partial` if a limit cut the search short
+pub fn print_list(matches: &[DirectoryMatch], format: OutputFormat, partial: bool) {
+    match format {
+        OutputFormat::Plain | OutputFormat::Null => {
+            let mut out = io::stdout().lock();
+            for m in matches {
+                let _ = platform::write_path(&mut out, &m.path, format.terminator());
+            }
+        }
+        OutputFormat::Json => {
+            let items: Vec<String> = matches
+                .iter()
+                .enumerate()
+                .map(|(rank, m)| match_json(m, rank, partial))
+                .collect();
+            println!("[{}]", items.join(","));
+        }
+        OutputFormat::Tsv => {
+            for m in matches {
+                println!("{}", match_tsv(m));
+            }
+        }
+    }
+}
+
+/// Print every match in ranked order, one highlighted path per line
+pub fn print_highlighted(matches: &[DirectoryMatch], highlighter: &Highlighter) {
+    let mut out = io::stdout().lock();
+    for m in matches {
+        if writeln!(out, "{}", highlighter.paint(m)).is_err() {
+            return;
+        }
+    }
+}
+
+/// A score for JSON, which has no infinity: bookmarks score `null`
+fn json_score(score: f64) -> String {
+    if score.is_finite() {
+        format!("{:.2}", score)
+    } else {
+        "null".to_string()
+    }
+}
+
+/// Print every match in ranked order with what placed it there: its tier,
+/// depth, usage points, score and why it follows the match above. JSON adds
+/// these to the objects of `--list --json`; the other formats print two
+/// indented lines under each path.
+pub fn print_explanations(
+    matches: &[DirectoryMatch],
+    explanations: &[Explanation],
+    format: OutputFormat,
+    partial: bool,
+) {
+    if format == OutputFormat::Json {
+        let items: Vec<String> = matches
+            .iter()
+            .zip(explanations)
+            .enumerate()
+            .map(|(rank, (m, e))| {
+                let object = match_json(m, rank, partial);
+                format!(
+                    "{},\"tier\":{},\"tier_name\":\"{}\",\"project_root\":{},\"same_repo\":{},\"frecency\":{:.2},\"recency\":{:.2},\"boost\":{},\"score\":{},\"reason\":\"{}\"}}",
+                    &object[..object.len() - 1],
+                    e.tier,
+                    e.tier_name(),
+                    m.project_root,
+                    m.same_repo,
+                    e.frecency,
+                    e.recency,
+                    e.boost,
+                    json_score(e.score),
+                    json_escape(&e.reason)
+                )
+            })
+            .collect();
+        println!("[{}]", items.join(","));
+        return;
+    }
+    // Long lists go to pagers and head, which may stop reading early
+    let mut out = io::stdout().lock();
+    for (rank, (m, e)) in matches.iter().zip(explanations).enumerate() {
+        let mut notes = String::new();
+        if m.project_root {
+            notes.push_str(", project root");
+        }
+        if m.same_repo {
+            notes.push_str(", current repository");
+        }
+        if e.boost != 1.0 {
+            notes.push_str(&format!(", boost x{}", e.boost));
+        }
+        let written = writeln!(
+            out,
+            "{:>2}. {}\n    tier {} ({}), depth {}{}, frecency {:+.1}, recency {:+.1}, score {:.1}\n    {}",
+            rank,
+            platform::path_to_str(&m.path),
+            e.tier,
+            e.tier_name(),
+            m.depth_from_current,
+            notes,
+            e.frecency,
+            e.recency,
+            e.score,
+            e.reason
+        );
+        if written.is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! `--fzf`: hands every candidate to an interactive picker and reports the
+//! user's choice. The picker is `fzf` unless `$JCD_PICKER` names another
+//! command; it reads one path per line and prints the chosen lines, which is
+//! also how skim, peco and `fzf --multi` behave. fzf and skim are handed the
+//! candidates highlighted unless colors are off, and strip the colors from
+//! what they print.
+
+use jcd::{output::Highlighter, platform, DirectoryMatch};
+use std::{
+    env,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+use tracing::debug;
+
+const DEFAULT_PICKER: &str = "fzf";
+
+/// Pickers that understand `--ansi`
+const ANSI_PICKERS: &[&str] = &["fzf", "sk"];
+
+/// The picker command line: `$JCD_PICKER` split on whitespace, else fzf
+fn picker_command() -> Vec<String> {
+    let command: Vec<String> = env::var("JCD_PICKER")
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    if command.is_empty() {
+        vec![DEFAULT_PICKER.to_string()]
+    } else {
+        command
+    }
+}
+
+/// Let the user choose among `matches`, returning the chosen matches in the
+/// order the picker printed them. None chosen means the picker was cancelled.
+pub fn pick(
+    matches: &[DirectoryMatch],
+    highlighter: Option<&Highlighter>,
+) -> Result<Vec<DirectoryMatch>, String> {
+    let command = picker_command();
+    let program = Path::new(&command[0])
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned());
+    let highlighter = highlighter
+        .filter(|_| program.is_some_and(|program| ANSI_PICKERS.contains(&program.as_str())));
+    debug!("Handing {} candidates to {:?}", matches.len(), command);
+    // The picker draws on the terminal itself; only the candidates go through pipes
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        .args(highlighter.map(|_| "--ansi"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Cannot run picker '{}': {}", command[0], e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        for m in matches {
+            // A picker that is done early closes its input; the rest is not needed
+            let written = match highlighter {
+                Some(highlighter) => writeln!(stdin, "{}", highlighter.paint(m)),
+                None => platform::write_path(&mut stdin, &m.path, b'\n'),
+            };
+            if written.is_err() {
+                break;
+            }
+        }
+    } // Dropping stdin ends the candidate list
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Picker '{}' failed: {}", command[0], e))?;
+    debug!("Picker exited with {}", output.status);
+
+    // Lines that are not candidates (e.g. the query with fzf --print-query) are ignored
+    let chosen = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            matches
+                .iter()
+                .find(|m| platform::path_to_str(&m.path) == line)
+        })
+        .cloned()
+        .collect();
+    Ok(chosen)
+}
@@ -0,0 +1,189 @@
+//! Pinned directories and the `jcd pin` subcommand. A pinned directory whose
+//! name matches the pattern comes before every other match, wherever it is
+//! and however seldom it is visited; one the search itself found moves to the
+//! front. Pins come from `ranking.pins` in config.toml and from the pin file
+//! that `jcd pin add` and `jcd pin rm` edit.
+
+use crate::{config::Config, platform, query::expand_home, EXIT_USAGE};
+use std::{
+    collections::BTreeSet,
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+const PINS_HEADER: &str = "# jcd pins v1";
+
+pub const USAGE: &str = "Usage: jcd pin add [dir] | rm [dir] | list";
+
+/// Location of the pin file, following the XDG Base Directory Specification
+pub fn pins_file_path() -> PathBuf {
+    platform::data_home().join("jcd").join("pins")
+}
+
+/// The directories pinned with `jcd pin add`
+#[derive(Debug, Default)]
+pub struct Pins {
+    dirs: BTreeSet<PathBuf>,
+}
+
+impl Pins {
+    /// Parse the pin file, one directory per line
+    pub fn parse(content: &str) -> Self {
+        let dirs = content
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(platform::str_to_path)
+            .collect();
+        Self { dirs }
+    }
+
+    /// Load the pins, returning none if the file does not exist
+    pub fn load() -> Self {
+        let path = pins_file_path();
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                let pins = Self::parse(&content);
+                debug!("Loaded {} pins from {}", pins.dirs.len(), path.display());
+                pins
+            }
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Write the file atomically by replacing it with a fully written temp file
+    pub fn save(&self) -> io::Result<()> {
+        let path = pins_file_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut content = String::from(PINS_HEADER);
+        content.push('\n');
+        for dir in &self.dirs {
+            content.push_str(&platform::path_to_str(dir));
+            content.push('\n');
+        }
+
+        let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        {
+            let mut file = fs::File::create(&tmp_path)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, &path)
+    }
+
+    /// Every pinned directory, in path order
+    pub fn dirs(&self) -> impl Iterator<Item = &PathBuf> {
+        self.dirs.iter()
+    }
+}
+
+/// The pins of `ranking.pins` followed by those of the pin file
+pub fn load_all(configured: &[PathBuf]) -> Vec<PathBuf> {
+    let mut dirs = configured.to_vec();
+    for dir in Pins::load().dirs {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+/// `dir` (default: the current directory) as an absolute path without symlinks
+fn resolve(dir: Option<&str>) -> Result<PathBuf, String> {
+    let cwd = env::current_dir().map_err(|e| format!("Cannot get current directory: {}", e))?;
+    let dir = match dir {
+        Some(dir) => cwd.join(expand_home(dir)),
+        None => cwd,
+    };
+    fs::canonicalize(&dir)
+        .ok()
+        .filter(|d| d.is_dir())
+        .ok_or_else(|| format!("Not a directory: {}", dir.display()))
+}
+
+/// `jcd pin add`: pin `dir` (default: the current directory)
+pub fn add(dir: Option<&str>) -> Result<String, String> {
+    let dir = resolve(dir)?;
+    // Paths are stored one per line
+    if platform::path_to_str(&dir).contains('\n') {
+        return Err(format!(
+            "Cannot pin {}: its name contains a newline",
+            dir.display()
+        ));
+    }
+    let mut pins = Pins::load();
+    if !pins.dirs.insert(dir.clone()) {
+        return Ok(format!("Already pinned: {}", dir.display()));
+    }
+    save(&pins)?;
+    Ok(format!("Pinned {}", dir.display()))
+}
+
+/// `jcd pin rm`: unpin `dir` (default: the current directory). A directory
+/// that no longer exists is unpinned by the path it was pinned with.
+pub fn remove(dir: Option<&str>) -> Result<String, String> {
+    let mut pins = Pins::load();
+    let dir = resolve(dir).or_else(|e| {
+        dir.map(|d| env::current_dir().unwrap_or_default().join(expand_home(d)))
+            .filter(|d| pins.dirs.contains(d))
+            .ok_or(e)
+    })?;
+    if !pins.dirs.remove(&dir) {
+        return Err(format!("Not pinned: {}", dir.display()));
+    }
+    save(&pins)?;
+    Ok(format!("Unpinned {}", dir.display()))
+}
+
+fn save(pins: &Pins) -> Result<(), String> {
+    pins.save()
+        .map_err(|e| format!("Cannot write {}: {}", pins_file_path().display(), e))
+}
+
+/// `jcd pin list`: every pin, those of config.toml first, marking those whose
+/// directory is gone
+fn list() -> i32 {
+    let dirs = load_all(&Config::load().pins);
+    if dirs.is_empty() {
+        println!("No pinned directories");
+        return 0;
+    }
+    for dir in &dirs {
+        let missing = if Path::new(dir).is_dir() {
+            ""
+        } else {
+            "  (missing)"
+        };
+        println!("{}{}", dir.display(), missing);
+    }
+    0
+}
+
+pub fn run_command(args: &[String]) -> i32 {
+    let result = match (args.first().map(String::as_str), args.len()) {
+        (Some("list"), 1) => return list(),
+        (Some("add"), 1) => add(None),
+        (Some("add"), 2) => add(Some(&args[1])),
+        (Some("rm"), 1) => remove(None),
+        (Some("rm"), 2) => remove(Some(&args[1])),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(message) => {
+            println!("{}", message);
+            0
+        }
+        Err(e) if e == USAGE => {
+            eprintln!("{}", USAGE);
+            EXIT_USAGE
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
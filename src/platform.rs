@@ -0,0 +1,243 @@
+//! Platform differences: where configuration and data live, how search
+//! terms spell paths, and how names that are not valid UTF-8 survive the trip
+//! through matching. On Windows the XDG variables still win when set (as in
+//! Git Bash setups that share dotfiles), otherwise the usual `%APPDATA%`,
+//! `%LOCALAPPDATA%` and `%ProgramData%` folders are used.
+
+use std::{
+    borrow::Cow,
+    env,
+    ffi::OsStr,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// The user's home directory: `HOME`, or `USERPROFILE` on Windows
+pub fn home_dir() -> Option<PathBuf> {
+    if let Ok(home) = env::var("HOME") {
+        return Some(PathBuf::from(home));
+    }
+    if cfg!(windows) {
+        if let Ok(profile) = env::var("USERPROFILE") {
+            return Some(PathBuf::from(profile));
+        }
+    }
+    None
+}
+
+/// Home directory of the account `user`, from the password database
+#[cfg(unix)]
+pub fn user_home(user: &str) -> Option<PathBuf> {
+    let passwd = fs::read_to_string("/etc/passwd").ok()?;
+    passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        (fields.len() >= 6 && fields[0] == user).then(|| PathBuf::from(fields[5]))
+    })
+}
+
+/// Home directory of the account `user`: the profile folder next to our own
+#[cfg(not(unix))]
+pub fn user_home(user: &str) -> Option<PathBuf> {
+    let profile = home_dir()?.parent()?.join(user);
+    profile.is_dir().then_some(profile)
+}
+
+/// `$var` if set, else the Windows folder `$windows_var`, else `fallback` below the home directory
+fn base_dir(var: &str, windows_var: &str, fallback: &[&str]) -> PathBuf {
+    if let Ok(dir) = env::var(var) {
+        return PathBuf::from(dir);
+    }
+    if cfg!(windows) {
+        if let Ok(dir) = env::var(windows_var) {
+            return PathBuf::from(dir);
+        }
+    }
+    let mut dir = home_dir().unwrap_or_default();
+    dir.extend(fallback);
+    dir
+}
+
+/// Per-user configuration: `$XDG_CONFIG_HOME`, `%APPDATA%` or `~/.config`
+pub fn config_home() -> PathBuf {
+    base_dir("XDG_CONFIG_HOME", "APPDATA", &[".config"])
+}
+
+/// Per-user data: `$XDG_DATA_HOME`, `%LOCALAPPDATA%` or `~/.local/share`
+pub fn data_home() -> PathBuf {
+    base_dir("XDG_DATA_HOME", "LOCALAPPDATA", &[".local", "share"])
+}
+
+/// Per-user cache: `$XDG_CACHE_HOME`, `%LOCALAPPDATA%` or `~/.cache`
+pub fn cache_home() -> PathBuf {
+    base_dir("XDG_CACHE_HOME", "LOCALAPPDATA", &[".cache"])
+}
+
+/// System-wide configuration: `/etc/jcd`, or `%ProgramData%\jcd` on Windows
+pub fn system_config_dir() -> PathBuf {
+    if cfg!(windows) {
+        let program_data =
+            env::var("ProgramData").unwrap_or_else(|_| String::from(r"C:\ProgramData"));
+        PathBuf::from(program_data).join("jcd")
+    } else {
+        PathBuf::from("/etc/jcd")
+    }
+}
+
+/// Spell a search term with '/' separators only, the form the search code
+/// works with. On Windows `..\src` and `C:\Users` mean `../src` and `C:/Users`;
+/// elsewhere a backslash is an ordinary character.
+pub fn normalize_separators(term: &str) -> Cow<'_, str> {
+    if cfg!(windows) && term.contains('\\') {
+        Cow::Owned(term.replace('\\', "/"))
+    } else {
+        Cow::Borrowed(term)
+    }
+}
+
+/// Whether a (normalized) search term names a path from a root:
+/// `/usr`, and on Windows also `C:/Users` and `//server/share`
+pub fn is_absolute_term(term: &str) -> bool {
+    if term.starts_with('/') {
+        return true;
+    }
+    let bytes = term.as_bytes();
+    cfg!(windows) && bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && &bytes[1..3] == b":/"
+}
+
+/// Drop one trailing separator unless that would leave an incomplete root
+/// (`/` itself or `C:/`)
+pub fn strip_trailing_separator(term: &str) -> &str {
+    let incomplete_root = |rest: &str| rest.is_empty() || (cfg!(windows) && rest.ends_with(':'));
+    match term.strip_suffix('/') {
+        Some(rest) if !incomplete_root(rest) => rest,
+        _ => term,
+    }
+}
+
+/// Code point standing for raw byte 0x00; bytes 0x80-0xFF use U+10FF80-U+10FFFF,
+/// the end of a private use plane that real file names do not use
+#[cfg(unix)]
+const RAW_BYTE_BASE: u32 = 0x10FF00;
+
+/// An OS string as text, without losing anything. On Unix every byte that is
+/// not part of valid UTF-8 becomes a private use character, so a pattern typed
+/// with the same bytes matches the name exactly and `str_to_path` restores the
+/// original bytes. Valid UTF-8 is borrowed unchanged.
+#[cfg(unix)]
+pub fn os_to_str(s: &OsStr) -> Cow<'_, str> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let bytes = s.as_bytes();
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(bytes.len() + 8);
+    for chunk in bytes.utf8_chunks() {
+        out.push_str(chunk.valid());
+        for &byte in chunk.invalid() {
+            out.extend(char::from_u32(RAW_BYTE_BASE + u32::from(byte)));
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// An OS string as text; Windows names are UTF-16 and convert losslessly
+/// except for unpaired surrogates
+#[cfg(not(unix))]
+pub fn os_to_str(s: &OsStr) -> Cow<'_, str> {
+    s.to_string_lossy()
+}
+
+/// Bytes read from a pipe as text, see `os_to_str`
+#[cfg(unix)]
+pub fn bytes_to_str(bytes: &[u8]) -> Cow<'_, str> {
+    use std::os::unix::ffi::OsStrExt;
+
+    os_to_str(OsStr::from_bytes(bytes))
+}
+
+/// Bytes read from a pipe as text; Windows tools write UTF-8
+#[cfg(not(unix))]
+pub fn bytes_to_str(bytes: &[u8]) -> Cow<'_, str> {
+    String::from_utf8_lossy(bytes)
+}
+
+/// A path as text, see `os_to_str`
+pub fn path_to_str(path: &Path) -> Cow<'_, str> {
+    os_to_str(path.as_os_str())
+}
+
+/// The path spelled by `text`, turning the characters `os_to_str` made from raw
+/// bytes back into those bytes
+#[cfg(unix)]
+pub fn str_to_path(text: &str) -> PathBuf {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+    let raw_bytes = RAW_BYTE_BASE + 0x80..=RAW_BYTE_BASE + 0xFF;
+    if !text.chars().any(|c| raw_bytes.contains(&u32::from(c))) {
+        return PathBuf::from(text);
+    }
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        if raw_bytes.contains(&u32::from(c)) {
+            bytes.push((u32::from(c) - RAW_BYTE_BASE) as u8);
+        } else {
+            bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+    }
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+pub fn str_to_path(text: &str) -> PathBuf {
+    PathBuf::from(text)
+}
+
+/// Write `path` exactly as the OS spells it, so the shell can `cd` to names
+/// that are not valid UTF-8, followed by `terminator` (newline or NUL)
+pub fn write_path(out: &mut impl Write, path: &Path, terminator: u8) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        out.write_all(path.as_os_str().as_bytes())?;
+    }
+    #[cfg(not(unix))]
+    write!(out, "{}", path.display())?;
+    out.write_all(&[terminator])
+}
+
+/// Identifies a directory however it was reached: (device, inode) on Unix,
+/// the canonical path elsewhere
+#[cfg(unix)]
+pub type DirKey = (u64, u64);
+#[cfg(not(unix))]
+pub type DirKey = PathBuf;
+
+/// Identity of the directory `path` leads to, following symlinks
+#[cfg(unix)]
+pub fn dir_key(path: &Path) -> Option<DirKey> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+pub fn dir_key(path: &Path) -> Option<DirKey> {
+    fs::canonicalize(path).ok()
+}
+
+/// Device (`st_dev`) of the filesystem holding `path`, following symlinks.
+/// Not available on Windows, where --same-filesystem has no effect.
+#[cfg(unix)]
+pub fn device_of(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    fs::metadata(path).ok().map(|metadata| metadata.dev())
+}
+
+#[cfg(not(unix))]
+pub fn device_of(_path: &Path) -> Option<u64> {
+    None
+}
@@ -0,0 +1,38 @@
+//! Project-root detection. A directory holding a version control directory or
+//! a build manifest is where a project starts; a directory of the same name
+//! deep in build output or a vendored tree is incidental, so project roots
+//! rank first among matches of the same priority. Matches in the git
+//! repository the user is working in rank before both.
+
+use crate::filesystem::FileSystem;
+use std::path::Path;
+
+/// Entries whose presence marks a directory as the root of a project
+pub const PROJECT_MARKERS: &[&str] = &[
+    ".git",
+    ".hg",
+    ".svn",
+    "Cargo.toml",
+    "package.json",
+    "go.mod",
+    "pyproject.toml",
+    "setup.py",
+    "pom.xml",
+    "build.gradle",
+    "CMakeLists.txt",
+    "Makefile",
+];
+
+/// Whether `dir` contains one of the project markers
+pub fn is_project_root(fs: &dyn FileSystem, dir: &Path) -> bool {
+    PROJECT_MARKERS
+        .iter()
+        .any(|marker| fs.exists(&dir.join(marker)))
+}
+
+/// Root of the git repository holding `dir`: the nearest directory, `dir`
+/// included, with a `.git` entry (a directory, or a file in worktrees and
+/// submodules)
+pub fn repo_root<'a>(fs: &dyn FileSystem, dir: &'a Path) -> Option<&'a Path> {
+    dir.ancestors().find(|d| fs.exists(&d.join(".git")))
+}
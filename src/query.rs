@@ -0,0 +1,169 @@
+//! Query filters (`depth:`, `root:`, `type:`) pulled out of a search term,
+//! and `~` expansion.
+
+use crate::platform;
+use std::path::{Path, PathBuf};
+
+/// Inclusive bounds on how many levels away from the search directory a match may be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthRange {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl DepthRange {
+    pub fn contains(&self, depth: i32) -> bool {
+        let depth = depth.unsigned_abs() as usize;
+        depth >= self.min && depth <= self.max
+    }
+}
+
+/// Kind of directory required by a `type:` filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirType {
+    Git,
+    Mercurial,
+    Subversion,
+}
+
+impl DirType {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "git" => Some(DirType::Git),
+            "hg" => Some(DirType::Mercurial),
+            "svn" => Some(DirType::Subversion),
+            _ => None,
+        }
+    }
+
+    /// Check whether a directory is of this type by looking for its marker entry
+    pub fn matches(&self, dir: &Path) -> bool {
+        let marker = match self {
+            DirType::Git => ".git",
+            DirType::Mercurial => ".hg",
+            DirType::Subversion => ".svn",
+        };
+        dir.join(marker).exists()
+    }
+}
+
+/// Structured filters embedded in a query, e.g. `api depth:<=2 type:git`
+#[derive(Debug, Clone, Default)]
+pub struct QueryFilters {
+    pub depth: Option<DepthRange>,
+    pub root: Option<PathBuf>,
+    pub dir_type: Option<DirType>,
+}
+
+impl QueryFilters {
+    pub fn is_empty(&self) -> bool {
+        self.depth.is_none() && self.root.is_none() && self.dir_type.is_none()
+    }
+}
+
+/// The home directory named by a leading `~` or `~user` of a (normalized)
+/// path, and the rest of the path after it
+fn split_home(value: &str) -> Option<(PathBuf, &str)> {
+    let rest = value.strip_prefix('~')?;
+    let (user, tail) = rest.split_once('/').unwrap_or((rest, ""));
+    let home = if user.is_empty() {
+        platform::home_dir()
+    } else {
+        platform::user_home(user)
+    }?;
+    Some((home, tail.trim_start_matches('/')))
+}
+
+/// Expand a leading `~` or `~user` to that user's home directory
+pub fn expand_home(value: &str) -> PathBuf {
+    let value = platform::normalize_separators(value);
+    match split_home(&value) {
+        Some((home, tail)) => home.join(tail),
+        None => platform::str_to_path(&value),
+    }
+}
+
+/// A search term starting with `~` or `~user`, spelled as the absolute path it
+/// stands for. A trailing '/' is kept, since it asks for the subdirectories.
+/// None when the term names no known home directory.
+pub fn expand_home_term(term: &str) -> Option<String> {
+    let (home, tail) = split_home(term)?;
+    let mut expanded = platform::normalize_separators(&platform::path_to_str(&home)).into_owned();
+    if !tail.is_empty() || term.ends_with('/') {
+        if !expanded.ends_with('/') {
+            expanded.push('/');
+        }
+        expanded.push_str(tail);
+    }
+    Some(expanded)
+}
+
+/// Parse a depth constraint such as `<=3`, `<3`, `>=2`, `>2` or `2`
+fn parse_depth(value: &str) -> Result<DepthRange, String> {
+    let (op, number) = if let Some(rest) = value.strip_prefix("<=") {
+        ("<=", rest)
+    } else if let Some(rest) = value.strip_prefix(">=") {
+        (">=", rest)
+    } else if let Some(rest) = value.strip_prefix('<') {
+        ("<", rest)
+    } else if let Some(rest) = value.strip_prefix('>') {
+        (">", rest)
+    } else if let Some(rest) = value.strip_prefix('=') {
+        ("=", rest)
+    } else {
+        ("=", value)
+    };
+
+    let n = number
+        .parse::<usize>()
+        .map_err(|_| format!("Invalid depth filter 'depth:{}'", value))?;
+
+    let range = match op {
+        "<=" => DepthRange { min: 0, max: n },
+        "<" if n > 0 => DepthRange { min: 0, max: n - 1 },
+        ">=" => DepthRange {
+            min: n,
+            max: usize::MAX,
+        },
+        ">" => DepthRange {
+            min: n + 1,
+            max: usize::MAX,
+        },
+        "=" => DepthRange { min: n, max: n },
+        _ => return Err(format!("Depth filter 'depth:{}' can never match", value)),
+    };
+    Ok(range)
+}
+
+/// Split a query into its name pattern and structured filters.
+/// Tokens with an unknown `key:` prefix stay part of the pattern, so directory
+/// names containing a colon keep working.
+pub fn parse_query(query: &str) -> Result<(String, QueryFilters), String> {
+    let mut filters = QueryFilters::default();
+    let mut pattern_tokens = Vec::new();
+
+    for token in query.split_whitespace() {
+        if let Some(value) = token.strip_prefix("depth:") {
+            filters.depth = Some(parse_depth(value)?);
+        } else if let Some(value) = token.strip_prefix("root:") {
+            if value.is_empty() {
+                return Err("Empty root filter 'root:'".to_string());
+            }
+            filters.root = Some(expand_home(value));
+        } else if let Some(value) = token.strip_prefix("type:") {
+            filters.dir_type = Some(
+                DirType::parse(value)
+                    .ok_or_else(|| format!("Unknown type filter 'type:{}'", value))?,
+            );
+        } else {
+            pattern_tokens.push(token);
+        }
+    }
+
+    // Leave the query untouched when it holds no filters at all
+    if filters.is_empty() {
+        return Ok((query.to_string(), filters));
+    }
+
+    Ok((pattern_tokens.join(" "), filters))
+}
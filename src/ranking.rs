@@ -0,0 +1,846 @@
+//! Ranking matches by a weighted score of their tier and usage (frecency and
+//! recency from the history), adjusted by `ranking.weights` and `--rank`; the
+//! boosts of `ranking.boost`; the order of matches that rank the same; and how
+//! many matches of each quality are kept.
+
+use crate::{
+    config::Config,
+    directory_last_access, finalized_order,
+    history::{self, History, PathPattern},
+    load_mount_table, match_priority, DirectoryMatch, MatchQuality, MountEntry, RECENCY_WEIGHT,
+};
+use std::{cmp::Ordering, ffi::OsStr, path::Path, time::Duration};
+use tracing::{debug, debug_span};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+const FRECENCY_SCALE: f64 = 10.0; // Frecency score at which the signal reaches 0.5
+pub const FRECENCY_WEIGHT: f64 = 50.0; // Default frecency weight, below one priority category
+
+/// Coefficients of the weighted ranking score that orders every search. The
+/// defaults are the priority table: the quality and direction weights put each tier at
+/// least 100 points above the next, more than frecency and recency can add
+/// together. The repository, project and depth weights are 0, since ranking
+/// is stable and the table already orders the matches of a tier by them.
+/// With `shortest_first` the direction weights are 0 too, so that the match
+/// kind alone sets the score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankWeights {
+    pub exact: f64,
+    pub prefix: f64,
+    pub word: f64,
+    pub acronym: f64,
+    pub partial: f64,
+    pub up: f64,
+    pub immediate: f64,
+    pub sibling: f64,
+    pub cousin: f64,
+    pub depth: f64,
+    pub project: f64,
+    pub repo: f64,
+    pub recency: f64,
+    pub frecency: f64,
+}
+
+impl Default for RankWeights {
+    fn default() -> Self {
+        Self {
+            exact: 700.0,
+            prefix: 600.0,
+            word: 500.0,
+            acronym: 400.0,
+            partial: 300.0,
+            up: 700.0,
+            immediate: 1200.0,
+            sibling: 600.0,
+            cousin: 500.0,
+            depth: 0.0,
+            project: 0.0,
+            repo: 0.0,
+            recency: 0.0,
+            frecency: FRECENCY_WEIGHT,
+        }
+    }
+}
+
+/// Names accepted by `--rank`, in the order they are documented
+pub const WEIGHT_NAMES: &[&str] = &[
+    "exact",
+    "prefix",
+    "word",
+    "acronym",
+    "partial",
+    "up",
+    "immediate",
+    "sibling",
+    "cousin",
+    "depth",
+    "project",
+    "repo",
+    "recency",
+    "frecency",
+];
+
+impl RankWeights {
+    fn weight_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "exact" => Some(&mut self.exact),
+            "prefix" => Some(&mut self.prefix),
+            "word" => Some(&mut self.word),
+            "acronym" => Some(&mut self.acronym),
+            "partial" => Some(&mut self.partial),
+            "up" => Some(&mut self.up),
+            "immediate" => Some(&mut self.immediate),
+            "sibling" => Some(&mut self.sibling),
+            "cousin" => Some(&mut self.cousin),
+            "depth" => Some(&mut self.depth),
+            "project" => Some(&mut self.project),
+            "repo" => Some(&mut self.repo),
+            "recency" => Some(&mut self.recency),
+            "frecency" => Some(&mut self.frecency),
+            _ => None,
+        }
+    }
+
+    /// Apply a comma-separated override list such as `exact=10,depth=-2`
+    pub fn apply_overrides(&mut self, spec: &str) -> Result<(), String> {
+        for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (name, value) = item
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid ranking weight '{}', expected name=value", item))?;
+            let value: f64 = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid value for ranking weight '{}'", name))?;
+            let name = name.trim();
+            let slot = self.weight_mut(name).ok_or_else(|| {
+                format!(
+                    "Unknown ranking weight '{}' (expected one of: {})",
+                    name,
+                    WEIGHT_NAMES.join(", ")
+                )
+            })?;
+            *slot = value;
+        }
+        Ok(())
+    }
+
+    /// The weights of a search: the defaults with `ranking.frecency` and, with
+    /// `--recent`, the recency weight, overridden by `ranking.weights` and then
+    /// by `spec` (from `--rank`)
+    pub fn for_search(
+        settings: &Config,
+        with_recency: bool,
+        shortest_first: bool,
+        spec: &str,
+    ) -> Result<Self, String> {
+        let mut weights = Self {
+            frecency: settings.frecency_weight,
+            ..Default::default()
+        };
+        if with_recency {
+            weights.recency = RECENCY_WEIGHT;
+        }
+        if shortest_first {
+            weights.up = 0.0;
+            weights.immediate = 0.0;
+            weights.sibling = 0.0;
+            weights.cousin = 0.0;
+        }
+        weights.apply_overrides(&settings.rank_weights)?;
+        weights.apply_overrides(spec)?;
+        Ok(weights)
+    }
+
+    /// The weights of a search without `--rank`
+    pub fn configured(settings: &Config, with_recency: bool, shortest_first: bool) -> Self {
+        // The setting was checked when the config was loaded
+        Self::for_search(settings, with_recency, shortest_first, "").unwrap_or_default()
+    }
+}
+
+/// Most matches each quality may keep among the results, so that a folder of
+/// similarly named directories cannot fill the whole match budget with, say,
+/// partial matches. Written as `partial_down=5,typo_down=2`; qualities that
+/// are not listed are not capped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TierLimits(Vec<(MatchQuality, usize)>);
+
+impl TierLimits {
+    /// Parse a comma-separated list of `quality=count` pairs
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut limits: Vec<(MatchQuality, usize)> = Vec::new();
+        for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (name, value) = item
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid tier limit '{}', expected quality=count", item))?;
+            let name = name.trim();
+            let quality = MatchQuality::from_name(name)
+                .ok_or_else(|| format!("Unknown match quality '{}' in tier limits", name))?;
+            let count = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid count for tier limit '{}'", name))?;
+            // A later entry for the same quality replaces the earlier one
+            limits.retain(|(q, _)| *q != quality);
+            limits.push((quality, count));
+        }
+        Ok(Self(limits))
+    }
+
+    /// The list as `parse` reads it
+    pub fn spec(&self) -> String {
+        self.0
+            .iter()
+            .map(|(quality, count)| format!("{}={}", quality.name(), count))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Whether no quality is capped
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Drop the matches of each capped quality beyond its limit, keeping the
+    /// best ranked ones; the others move up in the list
+    pub fn apply(&self, matches: &mut Vec<DirectoryMatch>) {
+        if self.is_empty() {
+            return;
+        }
+        let mut count = TierCount::new(self);
+        let before = matches.len();
+        matches.retain(|m| count.add(&m.match_quality));
+        debug!(
+            "Tier limits dropped {} of {} matches",
+            before - matches.len(),
+            before
+        );
+    }
+}
+
+/// Matches counted as a walk finds them, leaving out those beyond the limit
+/// of their quality, so that a walk stopping at `search.max_matches` has that
+/// many matches left once the limits are applied
+#[derive(Debug, Clone, Default)]
+pub struct TierCount {
+    limits: TierLimits,
+    found: Vec<usize>, // Matches of each capped quality, in the order of `limits`
+    kept: usize,
+}
+
+impl TierCount {
+    pub fn new(limits: &TierLimits) -> Self {
+        Self {
+            limits: limits.clone(),
+            found: vec![0; limits.0.len()],
+            kept: 0,
+        }
+    }
+
+    /// Count a match of `quality`; false if its tier limit leaves it out
+    pub fn add(&mut self, quality: &MatchQuality) -> bool {
+        if let Some(i) = self.limits.0.iter().position(|(q, _)| q == quality) {
+            self.found[i] += 1;
+            if self.found[i] > self.limits.0[i].1 {
+                return false;
+            }
+        }
+        self.kept += 1;
+        true
+    }
+
+    /// How many matches counted so far the limits keep
+    pub fn kept(&self) -> usize {
+        self.kept
+    }
+}
+
+/// A `ranking.boost` entry: a factor for the score of every match in the
+/// directories a pattern names, matched like `history.exclude`. `~/work=2`
+/// raises the matches below `~/work`, `**/vendor=0.5` lowers vendored copies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathBoost {
+    pattern: PathPattern,
+    factor: f64,
+}
+
+impl PathBoost {
+    /// Parse `pattern=factor`; the factor follows the last `=`
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (pattern, factor) = spec
+            .rsplit_once('=')
+            .ok_or_else(|| format!("Invalid boost '{}', expected pattern=factor", spec))?;
+        let factor = factor
+            .trim()
+            .parse()
+            .ok()
+            .filter(|f: &f64| f.is_finite() && *f > 0.0)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid factor for boost '{}', expected a number above 0",
+                    pattern
+                )
+            })?;
+        Ok(Self {
+            pattern: PathPattern::new(pattern.trim())?,
+            factor,
+        })
+    }
+
+    /// The entry as `parse` reads it
+    pub fn spec(&self) -> String {
+        format!("{}={}", self.pattern.pattern(), self.factor)
+    }
+}
+
+/// The factor `ranking.boost` scales the score of `m` by: the product of those
+/// of every entry matching its path. Bookmarks, pins and typos keep their
+/// places above and below every other match.
+pub fn boost_factor(m: &DirectoryMatch, boosts: &[PathBoost]) -> f64 {
+    match m.match_quality {
+        MatchQuality::Bookmark
+        | MatchQuality::Pinned
+        | MatchQuality::TypoUp
+        | MatchQuality::TypoDown => 1.0,
+        _ => boosts
+            .iter()
+            .filter(|boost| boost.pattern.matches(&m.path))
+            .map(|boost| boost.factor)
+            .product(),
+    }
+}
+
+/// A weighted score scaled by a boost factor: a factor above 1 always raises
+/// it, one below 1 lowers it, whatever the sign of the score
+fn boost_score(score: f64, factor: f64) -> f64 {
+    if score >= 0.0 {
+        score * factor
+    } else {
+        score / factor
+    }
+}
+
+/// Compare names naturally: runs of digits by their value, so `v2` comes
+/// before `v10` and `release-9` before `release-10`. Names that differ only
+/// in leading zeros are ordered byte by byte, so only equal names are equal.
+pub fn natural_cmp(a: &[u8], b: &[u8]) -> Ordering {
+    let digits_end = |s: &[u8], start: usize| {
+        start + s[start..].iter().take_while(|c| c.is_ascii_digit()).count()
+    };
+    fn without_zeros(s: &[u8]) -> &[u8] {
+        let zeros = s.iter().take_while(|&&c| c == b'0').count();
+        &s[zeros..]
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i].is_ascii_digit() && b[j].is_ascii_digit() {
+            let (a_end, b_end) = (digits_end(a, i), digits_end(b, j));
+            let (x, y) = (without_zeros(&a[i..a_end]), without_zeros(&b[j..b_end]));
+            // Without leading zeros, the longer number is the larger one
+            let order = x.len().cmp(&y.len()).then_with(|| x.cmp(y));
+            if order != Ordering::Equal {
+                return order;
+            }
+            (i, j) = (a_end, b_end);
+        } else if a[i] != b[j] {
+            return a[i].cmp(&b[j]);
+        } else {
+            i += 1;
+            j += 1;
+        }
+    }
+    (a.len() - i).cmp(&(b.len() - j)).then_with(|| a.cmp(b))
+}
+
+/// `natural_cmp` for file names
+pub fn natural_name_cmp(a: &OsStr, b: &OsStr) -> Ordering {
+    natural_cmp(a.as_encoded_bytes(), b.as_encoded_bytes())
+}
+
+/// `natural_cmp` for paths, one component at a time
+pub fn natural_path_cmp(a: &Path, b: &Path) -> Ordering {
+    let (mut a, mut b) = (a.components(), b.components());
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => match natural_name_cmp(x.as_os_str(), y.as_os_str()) {
+                Ordering::Equal => {}
+                order => return order,
+            },
+        }
+    }
+}
+
+/// How matches tied in the finalized order are ordered (`ranking.tie_break`).
+/// Whatever it leaves equal is settled by path (numbers in names by value, see
+/// `natural_cmp`), so the same tree and history
+/// always give the same order and a tab index always names the same directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TieBreak {
+    #[default]
+    Alphabetical, // By path, numbers by value
+    Length,     // Shorter paths first
+    Components, // Fewer path components first
+    Recency,    // Most lately visited first, by the history; unvisited last
+}
+
+impl TieBreak {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "alphabetical" => Some(TieBreak::Alphabetical),
+            "length" => Some(TieBreak::Length),
+            "components" => Some(TieBreak::Components),
+            "recency" => Some(TieBreak::Recency),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TieBreak::Alphabetical => "alphabetical",
+            TieBreak::Length => "length",
+            TieBreak::Components => "components",
+            TieBreak::Recency => "recency",
+        }
+    }
+
+    /// Order two tied matches
+    fn compare(self, a: &DirectoryMatch, b: &DirectoryMatch, history: &History) -> Ordering {
+        let last_visit = |m: &DirectoryMatch| history.get(&m.path).map(|entry| entry.last_visit);
+        match self {
+            TieBreak::Alphabetical => Ordering::Equal,
+            TieBreak::Length => a.path.as_os_str().len().cmp(&b.path.as_os_str().len()),
+            TieBreak::Components => a
+                .path
+                .components()
+                .count()
+                .cmp(&b.path.components().count()),
+            TieBreak::Recency => last_visit(b).cmp(&last_visit(a)),
+        }
+        .then_with(|| natural_path_cmp(&a.path, &b.path))
+    }
+
+    /// Why `m` follows `above` when the two are tied
+    fn reason(self) -> &'static str {
+        match self {
+            TieBreak::Alphabetical => "tie, kept in path order",
+            TieBreak::Length => "tie, longer path",
+            TieBreak::Components => "tie, more path components",
+            TieBreak::Recency => "tie, visited less lately",
+        }
+    }
+}
+
+/// Match kinds of the shortest-first order (`ranking.shortest_first`), best first
+const KIND_NAMES: &[&str] = &[
+    "bookmark or pin",
+    "exact",
+    "prefix",
+    "word",
+    "acronym",
+    "partial",
+    "typo",
+];
+
+/// The kind of a match, whichever way it was found: an index into `KIND_NAMES`
+fn match_kind(m: &DirectoryMatch) -> u32 {
+    match m.match_quality {
+        MatchQuality::Bookmark | MatchQuality::Pinned => 0,
+        MatchQuality::ExactUp
+        | MatchQuality::ExactSibling
+        | MatchQuality::ExactCousin
+        | MatchQuality::ExactDown => 1,
+        MatchQuality::PrefixDown => 2,
+        MatchQuality::WordDown => 3,
+        MatchQuality::AcronymDown => 4,
+        MatchQuality::PartialUp
+        | MatchQuality::PartialSibling
+        | MatchQuality::PartialCousin
+        | MatchQuality::PartialDown => 5,
+        MatchQuality::TypoUp | MatchQuality::TypoDown => 6,
+    }
+}
+
+fn path_components(m: &DirectoryMatch) -> usize {
+    m.path.components().count()
+}
+
+/// The category a match is ranked in before usage: its priority tier, or
+/// with `shortest_first` only its kind, so that direction does not count
+fn category(m: &DirectoryMatch, shortest_first: bool) -> u32 {
+    if shortest_first {
+        match_kind(m)
+    } else {
+        match_priority(m)
+    }
+}
+
+/// The order of matches with `shortest_first`: search root, match kind, then
+/// fewer path components, up or down the tree, before the finalized order.
+/// Of many `api` directories in a monorepo, the one nearest the top comes first.
+fn shortest_order(a: &DirectoryMatch, b: &DirectoryMatch) -> Ordering {
+    a.root
+        .cmp(&b.root)
+        .then_with(|| match_kind(a).cmp(&match_kind(b)))
+        .then_with(|| path_components(a).cmp(&path_components(b)))
+        .then_with(|| finalized_order(a, b))
+}
+
+/// Order the matches that the finalized order (or with `shortest_first`, the
+/// shortest-first order) leaves tied by `tie_break`. Rankings that follow are
+/// stable, so equal scores keep this order.
+pub fn break_ties(
+    matches: &mut [DirectoryMatch],
+    tie_break: TieBreak,
+    shortest_first: bool,
+    history: &History,
+) {
+    let order = if shortest_first {
+        shortest_order
+    } else {
+        finalized_order
+    };
+    matches.sort_by(|a, b| order(a, b).then_with(|| tie_break.compare(a, b, history)));
+}
+
+/// Usage signals of a match, each normalized to [0, 1]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UsageSignals {
+    pub recency: f64,
+    pub frecency: f64,
+}
+
+/// Recency signal: 1 for a directory used just now, halving after a day
+fn recency_feature(m: &DirectoryMatch, mounts: &[MountEntry], history: &History) -> f64 {
+    directory_last_access(m, mounts, history)
+        .and_then(|t| t.elapsed().ok())
+        .map_or(0.0, |age: Duration| {
+            1.0 / (1.0 + age.as_secs_f64() / SECONDS_PER_DAY)
+        })
+}
+
+/// Frecency signal: grows with the history score and saturates towards 1
+fn frecency_feature(m: &DirectoryMatch, history: &History, now: u64) -> f64 {
+    let score = history.frecency(&m.path, now);
+    score / (score + FRECENCY_SCALE)
+}
+
+fn usage_signals(
+    m: &DirectoryMatch,
+    with_recency: bool,
+    mounts: &[MountEntry],
+    history: &History,
+    now: u64,
+) -> UsageSignals {
+    UsageSignals {
+        recency: if with_recency {
+            recency_feature(m, mounts, history)
+        } else {
+            0.0
+        },
+        frecency: frecency_feature(m, history, now),
+    }
+}
+
+/// The `--rank` weight that scores a match quality
+fn quality_weight_name(quality: &MatchQuality) -> &'static str {
+    match quality {
+        MatchQuality::Bookmark => "bookmark",
+        MatchQuality::Pinned => "pinned",
+        MatchQuality::ExactUp
+        | MatchQuality::ExactSibling
+        | MatchQuality::ExactCousin
+        | MatchQuality::ExactDown => "exact",
+        MatchQuality::PrefixDown => "prefix",
+        MatchQuality::WordDown => "word",
+        MatchQuality::AcronymDown => "acronym",
+        MatchQuality::PartialUp
+        | MatchQuality::PartialSibling
+        | MatchQuality::PartialCousin
+        | MatchQuality::PartialDown => "partial",
+        MatchQuality::TypoUp | MatchQuality::TypoDown => "typo",
+    }
+}
+
+/// The terms of a match's weighted score, named after their weights
+fn score_components(
+    m: &DirectoryMatch,
+    weights: &RankWeights,
+    signals: UsageSignals,
+) -> [(&'static str, f64); 7] {
+    let quality = match m.match_quality {
+        MatchQuality::Bookmark | MatchQuality::Pinned => f64::INFINITY,
+        MatchQuality::ExactUp
+        | MatchQuality::ExactSibling
+        | MatchQuality::ExactCousin
+        | MatchQuality::ExactDown => weights.exact,
+        MatchQuality::PrefixDown => weights.prefix,
+        MatchQuality::WordDown => weights.word,
+        MatchQuality::AcronymDown => weights.acronym,
+        MatchQuality::PartialUp
+        | MatchQuality::PartialSibling
+        | MatchQuality::PartialCousin
+        | MatchQuality::PartialDown => weights.partial,
+        MatchQuality::TypoUp | MatchQuality::TypoDown => f64::NEG_INFINITY,
+    };
+    let direction = match m.match_quality {
+        MatchQuality::ExactUp | MatchQuality::PartialUp | MatchQuality::TypoUp => {
+            ("up", weights.up)
+        }
+        // Next to the current directory or below an ancestor: neither up nor below it
+        MatchQuality::ExactSibling | MatchQuality::PartialSibling => ("sibling", weights.sibling),
+        MatchQuality::ExactCousin | MatchQuality::PartialCousin => ("cousin", weights.cousin),
+        _ if m.depth_from_current == 1 => ("immediate", weights.immediate),
+        _ => ("immediate", 0.0),
+    };
+    let distance = f64::from(m.depth_from_current.unsigned_abs());
+    [
+        (quality_weight_name(&m.match_quality), quality),
+        direction,
+        ("depth", weights.depth * distance),
+        (
+            "project",
+            if m.project_root { weights.project } else { 0.0 },
+        ),
+        ("repo", if m.same_repo { weights.repo } else { 0.0 }),
+        ("recency", weights.recency * signals.recency),
+        ("frecency", weights.frecency * signals.frecency),
+    ]
+}
+
+/// Weighted score of a single match; higher ranks first
+pub fn score_match(m: &DirectoryMatch, weights: &RankWeights, signals: UsageSignals) -> f64 {
+    // Bookmarks and pins rank above anything the weights can produce, typos below it
+    match m.match_quality {
+        MatchQuality::Bookmark | MatchQuality::Pinned => return f64::INFINITY,
+        MatchQuality::TypoUp | MatchQuality::TypoDown => return f64::NEG_INFINITY,
+        _ => {}
+    }
+    score_components(m, weights, signals)
+        .iter()
+        .map(|(_, value)| value)
+        .sum()
+}
+
+/// Replace the contents of `matches` with the scored list, best score first.
+/// Matches below an extra root stay behind those of earlier roots whatever
+/// their score. The sort is stable, so equal scores keep their finalized order.
+fn apply_scores(matches: &mut [DirectoryMatch], mut scored: Vec<(f64, DirectoryMatch)>) {
+    scored.sort_by(|(a, a_match), (b, b_match)| {
+        a_match
+            .root
+            .cmp(&b_match.root)
+            .then_with(|| b.partial_cmp(a).unwrap_or(Ordering::Equal))
+    });
+    for (slot, (_, m)) in matches.iter_mut().zip(scored) {
+        *slot = m;
+    }
+}
+
+/// Order matches by weighted score, scaled by `boosts`. With the default
+/// weights this is the order of the priority table, with usage reordering the
+/// matches of a tier. Equal scores keep the order of `break_ties`.
+pub fn rank_by_weights(
+    matches: &mut [DirectoryMatch],
+    weights: &RankWeights,
+    boosts: &[PathBoost],
+    history: &History,
+) {
+    let _span = debug_span!("rank").entered();
+    let with_recency = weights.recency != 0.0;
+    let mounts = if with_recency {
+        load_mount_table()
+    } else {
+        Vec::new()
+    };
+    let now = history::now_secs();
+
+    let scored: Vec<(f64, DirectoryMatch)> = matches
+        .iter()
+        .map(|m| {
+            let signals = usage_signals(m, with_recency, &mounts, history, now);
+            let score = boost_score(score_match(m, weights, signals), boost_factor(m, boosts));
+            (score, m.clone())
+        })
+        .collect();
+
+    debug!("Weighted ranking {:?}:", weights);
+    for (score, m) in &scored {
+        debug!(score = format_args!("{:.2}", score), path = %m.path.display());
+    }
+
+    apply_scores(matches, scored);
+}
+
+/// Names of the priority tiers of `match_priority`, best first
+const TIER_NAMES: &[&str] = &[
+    "bookmark or pin",
+    "immediate exact",
+    "immediate prefix",
+    "immediate word",
+    "immediate acronym",
+    "immediate partial",
+    "exact up",
+    "exact sibling",
+    "exact cousin",
+    "partial up",
+    "partial sibling",
+    "partial cousin",
+    "exact down",
+    "prefix down",
+    "word down",
+    "acronym down",
+    "partial down",
+    "typo up",
+    "typo down",
+];
+
+/// How one match came by its place in the ranking, for `--explain`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub tier: u32,      // Priority category, best first
+    pub frecency: f64,  // Points from visits in the history
+    pub recency: f64,   // Points from the last use
+    pub boost: f64,     // Factor of ranking.boost the score was scaled by
+    pub score: f64,     // Ranking score, higher first
+    pub reason: String, // Why the match comes where it does
+}
+
+impl Explanation {
+    pub fn tier_name(&self) -> &'static str {
+        TIER_NAMES[self.tier as usize]
+    }
+}
+
+/// Why `m` comes after `above` in the finalized order, which the usage ranking
+/// keeps for equal scores: search root, tier, current repository, project
+/// root, distance, then the tie-breaker. With `shortest_first` the match kind
+/// and path components come before the tier.
+fn finalized_reason(
+    m: &DirectoryMatch,
+    above: &DirectoryMatch,
+    tie_break: TieBreak,
+    shortest_first: bool,
+) -> String {
+    let (tier, above_tier) = (match_priority(m), match_priority(above));
+    let (kind, above_kind) = (match_kind(m), match_kind(above));
+    let (components, above_components) = (path_components(m), path_components(above));
+    if m.root != above.root {
+        format!(
+            "found below a later search root ({} after {})",
+            m.root, above.root
+        )
+    } else if shortest_first && kind != above_kind {
+        format!(
+            "lower match kind: {} after {}",
+            KIND_NAMES[kind as usize], KIND_NAMES[above_kind as usize]
+        )
+    } else if shortest_first && components != above_components {
+        format!(
+            "more path components ({} > {})",
+            components, above_components
+        )
+    } else if tier != above_tier {
+        format!(
+            "lower tier: {} after {}",
+            TIER_NAMES[tier as usize], TIER_NAMES[above_tier as usize]
+        )
+    } else if m.same_repo != above.same_repo {
+        "outside the current repository".to_string()
+    } else if m.project_root != above.project_root {
+        "not a project root".to_string()
+    } else if m.depth_from_current != above.depth_from_current {
+        if m.depth_from_current < 0 {
+            "farther up".to_string()
+        } else {
+            "deeper".to_string()
+        }
+    } else {
+        tie_break.reason().to_string()
+    }
+}
+
+/// Explain the ranked `matches` of a search: how each scored by `weights`,
+/// scaled by `boosts`, and why it comes after the match above it
+pub fn explain(
+    matches: &[DirectoryMatch],
+    weights: &RankWeights,
+    tie_break: TieBreak,
+    shortest_first: bool,
+    boosts: &[PathBoost],
+    history: &History,
+) -> Vec<Explanation> {
+    let with_recency = weights.recency != 0.0;
+    let mounts = if with_recency {
+        load_mount_table()
+    } else {
+        Vec::new()
+    };
+    let now = history::now_secs();
+
+    let mut explained: Vec<Explanation> = Vec::with_capacity(matches.len());
+    let mut components = Vec::with_capacity(matches.len());
+    for (i, m) in matches.iter().enumerate() {
+        let signals = usage_signals(m, with_recency, &mounts, history, now);
+        let boost = boost_factor(m, boosts);
+        let frecency = weights.frecency * signals.frecency;
+        let recency = weights.recency * signals.recency;
+        let score = boost_score(score_match(m, weights, signals), boost);
+        components.push(score_components(m, weights, signals));
+
+        let reason = match i.checked_sub(1).map(|above| (above, &explained[above])) {
+            None => "best match".to_string(),
+            Some((above, _)) if m.root != matches[above].root => {
+                finalized_reason(m, &matches[above], tie_break, shortest_first)
+            }
+            Some((above, above_explained)) if score < above_explained.score => {
+                // Name the component that lost the most ground
+                let (name, lost) = components[i]
+                    .iter()
+                    .zip(&components[above])
+                    .map(|(&(name, value), &(_, above_value))| (name, value - above_value))
+                    .fold(
+                        ("", 0.0),
+                        |worst, part| if part.1 < worst.1 { part } else { worst },
+                    );
+                let above_category = category(&matches[above], shortest_first);
+                if boost < above_explained.boost {
+                    format!(
+                        "lower score ({:.1} < {:.1}), boosted less (x{} < x{})",
+                        score, above_explained.score, boost, above_explained.boost
+                    )
+                } else if category(m, shortest_first) > above_category {
+                    finalized_reason(m, &matches[above], tie_break, shortest_first)
+                } else if name == "frecency" || name == "recency" {
+                    format!(
+                        "{} fewer usage points ({:.1} < {:.1})",
+                        if category(m, shortest_first) == above_category {
+                            "same tier,"
+                        } else {
+                            "better tier, but"
+                        },
+                        frecency + recency,
+                        above_explained.frecency + above_explained.recency
+                    )
+                } else {
+                    format!(
+                        "lower score ({:.1} < {:.1}), mostly the {} weight ({:+.1})",
+                        score, above_explained.score, name, lost
+                    )
+                }
+            }
+            Some((above, _)) => finalized_reason(m, &matches[above], tie_break, shortest_first),
+        };
+        explained.push(Explanation {
+            tier: match_priority(m),
+            frecency,
+            recency,
+            boost,
+            score,
+            reason,
+        });
+    }
+    explained
+}
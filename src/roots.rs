@@ -0,0 +1,50 @@
+//! Extra search roots. The roots configured with `search.roots` (or
+//! `JCD_ROOTS`) are searched downward after every local search; the
+//! directories listed in `$CDPATH` only when nothing has matched at all. Each
+//! root is searched in the order given, and its matches rank after those of
+//! every earlier root.
+
+use crate::platform;
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+/// Add `root` unless it is missing, the current directory (already searched)
+/// or listed before
+fn push_root(roots: &mut Vec<PathBuf>, root: PathBuf, current_dir: &Path) {
+    if !root.is_dir() {
+        debug!("Skipping missing search root {}", root.display());
+        return;
+    }
+    if root != current_dir && !roots.contains(&root) {
+        roots.push(root);
+    }
+}
+
+/// The existing configured roots in order, relative ones resolved against `current_dir`
+pub fn configured_roots(configured: &[PathBuf], current_dir: &Path) -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    for root in configured {
+        push_root(&mut roots, current_dir.join(root), current_dir);
+    }
+    roots
+}
+
+/// Existing `$CDPATH` directories in order, relative entries resolved against
+/// `current_dir`. Empty entries mean "." to the shell and are skipped.
+pub fn cdpath_roots(current_dir: &Path) -> Vec<PathBuf> {
+    let Some(cdpath) = env::var_os("CDPATH") else {
+        return Vec::new();
+    };
+    let mut roots = Vec::new();
+    for entry in env::split_paths(&cdpath) {
+        if entry.as_os_str().is_empty() {
+            continue;
+        }
+        let entry = crate::query::expand_home(&platform::path_to_str(&entry));
+        push_root(&mut roots, current_dir.join(entry), current_dir);
+    }
+    roots
+}
@@ -0,0 +1,167 @@
+//! Which match the user went to for a pattern when it was not the first one,
+//! e.g. by pressing Tab a few times before Enter. The next search for the same
+//! pattern from the same area puts that directory first, so a choice made
+//! once becomes a direct jump.
+
+use crate::{filesystem::RealFileSystem, history, platform, project};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+const SELECTIONS_HEADER: &str = "# jcd selections v1";
+const MAX_SELECTIONS: usize = 1000; // The most recent choices kept
+
+/// Location of the selections file, following the XDG Base Directory Specification
+pub fn selections_file_path() -> PathBuf {
+    platform::data_home().join("jcd").join("selections.db")
+}
+
+/// Where a search from `dir` is: its git repository, so that a choice holds
+/// anywhere in the same checkout, or else the directory itself
+pub fn area(dir: &Path) -> PathBuf {
+    project::repo_root(&RealFileSystem, dir)
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+/// The directory chosen for a pattern in an area, and when
+#[derive(Debug, Clone, PartialEq)]
+struct Choice {
+    dir: PathBuf,
+    time: u64, // seconds since the Unix epoch
+}
+
+/// Choices by area and pattern, persisted between runs
+#[derive(Debug, Default)]
+pub struct Selections {
+    entries: HashMap<(PathBuf, String), Choice>,
+}
+
+impl Selections {
+    /// One `time<TAB>area<TAB>pattern<TAB>directory` line of the file
+    fn parse_line(line: &str) -> Option<((PathBuf, String), Choice)> {
+        let mut fields = line.splitn(4, '\t');
+        let (Some(time), Some(area), Some(pattern), Some(dir)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return None;
+        };
+        let choice = Choice {
+            dir: platform::str_to_path(dir),
+            time: time.parse().ok()?,
+        };
+        Some(((platform::str_to_path(area), pattern.to_string()), choice))
+    }
+
+    /// Parse the file content; malformed lines are skipped
+    pub fn parse(content: &str) -> Self {
+        let entries = content
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(Self::parse_line)
+            .collect();
+        Self { entries }
+    }
+
+    /// Load the selections, returning none if the file does not exist
+    pub fn load() -> Self {
+        let path = selections_file_path();
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                let selections = Self::parse(&content);
+                debug!(
+                    "Loaded {} selections from {}",
+                    selections.entries.len(),
+                    path.display()
+                );
+                selections
+            }
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Load the selections when `learn` is on; otherwise there are none
+    pub fn load_if(learn: bool) -> Self {
+        if learn {
+            Self::load()
+        } else {
+            Self::default()
+        }
+    }
+
+    /// Write the file atomically by replacing it with a fully written temp
+    /// file, keeping the most recent choices
+    pub fn save(&self) -> io::Result<()> {
+        let path = selections_file_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries
+            .sort_by(|(a_key, a), (b_key, b)| b.time.cmp(&a.time).then_with(|| a_key.cmp(b_key)));
+        entries.truncate(MAX_SELECTIONS);
+
+        let mut content = String::from(SELECTIONS_HEADER);
+        content.push('\n');
+        for ((area, pattern), choice) in entries {
+            content.push_str(&format!(
+                "{}\t{}\t{}\t{}\n",
+                choice.time,
+                platform::path_to_str(area),
+                pattern,
+                platform::path_to_str(&choice.dir)
+            ));
+        }
+
+        let tmp_path = path.with_extension(format!("db.{}.tmp", std::process::id()));
+        {
+            let mut file = fs::File::create(&tmp_path)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, &path)
+    }
+
+    /// Remember `dir` as the choice for `pattern` in `area`, replacing any earlier one
+    pub fn record(&mut self, area: &Path, pattern: &str, dir: &Path, now: u64) {
+        // Fields are separated by tabs and entries by newlines
+        let area_text = platform::path_to_str(area);
+        if pattern.contains(['\t', '\n'])
+            || area_text.contains(['\t', '\n'])
+            || platform::path_to_str(dir).contains('\n')
+        {
+            return;
+        }
+        self.entries.insert(
+            (area.to_path_buf(), pattern.to_string()),
+            Choice {
+                dir: dir.to_path_buf(),
+                time: now,
+            },
+        );
+    }
+
+    /// The directory last chosen for `pattern` in `area`
+    pub fn chosen(&self, area: &Path, pattern: &str) -> Option<&Path> {
+        self.entries
+            .get(&(area.to_path_buf(), pattern.to_string()))
+            .map(|choice| choice.dir.as_path())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Load the selections, remember `dir` as the choice for `pattern` searched
+/// from `search_dir`, and write them back
+pub fn record_choice(search_dir: &Path, pattern: &str, dir: &Path) -> io::Result<()> {
+    let mut selections = Selections::load();
+    selections.record(&area(search_dir), pattern, dir, history::now_secs());
+    selections.save()
+}
@@ -0,0 +1,489 @@
+//! `jcd --selftest`: searches built scenario trees and checks the results,
+//! for verifying a build on a new platform.
+
+use crate::{
+    config::Config, filesystem::MemoryFileSystem, find_matching_directories,
+    ignore::parse_ignore_patterns, matcher::PatternSyntax, platform, query::QueryFilters,
+    MatchQuality, MatchSink, SearchConfig,
+};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+type ScenarioFn = fn(&Path) -> Result<(), String>;
+
+/// Scenarios run by `jcd --selftest`, each against its own fresh directory tree
+const SCENARIOS: &[(&str, ScenarioFn)] = &[
+    ("exact match in subdirectory", scenario_exact_match),
+    ("prefix ranks above partial", scenario_prefix_order),
+    ("match up the tree", scenario_up_tree),
+    ("project roots rank first", scenario_project_root),
+    ("current repository ranks first", scenario_same_repo),
+    ("deep nesting respects depth limit", scenario_deep_nesting),
+    ("symlink loop terminates", scenario_symlink_loop),
+    ("following symlinks", scenario_follow_symlinks),
+    ("staying on one filesystem", scenario_same_filesystem),
+    ("ignore patterns and -x bypass", scenario_ignore),
+    ("glob ignore patterns", scenario_ignore_glob),
+    ("unicode directory names", scenario_unicode),
+    ("non-UTF-8 directory names", scenario_non_utf8),
+    ("query depth filter", scenario_depth_filter),
+    ("glob wildcards", scenario_glob),
+    ("regex patterns", scenario_regex),
+    ("gitignore rules", scenario_gitignore),
+    ("in-memory tree", scenario_memory_tree),
+    ("matches reported while searching", scenario_match_sink),
+];
+
+fn mkdirs(root: &Path, dirs: &[&str]) -> Result<(), String> {
+    for dir in dirs {
+        fs::create_dir_all(root.join(dir)).map_err(|e| format!("cannot create {}: {}", dir, e))?;
+    }
+    Ok(())
+}
+
+fn search(dir: &Path, pattern: &str, case_sensitive: bool) -> Vec<PathBuf> {
+    let config = SearchConfig::new(&Config::default(), case_sensitive, QueryFilters::default());
+    find_matching_directories(dir, pattern, &config, &[])
+        .into_iter()
+        .map(|m| m.path)
+        .collect()
+}
+
+fn expect_first(results: &[PathBuf], expected: &Path) -> Result<(), String> {
+    match results.first() {
+        Some(first) if first == expected => Ok(()),
+        Some(first) => Err(format!(
+            "expected {} first, got {}",
+            expected.display(),
+            first.display()
+        )),
+        None => Err(format!("expected {}, got no matches", expected.display())),
+    }
+}
+
+fn scenario_exact_match(root: &Path) -> Result<(), String> {
+    mkdirs(root, &["alpha", "beta", "gamma"])?;
+    expect_first(&search(root, "beta", true), &root.join("beta"))
+}
+
+fn scenario_prefix_order(root: &Path) -> Result<(), String> {
+    mkdirs(root, &["mytest", "testing"])?;
+    let results = search(root, "test", true);
+    expect_first(&results, &root.join("testing"))?;
+    if results.get(1) != Some(&root.join("mytest")) {
+        return Err("partial match missing after prefix match".to_string());
+    }
+    Ok(())
+}
+
+fn scenario_up_tree(root: &Path) -> Result<(), String> {
+    mkdirs(root, &["project/src/module"])?;
+    let results = search(&root.join("project/src/module"), "project", true);
+    expect_first(&results, &root.join("project"))
+}
+
+fn scenario_project_root(root: &Path) -> Result<(), String> {
+    // The project is one level deeper than the copy in build output
+    mkdirs(root, &["build/app", "src/lib/app"])?;
+    fs::write(root.join("src/lib/app/Cargo.toml"), "")
+        .map_err(|e| format!("cannot create Cargo.toml: {}", e))?;
+    expect_first(&search(root, "app", true), &root.join("src/lib/app"))
+}
+
+fn scenario_same_repo(root: &Path) -> Result<(), String> {
+    // A nested checkout holds a closer match than the repository itself
+    mkdirs(
+        root,
+        &[
+            "repo/.git",
+            "repo/other/.git",
+            "repo/other/api",
+            "repo/src/app/api",
+        ],
+    )?;
+    let repo = root.join("repo");
+    let mut config = SearchConfig::new(&Config::default(), true, QueryFilters::default());
+    config.repo_root = Some(repo.clone());
+    let results: Vec<PathBuf> = find_matching_directories(&repo, "api", &config, &[])
+        .into_iter()
+        .map(|m| m.path)
+        .collect();
+    expect_first(&results, &repo.join("src/app/api"))
+}
+
+fn scenario_deep_nesting(root: &Path) -> Result<(), String> {
+    // Directories at depth 9 are the deepest the downward search reports
+    mkdirs(root, &["l1/l2/l3/l4/l5/l6/l7/l8/target9/target10"])?;
+    let found = search(root, "target9", true);
+    expect_first(&found, &root.join("l1/l2/l3/l4/l5/l6/l7/l8/target9"))?;
+    if !search(root, "target10", true).is_empty() {
+        return Err("match beyond the maximum depth was returned".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn scenario_symlink_loop(root: &Path) -> Result<(), String> {
+    mkdirs(root, &["loop/inner"])?;
+    std::os::unix::fs::symlink(root.join("loop"), root.join("loop/inner/back"))
+        .map_err(|e| format!("cannot create symlink: {}", e))?;
+
+    let start = Instant::now();
+    let results = search(root, "inner", true);
+    if start.elapsed() > Duration::from_secs(5) {
+        return Err("search took too long on a symlink loop".to_string());
+    }
+    if results.len() != 1 {
+        return Err(format!("expected 1 match, got {}", results.len()));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn scenario_symlink_loop(_root: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn scenario_follow_symlinks(root: &Path) -> Result<(), String> {
+    use std::os::unix::fs::symlink;
+
+    mkdirs(root, &["farm", "store/tool/bin"])?;
+    let link = |target: &str, name: &str| {
+        symlink(root.join(target), root.join(name))
+            .map_err(|e| format!("cannot create symlink: {}", e))
+    };
+    link("store/tool", "farm/tool")?;
+    link("farm", "store/tool/back")?; // Cycle through the farm
+
+    let farm = root.join("farm");
+    if !search(&farm, "bin", true).is_empty() {
+        return Err("symlink followed without --follow-symlinks".to_string());
+    }
+
+    let mut config = SearchConfig::new(&Config::default(), true, QueryFilters::default());
+    config.follow_symlinks = true;
+    let start = Instant::now();
+    let results: Vec<PathBuf> = find_matching_directories(&farm, "bin", &config, &[])
+        .into_iter()
+        .map(|m| m.path)
+        .collect();
+    if start.elapsed() > Duration::from_secs(5) {
+        return Err("search took too long on a symlink cycle".to_string());
+    }
+    if results != [farm.join("tool/bin")] {
+        return Err(format!("expected only tool/bin, got {:?}", results));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn scenario_follow_symlinks(_root: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+/// Uses /proc as the other filesystem; passes trivially where it is not one
+#[cfg(target_os = "linux")]
+fn scenario_same_filesystem(root: &Path) -> Result<(), String> {
+    let proc_dir = Path::new("/proc");
+    if platform::device_of(proc_dir).is_none()
+        || platform::device_of(proc_dir) == platform::device_of(root)
+    {
+        return Ok(());
+    }
+    std::os::unix::fs::symlink(proc_dir, root.join("proc"))
+        .map_err(|e| format!("cannot create symlink: {}", e))?;
+
+    let mut config = SearchConfig::new(&Config::default(), true, QueryFilters::default());
+    config.follow_symlinks = true;
+    if find_matching_directories(root, "sys", &config, &[]).is_empty() {
+        return Err("/proc/sys not found through the link".to_string());
+    }
+    config.same_filesystem = true;
+    let results = find_matching_directories(root, "sys", &config, &[]);
+    if let Some(found) = results.first() {
+        return Err(format!(
+            "searched another filesystem: {}",
+            found.path.display()
+        ));
+    }
+    if find_matching_directories(root, "proc", &config, &[]).is_empty() {
+        return Err("mount point itself was not reported".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn scenario_same_filesystem(_root: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+fn scenario_ignore(root: &Path) -> Result<(), String> {
+    mkdirs(root, &["build", "builder"])?;
+    let ignore_patterns = parse_ignore_patterns("# comment\nsyntax: regex\n^build$\n[invalid\n");
+    let config = SearchConfig::new(&Config::default(), true, QueryFilters::default());
+
+    let results: Vec<PathBuf> = find_matching_directories(root, "build", &config, &ignore_patterns)
+        .into_iter()
+        .map(|m| m.path)
+        .collect();
+    if results.contains(&root.join("build")) {
+        return Err("ignored directory was returned".to_string());
+    }
+    expect_first(&results, &root.join("builder"))?;
+
+    // Without patterns (the -x flag) the ignored directory is found again
+    expect_first(&search(root, "build", true), &root.join("build"))
+}
+
+fn scenario_ignore_glob(root: &Path) -> Result<(), String> {
+    mkdirs(root, &["node_modules", "web.cache", "build-tmp", "rebuild"])?;
+    let ignore_patterns = parse_ignore_patterns("node_modules/\n*.cache\nbuild*\n");
+    let config = SearchConfig::new(&Config::default(), true, QueryFilters::default());
+
+    for ignored in ["node", "web", "build-tmp"] {
+        if !find_matching_directories(root, ignored, &config, &ignore_patterns).is_empty() {
+            return Err(format!("'{}' matched an ignored directory", ignored));
+        }
+    }
+    // Globs match whole names, unlike the unanchored regex syntax
+    let results: Vec<PathBuf> = find_matching_directories(root, "build", &config, &ignore_patterns)
+        .into_iter()
+        .map(|m| m.path)
+        .collect();
+    expect_first(&results, &root.join("rebuild"))
+}
+
+fn scenario_unicode(root: &Path) -> Result<(), String> {
+    mkdirs(root, &["café", "naïve-dir", "日本語"])?;
+    expect_first(&search(root, "café", true), &root.join("café"))?;
+    expect_first(&search(root, "naïve", true), &root.join("naïve-dir"))?;
+    expect_first(&search(root, "本", true), &root.join("日本語"))?;
+    expect_first(&search(root, "CAFÉ", false), &root.join("café"))
+}
+
+#[cfg(unix)]
+fn scenario_non_utf8(root: &Path) -> Result<(), String> {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    // "café" in Latin-1, next to a name it would collide with if decoded lossily
+    let name = OsStr::from_bytes(b"caf\xe9");
+    mkdirs(root, &["caf\u{FFFD}x"])?;
+    fs::create_dir(root.join(name)).map_err(|e| format!("cannot create directory: {}", e))?;
+
+    let pattern = platform::os_to_str(name);
+    let results = search(root, &pattern, true);
+    expect_first(&results, &root.join(name))?;
+    if results.len() != 1 {
+        return Err(format!("expected 1 match, got {}", results.len()));
+    }
+    if search(root, "caf\u{FFFD}x", true).contains(&root.join(name)) {
+        return Err("replacement character matched a raw byte".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn scenario_non_utf8(_root: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+fn scenario_depth_filter(root: &Path) -> Result<(), String> {
+    mkdirs(root, &["api", "a/api", "a/b/api"])?;
+    let (pattern, filters) = crate::query::parse_query("api depth:>=3")?;
+    let config = SearchConfig::new(&Config::default(), true, filters);
+    let results = find_matching_directories(root, &pattern, &config, &[]);
+    match results.as_slice() {
+        [only] if only.path == root.join("a/b/api") => {
+            if only.match_quality != MatchQuality::ExactDown {
+                return Err(format!("unexpected quality {:?}", only.match_quality));
+            }
+            Ok(())
+        }
+        _ => Err(format!(
+            "expected only a/b/api, got {} matches",
+            results.len()
+        )),
+    }
+}
+
+fn scenario_glob(root: &Path) -> Result<(), String> {
+    mkdirs(root, &["myproj-test", "proj-unit-test", "projects"])?;
+    let results = search(root, "proj*test", true);
+    expect_first(&results, &root.join("proj-unit-test"))?;
+    if results != [root.join("proj-unit-test"), root.join("myproj-test")] {
+        return Err(format!("expected 2 glob matches, got {}", results.len()));
+    }
+    expect_first(&search(root, "pro?ects", true), &root.join("projects"))
+}
+
+fn scenario_regex(root: &Path) -> Result<(), String> {
+    mkdirs(root, &["release-v2", "v10", "vendor"])?;
+    let mut config = SearchConfig::new(&Config::default(), true, QueryFilters::default());
+    config.syntax = PatternSyntax::Regex;
+    let results: Vec<PathBuf> = find_matching_directories(root, "v[0-9]+", &config, &[])
+        .into_iter()
+        .map(|m| m.path)
+        .collect();
+    // The full match ranks above the partial one; "vendor" has no digit
+    if results != [root.join("v10"), root.join("release-v2")] {
+        return Err(format!("expected v10 then release-v2, got {:?}", results));
+    }
+    Ok(())
+}
+
+fn scenario_gitignore(root: &Path) -> Result<(), String> {
+    mkdirs(
+        root,
+        &[
+            ".git/info",
+            "target/debug",
+            "app/build",
+            "app/keep-build",
+            "docs/build",
+        ],
+    )?;
+    let write = |path: &str, content: &str| {
+        fs::write(root.join(path), content).map_err(|e| format!("cannot write {}: {}", path, e))
+    };
+    write(".gitignore", "target/\nbuild\n!/docs/build\n")?;
+    write(".git/info/exclude", "*-build\n")?;
+
+    let mut config = SearchConfig::new(&Config::default(), true, QueryFilters::default());
+    config.gitignore = true;
+    let results: Vec<PathBuf> = find_matching_directories(root, "build", &config, &[])
+        .into_iter()
+        .map(|m| m.path)
+        .collect();
+    // Rules later in a file win, so the negation re-allows docs/build
+    if results != [root.join("docs/build")] {
+        return Err(format!("expected only docs/build, got {:?}", results));
+    }
+    if !find_matching_directories(root, "debug", &config, &[]).is_empty() {
+        return Err("directory inside an ignored tree was returned".to_string());
+    }
+
+    // Without the option every directory is searched
+    expect_first(&search(root, "debug", true), &root.join("target/debug"))
+}
+
+/// Searches a `MemoryFileSystem`; the scratch directory stays empty
+fn scenario_memory_tree(_root: &Path) -> Result<(), String> {
+    let tree = MemoryFileSystem::new()
+        .with_file("/mem/src/app/Cargo.toml")
+        .with_dir("/mem/gen/app")
+        .with_dir("/mem/build/out/app")
+        .with_dir("/mem/lib/myapp")
+        .with_symlink("/mem/link", "src");
+    let mem = Path::new("/mem");
+    let search_tree = |follow_symlinks: bool| -> Vec<PathBuf> {
+        let mut config = SearchConfig::new(&Config::default(), true, QueryFilters::default());
+        config.fs = Arc::new(tree.clone());
+        config.follow_symlinks = follow_symlinks;
+        find_matching_directories(mem, "app", &config, &[])
+            .into_iter()
+            .map(|m| m.path)
+            .collect()
+    };
+
+    let expected: Vec<PathBuf> = ["src/app", "gen/app", "build/out/app", "lib/myapp"]
+        .iter()
+        .map(|dir| mem.join(dir))
+        .collect();
+    let results = search_tree(false);
+    if results != expected {
+        return Err(format!("expected {:?}, got {:?}", expected, results));
+    }
+
+    // The link is listed before src, which is then not searched a second time
+    let results = search_tree(true);
+    if !results.contains(&mem.join("link/app")) || results.contains(&mem.join("src/app")) {
+        return Err(format!(
+            "expected link/app in place of src/app, got {:?}",
+            results
+        ));
+    }
+    Ok(())
+}
+
+fn scenario_match_sink(root: &Path) -> Result<(), String> {
+    mkdirs(root, &["one/lib", "two/three/lib", "four/five/six/lib"])?;
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let search_reporting = |wanted: usize| {
+        reported.lock().unwrap().clear();
+        let sink_reported = Arc::clone(&reported);
+        let mut config = SearchConfig::new(&Config::default(), true, QueryFilters::default());
+        config.on_match = Some(MatchSink::new(move |m| {
+            let mut reported = sink_reported.lock().unwrap();
+            reported.push(m.path.clone());
+            reported.len() < wanted
+        }));
+        find_matching_directories(root, "lib", &config, &[])
+    };
+
+    // Every match is reported, nearest level first
+    let results = search_reporting(usize::MAX);
+    let reported_paths = reported.lock().unwrap().clone();
+    let expected: Vec<PathBuf> = ["one/lib", "two/three/lib", "four/five/six/lib"]
+        .iter()
+        .map(|dir| root.join(dir))
+        .collect();
+    if reported_paths != expected || results.len() != expected.len() {
+        return Err(format!(
+            "expected {:?} reported, got {:?}",
+            expected, reported_paths
+        ));
+    }
+
+    // Declining a match ends the walk
+    let results = search_reporting(1);
+    if results.len() != 1 || reported.lock().unwrap().len() != 1 {
+        return Err(format!(
+            "walk went on after the sink declined, found {:?}",
+            results
+        ));
+    }
+    Ok(())
+}
+
+/// Run every scenario in a scratch directory and report the outcome.
+/// Returns the process exit code: 0 when all scenarios pass.
+pub fn run() -> i32 {
+    let base = env::temp_dir().join(format!("jcd-selftest-{}", process::id()));
+    let mut failed = 0;
+
+    println!("Running jcd self-test in {}", base.display());
+
+    for (index, (name, scenario)) in SCENARIOS.iter().enumerate() {
+        let root = base.join(format!("scenario{}", index));
+        let outcome = fs::create_dir_all(&root)
+            .map_err(|e| format!("cannot create scratch directory: {}", e))
+            .and_then(|_| {
+                // Canonicalize so expected paths line up with what the search reports
+                let root = fs::canonicalize(&root).map_err(|e| e.to_string())?;
+                scenario(&root)
+            });
+
+        match outcome {
+            Ok(()) => println!("PASS  {}", name),
+            Err(reason) => {
+                failed += 1;
+                println!("FAIL  {}: {}", name, reason);
+            }
+        }
+    }
+
+    let _ = fs::remove_dir_all(&base);
+
+    println!("{} passed, {} failed", SCENARIOS.len() - failed, failed);
+    if failed == 0 {
+        0
+    } else {
+        1
+    }
+}
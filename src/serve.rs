@@ -0,0 +1,259 @@
+//! `jcd serve --stdio`: answers JSON-RPC 2.0 requests on standard input, one
+//! message per line, so editor plugins can embed jcd in a directory picker
+//! without starting a process per keystroke. Settings, ignore files, the index
+//! and the history are loaded once for all searches.
+//!
+//! Methods: `search`, `history/list`, `history/record`, `bookmark/list`,
+//! `bookmark/add`, `bookmark/remove` and `exit`.
+
+use crate::json::{self, Value};
+use jcd::{
+    bookmark::{self, Bookmarks},
+    config::Config,
+    history::{self, History},
+    matcher::PatternSyntax,
+    output::{json_escape, match_json},
+    platform, prepare_search, pwd_fallback, query, search_origin, SearchFlags, SearchSession,
+    EXIT_USAGE,
+};
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    sync::Arc,
+};
+use tracing::debug;
+
+pub const USAGE: &str = "Usage: jcd serve --stdio";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const FAILED: i32 = -32000; // The operation itself failed, e.g. a missing bookmark
+
+/// A failed request: error code and message
+type RpcError = (i32, String);
+
+fn invalid_params(message: &str) -> RpcError {
+    (INVALID_PARAMS, message.to_string())
+}
+
+/// A string parameter, None when absent
+fn string_param<'a>(params: &'a Value, key: &str) -> Result<Option<&'a str>, RpcError> {
+    match params.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_str()
+            .map(Some)
+            .ok_or_else(|| invalid_params(&format!("'{}' must be a string", key))),
+    }
+}
+
+fn bool_param(params: &Value, key: &str) -> Result<bool, RpcError> {
+    match params.get(key) {
+        None | Some(Value::Null) => Ok(false),
+        Some(value) => value
+            .as_bool()
+            .ok_or_else(|| invalid_params(&format!("'{}' must be a boolean", key))),
+    }
+}
+
+fn required<'a>(params: &'a Value, key: &str) -> Result<&'a str, RpcError> {
+    string_param(params, key)?.ok_or_else(|| invalid_params(&format!("'{}' is required", key)))
+}
+
+/// Search state kept between requests
+struct Server<'a> {
+    session: SearchSession<'a>,
+    settings: &'a Config,
+    current_dir: PathBuf,
+}
+
+impl Server<'_> {
+    /// `search {query, from?, limit?, ignore_case?, regex?}`: the ranked matches,
+    /// as printed by `jcd --list --json`
+    fn search(&self, params: &Value) -> Result<String, RpcError> {
+        let term = required(params, "query")?;
+        let current_dir = match string_param(params, "from")? {
+            Some(dir) => search_origin(dir).map_err(|e| (FAILED, e))?,
+            None => self.current_dir.clone(),
+        };
+        let max_matches = match params.get("limit") {
+            None | Some(Value::Null) => None,
+            Some(limit) => match limit.as_f64() {
+                Some(n) if n >= 1.0 && n.fract() == 0.0 => Some(n as usize),
+                _ => return Err(invalid_params("'limit' must be a positive integer")),
+            },
+        };
+        let flags = SearchFlags {
+            case_sensitive: !bool_param(params, "ignore_case")?,
+            syntax: if bool_param(params, "regex")? {
+                PatternSyntax::Regex
+            } else {
+                PatternSyntax::Text
+            },
+            max_matches,
+            ..self.session.flags.clone()
+        };
+        let search =
+            prepare_search(term, &flags, &current_dir, self.settings).map_err(|e| (FAILED, e))?;
+        let stats = Arc::clone(&search.config.stats);
+        let matches = self.session.find(search);
+        let partial = stats.report().partial();
+        let items: Vec<String> = matches
+            .iter()
+            .enumerate()
+            .map(|(rank, m)| match_json(m, rank, partial))
+            .collect();
+        Ok(format!("[{}]", items.join(",")))
+    }
+
+    /// `history/record {path}`: count a visit, like `jcd --add`
+    fn record(&mut self, params: &Value) -> Result<String, RpcError> {
+        let path = query::expand_home(required(params, "path")?);
+        let path = self.current_dir.join(path);
+        if !path.is_dir() {
+            return Err((FAILED, format!("Not a directory: {}", path.display())));
+        }
+        history::record_visit(&path, self.settings)
+            .map_err(|e| (FAILED, format!("Cannot update history: {}", e)))?;
+        // Later searches rank with the new visit
+        self.session.history = History::load().with_decay(self.settings.decay);
+        Ok("null".to_string())
+    }
+
+    fn handle(&mut self, method: &str, params: &Value) -> Result<String, RpcError> {
+        match method {
+            "search" => self.search(params),
+            // `history/list {pattern?, sort?}`: as printed by `jcd history list --json`
+            "history/list" => {
+                let mut args = vec!["--json".to_string()];
+                if let Some(sort) = string_param(params, "sort")? {
+                    args.extend(["--sort".to_string(), sort.to_string()]);
+                }
+                if let Some(pattern) = string_param(params, "pattern")? {
+                    args.push(pattern.to_string());
+                }
+                history::list(&args).map_err(|e| (INVALID_PARAMS, e))
+            }
+            "history/record" => self.record(params),
+            "bookmark/list" => {
+                let items: Vec<String> = Bookmarks::load()
+                    .entries()
+                    .map(|(name, dir)| {
+                        format!(
+                            "{{\"name\":\"{}\",\"path\":\"{}\",\"exists\":{}}}",
+                            json_escape(name),
+                            json_escape(&platform::path_to_str(dir)),
+                            dir.is_dir()
+                        )
+                    })
+                    .collect();
+                Ok(format!("[{}]", items.join(",")))
+            }
+            // The bookmark methods answer with the message the CLI prints
+            "bookmark/add" => {
+                let name = required(params, "name")?;
+                let path = string_param(params, "path")?;
+                bookmark::add(name, path)
+                    .map(|message| Value::String(message).to_json())
+                    .map_err(|e| (FAILED, e))
+            }
+            "bookmark/remove" => bookmark::remove(required(params, "name")?)
+                .map(|message| Value::String(message).to_json())
+                .map_err(|e| (FAILED, e)),
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+        }
+    }
+}
+
+/// The response line for a request `id`
+fn response(id: &Value, result: Result<String, RpcError>) -> String {
+    match result {
+        Ok(result) => format!(
+            "{{\"jsonrpc\":\"2.0\",\"id\":{},\"result\":{}}}",
+            id.to_json(),
+            result
+        ),
+        Err((code, message)) => format!(
+            "{{\"jsonrpc\":\"2.0\",\"id\":{},\"error\":{{\"code\":{},\"message\":\"{}\"}}}}",
+            id.to_json(),
+            code,
+            json_escape(&message)
+        ),
+    }
+}
+
+fn serve() -> Result<(), String> {
+    let current_dir = env::current_dir()
+        .or_else(|e| pwd_fallback().ok_or(e))
+        .map_err(|e| format!("Cannot get current directory: {}", e))?;
+    let settings = Config::load();
+    let flags = SearchFlags {
+        quiet: true,
+        ..SearchFlags::default()
+    };
+    let mut server = Server {
+        session: SearchSession::new(&flags, &settings, None),
+        settings: &settings,
+        current_dir,
+    };
+
+    let mut out = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(|e| format!("Cannot read request: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        debug!("Request {}", line);
+        let request = match json::parse(&line) {
+            Ok(request @ Value::Object(_)) => request,
+            Ok(_) => {
+                let error = (INVALID_REQUEST, "Request must be an object".to_string());
+                writeln!(out, "{}", response(&Value::Null, Err(error)))
+                    .map_err(|e| e.to_string())?;
+                continue;
+            }
+            Err(e) => {
+                let error = (PARSE_ERROR, e);
+                writeln!(out, "{}", response(&Value::Null, Err(error)))
+                    .map_err(|e| e.to_string())?;
+                continue;
+            }
+        };
+
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            let id = request.get("id").cloned().unwrap_or(Value::Null);
+            let error = (INVALID_REQUEST, "Request needs a method".to_string());
+            writeln!(out, "{}", response(&id, Err(error))).map_err(|e| e.to_string())?;
+            continue;
+        };
+        if method == "exit" {
+            break;
+        }
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let result = server.handle(method, &params);
+        // Requests without an id are notifications and get no response
+        if let Some(id) = request.get("id") {
+            writeln!(out, "{}", response(id, result)).map_err(|e| e.to_string())?;
+        }
+        out.flush().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub fn run_command(args: &[String]) -> i32 {
+    if args != ["--stdio"] {
+        eprintln!("{}", USAGE);
+        return EXIT_USAGE;
+    }
+    match serve() {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! What a search did, counted while it runs: directories listed, entries
+//! looked at, directories skipped, directories that could not be read, and
+//! which limits cut it short. `--stats`
+//! prints the counts so that `search.max_depth`, `search.timeout_ms` and
+//! `search.max_matches` can be tuned on real numbers. A search that a limit
+//! stopped before it finished is partial: better matches may exist.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+/// A directory the search could not list or follow, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unreadable {
+    pub path: PathBuf,
+    pub reason: String, // As the OS puts it, e.g. "permission denied"
+}
+
+/// Counters of one search, shared by the threads that run it
+#[derive(Debug, Default)]
+pub struct SearchStats {
+    directories: AtomicUsize,
+    entries: AtomicUsize,
+    ignored: AtomicUsize,
+    depth_limited: AtomicUsize,
+    deepest: AtomicUsize,
+    indexed: AtomicBool,
+    timed_out: AtomicBool,
+    match_limited: AtomicBool,
+    stopped_early: AtomicBool,
+    elapsed_nanos: AtomicU64,
+    unreadable: Mutex<Vec<Unreadable>>,
+}
+
+impl SearchStats {
+    /// A directory `depth` levels below the search directory was listed,
+    /// with `entries` entries
+    pub(crate) fn listed(&self, entries: usize, depth: usize) {
+        self.directories.fetch_add(1, Ordering::Relaxed);
+        self.entries.fetch_add(entries, Ordering::Relaxed);
+        self.deepest.fetch_max(depth, Ordering::Relaxed);
+    }
+
+    /// The index answered a downward search from `candidates` entries
+    pub(crate) fn indexed(&self, candidates: usize) {
+        self.indexed.store(true, Ordering::Relaxed);
+        self.entries.fetch_add(candidates, Ordering::Relaxed);
+    }
+
+    /// A directory was skipped by an ignore pattern or .gitignore
+    pub(crate) fn ignored(&self) {
+        self.ignored.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A directory was not entered because it lies at the depth limit
+    pub(crate) fn depth_limited(&self) {
+        self.depth_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn timed_out(&self) {
+        self.timed_out.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn match_limited(&self) {
+        self.match_limited.store(true, Ordering::Relaxed);
+    }
+
+    /// The walk stopped at the match limit instead of finishing
+    pub(crate) fn stopped_early(&self) {
+        self.match_limited();
+        self.stopped_early.store(true, Ordering::Relaxed);
+    }
+
+    /// `path` could not be listed, or with --follow-symlinks followed.
+    /// Directories removed while the search ran are not worth a mention.
+    pub(crate) fn unreadable(&self, path: &Path, error: &io::Error) {
+        if error.kind() == io::ErrorKind::NotFound {
+            return;
+        }
+        // The OS message without its error number
+        let message = error.to_string();
+        let message = message.split(" (os error").next().unwrap_or_default();
+        let mut chars = message.chars();
+        let reason = chars
+            .next()
+            .map(|first| first.to_lowercase().chain(chars).collect())
+            .unwrap_or_default();
+        self.unreadable.lock().unwrap().push(Unreadable {
+            path: path.to_path_buf(),
+            reason,
+        });
+    }
+
+    /// The directories that could not be read so far, by path
+    pub fn unreadable_dirs(&self) -> Vec<Unreadable> {
+        let mut dirs = self.unreadable.lock().unwrap().clone();
+        dirs.sort_by(|a, b| a.path.cmp(&b.path));
+        dirs.dedup();
+        dirs
+    }
+
+    /// The walk ended after `elapsed`
+    pub(crate) fn finished(&self, elapsed: Duration) {
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.elapsed_nanos.store(nanos, Ordering::Relaxed);
+    }
+
+    /// The counts so far
+    pub fn report(&self) -> StatsReport {
+        StatsReport {
+            directories: self.directories.load(Ordering::Relaxed),
+            entries: self.entries.load(Ordering::Relaxed),
+            ignored: self.ignored.load(Ordering::Relaxed),
+            depth_limited: self.depth_limited.load(Ordering::Relaxed),
+            deepest: self.deepest.load(Ordering::Relaxed),
+            indexed: self.indexed.load(Ordering::Relaxed),
+            timed_out: self.timed_out.load(Ordering::Relaxed),
+            match_limited: self.match_limited.load(Ordering::Relaxed),
+            stopped_early: self.stopped_early.load(Ordering::Relaxed),
+            elapsed: Duration::from_nanos(self.elapsed_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// What one search did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatsReport {
+    pub directories: usize,   // Directories listed
+    pub entries: usize,       // Entries of those listings, and index entries looked at
+    pub ignored: usize,       // Directories skipped by ignore patterns or .gitignore
+    pub depth_limited: usize, // Directories at search.max_depth, not entered
+    pub deepest: usize,       // Deepest level listed below the search directory
+    pub indexed: bool,        // The index answered the downward search
+    pub timed_out: bool,      // search.timeout_ms ran out
+    pub match_limited: bool,  // search.max_matches was reached
+    pub stopped_early: bool,  // search.max_matches stopped the walk before it finished
+    pub elapsed: Duration,    // Time the walk took, without ranking and output
+}
+
+fn reached(hit: bool) -> &'static str {
+    if hit {
+        "reached"
+    } else {
+        "not reached"
+    }
+}
+
+impl StatsReport {
+    /// Whether a limit cut the search short, so better matches may have been missed
+    pub fn partial(&self) -> bool {
+        self.timed_out || self.stopped_early
+    }
+
+    /// The report for people, one figure per line
+    pub fn text(&self) -> String {
+        let depth_limit = if self.depth_limited > 0 {
+            format!("reached, {} directories not entered", self.depth_limited)
+        } else {
+            reached(false).to_string()
+        };
+        let match_limit = if self.stopped_early {
+            "reached, the search stopped early"
+        } else {
+            reached(self.match_limited)
+        };
+        format!(
+            "Search statistics:\n  Directories visited: {}{}\n  Entries examined:    {}\n  Ignore skips:        {}\n  Depth limit:         {}\n  Time budget:         {}\n  Match limit:         {}\n  Elapsed:             {:.1} ms",
+            self.directories,
+            if self.indexed { " (downward search from the index)" } else { "" },
+            self.entries,
+            self.ignored,
+            depth_limit,
+            reached(self.timed_out),
+            match_limit,
+            self.elapsed.as_secs_f64() * 1000.0
+        )
+    }
+
+    /// The report as one JSON object
+    pub fn json(&self) -> String {
+        format!(
+            "{{\"directories_visited\":{},\"entries_examined\":{},\"ignore_skips\":{},\"depth_limited\":{},\"from_index\":{},\"timed_out\":{},\"match_limit_hit\":{},\"partial\":{},\"elapsed_ms\":{:.3}}}",
+            self.directories,
+            self.entries,
+            self.ignored,
+            self.depth_limited,
+            self.indexed,
+            self.timed_out,
+            self.match_limited,
+            self.partial(),
+            self.elapsed.as_secs_f64() * 1000.0
+        )
+    }
+}
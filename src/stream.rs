@@ -0,0 +1,112 @@
+//! Searches that hand out their matches while the walk goes on, for editors
+//! and file managers that show results as they arrive. Built with the `async`
+//! feature, on tokio.
+//!
+//! ```
+//! use jcd::{config::Config, filesystem::MemoryFileSystem, prepare_search};
+//! use jcd::{SearchFlags, SearchSession};
+//! use std::{path::Path, sync::Arc};
+//!
+//! let tree = MemoryFileSystem::new()
+//!     .with_dir("/work/src/app")
+//!     .with_dir("/work/build/app");
+//! let settings = Config::default();
+//! let flags = SearchFlags {
+//!     quiet: true,
+//!     ..SearchFlags::default()
+//! };
+//! let runtime = tokio::runtime::Builder::new_current_thread()
+//!     .build()
+//!     .unwrap();
+//! runtime.block_on(async {
+//!     let session = SearchSession::new(&flags, &settings, None).with_file_system(Arc::new(tree));
+//!     let search = prepare_search("app", &flags, Path::new("/work"), &settings).unwrap();
+//!     let mut matches = session.stream(search);
+//!     let mut found = 0;
+//!     while let Some(m) = matches.next().await {
+//!         println!("{}", m.path.display());
+//!         found += 1;
+//!     }
+//!     assert_eq!(found, 2);
+//! });
+//! ```
+
+use crate::ranking::TierCount;
+use crate::{find_matching_directories, DirectoryMatch, MatchSink, PreparedSearch, SearchSession};
+use futures_core::Stream;
+use std::{
+    collections::HashSet,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+
+/// The matches of one search in the order the walk finds them, each path
+/// once, up to the search's match limit. They are not ranked: `find` returns
+/// the same search ranked. Dropping the stream ends the walk.
+#[derive(Debug)]
+pub struct MatchStream {
+    receiver: mpsc::UnboundedReceiver<DirectoryMatch>,
+}
+
+impl MatchStream {
+    /// The next match, or None once the walk is over
+    pub async fn next(&mut self) -> Option<DirectoryMatch> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for MatchStream {
+    type Item = DirectoryMatch;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<DirectoryMatch>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl SearchSession<'_> {
+    /// Run a prepared search on tokio's blocking thread pool and stream its
+    /// matches. Must be called from within a tokio runtime.
+    pub fn stream(&self, search: PreparedSearch) -> MatchStream {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        if !search.bookmarked.is_empty() {
+            for m in search.bookmark_matches() {
+                let _ = sender.send(m);
+            }
+            return MatchStream { receiver };
+        }
+
+        let PreparedSearch {
+            search_dir,
+            pattern,
+            mut config,
+            ..
+        } = search;
+        config.index = self.index.clone();
+        config.fs = Arc::clone(&self.fs);
+
+        // The walk may come across a directory twice, e.g. up the tree and again
+        // below an extra root; matches beyond their tier limit are not sent
+        let max_matches = config.max_matches;
+        let seen = Mutex::new((HashSet::new(), TierCount::new(&config.tier_limits)));
+        config.on_match = Some(MatchSink::new(move |m| {
+            let mut guard = seen.lock().unwrap();
+            let (seen, found) = &mut *guard;
+            if found.kept() < max_matches
+                && seen.insert(m.path.clone())
+                && found.add(&m.match_quality)
+            {
+                return sender.send(m.clone()).is_ok() && found.kept() < max_matches;
+            }
+            found.kept() < max_matches && !sender.is_closed()
+        }));
+
+        // The sink, and with it the sender, goes away when the walk ends
+        let ignore_patterns = self.ignore_patterns.clone();
+        tokio::task::spawn_blocking(move || {
+            find_matching_directories(&search_dir, &pattern, &config, &ignore_patterns);
+        });
+        MatchStream { receiver }
+    }
+}
@@ -0,0 +1,520 @@
+//! Minimal TOML reader covering what jcd's configuration needs: tables,
+//! dotted and quoted keys, strings, integers, floats, booleans and arrays.
+//! Inline tables, dates and multi-line strings are not supported. `set_value`
+//! edits one key of a document and leaves the rest of the text as it was.
+
+use std::{collections::BTreeMap, fmt};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Integer(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Boolean(_) => "boolean",
+            Value::Array(_) => "array",
+        }
+    }
+}
+
+/// The value as TOML text, e.g. `"~/src"` or `[1, 2]`
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(s) => {
+                f.write_str("\"")?;
+                for c in s.chars() {
+                    match c {
+                        '"' => f.write_str("\\\"")?,
+                        '\\' => f.write_str("\\\\")?,
+                        '\n' => f.write_str("\\n")?,
+                        '\t' => f.write_str("\\t")?,
+                        '\r' => f.write_str("\\r")?,
+                        c if c.is_control() => write!(f, "\\u{:04X}", c as u32)?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                f.write_str("\"")
+            }
+            Value::Integer(i) => write!(f, "{}", i),
+            // Debug keeps the fraction of whole numbers, so 168.0 stays a float
+            Value::Float(x) => write!(f, "{:?}", x),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+/// Parsed document with every key flattened to its dotted path, e.g. `search.max_depth`
+#[derive(Debug, Default, Clone)]
+pub struct Document {
+    pub values: BTreeMap<String, Value>,
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    line: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(line: &'a str) -> Self {
+        Self {
+            chars: line.chars().collect(),
+            pos: 0,
+            line,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(
+            self.peek(),
+            Some(' ') | Some('\t') | Some('\n') | Some('\r')
+        ) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skip whitespace and comments, including across the lines of a multi-line array
+    fn skip_whitespace_and_comments(&mut self) {
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some('#') {
+                while !matches!(self.peek(), None | Some('\n')) {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace_and_comments();
+        self.peek().is_none()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected '{}' in '{}'", c, self.line.trim()))
+        }
+    }
+
+    fn parse_basic_string(&mut self) -> Result<String, String> {
+        self.pos += 1; // opening quote
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                None => return Err("unterminated string".to_string()),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    let escaped = self.peek().ok_or("unterminated escape sequence")?;
+                    self.pos += 1;
+                    match escaped {
+                        'n' => out.push('\n'),
+                        't' => out.push('\t'),
+                        'r' => out.push('\r'),
+                        '\\' => out.push('\\'),
+                        '"' => out.push('"'),
+                        'u' | 'U' => {
+                            let len = if escaped == 'u' { 4 } else { 8 };
+                            let hex: String = self.chars.iter().skip(self.pos).take(len).collect();
+                            self.pos += len;
+                            let code = u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| {
+                                    format!("invalid unicode escape '\\{}{}'", escaped, hex)
+                                })?;
+                            out.push(code);
+                        }
+                        other => return Err(format!("invalid escape sequence '\\{}'", other)),
+                    }
+                }
+                Some(c) => {
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn parse_literal_string(&mut self) -> Result<String, String> {
+        self.pos += 1; // opening quote
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                None => return Err("unterminated string".to_string()),
+                Some('\'') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some(c) => {
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn parse_key_part(&mut self) -> Result<String, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"') => self.parse_basic_string(),
+            Some('\'') => self.parse_literal_string(),
+            _ => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                {
+                    self.pos += 1;
+                }
+                if start == self.pos {
+                    return Err(format!("invalid key in '{}'", self.line.trim()));
+                }
+                Ok(self.chars[start..self.pos].iter().collect())
+            }
+        }
+    }
+
+    /// Parse a possibly dotted key (`a.b."c d"`) into its parts
+    fn parse_key(&mut self) -> Result<Vec<String>, String> {
+        let mut parts = vec![self.parse_key_part()?];
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some('.') {
+                self.pos += 1;
+                parts.push(self.parse_key_part()?);
+            } else {
+                return Ok(parts);
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace_and_comments();
+        match self.peek() {
+            Some('"') => self.parse_basic_string().map(Value::String),
+            Some('\'') => self.parse_literal_string().map(Value::String),
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace_and_comments();
+                    if self.peek() == Some(']') {
+                        self.pos += 1;
+                        return Ok(Value::Array(items));
+                    }
+                    items.push(self.parse_value()?);
+                    self.skip_whitespace_and_comments();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(']') => {}
+                        _ => return Err("expected ',' or ']' in array".to_string()),
+                    }
+                }
+            }
+            Some(_) => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if !c.is_whitespace() && c != ',' && c != ']' && c != '#')
+                {
+                    self.pos += 1;
+                }
+                let token: String = self.chars[start..self.pos].iter().collect();
+                parse_scalar(&token)
+            }
+            None => Err("missing value".to_string()),
+        }
+    }
+}
+
+/// Parse a bare value such as `42`, `1.5` or `true`
+pub fn parse_scalar(token: &str) -> Result<Value, String> {
+    match token {
+        "true" => return Ok(Value::Boolean(true)),
+        "false" => return Ok(Value::Boolean(false)),
+        _ => {}
+    }
+    let cleaned = token.replace('_', "");
+    if let Ok(i) = cleaned.parse::<i64>() {
+        return Ok(Value::Integer(i));
+    }
+    // TOML spells the special floats inf and nan; Rust also reads Infinity and
+    // NaN, and turns an exponent too large for f64 into infinity
+    match cleaned.trim_start_matches(['+', '-']) {
+        "inf" if cleaned.starts_with('-') => return Ok(Value::Float(f64::NEG_INFINITY)),
+        "inf" => return Ok(Value::Float(f64::INFINITY)),
+        "nan" => return Ok(Value::Float(f64::NAN)),
+        _ => {}
+    }
+    if cleaned
+        .chars()
+        .all(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+    {
+        if let Ok(f) = cleaned.parse::<f64>() {
+            if f.is_finite() {
+                return Ok(Value::Float(f));
+            }
+        }
+    }
+    Err(format!("invalid value '{}'", token))
+}
+
+/// Count brackets outside of strings and comments to tell whether a value continues
+fn open_brackets(text: &str) -> i32 {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in text.chars() {
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' && q == '"' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                '#' => break,
+                _ => {}
+            },
+        }
+    }
+    depth
+}
+
+/// Parse a TOML document. Errors carry the 1-based line number.
+pub fn parse(content: &str) -> Result<Document, String> {
+    let mut doc = Document::default();
+    let mut table: Vec<String> = Vec::new();
+    let mut lines = content.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        let line_no = index + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let mut parser = Parser::new(line);
+        parser.skip_whitespace();
+
+        // Table header: [section] or [section.sub]
+        if parser.peek() == Some('[') {
+            parser.pos += 1;
+            table = parser
+                .parse_key()
+                .map_err(|e| format!("line {}: {}", line_no, e))?;
+            parser
+                .expect(']')
+                .map_err(|e| format!("line {}: {}", line_no, e))?;
+            if !parser.at_end() {
+                return Err(format!(
+                    "line {}: unexpected text after table header",
+                    line_no
+                ));
+            }
+            continue;
+        }
+
+        // Key/value pair; arrays may continue over several lines
+        let mut statement = line.to_string();
+        let mut depth = open_brackets(line);
+        while depth > 0 {
+            match lines.next() {
+                Some((_, next)) => {
+                    statement.push('\n');
+                    statement.push_str(next);
+                    depth += open_brackets(next);
+                }
+                None => return Err(format!("line {}: unterminated array", line_no)),
+            }
+        }
+
+        let mut parser = Parser::new(&statement);
+        let key = parser
+            .parse_key()
+            .map_err(|e| format!("line {}: {}", line_no, e))?;
+        parser
+            .expect('=')
+            .map_err(|e| format!("line {}: {}", line_no, e))?;
+        let value = parser
+            .parse_value()
+            .map_err(|e| format!("line {}: {}", line_no, e))?;
+        if !parser.at_end() {
+            return Err(format!("line {}: unexpected text after value", line_no));
+        }
+
+        let full_key = table
+            .iter()
+            .chain(key.iter())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(".");
+        if doc.values.insert(full_key.clone(), value).is_some() {
+            return Err(format!("line {}: duplicate key '{}'", line_no, full_key));
+        }
+    }
+
+    Ok(doc)
+}
+
+/// Parse one value as written after `key =`, e.g. `"~/src"`, `[1, 2]` or `true`
+pub fn parse_value(text: &str) -> Result<Value, String> {
+    let mut parser = Parser::new(text);
+    let value = parser.parse_value()?;
+    if !parser.at_end() {
+        return Err(format!("unexpected text after value in '{}'", text.trim()));
+    }
+    Ok(value)
+}
+
+/// One `key = value` statement of a document: its lines and full dotted key
+struct Statement {
+    first: usize, // 0-based index of its first line
+    last: usize,  // and of its last, for arrays over several lines
+    key: Vec<String>,
+    written_key: String,     // The key as written, without the table
+    comment: Option<String>, // A comment after the value, kept when it is replaced
+}
+
+/// The table headers and statements of a document that `parse` accepts
+fn statements(lines: &[&str]) -> (Vec<(usize, Vec<String>)>, Vec<Statement>) {
+    let mut headers = Vec::new();
+    let mut statements = Vec::new();
+    let mut table: Vec<String> = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        let trimmed = line.trim();
+        let first = index;
+        index += 1;
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let mut parser = Parser::new(line);
+        parser.skip_whitespace();
+        if parser.peek() == Some('[') {
+            parser.pos += 1;
+            table = parser.parse_key().unwrap_or_default();
+            headers.push((first, table.clone()));
+            continue;
+        }
+        let mut depth = open_brackets(line);
+        while depth > 0 && index < lines.len() {
+            depth += open_brackets(lines[index]);
+            index += 1;
+        }
+        let text = lines[first..index].join("\n");
+        let mut parser = Parser::new(&text);
+        let Ok(key) = parser.parse_key() else {
+            continue;
+        };
+        let written_key: String = parser.chars[..parser.pos].iter().collect();
+        let comment = parser
+            .expect('=')
+            .and_then(|_| parser.parse_value())
+            .ok()
+            .and_then(|_| {
+                parser.skip_whitespace();
+                (parser.peek() == Some('#')).then(|| parser.chars[parser.pos..].iter().collect())
+            });
+        statements.push(Statement {
+            first,
+            last: index - 1,
+            key: table.iter().chain(key.iter()).cloned().collect(),
+            written_key: written_key.trim().to_string(),
+            comment,
+        });
+    }
+    (headers, statements)
+}
+
+/// `content` with the dotted `key` set to `value`. An existing statement for
+/// the key is replaced, keeping its indentation, spelling and comment;
+/// otherwise the key is added to the end of its table, which is added if it
+/// is missing. Comments and the other keys are left as they were.
+pub fn set_value(content: &str, key: &str, value: &Value) -> Result<String, String> {
+    parse(content)?;
+    let original: Vec<&str> = content.lines().collect();
+    let (headers, statements) = statements(&original);
+    let mut lines: Vec<String> = original.iter().map(|line| line.to_string()).collect();
+    let parts: Vec<String> = key.split('.').map(str::to_string).collect();
+
+    if let Some(statement) = statements.iter().find(|s| s.key == parts) {
+        let line = &lines[statement.first];
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let mut replacement = format!("{}{} = {}", indent, statement.written_key, value);
+        if let Some(comment) = &statement.comment {
+            replacement.push(' ');
+            replacement.push_str(comment);
+        }
+        lines.splice(statement.first..=statement.last, [replacement]);
+    } else {
+        let (table, leaf) = parts.split_at(parts.len().saturating_sub(1));
+        let leaf = leaf.join(".");
+        match headers.iter().position(|(_, header)| header == table) {
+            Some(position) => {
+                // After the table's last statement, before any blank lines that follow it
+                let start = headers[position].0;
+                let end = headers
+                    .get(position + 1)
+                    .map_or(lines.len(), |(line, _)| *line);
+                let after = statements
+                    .iter()
+                    .filter(|s| s.first > start && s.first < end)
+                    .map(|s| s.last + 1)
+                    .max()
+                    .unwrap_or(start + 1);
+                lines.insert(after, format!("{} = {}", leaf, value));
+            }
+            None => {
+                if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                    lines.push(String::new());
+                }
+                if !table.is_empty() {
+                    lines.push(format!("[{}]", table.join(".")));
+                }
+                lines.push(format!("{} = {}", leaf, value));
+            }
+        }
+    }
+
+    let mut edited = lines.join("\n");
+    edited.push('\n');
+    Ok(edited)
+}
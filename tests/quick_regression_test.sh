@@ -123,6 +123,18 @@ else
     ((FAILED++))
 fi
 
+# Flags that take a value must not leave the value to be read as the pattern
+cd /tmp/jcd_regression_test
+XDG_DATA_HOME=/tmp/jcd_regression_test/data jcd --rank depth=-5 --color never foo >/dev/null 2>&1
+current_dir=$(pwd)
+if [[ "$current_dir" == "/tmp/jcd_regression_test/foo" ]]; then
+    echo -e "${GREEN}✓ PASSED${NC} - Shell function passes flag values through"
+    ((PASSED++))
+else
+    echo -e "${RED}✗ FAILED${NC} - Expected /tmp/jcd_regression_test/foo, got $current_dir"
+    ((FAILED++))
+fi
+
 # Cleanup
 echo -e "\n=== Cleanup ==="
 rm -rf /tmp/jcd_regression_test
@@ -0,0 +1,305 @@
+//! Ranking of searches over in-memory trees: the priority tiers of the default
+//! weights, the direction weights of `--rank`, and the order of tied matches.
+//! Nothing here touches the disk or the user's history, so the order is the
+//! same on every machine.
+
+use jcd::{
+    config::Config,
+    filesystem::MemoryFileSystem,
+    history::{self, History},
+    prepare_search,
+    ranking::{RankWeights, TieBreak},
+    selections::Selections,
+    DirectoryMatch, MatchQuality, SearchFlags, SearchSession,
+};
+use std::{path::Path, sync::Arc};
+
+/// Settings that leave out everything outside the tree, such as $CDPATH
+fn settings() -> Config {
+    Config {
+        cdpath: false,
+        ..Config::default()
+    }
+}
+
+fn flags() -> SearchFlags {
+    SearchFlags {
+        quiet: true,
+        timeout_ms: Some(0), // No time budget, so a slow machine finds the same
+        ..SearchFlags::default()
+    }
+}
+
+/// A session searching `tree`, ranking by `weights` or the configured ones
+fn search_in<'a>(
+    flags: &'a SearchFlags,
+    settings: &'a Config,
+    tree: MemoryFileSystem,
+    weights: Option<RankWeights>,
+    history: History,
+) -> SearchSession<'a> {
+    let shortest_first = flags.shortest_first.unwrap_or(settings.shortest_first);
+    SearchSession {
+        flags,
+        settings,
+        rank_weights: weights.unwrap_or_else(|| {
+            RankWeights::configured(settings, flags.recency_boost, shortest_first)
+        }),
+        ignore_patterns: Vec::new(),
+        index: None,
+        history,
+        selections: Selections::default(),
+        pins: Vec::new(),
+        fs: Arc::new(tree),
+    }
+}
+
+fn find(session: &SearchSession, term: &str, from: &str) -> Vec<DirectoryMatch> {
+    let search = prepare_search(term, session.flags, Path::new(from), session.settings)
+        .expect("the term is valid");
+    session.find(search)
+}
+
+/// The matches of a search as paths below `from`, or whole paths outside it
+fn found(session: &SearchSession, term: &str, from: &str) -> Vec<String> {
+    find(session, term, from)
+        .iter()
+        .map(|m| match m.path.strip_prefix(from) {
+            Ok(below) => below.display().to_string(),
+            Err(_) => m.path.display().to_string(),
+        })
+        .collect()
+}
+
+/// Up and down matches of every kind from /api/rapids/here
+fn tiered_tree() -> MemoryFileSystem {
+    MemoryFileSystem::new()
+        .with_dir("/api/rapids/here/a/api")
+        .with_dir("/api/rapids/here/b/apix")
+        .with_dir("/api/rapids/here/c/my-api")
+        .with_dir("/api/rapids/here/d/alpha-pine-ice")
+        .with_dir("/api/rapids/here/e/rapid")
+}
+
+const HERE: &str = "/api/rapids/here";
+
+#[test]
+fn default_weights_rank_by_tier() {
+    let (flags, settings) = (flags(), settings());
+    let session = search_in(&flags, &settings, tiered_tree(), None, History::default());
+    let matches = find(&session, "api", HERE);
+    let qualities: Vec<MatchQuality> = matches.iter().map(|m| m.match_quality.clone()).collect();
+    assert_eq!(
+        qualities,
+        [
+            MatchQuality::ExactUp,
+            MatchQuality::PartialUp,
+            MatchQuality::ExactDown,
+            MatchQuality::PrefixDown,
+            MatchQuality::WordDown,
+            MatchQuality::AcronymDown,
+            MatchQuality::PartialDown,
+        ]
+    );
+    let tiers: Vec<u32> = session.explain(&matches).iter().map(|e| e.tier).collect();
+    assert!(
+        tiers.windows(2).all(|pair| pair[0] <= pair[1]),
+        "{:?}",
+        tiers
+    );
+}
+
+#[test]
+fn immediate_matches_rank_above_the_rest() {
+    let (flags, settings) = (flags(), settings());
+    let tree = tiered_tree().with_dir("/api/rapids/here/rapid-tools");
+    let session = search_in(&flags, &settings, tree, None, History::default());
+    assert_eq!(found(&session, "api", HERE)[0], "rapid-tools");
+}
+
+#[test]
+fn project_roots_then_nearer_matches_lead_a_tier() {
+    let (flags, settings) = (flags(), settings());
+    let tree = MemoryFileSystem::new()
+        .with_dir("/w/here/a/api")
+        .with_dir("/w/here/m/n/api")
+        .with_file("/w/here/x/api/Cargo.toml");
+    let session = search_in(&flags, &settings, tree, None, History::default());
+    assert_eq!(
+        found(&session, "api", "/w/here"),
+        ["x/api", "a/api", "m/n/api"]
+    );
+}
+
+#[test]
+fn usage_reorders_matches_within_their_tier_only() {
+    let (flags, settings) = (flags(), settings());
+    let mut visits = History::default();
+    let now = history::now_secs();
+    for _ in 0..100 {
+        visits.record(Path::new("/api/rapids/here/e/rapid"), now);
+    }
+    let tree = tiered_tree().with_dir("/api/rapids/here/f/api");
+    let session = search_in(&flags, &settings, tree, None, visits);
+    let order = found(&session, "api", HERE);
+    assert_eq!(order[2..4], ["a/api", "f/api"]);
+    assert_eq!(order.last().map(String::as_str), Some("e/rapid"));
+
+    let mut visits = History::default();
+    for _ in 0..100 {
+        visits.record(Path::new("/api/rapids/here/f/api"), now);
+    }
+    let tree = tiered_tree().with_dir("/api/rapids/here/f/api");
+    let session = search_in(&flags, &settings, tree, None, visits);
+    assert_eq!(found(&session, "api", HERE)[2..4], ["f/api", "a/api"]);
+}
+
+#[test]
+fn spelled_out_defaults_give_the_same_order() {
+    let (flags, settings) = (flags(), settings());
+    let defaults = "exact=700,prefix=600,word=500,acronym=400,partial=300,\
+                    up=700,immediate=1200,sibling=600,cousin=500,\
+                    depth=0,project=0,repo=0,recency=0,frecency=50";
+    let weights = RankWeights::for_search(&settings, false, false, defaults).unwrap();
+    let tree = tiered_tree().with_file("/api/rapids/here/c/my-api/go.mod");
+    let configured = search_in(&flags, &settings, tree.clone(), None, History::default());
+    let spelled = search_in(&flags, &settings, tree, Some(weights), History::default());
+    assert_eq!(
+        found(&configured, "api", HERE),
+        found(&spelled, "api", HERE)
+    );
+}
+
+#[test]
+fn direction_weights_move_matches_up_and_down() {
+    let (flags, settings) = (flags(), settings());
+    let weights = RankWeights::for_search(&settings, false, false, "up=-1000").unwrap();
+    let session = search_in(
+        &flags,
+        &settings,
+        tiered_tree(),
+        Some(weights),
+        History::default(),
+    );
+    let order = found(&session, "api", HERE);
+    assert_eq!(order[order.len() - 2..], ["/api", "/api/rapids"]);
+
+    // A sibling match outranks an immediate one once its weight is higher
+    let flags = SearchFlags {
+        siblings: Some(true),
+        ..flags
+    };
+    let tree = MemoryFileSystem::new()
+        .with_dir("/w/src/libs")
+        .with_dir("/w/lib");
+    let by_default = search_in(&flags, &settings, tree.clone(), None, History::default());
+    assert_eq!(found(&by_default, "lib", "/w/src"), ["libs", "/w/lib"]);
+    let weights = RankWeights::for_search(&settings, false, false, "sibling=1200").unwrap();
+    let raised = search_in(&flags, &settings, tree, Some(weights), History::default());
+    assert_eq!(found(&raised, "lib", "/w/src"), ["/w/lib", "libs"]);
+}
+
+#[test]
+fn depth_weight_prefers_nearer_matches_across_tiers() {
+    let (flags, settings) = (flags(), settings());
+    let tree = MemoryFileSystem::new()
+        .with_dir("/w/a/b/c/api")
+        .with_dir("/w/d/apix");
+    let by_default = search_in(&flags, &settings, tree.clone(), None, History::default());
+    assert_eq!(found(&by_default, "api", "/w"), ["a/b/c/api", "d/apix"]);
+    let weights = RankWeights::for_search(&settings, false, false, "depth=-60").unwrap();
+    let weighted = search_in(&flags, &settings, tree, Some(weights), History::default());
+    assert_eq!(found(&weighted, "api", "/w"), ["d/apix", "a/b/c/api"]);
+}
+
+/// Three exact matches at the same depth, none a project root
+fn tied_tree() -> MemoryFileSystem {
+    MemoryFileSystem::new()
+        .with_dir("/t/zz/svc")
+        .with_dir("/t/aaaa/svc")
+        .with_dir("/t/m/svc")
+}
+
+#[test]
+fn tied_matches_are_in_path_order() {
+    let (flags, settings) = (flags(), settings());
+    let session = search_in(&flags, &settings, tied_tree(), None, History::default());
+    assert_eq!(
+        found(&session, "svc", "/t"),
+        ["aaaa/svc", "m/svc", "zz/svc"]
+    );
+}
+
+#[test]
+fn tie_break_length_puts_shorter_paths_first() {
+    let flags = flags();
+    let settings = Config {
+        tie_break: TieBreak::Length,
+        ..settings()
+    };
+    let session = search_in(&flags, &settings, tied_tree(), None, History::default());
+    assert_eq!(
+        found(&session, "svc", "/t"),
+        ["m/svc", "zz/svc", "aaaa/svc"]
+    );
+}
+
+#[test]
+fn tie_break_recency_puts_the_latest_visit_first() {
+    let flags = flags();
+    let settings = Config {
+        tie_break: TieBreak::Recency,
+        frecency_weight: 0.0, // Only the tie-breaker tells the visited matches apart
+        ..settings()
+    };
+    let mut visits = History::default();
+    let now = history::now_secs();
+    visits.record(Path::new("/t/m/svc"), now - 60);
+    visits.record(Path::new("/t/zz/svc"), now);
+    let session = search_in(&flags, &settings, tied_tree(), None, visits);
+    assert_eq!(
+        found(&session, "svc", "/t"),
+        ["zz/svc", "m/svc", "aaaa/svc"]
+    );
+}
+
+#[test]
+fn numbers_in_tied_names_are_ordered_by_value() {
+    let (flags, settings) = (flags(), settings());
+    let tree = MemoryFileSystem::new()
+        .with_dir("/n/release-10")
+        .with_dir("/n/release-9")
+        .with_dir("/n/release-1");
+    let session = search_in(&flags, &settings, tree, None, History::default());
+    assert_eq!(
+        found(&session, "release", "/n"),
+        ["release-1", "release-9", "release-10"]
+    );
+}
+
+#[test]
+fn shortest_first_ranks_by_kind_then_path_components() {
+    let flags = SearchFlags {
+        shortest_first: Some(true),
+        ..flags()
+    };
+    let settings = settings();
+    let tree = MemoryFileSystem::new()
+        .with_dir("/mono/libs/api")
+        .with_file("/mono/services/billing/api/package.json")
+        .with_dir("/mono/services/billing/apis");
+    let session = search_in(&flags, &settings, tree.clone(), None, History::default());
+    assert_eq!(
+        found(&session, "api", "/mono"),
+        ["libs/api", "services/billing/api", "services/billing/apis"]
+    );
+    let by_tier = SearchFlags {
+        shortest_first: Some(false),
+        ..flags
+    };
+    let session = search_in(&by_tier, &settings, tree, None, History::default());
+    assert_eq!(
+        found(&session, "api", "/mono"),
+        ["services/billing/api", "libs/api", "services/billing/apis"]
+    );
+}
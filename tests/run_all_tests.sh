@@ -65,6 +65,14 @@ run_python_test() {
 # Change to the tests directory
 cd "$(dirname "$0")"
 
+# Keep the user's jcd history, index and daemon out of the tests (and the tests out of them)
+JCD_TEST_DATA_HOME="$(mktemp -d)"
+export XDG_DATA_HOME="$JCD_TEST_DATA_HOME/data"
+export XDG_CACHE_HOME="$JCD_TEST_DATA_HOME/cache"
+export XDG_RUNTIME_DIR="$JCD_TEST_DATA_HOME/run"
+unset CDPATH
+trap 'rm -rf "$JCD_TEST_DATA_HOME"' EXIT
+
 # Make sure all shell scripts are executable
 chmod +x *.sh 2>/dev/null
 
@@ -76,6 +84,29 @@ run_test "Simple Functionality Test" "./simple_test.sh"
 run_test "Comprehensive Relative Path Test" "./test_relative_comprehensive.sh"
 run_test "Ignore Functionality Test" "./test_ignore_functionality.sh"
 run_test "Validation Test" "./validate_jcd.sh"
+run_test "History Test" "./test_history.sh"
+run_test "Import Test" "./test_import.sh"
+run_test "Config Test" "./test_config.sh"
+run_test "Index Test" "./test_index.sh"
+run_test "Daemon Test" "./test_daemon.sh"
+run_test "Ignore Command Test" "./test_ignore_command.sh"
+run_test "Bookmark Test" "./test_bookmark.sh"
+run_test "Pin Test" "./test_pin.sh"
+run_test "Search Roots Test" "./test_roots.sh"
+run_test "Picker Test" "./test_picker.sh"
+run_test "Color Test" "./test_color.sh"
+run_test "Exit Status Test" "./test_exit_codes.sh"
+run_test "Batch Test" "./test_batch.sh"
+run_test "Serve Test" "./test_serve.sh"
+run_test "Init Test" "./test_init.sh"
+run_test "Completions Test" "./test_completions.sh"
+run_test "Man Page Test" "./test_manpage.sh"
+run_test "Logging Test" "./test_logging.sh"
+run_test "Explain Test" "./test_explain.sh"
+run_test "Stats Test" "./test_stats.sh"
+run_test "Matching Test" "./test_matching.sh"
+run_test "Doctor Test" "./test_doctor.sh"
+run_test "Bench Test" "./test_bench.sh"
 
 # Regression and bug fix tests
 run_test "Quick Regression Test" "./quick_regression_test.sh"
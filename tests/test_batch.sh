@@ -0,0 +1,131 @@
+#!/bin/bash
+
+# Test for JCD batch searches
+# Verifies that --stdin prints one answer per input line, in order, and loads
+# the ignore files once for the whole batch, and that --live lists the matches
+# of each query from a tree read into memory once
+
+echo "=== JCD Batch Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_batch_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's settings, cached results and history out of the test
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export XDG_DATA_HOME="$TEST_DIR/data"
+
+mkdir -p "$TEST_DIR"/tree/{src/api,work/tools,docs}
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test 1: One answer per line ===${NC}"
+check "Each pattern prints its best match, in input order" \
+    "$TEST_DIR/tree/work/tools|$TEST_DIR/tree/src/api|$TEST_DIR/tree/docs" \
+    "$(printf 'tools\napi\ndocs\n' | "$JCD_BIN" --stdin | paste -sd '|')"
+check "A pattern without matches prints an empty line" \
+    "$TEST_DIR/tree/src/api||$TEST_DIR/tree/docs" \
+    "$(printf 'api\nnothing_here\ndocs\n' | "$JCD_BIN" --stdin | paste -sd '|')"
+check "Empty and malformed patterns keep their place" \
+    "3|$TEST_DIR/tree/docs" \
+    "$(printf '\n(\ndocs\n' | "$JCD_BIN" --stdin -E 2>/dev/null | wc -l | tr -d ' ')|$(printf '\n(\ndocs\n' | "$JCD_BIN" --stdin -E 2>/dev/null | tail -1)"
+check "The last line needs no newline" \
+    "$TEST_DIR/tree/docs" \
+    "$(printf 'docs' | "$JCD_BIN" --stdin)"
+
+echo -e "\n${BLUE}=== Test 2: Formats and exit status ===${NC}"
+check "--json prints null for a pattern without matches" \
+    "exact_down|null" \
+    "$(printf 'api\nnothing_here\n' | "$JCD_BIN" --stdin --json | sed 's/.*"quality":"\([a-z_]*\)".*/\1/' | paste -sd '|')"
+check "-0 ends every answer with a NUL byte" \
+    "2" \
+    "$(printf 'api\nnothing_here\n' | "$JCD_BIN" --stdin -0 | tr -cd '\0' | wc -c | tr -d ' ')"
+check "Every pattern found: exit status 0" \
+    "0" \
+    "$(printf 'api\ndocs\n' | "$JCD_BIN" --stdin >/dev/null; echo $?)"
+check "Any pattern not found: exit status 1" \
+    "1" \
+    "$(printf 'api\nnothing_here\n' | "$JCD_BIN" --stdin >/dev/null; echo $?)"
+
+echo -e "\n${BLUE}=== Test 3: One process for the batch ===${NC}"
+check "Ignore files are loaded once for every pattern" \
+    "1" \
+    "$(printf 'api\ndocs\ntools\n' | JCD_DEBUG=1 "$JCD_BIN" --stdin 2>&1 >/dev/null | grep -c 'No ignore file found\|ignore patterns in effect')"
+printf 'api\ndocs\n' | "$JCD_BIN" --stdin --record >/dev/null
+check "--record records every answer" \
+    "2" \
+    "$(grep -cE "$TEST_DIR/tree/(src/api|docs)$" "$XDG_DATA_HOME/jcd/history.db")"
+check "A pattern on the command line is rejected" \
+    "Error: --stdin and --live read the patterns from standard input, not the command line" \
+    "$("$JCD_BIN" --stdin api </dev/null 2>&1)"
+check "--list cannot be combined with --stdin" \
+    "2" \
+    "$("$JCD_BIN" --stdin --list </dev/null >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test 4: Live queries ===${NC}"
+check "Each query lists its matches and ends with an empty line" \
+    "$TEST_DIR/tree/docs||$TEST_DIR/tree/src/api|" \
+    "$(printf 'docs\napi\n' | "$JCD_BIN" --live | paste -sd '|')"
+check "With --json each query gets one array" \
+    "2|[]" \
+    "$(printf 'api\nnothing_here\n' | "$JCD_BIN" --live --json | wc -l | tr -d ' ')|$(printf 'api\nnothing_here\n' | "$JCD_BIN" --live --json | tail -1)"
+check "Half-typed malformed queries list nothing" \
+    "|$TEST_DIR/tree/docs|" \
+    "$(printf 'doc(\ndocs\n' | "$JCD_BIN" --live -E 2>/dev/null | paste -sd '|')"
+check "Later queries are answered from the tree in memory" \
+    "Downward search answered from the index" \
+    "$( (echo api; sleep 1; echo tools) | JCD_DEBUG=1 "$JCD_BIN" --live 2>&1 >/dev/null | sed -n '/Read .* directories into memory/,$p' | grep -m1 -o 'Downward search answered from the index')"
+check "--live cannot be combined with --list" \
+    "2" \
+    "$("$JCD_BIN" --live --list </dev/null >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All batch tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some batch tests failed.${NC}"
+    exit 1
+fi
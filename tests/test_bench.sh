@@ -0,0 +1,133 @@
+#!/bin/bash
+
+# Test for jcd bench
+# Verifies that a pattern is timed cold, warm and from several threads by
+# walking the tree and through the index, with latency percentiles, the
+# matches and the directories listed, and that bad arguments are refused
+
+echo "=== JCD Bench Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+# Not named after the subcommand, so that "jcd bench" finds only the directory below
+TEST_DIR="/tmp/jcd_timing_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's settings, history, index and daemon out of the test; the
+# index and config live outside the tree so that indexing it does not change it
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CONFIG_HOME="$TEST_DIR/xdg"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export XDG_DATA_HOME="$TEST_DIR/data"
+export XDG_RUNTIME_DIR="$TEST_DIR/run"
+
+mkdir -p "$TEST_DIR"/tree/{src/api,lib/api/v1,docs/guide/deep,docs/bench} "$XDG_RUNTIME_DIR"
+cd "$TEST_DIR/tree" || exit 1
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+# strategy, mode and threads of each row of the table
+rows() {
+    "$JCD_BIN" bench "$@" | awk '$2 == "cold" || $2 == "warm" { print $1, $2, $3 }' | paste -sd '|'
+}
+
+check "The header names the pattern, directory and runs" \
+    "Benchmark of 'api' from $TEST_DIR/tree, 3 runs per thread" \
+    "$("$JCD_BIN" bench walk api --runs 3 --threads 2 | head -n 1)"
+check "The table has percentile columns" \
+    "strategy mode threads p50 p90 p99 max matches dirs" \
+    "$("$JCD_BIN" bench walk api --runs 3 | sed -n 3p | tr -s ' ')"
+check "The walk is timed cold, warm and from several threads" \
+    "walk cold 1|walk warm 1|walk warm 2" \
+    "$(rows walk api --runs 3 --threads 2)"
+check "One thread leaves out the parallel row" \
+    "walk cold 1|walk warm 1" \
+    "$(rows walk api --runs 3 --threads 1)"
+check "Each row counts the matches and the directories listed" \
+    "2 10" \
+    "$("$JCD_BIN" bench walk api --runs 2 --threads 1 | awk '$2 == "warm" { print $(NF-1), $NF }')"
+check "Percentiles never decrease" \
+    "ok" \
+    "$("$JCD_BIN" bench walk api --runs 20 --threads 1 | awk '$2 == "warm" { print ($4 <= $6 && $6 <= $8 && $8 <= $10) ? "ok" : "out of order" }')"
+
+check "Without an index, all benchmarks the walk only" \
+    "walk cold 1|walk warm 1" \
+    "$(rows all api --runs 2 --threads 1)"
+check "Without an index, all says why it was skipped" \
+    "index: skipped, no daemon is running and there is no fresh index (jcd index build)" \
+    "$("$JCD_BIN" bench all api --runs 2 --threads 1 | tail -n 1)"
+check "Without an index, bench index fails" \
+    "1" \
+    "$("$JCD_BIN" bench index api --runs 2 >/dev/null 2>&1; echo $?)"
+
+echo "index.roots = [\"$TEST_DIR/tree\"]" > "$JCD_CONFIG"
+"$JCD_BIN" index build >/dev/null
+check "With an index, all benchmarks both" \
+    "walk cold 1|walk warm 1|index cold 1|index warm 1" \
+    "$(rows all api --runs 2 --threads 1)"
+check "The index finds the same matches without listing the tree" \
+    "2 0" \
+    "$("$JCD_BIN" bench index api --runs 2 --threads 1 | awk '$2 == "warm" { print $(NF-1), $NF }')"
+check "The warm medians are compared" \
+    "Warm median: index" \
+    "$("$JCD_BIN" bench all api --runs 2 --threads 1 | tail -n 1 | cut -c1-18)"
+
+check "--runs must be positive" \
+    "Error: --runs must be a positive number" \
+    "$("$JCD_BIN" bench walk api --runs 0 2>&1)"
+check "A pattern is required" \
+    "Usage: jcd bench all|walk|index <pattern> [--runs <n>] [--threads <n>]" \
+    "$("$JCD_BIN" bench walk --runs 2 2>&1)"
+check "A lone bench is a search" \
+    "$TEST_DIR/tree/docs/bench" \
+    "$("$JCD_BIN" bench)"
+check "So is bench with an index" \
+    "$TEST_DIR/tree/docs/bench" \
+    "$("$JCD_BIN" bench 0)"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All bench tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some bench tests failed.${NC}"
+    exit 1
+fi
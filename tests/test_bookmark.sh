@@ -0,0 +1,112 @@
+#!/bin/bash
+
+# Test for JCD named bookmarks
+# Verifies `jcd bookmark add/rm/list` and that @name terms resolve to bookmarks first
+
+echo "=== JCD Bookmark Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_bookmark_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the bookmark file inside the test directory
+export XDG_DATA_HOME="$TEST_DIR/data"
+BOOKMARKS="$XDG_DATA_HOME/jcd/bookmarks"
+
+mkdir -p "$TEST_DIR"/tree/{work/api,docs,@types/node,gone}
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test 1: Adding bookmarks ===${NC}"
+(cd work/api && "$JCD_BIN" bookmark add api >/dev/null)
+"$JCD_BIN" bookmark add docs "$TEST_DIR/tree/docs" >/dev/null
+"$JCD_BIN" bookmark add gone gone >/dev/null
+check "Bookmark file is created" \
+    "present" \
+    "$([ -f "$BOOKMARKS" ] && echo present || echo missing)"
+check "Current directory is bookmarked by default" \
+    "$TEST_DIR/tree/work/api" \
+    "$(grep "^api	" "$BOOKMARKS" | cut -f2)"
+check "Invalid names are rejected" \
+    "1" \
+    "$("$JCD_BIN" bookmark add 'a/b' >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test 2: Resolving @name ===${NC}"
+check "@name jumps to the bookmark" \
+    "$TEST_DIR/tree/docs" \
+    "$(cd / && "$JCD_BIN" @docs 0)"
+check "Bookmark names match by prefix" \
+    "$TEST_DIR/tree/work/api" \
+    "$(cd / && "$JCD_BIN" @ap 0)"
+check "Bookmarks rank above directory matches" \
+    "$TEST_DIR/tree/docs" \
+    "$("$JCD_BIN" --list @docs | head -n 1)"
+check "Unknown names fall back to a directory search" \
+    "$TEST_DIR/tree/@types" \
+    "$("$JCD_BIN" @types 0)"
+
+echo -e "\n${BLUE}=== Test 3: Listing and removing ===${NC}"
+rmdir gone
+check "Missing directories are marked in the list" \
+    "1" \
+    "$("$JCD_BIN" bookmark list | grep -c "@gone .*(missing)")"
+check "Bookmarks to missing directories do not match" \
+    "1" \
+    "$(cd / && "$JCD_BIN" @gone 0 >/dev/null 2>&1; echo $?)"
+"$JCD_BIN" bookmark rm docs >/dev/null
+check "Removed bookmark is gone from the file" \
+    "" \
+    "$(grep "^docs	" "$BOOKMARKS")"
+check "Removing an unknown bookmark fails" \
+    "1" \
+    "$("$JCD_BIN" bookmark rm docs >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All bookmark tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some bookmark tests failed.${NC}"
+    exit 1
+fi
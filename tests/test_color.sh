@@ -0,0 +1,197 @@
+#!/bin/bash
+
+# Test for JCD --color, NO_COLOR and the busy indicator
+# Checks that decorations appear on terminals only, unless asked for or turned off
+
+echo "=== JCD Color Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_color_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's settings, cached results and history out of the test
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export XDG_DATA_HOME="$TEST_DIR/data"
+unset NO_COLOR JCD_MATCH_STYLE JCD_PATH_STYLE
+
+mkdir -p "$TEST_DIR"/tree/{alpha/app,lib/app} "$TEST_DIR/fzf"
+
+# Stands in for fzf and records how it was started
+cat > "$TEST_DIR/fzf/fzf" <<EOF
+#!/bin/sh
+echo "\$*" > "$TEST_DIR/fzf-args"
+tee "$TEST_DIR/fzf-input" | head -1 | sed 's/\x1b\[[0-9;]*m//g'
+EOF
+chmod +x "$TEST_DIR/fzf/fzf"
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+# Lines of --list output that carry escape sequences
+escapes() {
+    grep -c "$(printf '\033')"
+}
+
+# Run jcd with a terminal for stdout and stderr, when `script` is available
+on_terminal() {
+    local command="'$JCD_BIN'"
+    local arg
+    for arg in "$@"; do
+        command+=" '$arg'"
+    done
+    script -qc "$command" /dev/null | tr -d '\r'
+}
+
+cd "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test 1: Pipes ===${NC}"
+check "--list into a pipe has no escape sequences" \
+    "0" \
+    "$("$JCD_BIN" --list app | escapes)"
+check "--color=always highlights a pipe too" \
+    "2" \
+    "$("$JCD_BIN" --list --color=always app | escapes)"
+check "--color always is the same as --color=always" \
+    "2" \
+    "$("$JCD_BIN" --list --color always app | escapes)"
+check "NO_COLOR does not override --color=always" \
+    "2" \
+    "$(NO_COLOR=1 "$JCD_BIN" --list --color=always app | escapes)"
+check "No busy indicator on a stderr that is not a terminal" \
+    "0" \
+    "$(JCD_PROGRESS_DELAY_MS=0 "$JCD_BIN" app 2>&1 >/dev/null | escapes)"
+check "An unknown --color value is refused" \
+    "2|yes" \
+    "$("$JCD_BIN" --color=sometimes app >/dev/null 2>&1; echo $?)|$("$JCD_BIN" --color sometimes app 2>&1 | grep -q "auto, always or never" && echo yes || echo no)"
+
+if command -v script >/dev/null 2>&1; then
+    echo -e "\n${BLUE}=== Test 2: Terminals ===${NC}"
+    check "--list on a terminal is highlighted" \
+        "2" \
+        "$(on_terminal --list app | escapes)"
+    check "NO_COLOR turns highlighting off" \
+        "0" \
+        "$(export NO_COLOR=1; on_terminal --list app | escapes)"
+    check "An empty NO_COLOR does not" \
+        "2" \
+        "$(export NO_COLOR=; on_terminal --list app | escapes)"
+    check "--color=never turns highlighting off" \
+        "0" \
+        "$(on_terminal --list --color=never app | escapes)"
+fi
+
+echo -e "\n${BLUE}=== Test 3: Picker ===${NC}"
+JCD_PICKER="$TEST_DIR/fzf/fzf" "$JCD_BIN" --fzf app >/dev/null
+check "fzf gets colored candidates and --ansi" \
+    "--ansi|2" \
+    "$(cat "$TEST_DIR/fzf-args")|$(escapes < "$TEST_DIR/fzf-input")"
+JCD_PICKER="$TEST_DIR/fzf/fzf" NO_COLOR=1 "$JCD_BIN" --fzf app >/dev/null
+check "With NO_COLOR fzf gets bare paths" \
+    "|0" \
+    "$(cat "$TEST_DIR/fzf-args")|$(escapes < "$TEST_DIR/fzf-input")"
+JCD_PICKER="$TEST_DIR/fzf/fzf" "$JCD_BIN" --fzf --color=never app >/dev/null
+check "With --color=never fzf gets bare paths" \
+    "|0" \
+    "$(cat "$TEST_DIR/fzf-args")|$(escapes < "$TEST_DIR/fzf-input")"
+
+echo -e "\n${BLUE}=== Test 4: Busy indicator ===${NC}"
+# Enough directories that the search is still running when the indicator starts
+for a in $(seq 1 20); do
+    for b in $(seq 1 20); do
+        mkdir -p "$TEST_DIR/wide/d$a/e$b/f/g"
+    done
+done
+# Run a slow search and keep what it draws on stderr. On a single CPU the
+# search may finish before the indicator gets to run, so a few attempts are made.
+indicator_output() {
+    local attempt
+    for attempt in 1 2 3 4 5; do
+        (cd "$TEST_DIR/wide" && JCD_PROGRESS_DELAY_MS=0 "$JCD_BIN" --color=always "$@" nothing-here 2>"$TEST_DIR/indicator" >/dev/null)
+        [ -s "$TEST_DIR/indicator" ] && break
+    done
+}
+# The first frame drawn
+indicator() {
+    indicator_output "$@"
+    tr '\r' '\n' < "$TEST_DIR/indicator" | grep -a . | head -1
+}
+check "The indicator counts the directories listed and the depth reached" \
+    "yes" \
+    "$(indicator | grep -Eq "searching… [0-9,]+ dirs, depth [0-9]+" && echo yes || echo no)"
+check "The indicator line is cleared at the end" \
+    "0d1b5b4b" \
+    "$(indicator_output; tail -c 4 "$TEST_DIR/indicator" | od -An -tx1 | tr -d ' \n')"
+
+check "The spinner style turns a bar in front of the counter" \
+    "yes" \
+    "$(JCD_PROGRESS_STYLE=spinner indicator | grep -Eq '^.\[K[|/\\-] searching… [0-9,]+ dirs' && echo yes || echo no)"
+check "The dots style draws dots without figures" \
+    "yes" \
+    "$(JCD_PROGRESS_STYLE=dots indicator | grep -Eq '^.\[K \.+$' && echo yes || echo no)"
+check "The none style draws nothing" \
+    "" \
+    "$(JCD_PROGRESS_STYLE=none indicator)"
+check "--no-progress draws nothing" \
+    "" \
+    "$(indicator --no-progress)"
+check "--no-progress still reports errors" \
+    "yes" \
+    "$("$JCD_BIN" --no-progress --from "$TEST_DIR/missing" app 2>&1 | grep -q Error && echo yes || echo no)"
+check "An unknown style is reported" \
+    "yes" \
+    "$(JCD_PROGRESS_STYLE=fireworks "$JCD_BIN" app 2>&1 >/dev/null | grep -q "counter, spinner, dots or none" && echo yes || echo no)"
+
+started=$(date +%s%N)
+JCD_PROGRESS_DELAY_MS=3000 "$JCD_BIN" --color=always app >/dev/null 2>&1
+elapsed_ms=$(( ($(date +%s%N) - started) / 1000000 ))
+check "A quick search does not wait out the indicator delay" \
+    "yes" \
+    "$( (( elapsed_ms < 1500 )) && echo yes || echo "no (${elapsed_ms} ms)")"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All color tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some color tests failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,148 @@
+#!/bin/bash
+
+# Test for the JCD command-line completion generator
+# Verifies that `jcd completions` completes flags, subcommands, their verbs and
+# bookmark names, and hands directory patterns to the shell function
+
+echo "=== JCD Completions Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_completions_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's settings, cached results and history out of the test
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export XDG_DATA_HOME="$TEST_DIR/data"
+unset JCD_BINARY
+
+mkdir -p "$TEST_DIR"/tree/{alpha/target,beta}
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+BIN_PATH="$(cd "$(dirname "$JCD_BIN")" && pwd)/$(basename "$JCD_BIN")"
+
+# Complete the words of a command line, the last one being completed, and
+# print the candidates on one line. Runs bash with jcd's function and the
+# command-line completion loaded, the way an interactive shell would.
+complete_line() {
+    (cd "$TEST_DIR/tree" && bash -c '
+        eval "$("$0" init bash)"
+        eval "$("$0" completions bash)"
+        COMP_WORDS=("$@")
+        COMP_CWORD=$(( $# - 1 ))
+        _jcd_cli_complete
+        echo "${COMPREPLY[*]}"' "$BIN_PATH" "$@" 2>/dev/null)
+}
+
+echo -e "\n${BLUE}=== Test 1: Registration ===${NC}"
+check "The completion is registered for jcd" \
+    "complete -F _jcd_cli_complete jcd" \
+    "$(bash -c 'eval "$("$0" completions bash)"; complete -p jcd' "$BIN_PATH")"
+check "--cmd registers it for another name" \
+    "complete -F _jcd_cli_complete j" \
+    "$(bash -c 'eval "$("$0" completions bash --cmd j)"; complete -p j' "$BIN_PATH")"
+check "The script calls the binary that printed it" \
+    "1" \
+    "$("$JCD_BIN" completions bash | grep -cF "\${JCD_BINARY:-'$BIN_PATH'}")"
+check "The zsh script registers its completion" \
+    "1" \
+    "$("$JCD_BIN" completions zsh | grep -c '^    compdef _jcd_cli jcd$')"
+check "Unknown shells print the usage" \
+    "Usage: jcd completions bash|zsh [--cmd <name>]" \
+    "$("$JCD_BIN" completions bash --shell 2>&1)"
+
+echo -e "\n${BLUE}=== Test 2: Flags and subcommands ===${NC}"
+check "Flags are completed" \
+    "--regex --recent --record --report-errors" \
+    "$(complete_line jcd --re)"
+check "Subcommand names are completed" \
+    "history" \
+    "$(complete_line jcd hist)"
+check "The verbs of a subcommand are completed" \
+    "list prune" \
+    "$(complete_line jcd history '')"
+check "The options of a verb are completed" \
+    "--sort" \
+    "$(complete_line jcd history list --s)"
+check "Option values are completed" \
+    "recent" \
+    "$(complete_line jcd history list --sort re)"
+check "--rank completes weight names" \
+    "depth=" \
+    "$(complete_line jcd --rank dep)"
+check "config set completes config keys" \
+    "index.max_depth index.max_age_secs" \
+    "$(complete_line jcd config set index.max)"
+
+echo -e "\n${BLUE}=== Test 3: Bookmarks ===${NC}"
+"$JCD_BIN" bookmark add proj "$TEST_DIR/tree/alpha" >/dev/null
+"$JCD_BIN" bookmark add work "$TEST_DIR/tree/beta" >/dev/null
+check "bookmark rm completes bookmark names" \
+    "proj work" \
+    "$(complete_line jcd bookmark rm '')"
+check "@ completes bookmark names" \
+    "@work" \
+    "$(complete_line jcd @w)"
+
+echo -e "\n${BLUE}=== Test 4: Directory patterns ===${NC}"
+check "Other words are left to the jcd function's completion" \
+    "$TEST_DIR/tree/alpha/target/" \
+    "$(complete_line jcd targ)"
+
+echo -e "\n${BLUE}=== Test 5: zsh ===${NC}"
+if command -v zsh >/dev/null 2>&1; then
+    check "The script parses in zsh" \
+        "ok" \
+        "$("$JCD_BIN" completions zsh | zsh -n && echo ok)"
+else
+    echo "zsh not installed; skipping"
+fi
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All completions tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some completions tests failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,278 @@
+#!/bin/bash
+
+# Test for the JCD configuration file (config.toml)
+# Verifies that search tunables are read from the file and overridden by the environment
+
+echo "=== JCD Config Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_config_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Point jcd at a config file and result cache inside the test directory
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+unset JCD_MAX_MATCHES JCD_TIMEOUT_MS JCD_MAX_DEPTH JCD_GITIGNORE JCD_SAME_FILESYSTEM JCD_SMART_CASE JCD_CACHE_TTL_SECS JCD_RANK_FRECENCY JCD_HISTORY_DECAY JCD_HISTORY_HALF_LIFE_HOURS JCD_HISTORY_MAX_AGE JCD_HISTORY_EXCLUDE JCD_PROGRESS_DELAY_MS
+
+mkdir -p "$TEST_DIR"/tree/a/b/c/deeptarget
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test 1: Defaults without a config file ===${NC}"
+check "Directory four levels down is found" \
+    "$TEST_DIR/tree/a/b/c/deeptarget" \
+    "$("$JCD_BIN" deeptarget 0 2>&1)"
+
+echo -e "\n${BLUE}=== Test 2: Settings from config.toml ===${NC}"
+cat > "$JCD_CONFIG" << 'TOML'
+# Keep the downward search shallow
+[search]
+max_depth = 2 # levels below the current directory
+TOML
+check "max_depth from the file limits the search" \
+    "" \
+    "$("$JCD_BIN" deeptarget 0 2>&1)"
+
+echo -e "\n${BLUE}=== Test 3: Environment overrides ===${NC}"
+check "JCD_MAX_DEPTH takes precedence over the file" \
+    "$TEST_DIR/tree/a/b/c/deeptarget" \
+    "$(JCD_MAX_DEPTH=6 "$JCD_BIN" deeptarget 0 2>&1)"
+
+echo -e "\n${BLUE}=== Test 4: Invalid configuration ===${NC}"
+cat > "$JCD_CONFIG" << 'TOML'
+[search
+max_depth = 2
+TOML
+check "A malformed file is reported and ignored" \
+    "yes" \
+    "$("$JCD_BIN" deeptarget 0 2>&1 >/dev/null | grep -q "Warning: Ignoring config file" && echo yes || echo no)"
+check "Search still uses the defaults" \
+    "$TEST_DIR/tree/a/b/c/deeptarget" \
+    "$("$JCD_BIN" deeptarget 0 2>/dev/null)"
+check "An invalid environment value is reported" \
+    "yes" \
+    "$(JCD_MAX_DEPTH=deep "$JCD_BIN" deeptarget 0 2>&1 >/dev/null | grep -q "Warning: JCD_MAX_DEPTH" && echo yes || echo no)"
+cat > "$JCD_CONFIG" << 'TOML'
+[search]
+timeout_ms = nan
+max_depth = 2
+[ranking]
+frecency = inf
+TOML
+check "inf and nan are refused where a number is expected" \
+    "Warning: $JCD_CONFIG: 'ranking.frecency' must be a finite number, found inf|Warning: $JCD_CONFIG: 'search.timeout_ms' must be an integer, found NaN|2" \
+    "$("$JCD_BIN" config get search.max_depth 2>&1 | paste -sd '|')"
+rm -f "$JCD_CONFIG"
+check "So are spellings TOML does not have" \
+    "Warning: JCD_TIMEOUT_MS: 'search.timeout_ms' must be an integer, found string|500" \
+    "$(JCD_TIMEOUT_MS=Infinity "$JCD_BIN" config get search.timeout_ms 2>&1 | paste -sd '|')"
+
+echo -e "\n${BLUE}=== Test 5: --timeout flag ===${NC}"
+rm -f "$JCD_CONFIG"
+check "--timeout 0 (unlimited) still finds path patterns" \
+    "$TEST_DIR/tree/a/b/c/deeptarget" \
+    "$("$JCD_BIN" --timeout 0 c/deeptarget 0 2>&1)"
+check "--timeout overrides JCD_TIMEOUT_MS" \
+    "$TEST_DIR/tree/a/b/c/deeptarget" \
+    "$(JCD_TIMEOUT_MS=1 "$JCD_BIN" --timeout 5000 c/deeptarget 0 2>&1)"
+check "An invalid --timeout is rejected" \
+    "yes" \
+    "$("$JCD_BIN" --timeout soon deeptarget 0 2>&1 >/dev/null | grep -q "Error: --timeout" && echo yes || echo no)"
+
+echo -e "\n${BLUE}=== Test 6: Match limit ===${NC}"
+mkdir -p "$TEST_DIR"/many/match{01..25}
+cd "$TEST_DIR/many"
+check "At most 20 matches are listed by default" \
+    "20" \
+    "$("$JCD_BIN" --list match 2>/dev/null | wc -l | tr -d ' ')"
+check "--max-matches raises the limit" \
+    "25" \
+    "$("$JCD_BIN" --list --max-matches 50 match 2>/dev/null | wc -l | tr -d ' ')"
+check "--max-matches 1 lists only the best match" \
+    "$TEST_DIR/many/match01" \
+    "$("$JCD_BIN" --list --max-matches 1 match 2>/dev/null)"
+check "JCD_MAX_MATCHES applies too" \
+    "3" \
+    "$(JCD_MAX_MATCHES=3 "$JCD_BIN" --list match 2>/dev/null | wc -l | tr -d ' ')"
+mkdir -p "$TEST_DIR"/early/{a,b,c}/target "$TEST_DIR"/early/a/deep/deeper/target
+cd "$TEST_DIR/early"
+check "Enough exact matches end the walk below" \
+    "4" \
+    "$("$JCD_BIN" --list --stats --max-matches 3 target 2>&1 >/dev/null | sed -n 's/ *Directories visited: *//p')"
+check "A walk ended by enough exact matches is not cut short" \
+    "0" \
+    "$("$JCD_BIN" --list --max-matches 3 target >/dev/null 2>&1; echo $?)"
+check "Without enough exact matches the walk goes deeper" \
+    "$TEST_DIR/early/a/deep/deeper/target" \
+    "$("$JCD_BIN" --list --max-matches 4 target 2>/dev/null | tail -n 1)"
+cd "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test 7: Smart case ===${NC}"
+mkdir -p "$TEST_DIR"/cases/Projects
+cd "$TEST_DIR/cases"
+check "Matching is case-sensitive by default" \
+    "" \
+    "$("$JCD_BIN" proj 2>/dev/null)"
+check "--smart-case ignores case for a lowercase pattern" \
+    "$TEST_DIR/cases/Projects" \
+    "$("$JCD_BIN" --smart-case proj 2>/dev/null)"
+check "--smart-case respects case once the pattern has an uppercase letter" \
+    "" \
+    "$("$JCD_BIN" --smart-case PROJ 2>/dev/null)"
+printf '[search]\nsmart_case = true\n' > "$JCD_CONFIG"
+check "search.smart_case makes it the default" \
+    "$TEST_DIR/cases/Projects" \
+    "$("$JCD_BIN" proj 2>/dev/null)"
+check "--no-smart-case overrides the config" \
+    "" \
+    "$("$JCD_BIN" --no-smart-case proj 2>/dev/null)"
+check "-i still ignores case for uppercase patterns" \
+    "$TEST_DIR/cases/Projects" \
+    "$("$JCD_BIN" -i PROJ 2>/dev/null)"
+rm -f "$JCD_CONFIG"
+cd "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test 8: Result cache ===${NC}"
+mkdir -p "$TEST_DIR"/results/x/item_b "$TEST_DIR"/results/y
+cd "$TEST_DIR/results"
+check "The first match is searched for" \
+    "$TEST_DIR/results/x/item_b" \
+    "$("$JCD_BIN" item 0 2>/dev/null)"
+mkdir "$TEST_DIR/results/y/item_c"
+check "A later match within the TTL comes from the cached search" \
+    "" \
+    "$("$JCD_BIN" item 1 2>/dev/null)"
+check "search.cache_ttl_secs = 0 searches again" \
+    "$TEST_DIR/results/y/item_c" \
+    "$(JCD_CACHE_TTL_SECS=0 "$JCD_BIN" item 1 2>/dev/null)"
+"$JCD_BIN" item 0 >/dev/null 2>&1
+mkdir "$TEST_DIR/results/z"
+check "A change to the directory invalidates the cache" \
+    "$TEST_DIR/results/y/item_c" \
+    "$("$JCD_BIN" item 1 2>/dev/null)"
+cd "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test 9: jcd config ===${NC}"
+rm -f "$JCD_CONFIG"
+check "config path names the file set edits" \
+    "$JCD_CONFIG" \
+    "$("$JCD_BIN" config path)"
+check "config show lists every setting with its default" \
+    "search.max_depth = 8                # default" \
+    "$("$JCD_BIN" config show | grep '^search.max_depth')"
+mkdir -p "$TEST_DIR/tree/config"
+check "A lone config is a search" \
+    "$TEST_DIR/tree/config" \
+    "$("$JCD_BIN" config)"
+rmdir "$TEST_DIR/tree/config"
+check "config show lists the index settings too" \
+    "index.max_age_secs = 86400          # default" \
+    "$("$JCD_BIN" config show | grep '^index.max_age_secs')"
+cat > "$JCD_CONFIG" << 'TOML'
+# Keep the downward search shallow
+[search]
+max_depth = 2 # levels below the current directory
+
+[history]
+exclude = [
+    "/tmp",
+]
+TOML
+check "config show names the file a value came from" \
+    "$JCD_CONFIG" \
+    "$("$JCD_BIN" config show | sed -n 's/^search.max_depth = 2 *# //p')"
+check "config show names the variable a value came from" \
+    "JCD_MAX_DEPTH" \
+    "$(JCD_MAX_DEPTH=5 "$JCD_BIN" config show | sed -n 's/^search.max_depth = 5 *# //p')"
+check "config get prints a value" \
+    "2" \
+    "$("$JCD_BIN" config get search.max_depth)"
+check "config get prints a string without quotes" \
+    "buckets" \
+    "$("$JCD_BIN" config get history.decay)"
+check "config get prints a list as TOML" \
+    '["/tmp"]' \
+    "$("$JCD_BIN" config get history.exclude)"
+check "config get refuses an unknown key" \
+    "Error: Unknown config key 'search.depth' (see 'jcd config show')" \
+    "$("$JCD_BIN" config get search.depth 2>&1)"
+check "config set replaces a value and keeps its comment" \
+    "Set search.max_depth = 6 in $JCD_CONFIG|max_depth = 6 # levels below the current directory" \
+    "$("$JCD_BIN" config set search.max_depth 6)|$(grep '^max_depth' "$JCD_CONFIG")"
+check "The new value takes effect" \
+    "$TEST_DIR/tree/a/b/c/deeptarget" \
+    "$("$JCD_BIN" deeptarget 0 2>&1)"
+"$JCD_BIN" config set search.timeout_ms 900 >/dev/null
+"$JCD_BIN" config set history.decay half-life >/dev/null
+"$JCD_BIN" config set history.exclude '["/tmp", "re:^/mnt"]' >/dev/null
+"$JCD_BIN" config set ui.progress_delay_ms 50 >/dev/null
+check "config set adds keys to their tables and new tables at the end" \
+    "# Keep the downward search shallow|[search]|max_depth = 6 # levels below the current directory|timeout_ms = 900||[history]|exclude = [\"/tmp\", \"re:^/mnt\"]|decay = \"half-life\"||[ui]|progress_delay_ms = 50" \
+    "$(paste -sd '|' "$JCD_CONFIG")"
+check "config set refuses an invalid value and leaves the file alone" \
+    "Error: 'search.max_depth' must be an integer, found string|6" \
+    "$("$JCD_BIN" config set search.max_depth deep 2>&1)|$("$JCD_BIN" config get search.max_depth)"
+check "config set refuses an unknown key" \
+    "1" \
+    "$("$JCD_BIN" config set search.depth 3 >/dev/null 2>&1; echo $?)"
+check "config set refuses malformed TOML" \
+    "Error: Invalid value '[1': expected ',' or ']' in array" \
+    "$("$JCD_BIN" config set search.roots '[1' 2>&1)"
+check "config set warns when a variable overrides the key" \
+    "Warning: JCD_MAX_DEPTH is set and overrides this setting" \
+    "$(JCD_MAX_DEPTH=3 "$JCD_BIN" config set search.max_depth 7 2>&1 >/dev/null)"
+rm -f "$JCD_CONFIG"
+check "config set creates the file" \
+    "[search]|smart_case = true" \
+    "$("$JCD_BIN" config set search.smart_case true >/dev/null; paste -sd '|' "$JCD_CONFIG")"
+rm -f "$JCD_CONFIG"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All config tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some config tests failed.${NC}"
+    exit 1
+fi
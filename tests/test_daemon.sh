@@ -0,0 +1,139 @@
+#!/bin/bash
+
+# Test for the JCD background daemon
+# Verifies that searches use the daemon and that filesystem changes reach its index
+
+echo "=== JCD Daemon Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_daemon_test_$$"
+
+cleanup() {
+    "$JCD_BIN" daemon stop >/dev/null 2>&1
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the socket, index and config inside the test directory
+export XDG_RUNTIME_DIR="$TEST_DIR/run"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export JCD_CONFIG="$TEST_DIR/config.toml"
+
+mkdir -p "$TEST_DIR"/tree/{alpha/deep/target,beta/other}
+cat > "$JCD_CONFIG" << TOML
+[index]
+roots = ["$TEST_DIR/tree"]
+TOML
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/tree"
+
+uses_daemon() {
+    JCD_DEBUG=1 "$JCD_BIN" "$1" 0 2>&1 | grep -q "Using jcd daemon" && echo yes || echo no
+}
+
+# Give the daemon a moment to process inotify events
+wait_for() {
+    local pattern="$1"
+    local expected="$2"
+    for _ in $(seq 1 40); do
+        [[ "$("$JCD_BIN" "$pattern" 0 2>/dev/null)" == "$expected" ]] && return
+        sleep 0.05
+    done
+}
+
+echo -e "\n${BLUE}=== Test 1: Starting the daemon ===${NC}"
+"$JCD_BIN" daemon start >/dev/null
+check "daemon status reports a running daemon" \
+    "0" \
+    "$("$JCD_BIN" daemon status >/dev/null; echo $?)"
+check "Search returns the indexed directory" \
+    "$TEST_DIR/tree/alpha/deep/target" \
+    "$("$JCD_BIN" target 0)"
+check "Search is answered by the daemon" \
+    "yes" \
+    "$(uses_daemon target)"
+check "The daemon listens on \$XDG_RUNTIME_DIR/jcd.sock" \
+    "yes" \
+    "$([[ -S "$XDG_RUNTIME_DIR/jcd.sock" ]] && echo yes || echo no)"
+mkdir -p "$TEST_DIR/tree/beta/tab"$'\t'"name" "$TEST_DIR/tree/beta/line"$'\n'"break"
+wait_for "line"$'\n'"break" "$TEST_DIR/tree/beta/line"$'\n'"break"
+check "Names with tabs and newlines come back from the daemon intact" \
+    "$TEST_DIR/tree/beta/tab"$'\t'"name|$TEST_DIR/tree/beta/line"$'\n'"break" \
+    "$("$JCD_BIN" "tab"$'\t'"name" 0)|$("$JCD_BIN" "line"$'\n'"break" 0)"
+
+echo -e "\n${BLUE}=== Test 2: Following changes ===${NC}"
+mkdir -p "$TEST_DIR/tree/beta/fresh/nested"
+wait_for nested "$TEST_DIR/tree/beta/fresh/nested"
+check "A new directory tree is found" \
+    "$TEST_DIR/tree/beta/fresh/nested" \
+    "$("$JCD_BIN" nested 0)"
+check "The new directory is answered by the daemon" \
+    "yes" \
+    "$(JCD_DEBUG=1 "$JCD_BIN" nested 0 2>&1 | grep -q "answered from the index" && echo yes || echo no)"
+mv "$TEST_DIR/tree/alpha/deep/target" "$TEST_DIR/tree/beta/moved"
+wait_for moved "$TEST_DIR/tree/beta/moved"
+check "A renamed directory is found under its new name" \
+    "$TEST_DIR/tree/beta/moved" \
+    "$("$JCD_BIN" moved 0)"
+check "The old name is gone" \
+    "" \
+    "$("$JCD_BIN" --list target 2>/dev/null)"
+
+echo -e "\n${BLUE}=== Test 3: Stopping the daemon ===${NC}"
+"$JCD_BIN" daemon stop >/dev/null
+check "daemon status reports no daemon" \
+    "1" \
+    "$("$JCD_BIN" daemon status >/dev/null; echo $?)"
+check "Searches still work without the daemon" \
+    "$TEST_DIR/tree/beta/moved" \
+    "$("$JCD_BIN" moved 0)"
+: > "$XDG_RUNTIME_DIR/jcd.sock"
+check "A socket nobody answers on is ignored" \
+    "$TEST_DIR/tree/beta/moved|no" \
+    "$("$JCD_BIN" moved 0)|$(uses_daemon moved)"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All daemon tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some daemon tests failed.${NC}"
+    exit 1
+fi
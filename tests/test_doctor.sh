@@ -0,0 +1,211 @@
+#!/bin/bash
+
+# Test for jcd doctor
+# Verifies that each check reports a healthy installation as ok and points out
+# a missing shell setup, bad config values, ignore lines that have no effect,
+# a damaged history, a stale daemon socket and an unreadable index, with a fix
+
+echo "=== JCD Doctor Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_doctor_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's startup files, settings, history, index and daemon out of the test
+export HOME="$TEST_DIR/home"
+export SHELL=/bin/bash
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CONFIG_HOME="$TEST_DIR/xdg"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export XDG_DATA_HOME="$TEST_DIR/data"
+export XDG_RUNTIME_DIR="$TEST_DIR/run"
+export JCD_BINARY="$JCD_BIN"
+unset _JCD_SHELL _JCD_FUNCTION_REVISION
+
+mkdir -p "$HOME" "$XDG_CONFIG_HOME/jcd" "$XDG_CACHE_HOME/jcd" "$XDG_DATA_HOME/jcd" "$XDG_RUNTIME_DIR"
+cd "$TEST_DIR" || exit 1
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+# First line of a check's report with this status, e.g. "PROBLEM  Ignore: ..."
+finding() {
+    "$JCD_BIN" doctor "$1" | grep -m1 "^$2"
+}
+
+# A fresh installation: nothing configured, nothing recorded
+echo 'eval "$(jcd init bash)"' > "$HOME/.bashrc"
+check "A fresh installation has no problems" \
+    "No problems found" \
+    "$("$JCD_BIN" doctor all | tail -n 1)"
+check "A fresh installation exits 0" \
+    "0" \
+    "$("$JCD_BIN" doctor all >/dev/null; echo $?)"
+mkdir -p "$TEST_DIR/tree/doctor"
+check "A lone doctor is a search" \
+    "$TEST_DIR/tree/doctor" \
+    "$(cd "$TEST_DIR/tree" && "$JCD_BIN" doctor)"
+check "The startup line is found" \
+    "ok       Shell: $HOME/.bashrc loads jcd with 'eval \"\$(jcd init bash)\"'" \
+    "$(finding shell ok)"
+check "Without a config file the defaults are used" \
+    "ok       Config: no config file, using the defaults" \
+    "$(finding config ok)"
+
+# Shell setup
+rm "$HOME/.bashrc"
+check "A missing startup line is a problem" \
+    "PROBLEM  Shell: no bash startup file loads jcd" \
+    "$(finding shell PROBLEM)"
+check "The fix appends the init line" \
+    "         Fix: echo 'eval \"\$(jcd init bash)\"' >> $HOME/.bashrc, then open a new shell" \
+    "$("$JCD_BIN" doctor shell | grep -m1 'Fix:')"
+check "A missing startup line exits 1" \
+    "1" \
+    "$("$JCD_BIN" doctor shell >/dev/null; echo $?)"
+echo "source ~/old/jcd_function.sh" > "$HOME/.bashrc"
+check "A sourced script that is gone is a problem" \
+    "PROBLEM  Shell: $HOME/.bashrc sources $HOME/old/jcd_function.sh: No such file or directory (os error 2)" \
+    "$(finding shell PROBLEM)"
+mkdir -p "$HOME/old"
+echo "jcd() { :; }" > "$HOME/old/jcd_function.sh"
+check "A sourced script from another version is a warning" \
+    "warning  Shell: $HOME/.bashrc sources $HOME/old/jcd_function.sh, which differs from the function of this jcd" \
+    "$(finding shell warning)"
+cp "$PROJECT_ROOT/src/jcd_function.sh" "$HOME/old/jcd_function.sh"
+check "The sourced script of this version is ok" \
+    "ok       Shell: $HOME/.bashrc sources $HOME/old/jcd_function.sh" \
+    "$(finding shell ok)"
+check "The loaded bash function is current" \
+    "ok       Shell: the bash function is loaded and current" \
+    "$(bash -c "source '$PROJECT_ROOT/src/jcd_function.sh'; jcd doctor shell" | grep -m1 '^ok')"
+check "A function loaded from an older jcd is a warning" \
+    "warning  Shell: the loaded bash function is revision 0, this jcd ships revision 19" \
+    "$(_JCD_SHELL=bash _JCD_FUNCTION_REVISION=0 "$JCD_BIN" doctor shell | grep -m1 '^warning')"
+check "JCD_BINARY naming a missing file is a problem" \
+    "PROBLEM  Shell: JCD_BINARY names $TEST_DIR/missing, which does not exist" \
+    "$(JCD_BINARY="$TEST_DIR/missing" "$JCD_BIN" doctor shell | grep -m1 '^PROBLEM')"
+
+# Config
+printf 'search.max_depth = "deep"\nsearch.max_dept = 4\n' > "$JCD_CONFIG"
+check "A rejected value is a problem" \
+    "PROBLEM  Config: $JCD_CONFIG: 'search.max_depth' must be an integer, found string" \
+    "$(finding config PROBLEM)"
+check "An unknown key is a warning" \
+    "warning  Config: $JCD_CONFIG: unknown key 'search.max_dept' has no effect" \
+    "$(finding config warning)"
+check "Config problems are not also printed as warnings on stderr" \
+    "" \
+    "$("$JCD_BIN" doctor config 2>&1 >/dev/null)"
+echo "search.max_depth = 4" > "$JCD_CONFIG"
+check "A valid config file is ok" \
+    "ok       Config: $JCD_CONFIG read without problems" \
+    "$(finding config ok)"
+
+# Ignore files
+printf 'node_modules\nsrc/build\n' > "$XDG_CONFIG_HOME/jcd/ignore"
+printf 'syntax: regex\n^target$\n[abc\n' > "$TEST_DIR/.jcdignore"
+check "A glob with a slash is reported with its line" \
+    "PROBLEM  Ignore: $XDG_CONFIG_HOME/jcd/ignore line 2 'src/build': globs match single directory names and cannot contain '/'" \
+    "$(finding ignore PROBLEM)"
+check "An invalid regex is reported with its line" \
+    "PROBLEM  Ignore: $TEST_DIR/.jcdignore line 3 '[abc': invalid regex: unclosed character class" \
+    "$("$JCD_BIN" doctor ignore | grep '^PROBLEM' | tail -n 1)"
+rm "$TEST_DIR/.jcdignore"
+echo "node_modules" > "$XDG_CONFIG_HOME/jcd/ignore"
+check "Clean ignore files are ok" \
+    "ok       Ignore: 1 patterns from 1 files" \
+    "$(finding ignore ok)"
+
+# History
+check "No history yet is ok" \
+    "ok       History: no visits recorded yet ($XDG_DATA_HOME/jcd/history.db)" \
+    "$(finding history ok)"
+mkdir -p "$TEST_DIR/visited"
+printf '3\t100\t%s\n2\t100\t%s\nnot an entry\n' "$TEST_DIR/visited" "$TEST_DIR/gone" > "$XDG_DATA_HOME/jcd/history.db"
+check "Malformed history lines are a warning" \
+    "warning  History: $XDG_DATA_HOME/jcd/history.db has malformed lines, which are skipped: 3" \
+    "$(finding history warning)"
+check "Entries for missing directories are a warning" \
+    "warning  History: 1 of 2 entries are for directories that no longer exist" \
+    "$("$JCD_BIN" doctor history | grep '^warning' | tail -n 1)"
+"$JCD_BIN" history prune >/dev/null
+check "The suggested prune leaves a healthy history" \
+    "ok       History: 1 entries in $XDG_DATA_HOME/jcd/history.db" \
+    "$(finding history ok)"
+
+# Daemon and index
+check "No daemon and no index roots is ok" \
+    "ok       Daemon: not running|ok       Index: not used (index.roots is not set)" \
+    "$("$JCD_BIN" doctor index | grep '^ok' | paste -sd '|')"
+touch "$XDG_RUNTIME_DIR/jcd.sock"
+check "A socket nothing answers on is a warning" \
+    "warning  Daemon: $XDG_RUNTIME_DIR/jcd.sock exists but nothing answers on it" \
+    "$(finding index warning)"
+rm "$XDG_RUNTIME_DIR/jcd.sock"
+echo "index.roots = [\"$TEST_DIR\"]" > "$JCD_CONFIG"
+check "Index roots without an index is a warning" \
+    "warning  Index: index.roots is set, but no index has been built" \
+    "$(finding index warning)"
+echo "garbage" > "$XDG_CACHE_HOME/jcd/index.db"
+check "An unreadable index is a problem" \
+    "PROBLEM  Index: $XDG_CACHE_HOME/jcd/index.db is not in the expected format" \
+    "$(finding index PROBLEM)"
+"$JCD_BIN" index build >/dev/null
+check "A fresh index is ok" \
+    "ok" \
+    "$(finding index 'ok       Index' | cut -c1-2)"
+
+check "A single word is still a search" \
+    "$TEST_DIR/home" \
+    "$("$JCD_BIN" home 0)"
+check "An unknown check prints the usage" \
+    "Usage: jcd doctor all | shell | config | ignore | history | index" \
+    "$("$JCD_BIN" doctor all extra 2>&1)"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All doctor tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some doctor tests failed.${NC}"
+    exit 1
+fi
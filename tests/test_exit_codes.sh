@@ -0,0 +1,161 @@
+#!/bin/bash
+
+# Test for the JCD exit statuses
+# Verifies that no match, usage errors, timeouts and I/O errors exit with
+# their own status, that searches cut short by a limit say so, and that the
+# bash function reports each outcome
+
+echo "=== JCD Exit Status Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_exit_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's settings, cached results and history out of the test
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export XDG_DATA_HOME="$TEST_DIR/data"
+unset JCD_BINARY
+
+mkdir -p "$TEST_DIR"/tree/{alpha/target,beta}
+# Enough directories that a 1 ms time budget runs out
+for a in $(seq 1 20); do
+    for b in $(seq 1 20); do
+        mkdir -p "$TEST_DIR/wide/d$a/e$b/f/g"
+    done
+done
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+# The exit status of jcd run in the test tree
+status() {
+    (cd "$TEST_DIR/tree" && "$JCD_BIN" "$@" >/dev/null 2>&1)
+    echo $?
+}
+
+BIN_PATH="$(cd "$(dirname "$JCD_BIN")" && pwd)/$(basename "$JCD_BIN")"
+
+# Run the bash function in `dir` and print its output, status and final directory
+function_run() {
+    local dir="$1"
+    shift
+    (cd "$dir" && bash -c 'eval "$("$0" init bash)"; jcd "$@" 2>&1; echo "$?|$(pwd)"' "$BIN_PATH" "$@")
+}
+
+echo -e "\n${BLUE}=== Test 1: Searches ===${NC}"
+check "A match exits 0" "0" "$(status targ)"
+check "No match exits 1" "1" "$(status nothing-here)"
+check "No match with --list exits 1" "1" "$(status --list nothing-here)"
+check "A time budget that runs out exits 3" \
+    "3" \
+    "$(cd "$TEST_DIR/wide" && JCD_TIMEOUT_MS=1 "$JCD_BIN" g/nothing-here >/dev/null 2>&1; echo $?)"
+check "A time budget that runs out stops a downward search too" \
+    "3" \
+    "$(cd "$TEST_DIR/wide" && JCD_TIMEOUT_MS=1 "$JCD_BIN" nothing-here >/dev/null 2>&1; echo $?)"
+check "--timeout 0 searches to the end" \
+    "1" \
+    "$(cd "$TEST_DIR/wide" && "$JCD_BIN" --timeout 0 g/nothing-here >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test 2: Usage errors ===${NC}"
+check "An invalid regex exits 2" "2" "$(status -E 'a(')"
+check "A bad --color value exits 2" "2" "$(status --color=sometimes targ)"
+check "A missing option value exits 2" "2" "$(status targ --timeout)"
+check "Subcommand usage errors exit 2" "2" "$(status bookmark add)"
+check "A subcommand that fails exits 1" "1" "$(status bookmark rm no-such-bookmark)"
+
+echo -e "\n${BLUE}=== Test 3: I/O errors ===${NC}"
+check "A picker that cannot be started exits 4" \
+    "4" \
+    "$(JCD_PICKER="$TEST_DIR/no-such-picker" status --fzf targ)"
+
+echo -e "\n${BLUE}=== Test 4: Partial results ===${NC}"
+# Twenty directories match e7/f; a path pattern stops looking at the match limit
+check "A search stopped at the match limit exits 3" \
+    "3" \
+    "$(cd "$TEST_DIR/wide" && "$JCD_BIN" --max-matches 3 e7/f >/dev/null 2>&1; echo $?)"
+check "A search that found every match below the limit exits 0" \
+    "0" \
+    "$(cd "$TEST_DIR/wide" && "$JCD_BIN" --max-matches 50 e7/f >/dev/null 2>&1; echo $?)"
+check "A search stopped at the match limit says so on stderr" \
+    "Warning: the search stopped at 3 matches and may have missed better ones (--max-matches raises the limit)" \
+    "$(cd "$TEST_DIR/wide" && "$JCD_BIN" --max-matches 3 e7/f 2>&1 >/dev/null)"
+check "A search that ran out of time says so on stderr" \
+    "Warning: the search stopped after 1 ms and may have missed matches (--timeout 0 removes the limit)" \
+    "$(cd "$TEST_DIR/wide" && JCD_TIMEOUT_MS=1 "$JCD_BIN" g/nothing-here 2>&1 >/dev/null)"
+check "--quiet leaves the warning out" \
+    "" \
+    "$(cd "$TEST_DIR/wide" && "$JCD_BIN" --quiet --max-matches 3 e7/f 2>&1 >/dev/null)"
+check "JSON matches of a search cut short are marked partial" \
+    "3|3" \
+    "$(cd "$TEST_DIR/wide" && "$JCD_BIN" --list --json --max-matches 3 e7/f 2>/dev/null | grep -o '"partial":true' | wc -l | tr -d ' ')|$(cd "$TEST_DIR/wide" && "$JCD_BIN" --list --json --max-matches 3 e7/f >/dev/null 2>&1; echo $?)"
+check "JSON matches of a complete search are not" \
+    "0" \
+    "$(cd "$TEST_DIR/wide" && "$JCD_BIN" --list --json --max-matches 50 e7/f 2>/dev/null | grep -c '"partial"')"
+check "--stats reports a partial search" \
+    "yes" \
+    "$(cd "$TEST_DIR/wide" && "$JCD_BIN" --stats --json --max-matches 3 e7/f 2>&1 >/dev/null | grep -q '"partial":true' && echo yes || echo no)"
+
+echo -e "\n${BLUE}=== Test 5: Shell function ===${NC}"
+check "The function moves to a match" \
+    "0|$TEST_DIR/tree/alpha/target" \
+    "$(function_run "$TEST_DIR/tree" targ | tail -1)"
+check "The function reports no match" \
+    "No directories found matching 'nothing-here'|1|$TEST_DIR/tree" \
+    "$(function_run "$TEST_DIR/tree" nothing-here | tr '\n' '|' | sed 's/|$//')"
+check "The function leaves usage errors to jcd and passes on the status" \
+    "yes|2" \
+    "$(output=$(function_run "$TEST_DIR/tree" -E 'a('); grep -q "Invalid regex" <<< "$output" && echo -n yes || echo -n no; echo "|$(tail -1 <<< "$output" | cut -d'|' -f1)")"
+check "The function reports a search that stopped without a match" \
+    "yes|3" \
+    "$(output=$(JCD_TIMEOUT_MS=1 function_run "$TEST_DIR/wide" g/nothing-here); grep -q "before the search stopped" <<< "$output" && echo -n yes || echo -n no; echo "|$(tail -1 <<< "$output" | cut -d'|' -f1)")"
+check "The function moves to the best match of a search cut short" \
+    "yes" \
+    "$(cd "$TEST_DIR/wide" && bash -c 'eval "$("$0" init bash)"; jcd --max-matches 1 e7/f >/dev/null 2>&1; pwd' "$BIN_PATH" | grep -q '/e7/f$' && echo yes || echo no)"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All exit status tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some exit status tests failed.${NC}"
+    exit 1
+fi
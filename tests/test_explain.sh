@@ -0,0 +1,289 @@
+#!/bin/bash
+
+# Test for jcd --explain
+# Verifies that every match is listed with its tier, depth, usage points and
+# score, and that the reason given for its place follows the weighted ranking:
+# the priority tiers of the default weights, visits in the history, or --rank
+# weights
+
+echo "=== JCD Explain Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_explain_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's settings, cached results and history out of the test
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export XDG_DATA_HOME="$TEST_DIR/data"
+unset JCD_RECENT JCD_DEBUG JCD_LOG
+
+mkdir -p "$TEST_DIR"/tree/{src/api,lib/api,other/deep/api,misc/rapid}
+touch "$TEST_DIR/tree/src/api/Cargo.toml"
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/tree"
+
+# The explanation of the match printed under its path, e.g. `reason other/deep/api`
+reason() {
+    "$JCD_BIN" --explain "${@:2}" api | grep -A2 "/$1\$" | sed -n 3p | sed 's/^ *//'
+}
+
+echo -e "\n${BLUE}=== Test 1: Priority tiers ===${NC}"
+check "Every match is listed in ranked order" \
+    "src/api lib/api other/deep/api misc/rapid" \
+    "$("$JCD_BIN" --explain api | grep '^ *[0-9]*\. ' | sed "s|.*$TEST_DIR/tree/||" | tr '\n' ' ' | sed 's/ $//')"
+check "The list follows the order of --list" \
+    "$("$JCD_BIN" --list api | tr '\n' ' ')" \
+    "$("$JCD_BIN" --explain api | grep '^ *[0-9]*\. ' | sed 's/^ *[0-9]*\. //' | tr '\n' ' ')"
+check "Each match shows its tier, depth and score" \
+    "tier 12 (exact down), depth 2, project root, frecency +0.0, recency +0.0, score 700.0" \
+    "$("$JCD_BIN" --explain api | sed -n 2p | sed 's/^ *//')"
+check "The first match is the best" \
+    "best match" \
+    "$(reason src/api)"
+check "A project root ranks first among equals" \
+    "not a project root" \
+    "$(reason lib/api)"
+check "Then the nearer match" \
+    "deeper" \
+    "$(reason other/deep/api)"
+check "A lower tier says which tiers it compares" \
+    "lower tier: partial down after exact down" \
+    "$(reason misc/rapid)"
+check "Nothing found is an error" \
+    "1|0" \
+    "$("$JCD_BIN" --explain nowhere >/dev/null; echo $?)|$("$JCD_BIN" --explain nowhere | wc -l | tr -d ' ')"
+check "--explain lists matches itself" \
+    "2" \
+    "$("$JCD_BIN" --explain --list api >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test 2: Usage ===${NC}"
+"$JCD_BIN" --add "$TEST_DIR/tree/other/deep/api"
+check "A visited directory moves up within its tier" \
+    "other/deep/api" \
+    "$("$JCD_BIN" --explain api | sed -n 1p | sed "s|.*$TEST_DIR/tree/||")"
+check "Its frecency points are shown" \
+    "yes" \
+    "$("$JCD_BIN" --explain api | sed -n 2p | grep -q 'frecency +[1-9]' && echo yes || echo no)"
+check "Matches it overtook have fewer usage points" \
+    "yes" \
+    "$(reason src/api | grep -q '^same tier, fewer usage points (0.0 < [1-9]' && echo yes || echo no)"
+check "Usage does not lift a match out of its tier" \
+    "lower tier: partial down after exact down" \
+    "$(reason misc/rapid)"
+
+echo -e "\n${BLUE}=== Test 3: Weights ===${NC}"
+check "--rank names the weight that cost the most" \
+    "lower score (685.0 < 690.0), mostly the depth weight (-5.0)" \
+    "$(reason other/deep/api --rank depth=-5,frecency=0)"
+check "Equal weighted scores keep the search order" \
+    "not a project root" \
+    "$(reason lib/api --rank depth=-5,frecency=0)"
+check "ranking.weights ranks every search by the weighted score" \
+    "lower score (685.0 < 690.0), mostly the depth weight (-5.0)" \
+    "$(JCD_RANK_WEIGHTS=depth=-5,frecency=0 reason other/deep/api)"
+check "--rank overrides ranking.weights" \
+    "lower score (694.0 < 696.0), mostly the depth weight (-2.0)" \
+    "$(JCD_RANK_WEIGHTS=depth=-5,frecency=0 reason other/deep/api --rank depth=-2)"
+check "An unknown weight in ranking.weights is reported" \
+    "yes" \
+    "$(JCD_RANK_WEIGHTS=deep=-5 "$JCD_BIN" api 2>&1 >/dev/null | grep -q "Unknown ranking weight 'deep'" && echo yes || echo no)"
+# From src, its subdirectory libs is an immediate prefix match and ../lib an exact sibling
+mkdir -p "$TEST_DIR/tree/src/libs"
+siblings() {
+    (cd "$TEST_DIR/tree/src" && "$JCD_BIN" --siblings "$@" lib 0 | sed "s|$TEST_DIR/tree/||")
+}
+check "The default weights keep sibling matches below immediate ones" \
+    "src/libs|src/libs" \
+    "$(siblings)|$(siblings --rank frecency=0)"
+check "The sibling weight scores matches next to the current directory" \
+    "lib" \
+    "$(siblings --rank sibling=1200)"
+rmdir "$TEST_DIR/tree/src/libs"
+# A well-used project root with a word match, and a prefix match
+mkdir -p "$TEST_DIR"/words/{a/my-api,b/apix}
+touch "$TEST_DIR/words/a/my-api/Cargo.toml"
+printf '100\t%s\t%s\n' "$(date +%s)" "$TEST_DIR/words/a/my-api" >> "$XDG_DATA_HOME/jcd/history.db"
+words() {
+    (cd "$TEST_DIR/words" && "$JCD_BIN" --list "$@" api | sed "s|$TEST_DIR/words/||" | tr '\n' ' ' | sed 's/ $//')
+}
+check "Usage and project roots do not lift a match into a better tier" \
+    "b/apix a/my-api|b/apix a/my-api" \
+    "$(words --recent)|$(words --recent --rank depth=0)"
+
+echo -e "\n${BLUE}=== Test 4: JSON ===${NC}"
+check "--json adds the explanation to each match object" \
+    "{\"path\":\"$TEST_DIR/tree/misc/rapid\",\"quality\":\"partial_down\",\"depth\":2,\"rank\":3,\"tier\":16,\"tier_name\":\"partial down\",\"project_root\":false,\"same_repo\":false,\"frecency\":0.00,\"recency\":0.00,\"boost\":1,\"score\":300.00,\"reason\":\"lower tier: partial down after exact down\"}" \
+    "$("$JCD_BIN" --explain --json api | grep -o '{[^}]*misc/rapid[^}]*}')"
+check "Nothing found is an empty array" \
+    "[]" \
+    "$("$JCD_BIN" --explain --json nowhere)"
+
+echo -e "\n${BLUE}=== Test 5: Tie-breaking ===${NC}"
+# Three exact matches at the same depth, none a project root: all tied
+mkdir -p "$TEST_DIR"/ties/{zz,aaaa,m}/svc
+ties() {
+    (cd "$TEST_DIR/ties" && "$JCD_BIN" --list "$@" svc | sed "s|$TEST_DIR/ties/||; s|/svc||" | tr '\n' ' ' | sed 's/ $//')
+}
+check "Tied matches are in path order by default" \
+    "aaaa m zz" \
+    "$(ties)"
+check "ranking.tie_break = length puts shorter paths first" \
+    "m zz aaaa" \
+    "$(JCD_RANK_TIE_BREAK=length ties)"
+check "--explain names the tie-breaker" \
+    "tie, longer path" \
+    "$(cd "$TEST_DIR/ties" && JCD_RANK_TIE_BREAK=length "$JCD_BIN" --explain svc | grep -A2 '/aaaa/svc$' | sed -n 3p | sed 's/^ *//')"
+check "What the tie-breaker leaves equal is in path order" \
+    "aaaa m zz" \
+    "$(JCD_RANK_TIE_BREAK=components ties)"
+check "The order is the same every time" \
+    "m zz aaaa|m zz aaaa|m zz aaaa" \
+    "$(JCD_RANK_TIE_BREAK=length ties)|$(JCD_RANK_TIE_BREAK=length ties)|$(JCD_RANK_TIE_BREAK=length ties)"
+"$JCD_BIN" --add "$TEST_DIR/ties/m/svc"
+sleep 1
+"$JCD_BIN" --add "$TEST_DIR/ties/zz/svc"
+check "ranking.tie_break = recency puts the latest visit first" \
+    "zz m aaaa" \
+    "$(JCD_RANK_TIE_BREAK=recency ties)"
+mkdir -p "$TEST_DIR"/numbered/{release-10,release-9,release-1,v10,v2}
+numbered() {
+    (cd "$TEST_DIR/numbered" && "$JCD_BIN" --list "$@" | sed "s|$TEST_DIR/numbered/||" | tr '\n' ' ' | sed 's/ $//')
+}
+check "Numbers in tied names are ordered by value" \
+    "release-1 release-9 release-10|v2 v10" \
+    "$(numbered release)|$(numbered v)"
+check "So are names the tie-breaker leaves equal" \
+    "release-1 release-9 release-10" \
+    "$(JCD_RANK_TIE_BREAK=length numbered release)"
+check "An unknown tie-breaker is reported" \
+    "yes" \
+    "$(JCD_RANK_TIE_BREAK=random ties 2>&1 | grep -q "alphabetical, length, components or recency" && echo yes || echo no)"
+
+echo -e "\n${BLUE}=== Test 6: Shortest path first ===${NC}"
+# A project root deep down outranks a plain directory nearer the top,
+# and an immediate subdirectory outranks a parent
+mkdir -p "$TEST_DIR"/mono/{libs/api,services/billing/api/v1/api,services/billing/apis}
+touch "$TEST_DIR/mono/services/billing/api/package.json"
+mono() {
+    (cd "$TEST_DIR/mono/$1" && "$JCD_BIN" --list "${@:2}" api | sed "s|$TEST_DIR/mono/||" | tr '\n' ' ' | sed 's/ $//')
+}
+check "The tiers put the nested project root first" \
+    "services/billing/api libs/api services/billing/api/v1/api services/billing/apis" \
+    "$(mono .)"
+check "--shortest-first puts fewer path components first" \
+    "libs/api services/billing/api services/billing/api/v1/api services/billing/apis" \
+    "$(mono . --shortest-first)"
+check "ranking.shortest_first does the same" \
+    "libs/api services/billing/api services/billing/api/v1/api services/billing/apis" \
+    "$(JCD_RANK_SHORTEST_FIRST=true mono .)"
+check "A parent with fewer components beats an immediate subdirectory" \
+    "services/billing/api/v1/api services/billing/api|services/billing/api services/billing/api/v1/api" \
+    "$(mono services/billing/api/v1)|$(mono services/billing/api/v1 --shortest-first)"
+depth=$(echo -n "$TEST_DIR" | tr -cd / | wc -c) # Components of $TEST_DIR, less the root
+check "--explain names the path components" \
+    "more path components ($(( depth + 5 )) > $(( depth + 4 )))" \
+    "$(cd "$TEST_DIR/mono" && "$JCD_BIN" --explain --shortest-first api | grep -A2 '/billing/api$' | sed -n 3p | sed 's/^ *//')"
+check "--explain names a worse match kind" \
+    "lower match kind: prefix after exact" \
+    "$(cd "$TEST_DIR/mono" && "$JCD_BIN" --explain --shortest-first api | grep -A2 '/apis$' | sed -n 3p | sed 's/^ *//')"
+
+echo -e "\n${BLUE}=== Test 7: Boosts ===${NC}"
+mkdir -p "$TEST_DIR"/boost/{mine/api,lib/vendor/api,other/apix}
+boost() {
+    (cd "$TEST_DIR/boost" && "$JCD_BIN" --list "$@" api | sed "s|$TEST_DIR/boost/||" | tr '\n' ' ' | sed 's/ $//')
+}
+check "Without boosts the tiers decide" \
+    "mine/api lib/vendor/api other/apix" \
+    "$(boost)"
+check "A factor below 1 lowers matches below a glob" \
+    "mine/api other/apix lib/vendor/api" \
+    "$(JCD_RANK_BOOST='**/vendor=0.5' boost)"
+check "A factor above 1 lifts matches out of their tier" \
+    "other/apix mine/api lib/vendor/api" \
+    "$(JCD_RANK_BOOST="$TEST_DIR/boost/other=2.5" boost)"
+check "Factors apply to --rank scores too" \
+    "lib/vendor/api mine/api other/apix" \
+    "$(JCD_RANK_BOOST="re:/lib/=2" boost --rank depth=-5)"
+check "--explain shows the factor and names it as the reason" \
+    "tier 12 (exact down), depth 3, boost x0.5, frecency +0.0, recency +0.0, score 350.0|lower score (350.0 < 600.0), boosted less (x0.5 < x1)" \
+    "$(cd "$TEST_DIR/boost" && JCD_RANK_BOOST='**/vendor=0.5' "$JCD_BIN" --explain api | grep -A2 '/vendor/api$' | sed -n '2,3p' | sed 's/^ *//' | paste -sd '|')"
+check "A factor must be above 0" \
+    "Warning: JCD_RANK_BOOST: 'ranking.boost': Invalid factor for boost '**/vendor', expected a number above 0" \
+    "$(cd "$TEST_DIR/boost" && JCD_RANK_BOOST='**/vendor=0' "$JCD_BIN" api 2>&1 >/dev/null)"
+
+echo -e "\n${BLUE}=== Test 8: Default weights ===${NC}"
+# From inside a repository named like the pattern: a partial match up, exact
+# matches below that are visited, in a nested repository, or project roots,
+# and prefix, word and partial matches with and without visits
+mkdir -p "$TEST_DIR"/rapids/{.git,work/{a/api,b/api,c/.git,c/api,d/deep/api,e/apix,f/apix,g/my-api,h/rapid,i/rapid,j/.git,j/api}}
+touch "$TEST_DIR/rapids/work/b/api/Cargo.toml" "$TEST_DIR/rapids/work/f/apix/package.json" "$TEST_DIR/rapids/work/g/my-api/go.mod"
+for visits in 100:d/deep/api 30:e/apix 5:c/api 100:i/rapid; do
+    printf '%s\t%s\t%s\n' "${visits%%:*}" "$(date +%s)" "$TEST_DIR/rapids/work/${visits#*:}" >> "$XDG_DATA_HOME/jcd/history.db"
+done
+mixed() {
+    (cd "$TEST_DIR/rapids/work" && "$JCD_BIN" --list "$@" api | sed "s|$TEST_DIR/rapids/work/||; s|$TEST_DIR/rapids\$|..|" | tr '\n' ' ' | sed 's/ $//')
+}
+mixed_order=".. d/deep/api c/api b/api a/api j/api e/apix f/apix g/my-api i/rapid h/rapid"
+check "The default weights give the order of the priority table" \
+    "$mixed_order|$mixed_order" \
+    "$(mixed)|$(mixed --recent)"
+check "Tiers never go down the list" \
+    "yes" \
+    "$(cd "$TEST_DIR/rapids/work" && "$JCD_BIN" --explain api | grep -o '^ *tier [0-9]*' | awk '{print $2}' | sort -n -c && echo yes || echo no)"
+check "Spelling out the defaults changes nothing" \
+    "$mixed_order" \
+    "$(mixed --rank exact=700,prefix=600,word=500,acronym=400,partial=300,up=700,immediate=1200,sibling=600,cousin=500,depth=0,project=0,repo=0,recency=0,frecency=50)"
+check "A match outside the current repository says so" \
+    "outside the current repository" \
+    "$(cd "$TEST_DIR/rapids/work" && "$JCD_BIN" --explain api | grep -A2 '/j/api$' | sed -n 3p | sed 's/^ *//')"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All explain tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some explain tests failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,221 @@
+#!/bin/bash
+
+# Test for the JCD history (frecency) database
+# Verifies that recorded jumps are persisted and boost the ranking of matches
+
+echo "=== JCD History Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_history_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the history database inside the test directory
+export XDG_DATA_HOME="$TEST_DIR/data"
+HISTORY_DB="$XDG_DATA_HOME/jcd/history.db"
+
+mkdir -p "$TEST_DIR"/tree/{alpha/proj,beta/proj}
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test 1: Stateless ranking without history ===${NC}"
+check "Alphabetical order wins without history" \
+    "$TEST_DIR/tree/alpha/proj" \
+    "$("$JCD_BIN" proj 0)"
+check "No database is created by a plain search" \
+    "missing" \
+    "$([ -f "$HISTORY_DB" ] && echo present || echo missing)"
+
+echo -e "\n${BLUE}=== Test 2: Recording jumps ===${NC}"
+"$JCD_BIN" --record proj 1 >/dev/null
+check "Database is created by --record" \
+    "present" \
+    "$([ -f "$HISTORY_DB" ] && echo present || echo missing)"
+check "Recorded directory is stored with one visit" \
+    "1" \
+    "$(grep -F "$TEST_DIR/tree/beta/proj" "$HISTORY_DB" | cut -f1)"
+
+echo -e "\n${BLUE}=== Test 3: Frecency boost ===${NC}"
+check "Visited directory now ranks first" \
+    "$TEST_DIR/tree/beta/proj" \
+    "$("$JCD_BIN" proj 0)"
+check "Boost can be disabled with --rank frecency=0" \
+    "$TEST_DIR/tree/alpha/proj" \
+    "$("$JCD_BIN" --rank frecency=0 proj 0)"
+
+echo -e "\n${BLUE}=== Test 4: --add hook ===${NC}"
+(cd "$TEST_DIR/tree" && "$JCD_BIN" --add alpha/proj)
+"$JCD_BIN" --add "$TEST_DIR/tree/alpha/proj"
+check "Relative and absolute --add paths count for the same directory" \
+    "2" \
+    "$(grep -F "$TEST_DIR/tree/alpha/proj" "$HISTORY_DB" | cut -f1)"
+"$JCD_BIN" --add "$TEST_DIR/tree/missing"
+check "Missing directories are not recorded" \
+    "" \
+    "$(grep -F "$TEST_DIR/tree/missing" "$HISTORY_DB")"
+
+echo -e "\n${BLUE}=== Test 5: Pruning ===${NC}"
+mkdir -p "$TEST_DIR/tree/old" "$TEST_DIR/tree/deleted"
+"$JCD_BIN" --add "$TEST_DIR/tree/deleted"
+printf '1\t1000000000\t%s\n' "$TEST_DIR/tree/old" >> "$HISTORY_DB"
+rmdir "$TEST_DIR/tree/deleted"
+check "--dry-run lists missing directories without removing them" \
+    "Would remove $TEST_DIR/tree/deleted|present" \
+    "$("$JCD_BIN" history prune --dry-run | head -n 1)|$(grep -qF "$TEST_DIR/tree/deleted" "$HISTORY_DB" && echo present)"
+"$JCD_BIN" history prune >/dev/null
+check "prune removes missing directories" \
+    "" \
+    "$(grep -F "$TEST_DIR/tree/deleted" "$HISTORY_DB")"
+check "Old entries are kept without --days" \
+    "1" \
+    "$(grep -cF "$TEST_DIR/tree/old" "$HISTORY_DB")"
+check "--days also removes entries not visited for that long" \
+    "Removed 1 of 3 history entries" \
+    "$("$JCD_BIN" history prune --days 30 | tail -n 1)"
+
+echo -e "\n${BLUE}=== Test 6: Listing ===${NC}"
+printf '9\t1000000000\t%s\n' "$TEST_DIR/tree/old" >> "$HISTORY_DB"
+check "list shows a header and one row per directory" \
+    "4" \
+    "$("$JCD_BIN" history list | wc -l | tr -d ' ')"
+check "--sort visits puts the most visited directory first" \
+    "$TEST_DIR/tree/old" \
+    "$("$JCD_BIN" history list --sort visits | sed -n 2p | awk '{print $NF}')"
+check "--sort recent puts the stale directory last" \
+    "$TEST_DIR/tree/old" \
+    "$("$JCD_BIN" history list --sort recent | tail -n 1 | awk '{print $NF}')"
+check "Last visit is shown in UTC" \
+    "2001-09-09 01:46" \
+    "$("$JCD_BIN" history list old | sed -n 2p | grep -o '2001-09-09 01:46')"
+check "A pattern filters by path" \
+    "$TEST_DIR/tree/alpha/proj" \
+    "$("$JCD_BIN" history list alpha | sed -n 2p | awk '{print $NF}')"
+check "--json prints machine-readable entries" \
+    "[{\"path\":\"$TEST_DIR/tree/old\",\"visits\":9,\"last_visit\":1000000000,\"score\":" \
+    "$("$JCD_BIN" history list old --json | grep -o '^.*"score":')"
+
+echo -e "\n${BLUE}=== Test 7: Frecency weight ===${NC}"
+mkdir -p "$TEST_DIR/tree/gamma/projects"
+printf '100\t%s\t%s\n' "$(date +%s)" "$TEST_DIR/tree/gamma/projects" >> "$HISTORY_DB"
+check "By default frecency does not lift a prefix match over exact ones" \
+    "$TEST_DIR/tree/alpha/proj" \
+    "$(cd "$TEST_DIR/tree" && "$JCD_BIN" proj 0)"
+check "A higher ranking.frecency lets a well-used directory overtake better matches" \
+    "$TEST_DIR/tree/gamma/projects" \
+    "$(cd "$TEST_DIR/tree" && JCD_RANK_FRECENCY=300 "$JCD_BIN" proj 0)"
+check "--rank overrides the configured weight" \
+    "$TEST_DIR/tree/alpha/proj" \
+    "$(cd "$TEST_DIR/tree" && JCD_RANK_FRECENCY=300 "$JCD_BIN" --rank frecency=50 proj 0)"
+
+echo -e "\n${BLUE}=== Test 8: Decay models ===${NC}"
+check "count ranks by plain visits" \
+    "9.00" \
+    "$(JCD_HISTORY_DECAY=count "$JCD_BIN" history list old | sed -n 2p | awk '{print $1}')"
+check "buckets weigh visits older than a week by a quarter" \
+    "2.25" \
+    "$("$JCD_BIN" history list old | sed -n 2p | awk '{print $1}')"
+mkdir -p "$TEST_DIR/tree/delta"
+printf '4\t%s\t%s\n' "$(( $(date +%s) - 7200 ))" "$TEST_DIR/tree/delta" >> "$HISTORY_DB"
+JCD_HISTORY_DECAY=half-life JCD_HISTORY_HALF_LIFE_HOURS=1 "$JCD_BIN" --add "$TEST_DIR/tree/delta"
+check "half-life decays stored visits before counting a new one" \
+    "2" \
+    "$(grep -F "$TEST_DIR/tree/delta" "$HISTORY_DB" | cut -f1)"
+JCD_HISTORY_DECAY=zoxide JCD_HISTORY_MAX_AGE=50 "$JCD_BIN" --add "$TEST_DIR/tree/delta"
+check "zoxide aging scales the total down to at most 90% of max_age" \
+    "yes" \
+    "$(grep -v '^#' "$HISTORY_DB" | awk -F '\t' '{ total += $1 } END { print (total <= 45 ? "yes" : "no") }')"
+check "zoxide aging forgets directories left below one visit" \
+    "" \
+    "$(grep -F "$TEST_DIR/tree/beta/proj" "$HISTORY_DB")"
+check "An unknown decay model is reported" \
+    "yes" \
+    "$(JCD_HISTORY_DECAY=fast "$JCD_BIN" history list 2>&1 >/dev/null | grep -q "Warning: JCD_HISTORY_DECAY" && echo yes || echo no)"
+
+echo -e "\n${BLUE}=== Test 9: Exclusions ===${NC}"
+mkdir -p "$TEST_DIR/tree/secret/inner" "$TEST_DIR/tree/alpha/scratch" "$TEST_DIR/tree/ssh-keys"
+JCD_HISTORY_EXCLUDE="$TEST_DIR/tree/secret" "$JCD_BIN" --add "$TEST_DIR/tree/secret/inner"
+check "A path excludes itself and everything below it" \
+    "" \
+    "$(grep -F "$TEST_DIR/tree/secret" "$HISTORY_DB")"
+JCD_HISTORY_EXCLUDE='**/scratch' "$JCD_BIN" --add "$TEST_DIR/tree/alpha/scratch"
+check "A glob excludes matching directories" \
+    "" \
+    "$(grep -F "$TEST_DIR/tree/alpha/scratch" "$HISTORY_DB")"
+(cd "$TEST_DIR/tree" && JCD_HISTORY_EXCLUDE='re:-keys$' "$JCD_BIN" --record ssh-keys 0 >/dev/null)
+check "A re: pattern excludes paths the regex matches, also for --record" \
+    "" \
+    "$(grep -F "$TEST_DIR/tree/ssh-keys" "$HISTORY_DB")"
+JCD_HISTORY_EXCLUDE='**/scratch' "$JCD_BIN" --add "$TEST_DIR/tree/secret/inner"
+check "Directories no pattern matches are still recorded" \
+    "1" \
+    "$(grep -cF "$TEST_DIR/tree/secret/inner" "$HISTORY_DB")"
+
+echo -e "\n${BLUE}=== Test 10: Learning from choices ===${NC}"
+mkdir -p "$TEST_DIR"/tree/learn/{one/lib,two/lib}
+cd "$TEST_DIR/tree/learn"
+check "The first match wins before any choice" \
+    "$TEST_DIR/tree/learn/one/lib" \
+    "$("$JCD_BIN" lib 0)"
+"$JCD_BIN" --chosen-for lib "$TEST_DIR/tree/learn/two/lib" 0 >/dev/null
+check "--chosen-for stores the choice" \
+    "1" \
+    "$(grep -cF "$TEST_DIR/tree/learn/two/lib" "$XDG_DATA_HOME/jcd/selections.db")"
+check "The chosen match comes first for the same pattern" \
+    "$TEST_DIR/tree/learn/two/lib" \
+    "$("$JCD_BIN" lib 0)"
+check "Other search directories are not affected" \
+    "$TEST_DIR/tree/learn/one/lib" \
+    "$(cd "$TEST_DIR/tree" && "$JCD_BIN" lib 0)"
+check "ranking.learn = false ignores earlier choices" \
+    "$TEST_DIR/tree/learn/one/lib" \
+    "$(JCD_RANK_LEARN=0 "$JCD_BIN" lib 0)"
+cd "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All history tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some history tests failed.${NC}"
+    exit 1
+fi
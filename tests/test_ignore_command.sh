@@ -0,0 +1,119 @@
+#!/bin/bash
+
+# Test for the "jcd ignore" management subcommand
+# Verifies adding, listing, testing and removing ignore patterns
+
+echo "=== JCD Ignore Command Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_ignore_command_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep every ignore file jcd reads inside the test directory
+export HOME="$TEST_DIR/home"
+export XDG_CONFIG_HOME="$TEST_DIR/config"
+USER_IGNORE="$XDG_CONFIG_HOME/jcd/ignore"
+
+mkdir -p "$HOME" "$TEST_DIR"/project/{target,src,.config}
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/project"
+
+echo -e "\n${BLUE}=== Test 1: Adding patterns ===${NC}"
+"$JCD_BIN" ignore add target >/dev/null
+check "ignore add writes the user ignore file" \
+    "target" \
+    "$(cat "$USER_IGNORE" 2>/dev/null)"
+check "The pattern is applied to searches" \
+    "" \
+    "$("$JCD_BIN" target 0 2>/dev/null)"
+"$JCD_BIN" ignore add target >/dev/null
+check "Adding a pattern twice keeps one copy" \
+    "1" \
+    "$(grep -c "^target$" "$USER_IGNORE")"
+"$JCD_BIN" ignore add --regex '^\.c' >/dev/null
+check "--regex switches the file to regex syntax for the new line" \
+    "target|syntax: regex|^\.c" \
+    "$(paste -sd'|' "$USER_IGNORE")"
+"$JCD_BIN" ignore add --project '!.config' >/dev/null
+check "--project writes the project ignore file" \
+    "!.config" \
+    "$(cat "$TEST_DIR/project/.jcdignore" 2>/dev/null)"
+check "Invalid patterns are rejected" \
+    "1" \
+    "$("$JCD_BIN" ignore add --regex '[' >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test 2: Listing and testing ===${NC}"
+check "ignore list shows each file with its patterns" \
+    "$USER_IGNORE:|target|^\.c|$TEST_DIR/project/.jcdignore:|!.config" \
+    "$("$JCD_BIN" ignore list | sed 's/^ *[0-9]* *//' | paste -sd'|')"
+check "ignore test names the rule and file that ignore a directory" \
+    "'target' is ignored by 'target' ($USER_IGNORE:1)" \
+    "$("$JCD_BIN" ignore test target)"
+check "ignore test reports negations" \
+    "'.config' is not ignored: re-allowed by '!.config' ($TEST_DIR/project/.jcdignore:1)" \
+    "$("$JCD_BIN" ignore test .config)"
+check "ignore test exits 1 for directories that are not ignored" \
+    "1" \
+    "$("$JCD_BIN" ignore test src >/dev/null; echo $?)"
+
+echo -e "\n${BLUE}=== Test 3: Removing patterns ===${NC}"
+"$JCD_BIN" ignore remove target >/dev/null
+check "ignore remove deletes the pattern" \
+    "0" \
+    "$(grep -c "^target$" "$USER_IGNORE")"
+check "The directory is found again" \
+    "$TEST_DIR/project/target" \
+    "$("$JCD_BIN" target 0)"
+check "Removing a missing pattern fails" \
+    "1" \
+    "$("$JCD_BIN" ignore remove target >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All ignore command tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some ignore command tests failed.${NC}"
+    exit 1
+fi
@@ -228,37 +228,41 @@ test_jcd "Documents should still be found (not in legacy ignore)" \
     "Documents" \
     "$TEST_DIR"
 
-echo -e "\n${BLUE}=== Test 6: Multiple ignore files (precedence) ===${NC}"
+# Remove legacy ignore; all files are merged, so it would leak into later tests
+rm -f "$HOME/.jcdignore"
+
+echo -e "\n${BLUE}=== Test 6: Multiple ignore files (merged) ===${NC}"
 # Create both project-local and user config
 cat > "$TEST_DIR/project/.jcdignore" << 'EOF'
-# Project overrides user config
+# Project patterns add to the user config
 target
 node_modules
 EOF
 
 mkdir -p "$HOME/.config/jcd"
 cat > "$HOME/.config/jcd/ignore" << 'EOF'
-# User config - should be overridden by project
+# User config - still applies next to the project file
 src
 cache
 EOF
 
 cd "$TEST_DIR/project"
-test_jcd "Target ignored by project config (precedence test)" \
+test_jcd "Target ignored by project config (merge test)" \
     "$JCD_BIN target" \
     "should_not_find" \
     "target" \
     "$TEST_DIR/project"
 
-test_jcd "Src NOT ignored (project config takes precedence)" \
+test_jcd "Src ignored by user config (files are merged)" \
     "$JCD_BIN src" \
-    "should_find" \
+    "should_not_find" \
     "src" \
     "$TEST_DIR/project"
 
 echo -e "\n${BLUE}=== Test 7: Invalid regex patterns ===${NC}"
 # Create ignore file with invalid regex
 cat > "$TEST_DIR/project/.jcdignore" << 'EOF'
+syntax: regex
 # Valid pattern
 target
 # Invalid regex pattern (unclosed bracket)
@@ -282,6 +286,7 @@ test_jcd "Node_modules still ignored despite invalid regex" \
 
 echo -e "\n${BLUE}=== Test 8: Complex regex patterns ===${NC}"
 cat > "$TEST_DIR/project/.jcdignore" << 'EOF'
+syntax: regex
 # Complex regex patterns
 .*\.git.*
 target|build|dist
@@ -342,6 +347,164 @@ test_jcd "All directories found with comment-only ignore file" \
     "target" \
     "$TEST_DIR/project"
 
+echo -e "\n${BLUE}=== Test 11: Gitignore-style glob patterns ===${NC}"
+mkdir -p "$TEST_DIR"/project/{app.cache,build-out,rebuild}
+cat > "$TEST_DIR/project/.jcdignore" << 'EOF'
+# Globs match whole directory names
+node_modules/
+*.cache
+build*
+EOF
+
+cd "$TEST_DIR/project"
+test_jcd "Glob with * ignores matching names" \
+    "$JCD_BIN app" \
+    "should_not_find" \
+    "app.cache" \
+    "$TEST_DIR/project"
+
+test_jcd "Trailing slash pattern ignores the directory" \
+    "$JCD_BIN node" \
+    "should_not_find" \
+    "node_modules" \
+    "$TEST_DIR/project"
+
+test_jcd "Prefix glob ignores build-out" \
+    "$JCD_BIN build-out" \
+    "should_not_find" \
+    "build-out" \
+    "$TEST_DIR/project"
+
+test_jcd "Globs are anchored, so rebuild is still found" \
+    "$JCD_BIN rebuild" \
+    "should_find" \
+    "rebuild" \
+    "$TEST_DIR/project"
+
+echo -e "\n${BLUE}=== Test 12: Negation patterns ===${NC}"
+mkdir -p "$TEST_DIR"/project/{.config,.secrets}
+mkdir -p "$HOME/.config/jcd"
+cat > "$HOME/.config/jcd/ignore" << 'EOF'
+# Skip every dot-directory...
+.*
+EOF
+cat > "$TEST_DIR/project/.jcdignore" << 'EOF'
+# ...except .config, re-allowed by the more specific project file
+!.config
+EOF
+
+cd "$TEST_DIR/project"
+test_jcd "Broad pattern still ignores .secrets" \
+    "$JCD_BIN .secrets" \
+    "should_not_find" \
+    ".secrets" \
+    "$TEST_DIR/project"
+
+test_jcd "Negated pattern in a later file re-allows .config" \
+    "$JCD_BIN .config" \
+    "should_find" \
+    ".config" \
+    "$TEST_DIR/project"
+
+cat > "$TEST_DIR/project/.jcdignore" << 'EOF'
+# Within one file the last matching line wins
+!.config
+.*
+EOF
+
+test_jcd "A negation followed by a broader pattern is overridden" \
+    "$JCD_BIN .config" \
+    "should_not_find" \
+    ".config" \
+    "$TEST_DIR/project"
+
+rm -rf "$HOME/.config/jcd"
+
+echo -e "\n${BLUE}=== Test 13: Nested .jcdignore files ===${NC}"
+mkdir -p "$TEST_DIR"/tree/{app/build,lib/build,lib/vendor/dist,web/dist}
+cat > "$TEST_DIR/tree/.jcdignore" << 'EOF'
+dist
+EOF
+cat > "$TEST_DIR/tree/app/.jcdignore" << 'EOF'
+# Only app's build output is noise
+build
+EOF
+cat > "$TEST_DIR/tree/lib/.jcdignore" << 'EOF'
+!dist
+EOF
+
+test_jcd "Nested file ignores build in its own subtree" \
+    "$JCD_BIN build" \
+    "should_not_find" \
+    "app/build" \
+    "$TEST_DIR/tree"
+
+test_jcd "Nested file leaves build elsewhere alone" \
+    "$JCD_BIN build" \
+    "should_find" \
+    "lib/build" \
+    "$TEST_DIR/tree"
+
+test_jcd "Negation in a nested file re-allows dist below it" \
+    "$JCD_BIN dist" \
+    "should_find" \
+    "lib/vendor/dist" \
+    "$TEST_DIR/tree"
+
+test_jcd "Negation does not reach sibling subtrees" \
+    "$JCD_BIN dist" \
+    "should_not_find" \
+    "web/dist" \
+    "$TEST_DIR/tree"
+
+test_jcd "Bypass flag also skips nested files" \
+    "$JCD_BIN -x build" \
+    "should_find" \
+    "app/build" \
+    "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test 14: --exclude for one search ===${NC}"
+mkdir -p "$TEST_DIR"/excl/{node_modules/pkg/build,src/build,vendor/build,keep/build}
+cat > "$TEST_DIR/excl/.jcdignore" << 'EOF'
+!node_modules
+EOF
+
+test_jcd "Without --exclude build is found inside node_modules" \
+    "$JCD_BIN --list build" \
+    "should_find" \
+    "node_modules/pkg/build" \
+    "$TEST_DIR/excl"
+
+test_jcd "--exclude skips build inside node_modules" \
+    "$JCD_BIN --list --exclude node_modules build" \
+    "should_not_find" \
+    "node_modules/pkg/build" \
+    "$TEST_DIR/excl"
+
+test_jcd "--exclude leaves other directories alone" \
+    "$JCD_BIN --list --exclude node_modules build" \
+    "should_find" \
+    "src/build" \
+    "$TEST_DIR/excl"
+
+test_jcd "--exclude can be repeated" \
+    "$JCD_BIN --list --exclude=node_modules --exclude vendor build" \
+    "should_not_find" \
+    "vendor/build" \
+    "$TEST_DIR/excl"
+
+test_jcd "--exclude still applies with -x" \
+    "$JCD_BIN --list -x --exclude keep build" \
+    "should_not_find" \
+    "keep/build" \
+    "$TEST_DIR/excl"
+
+test_jcd "A --exclude containing / is refused" \
+    "$JCD_BIN --exclude a/b build" \
+    "should_find" \
+    "cannot contain '/'" \
+    "$TEST_DIR/excl"
+
 # Summary
 echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
 echo -e "Tests passed: ${GREEN}$PASSED${NC}"
@@ -30,6 +30,7 @@ done
 
 # Create .jcdignore with a mix of valid, invalid, and dangerous patterns
 cat > "$TEST_ROOT/.jcdignore" <<EOF
+syntax: regex
 ^skipme$
 ^((a+)+)$
 [
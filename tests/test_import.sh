@@ -0,0 +1,133 @@
+#!/bin/bash
+
+# Test for importing the data of other directory jumpers into the JCD history
+# Verifies that `jcd import` seeds the history database and boosts the imported directories
+
+echo "=== JCD Import Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_import_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the history database and the imported files inside the test directory
+export XDG_DATA_HOME="$TEST_DIR/data"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+HISTORY_DB="$XDG_DATA_HOME/jcd/history.db"
+
+mkdir -p "$TEST_DIR"/tree/{alpha/proj,beta/proj,gamma/proj,"pipe|dir"} "$XDG_DATA_HOME"
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+# Visits recorded for a directory in the history database
+visits() {
+    grep -F "	$1" "$HISTORY_DB" | cut -f1
+}
+
+cd "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test 1: zoxide ===${NC}"
+# zoxide's db.zo: version 3, then (path, rank, last access) per directory in bincode
+python3 - "$TEST_DIR" << 'PY'
+import os, struct, sys, time
+root = sys.argv[1]
+dirs = [(root + "/tree/beta/proj", 12.0), (root + "/tree/gone", 3.0)]
+data = struct.pack("<IQ", 3, len(dirs))
+for path, rank in dirs:
+    raw = path.encode()
+    data += struct.pack("<Q", len(raw)) + raw + struct.pack("<dQ", rank, int(time.time()))
+os.makedirs(root + "/data/zoxide", exist_ok=True)
+open(root + "/data/zoxide/db.zo", "wb").write(data)
+PY
+check "zoxide database is imported" \
+    "Imported 1 directories from $XDG_DATA_HOME/zoxide/db.zo, skipped 1 missing" \
+    "$("$JCD_BIN" import zoxide)"
+check "zoxide rank becomes the visit count" \
+    "12" \
+    "$(visits "$TEST_DIR/tree/beta/proj")"
+check "Imported directory ranks first" \
+    "$TEST_DIR/tree/beta/proj" \
+    "$("$JCD_BIN" proj 0)"
+check "Importing again adds to the visits" \
+    "24" \
+    "$("$JCD_BIN" import zoxide --file "$XDG_DATA_HOME/zoxide/db.zo" >/dev/null; visits "$TEST_DIR/tree/beta/proj")"
+check "Unknown database versions are rejected" \
+    "1" \
+    "$(printf '\x07\x00\x00\x00' > "$TEST_DIR/bad.zo"; "$JCD_BIN" import zoxide --file "$TEST_DIR/bad.zo" >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test 2: autojump ===${NC}"
+mkdir -p "$XDG_DATA_HOME/autojump"
+printf '30.0\t%s\n10.0\t%s\n' "$TEST_DIR/tree/gamma/proj" "$TEST_DIR/tree/alpha/proj" \
+    > "$XDG_DATA_HOME/autojump/autojump.txt"
+check "autojump data file is imported" \
+    "Imported 2 directories from $XDG_DATA_HOME/autojump/autojump.txt" \
+    "$("$JCD_BIN" import autojump)"
+check "autojump weight maps to (weight/10)^2 visits" \
+    "9 1" \
+    "$(visits "$TEST_DIR/tree/gamma/proj") $(visits "$TEST_DIR/tree/alpha/proj")"
+check "Malformed autojump lines are rejected" \
+    "1" \
+    "$(echo "not a weight" > "$TEST_DIR/bad.txt"; "$JCD_BIN" import autojump --file "$TEST_DIR/bad.txt" >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test 3: rupa/z ===${NC}"
+printf '%s|5|1700000000\n%s|2|1700000100\n' "$TEST_DIR/tree/pipe|dir" "$TEST_DIR/tree/alpha/proj" \
+    > "$TEST_DIR/z-data"
+check "z datafile is imported with --file" \
+    "Imported 2 directories from $TEST_DIR/z-data" \
+    "$("$JCD_BIN" import z --file "$TEST_DIR/z-data")"
+check "Paths containing '|' keep their rank and time" \
+    "5 1700000000" \
+    "$(grep -F "	$TEST_DIR/tree/pipe|dir" "$HISTORY_DB" | cut -f1,2 | tr '\t' ' ')"
+check "Visits are added to earlier imports" \
+    "3" \
+    "$(visits "$TEST_DIR/tree/alpha/proj")"
+check "_Z_DATA locates the default datafile" \
+    "0" \
+    "$(_Z_DATA="$TEST_DIR/z-data" "$JCD_BIN" import z >/dev/null; echo $?)"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All import tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some import tests failed.${NC}"
+    exit 1
+fi
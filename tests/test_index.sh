@@ -0,0 +1,129 @@
+#!/bin/bash
+
+# Test for the JCD on-disk directory index
+# Verifies that searches are answered from the index and that stale data is not returned
+
+echo "=== JCD Index Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_index_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the index and config inside the test directory
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export JCD_CONFIG="$TEST_DIR/config.toml"
+INDEX_DB="$XDG_CACHE_HOME/jcd/index.db"
+
+mkdir -p "$TEST_DIR"/tree/{alpha/deep/target,beta/other}
+cat > "$JCD_CONFIG" << TOML
+[index]
+roots = ["$TEST_DIR/tree"]
+TOML
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test 1: Building the index ===${NC}"
+"$JCD_BIN" index build >/dev/null
+check "index build writes the index file" \
+    "present" \
+    "$([ -f "$INDEX_DB" ] && echo present || echo missing)"
+check "Every directory under the root is recorded" \
+    "6" \
+    "$(grep -c "^dir" "$INDEX_DB")"
+
+echo -e "\n${BLUE}=== Test 2: Answering from the index ===${NC}"
+check "Search returns the indexed directory" \
+    "$TEST_DIR/tree/alpha/deep/target" \
+    "$("$JCD_BIN" target 0)"
+check "Search is answered from the index" \
+    "yes" \
+    "$(JCD_DEBUG=1 "$JCD_BIN" target 0 2>&1 | grep -q "answered from the index" && echo yes || echo no)"
+
+echo -e "\n${BLUE}=== Test 3: Freshness checks ===${NC}"
+rmdir "$TEST_DIR/tree/alpha/deep/target"
+mkdir "$TEST_DIR/tree/alpha/deep/target2"
+check "Removed directories are not returned" \
+    "" \
+    "$("$JCD_BIN" --list target 2>/dev/null | grep -x "$TEST_DIR/tree/alpha/deep/target")"
+mkdir "$TEST_DIR/tree/newdir"
+check "A changed search directory falls back to walking the tree" \
+    "$TEST_DIR/tree/newdir" \
+    "$("$JCD_BIN" newdir 0)"
+
+echo -e "\n${BLUE}=== Test 4: Clearing the index ===${NC}"
+"$JCD_BIN" index clear
+check "index clear removes the index file" \
+    "missing" \
+    "$([ -f "$INDEX_DB" ] && echo present || echo missing)"
+check "Searches still work without an index" \
+    "$TEST_DIR/tree/alpha/deep/target2" \
+    "$("$JCD_BIN" target2 0)"
+
+echo -e "\n${BLUE}=== Test 5: Git-ignored directories ===${NC}"
+mkdir -p "$TEST_DIR/tree/.git" "$TEST_DIR/tree/beta/vendored/target3"
+echo "vendored/" > "$TEST_DIR/tree/beta/.gitignore"
+"$JCD_BIN" index build >/dev/null
+check "--gitignore drops index entries inside ignored directories" \
+    "" \
+    "$("$JCD_BIN" --gitignore --list target3 2>/dev/null)"
+check "Without --gitignore they are still returned" \
+    "$TEST_DIR/tree/beta/vendored/target3" \
+    "$("$JCD_BIN" target3 0)"
+
+echo -e "\n${BLUE}=== Test 6: Typos below an extra root ===${NC}"
+# The index cannot answer typo matches, so an indexed extra root is walked
+mkdir -p "$TEST_DIR/tree/extra/services" "$TEST_DIR/elsewhere"
+"$JCD_BIN" index build >/dev/null
+check "--typos finds a misspelled name below an indexed extra root" \
+    "$TEST_DIR/tree/extra/services" \
+    "$(cd "$TEST_DIR/elsewhere" && JCD_ROOTS="$TEST_DIR/tree/extra" "$JCD_BIN" --typos --list srevices 2>/dev/null)"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All index tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some index tests failed.${NC}"
+    exit 1
+fi
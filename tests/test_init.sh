@@ -0,0 +1,163 @@
+#!/bin/bash
+
+# Test for the JCD shell integration generator
+# Verifies that `jcd init` prints scripts bound to the running binary and to
+# the chosen command name, and runs the zsh one when zsh is installed
+
+echo "=== JCD Init Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_init_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's settings, cached results and history out of the test
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export XDG_DATA_HOME="$TEST_DIR/data"
+unset JCD_BINARY
+
+mkdir -p "$TEST_DIR"/tree/{alpha/target,beta}
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+BIN_PATH="$(cd "$(dirname "$JCD_BIN")" && pwd)/$(basename "$JCD_BIN")"
+
+echo -e "\n${BLUE}=== Test 1: zsh script ===${NC}"
+check "The script calls the binary that printed it" \
+    "typeset -g _JCD_BINARY=\${JCD_BINARY:-'$BIN_PATH'}" \
+    "$("$JCD_BIN" init zsh | grep '^typeset -g _JCD_BINARY')"
+check "The script defines the jcd function and its completion" \
+    "2" \
+    "$("$JCD_BIN" init zsh | grep -cE '^(jcd|_jcd)\(\) \{')"
+check "The completion menu describes candidates from --complete --tsv" \
+    "2" \
+    "$("$JCD_BIN" init zsh | grep -cE -- '--complete --tsv|compadd .*-d descriptions')"
+check "A jump to a completed candidate is remembered for the pattern" \
+    "1" \
+    "$("$JCD_BIN" init zsh | grep -cF -- '--chosen-for "$_JCD_ORIGINAL_PATTERN"')"
+check "The completion is registered when compinit has run" \
+    "1" \
+    "$("$JCD_BIN" init zsh | grep -c 'compdef _jcd jcd')"
+
+echo -e "\n${BLUE}=== Test 2: bash script and custom names ===${NC}"
+check "jcd init bash moves the shell to the best match" \
+    "$TEST_DIR/tree/alpha/target" \
+    "$(cd "$TEST_DIR/tree" && bash -c 'eval "$("$0" init bash)"; jcd targ >/dev/null; pwd' "$BIN_PATH" 2>/dev/null)"
+check "--cmd binds the function and its completion to the chosen name" \
+    "$TEST_DIR/tree/alpha/target|complete -o nospace -F _jcd_tab_complete j|no jcd" \
+    "$(cd "$TEST_DIR/tree" && bash -c 'eval "$("$0" init bash --cmd j)"; j targ >/dev/null; echo "$(pwd)|$(complete -p j)|$(type -t jcd || echo no jcd)"' "$BIN_PATH" 2>/dev/null)"
+check "--cmd renames the zsh function and completion" \
+    "2" \
+    "$("$JCD_BIN" init zsh --cmd j | grep -cE '^j\(\) \{|compdef _jcd j$')"
+check "A name that is not a valid function name is rejected" \
+    "Error: 'j k' cannot be used as a command name|1" \
+    "$("$JCD_BIN" init bash --cmd 'j k' 2>&1)|$("$JCD_BIN" init bash --cmd 'j k' >/dev/null 2>&1; echo $?)"
+check "Unknown options print the usage" \
+    "Usage: jcd init bash|zsh [--cmd <name>] [--hook none|prompt|pwd]" \
+    "$("$JCD_BIN" init zsh --name j 2>&1)"
+
+echo -e "\n${BLUE}=== Test 3: Visit hooks ===${NC}"
+HISTORY_DB="$XDG_DATA_HOME/jcd/history.db"
+visits() {
+    grep -F "$1" "$HISTORY_DB" 2>/dev/null | cut -f1
+}
+(cd "$TEST_DIR/tree/beta" && bash -c 'eval "$("$0" init bash --hook pwd)"; eval "$PROMPT_COMMAND"; eval "$PROMPT_COMMAND"' "$BIN_PATH")
+check "--hook pwd records a directory once per change" \
+    "1" \
+    "$(visits "$TEST_DIR/tree/beta")"
+(cd "$TEST_DIR/tree/beta" && bash -c 'eval "$("$0" init bash --hook prompt)"; eval "$PROMPT_COMMAND"; eval "$PROMPT_COMMAND"' "$BIN_PATH")
+check "--hook prompt records a directory on every prompt" \
+    "3" \
+    "$(visits "$TEST_DIR/tree/beta")"
+mkdir -p "$TEST_DIR/tree/gamma/hooked"
+(cd "$TEST_DIR/tree" && bash -c 'eval "$("$0" init bash --hook pwd)"; jcd hooked >/dev/null' "$BIN_PATH")
+check "With a hook, jumps are left for the hook to record" \
+    "" \
+    "$(visits "$TEST_DIR/tree/gamma/hooked")"
+(cd "$TEST_DIR/tree" && bash -c 'eval "$("$0" init bash)"; jcd hooked >/dev/null' "$BIN_PATH")
+check "Without a hook, jumps are recorded" \
+    "1" \
+    "$(visits "$TEST_DIR/tree/gamma/hooked")"
+check "The zsh script hooks chpwd for --hook pwd" \
+    "typeset -g _JCD_HOOK=pwd" \
+    "$("$JCD_BIN" init zsh --hook pwd | grep '^typeset -g _JCD_HOOK')"
+check "An unknown hook is rejected" \
+    "Error: --hook must be none, prompt or pwd" \
+    "$("$JCD_BIN" init bash --hook always 2>&1)"
+
+echo -e "\n${BLUE}=== Test 4: Searching ===${NC}"
+(cd "$TEST_DIR/tree" && mkdir -p init)
+check "Without a known shell, init is still a search" \
+    "$TEST_DIR/tree/init" \
+    "$(cd "$TEST_DIR/tree" && "$JCD_BIN" init 0)"
+
+echo -e "\n${BLUE}=== Test 5: Running in zsh ===${NC}"
+if command -v zsh >/dev/null 2>&1; then
+    check "The script parses in zsh" \
+        "ok" \
+        "$("$JCD_BIN" init zsh | zsh -n && echo ok)"
+    check "jcd moves the shell to the best match" \
+        "$TEST_DIR/tree/alpha/target" \
+        "$(cd "$TEST_DIR/tree" && zsh -fc 'eval "$("$0" init zsh)"; jcd targ >/dev/null; pwd' "$BIN_PATH")"
+    check "--cmd works in zsh" \
+        "$TEST_DIR/tree/alpha/target" \
+        "$(cd "$TEST_DIR/tree" && zsh -fc 'eval "$("$0" init zsh --cmd j)"; j targ >/dev/null; pwd' "$BIN_PATH")"
+    # As if Tab had offered both libs for "lib" and the second was taken
+    mkdir -p "$TEST_DIR"/learn/{one,two}/lib
+    check "A completed candidate that was jumped to comes first next time" \
+        "$TEST_DIR/learn/two/lib" \
+        "$(cd "$TEST_DIR/learn" && zsh -fc 'eval "$("$0" init zsh)"
+            _JCD_ORIGINAL_PATTERN=lib
+            _JCD_CURRENT_MATCHES=("$PWD/one/lib" "$PWD/two/lib")
+            jcd "$PWD/two/lib" >/dev/null; cd "$1"; jcd lib >/dev/null; pwd' "$BIN_PATH" "$TEST_DIR/learn")"
+else
+    echo "zsh not installed; skipping"
+fi
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All init tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some init tests failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,156 @@
+#!/bin/bash
+
+# Test for JCD tracing
+# Verifies that JCD_DEBUG and JCD_LOG choose which trace events reach stderr,
+# tagged with the search phase they belong to, and that JCD_LOG_FILE or
+# log.file sends them to a file instead
+
+echo "=== JCD Logging Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_logging_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's settings, cached results and history out of the test
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export XDG_DATA_HOME="$TEST_DIR/data"
+
+mkdir -p "$TEST_DIR"/tree/{src/api,work/deep/tools}
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/tree"
+
+# Whether stderr of `jcd tools 0` has a line matching the pattern, under the given environment
+traced() {
+    local pattern="$1"
+    shift
+    env "$@" "$JCD_BIN" tools 0 2>&1 >/dev/null | grep -q -- "$pattern" && echo yes || echo no
+}
+
+echo -e "\n${BLUE}=== Test 1: Choosing what is traced ===${NC}"
+check "Nothing is traced by default" \
+    "0" \
+    "$("$JCD_BIN" tools 0 2>&1 >/dev/null | wc -l | tr -d ' ')"
+check "Traces leave the answer on stdout alone" \
+    "$TEST_DIR/tree/work/deep/tools" \
+    "$(JCD_DEBUG=1 "$JCD_BIN" tools 0 2>/dev/null)"
+check "JCD_DEBUG=1 traces the search at debug level" \
+    "yes" \
+    "$(traced '^DEBUG .*Deep Exact match at depth 3' JCD_DEBUG=1)"
+check "JCD_DEBUG=1 leaves out trace level events" \
+    "no" \
+    "$(traced '^TRACE' JCD_DEBUG=1)"
+check "JCD_LOG=trace adds the per-directory walk" \
+    "yes" \
+    "$(traced '^TRACE .*Searching depth 2 in' JCD_LOG=trace)"
+check "JCD_LOG overrides JCD_DEBUG" \
+    "0" \
+    "$(JCD_DEBUG=1 JCD_LOG=warn "$JCD_BIN" tools 0 2>&1 >/dev/null | wc -l | tr -d ' ')"
+check "A module filter keeps only that module's events" \
+    "jcd::ranking:" \
+    "$(JCD_LOG=jcd::ranking=debug JCD_RECENT=1 "$JCD_BIN" tools 0 2>&1 >/dev/null | grep -o ' jcd[a-z:_]*:' | sort -u | tr -d ' ')"
+
+echo -e "\n${BLUE}=== Test 2: Search phases ===${NC}"
+check "Events of the upward search carry its span" \
+    "yes" \
+    "$(traced 'search{term="tools" [^}]*}:up: ' JCD_DEBUG=1)"
+check "Events of the downward search carry its span" \
+    "yes" \
+    "$(traced 'search{term="tools" [^}]*}:down: ' JCD_DEBUG=1)"
+check "Ranking events carry the rank span" \
+    "yes" \
+    "$(traced '^DEBUG rank: ' JCD_DEBUG=1 JCD_RECENT=1)"
+check "A span filter traces one phase only" \
+    "down" \
+    "$(JCD_LOG='jcd[down]=trace' "$JCD_BIN" tools 0 2>&1 >/dev/null | cut -d' ' -f2 | tr -d ':' | sort -u)"
+
+echo -e "\n${BLUE}=== Test 3: Invalid filters ===${NC}"
+check "An invalid JCD_LOG is reported" \
+    "yes" \
+    "$(traced '^Warning: Invalid JCD_LOG' 'JCD_LOG=jcd=[')"
+check "Tracing then falls back to the debug level" \
+    "yes" \
+    "$(traced '^DEBUG ' 'JCD_LOG=jcd=[')"
+
+echo -e "\n${BLUE}=== Test 4: Log file ===${NC}"
+LOG="$TEST_DIR/jcd.log"
+check "With JCD_LOG_FILE nothing is traced on stderr" \
+    "0" \
+    "$(JCD_DEBUG=1 JCD_LOG_FILE="$LOG" "$JCD_BIN" tools 0 2>&1 >/dev/null | wc -l | tr -d ' ')"
+check "The traces are in the file, with timestamps" \
+    "yes" \
+    "$(grep -q '^[0-9-]*T[0-9:.]*Z DEBUG .*Deep Exact match at depth 3' "$LOG" && echo yes || echo no)"
+JCD_DEBUG=1 JCD_LOG_FILE="$LOG" "$JCD_BIN" tools 0 >/dev/null 2>&1
+check "Later runs append to the file" \
+    "2" \
+    "$(grep -c 'Deep Exact match at depth 3' "$LOG")"
+check "Without JCD_DEBUG or JCD_LOG no file is written" \
+    "no" \
+    "$(JCD_LOG_FILE="$TEST_DIR/quiet.log" "$JCD_BIN" tools 0 >/dev/null 2>&1; [ -e "$TEST_DIR/quiet.log" ] && echo yes || echo no)"
+printf '[log]\nfile = "%s"\n' "$TEST_DIR/config.log" > "$JCD_CONFIG"
+check "log.file in config.toml names the file too" \
+    "0|yes" \
+    "$(JCD_DEBUG=1 "$JCD_BIN" tools 0 2>&1 >/dev/null | wc -l | tr -d ' ')|$(grep -q 'DEBUG' "$TEST_DIR/config.log" && echo yes || echo no)"
+check "An empty JCD_LOG_FILE overrides the config file" \
+    "yes" \
+    "$(traced '^DEBUG ' JCD_DEBUG=1 JCD_LOG_FILE=)"
+rm -f "$JCD_CONFIG"
+check "A file that cannot be opened is reported" \
+    "yes" \
+    "$(traced '^Warning: Cannot open log file' JCD_DEBUG=1 JCD_LOG_FILE="$TEST_DIR/missing/jcd.log")"
+check "Traces then go to stderr" \
+    "yes" \
+    "$(traced '^DEBUG ' JCD_DEBUG=1 JCD_LOG_FILE="$TEST_DIR/missing/jcd.log")"
+check "The bash function's debug lines go to the file" \
+    "0|yes" \
+    "$(JCD_DEBUG=1 JCD_LOG_FILE="$TEST_DIR/shell.log" bash -c "source '$PROJECT_ROOT/src/jcd_function.sh'; _jcd_debug 'from the shell'" 2>&1 | wc -l | tr -d ' ')|$(grep -q '^DEBUG: from the shell$' "$TEST_DIR/shell.log" && echo yes || echo no)"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All logging tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some logging tests failed.${NC}"
+    exit 1
+fi
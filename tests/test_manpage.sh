@@ -0,0 +1,108 @@
+#!/bin/bash
+
+# Test for the JCD man page generator
+# Verifies that `jcd --manpage` prints a roff page documenting every flag,
+# subcommand and environment variable, and that it renders when man is installed
+
+echo "=== JCD Man Page Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_manpage_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+mkdir -p "$TEST_DIR"
+PAGE="$TEST_DIR/jcd.1"
+"$JCD_BIN" --manpage > "$PAGE"
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+
+echo -e "\n${BLUE}=== Test 1: Page structure ===${NC}"
+check "The page is a section 1 page for jcd" \
+    '.TH JCD 1 "" "jcd 0.0.0" "User Commands"' \
+    "$(head -1 "$PAGE" | sed 's/"jcd [^"]*"/"jcd 0.0.0"/')"
+check "It has the usual sections" \
+    "NAME SYNOPSIS DESCRIPTION OPTIONS PATTERNS SUBCOMMANDS ENVIRONMENT FILES EXIT STATUS SEE ALSO" \
+    "$(sed -n 's/^\.SH //p' "$PAGE" | tr '\n' ' ' | sed 's/ $//')"
+
+echo -e "\n${BLUE}=== Test 2: Generated from the command line ===${NC}"
+# The flags the bash completion offers come from the same table
+FLAGS=$("$JCD_BIN" completions bash | sed -n 's/.*compgen -W "\(-[^"]*\)".*/\1/p')
+missing=""
+for flag in $FLAGS; do
+    grep -qF "\\fB${flag//-/\\-}\\fR" "$PAGE" || missing="$missing $flag"
+done
+check "Every flag is documented" \
+    "" \
+    "$missing"
+check "Options taking a value name it" \
+    '\fB\-\-timeout\fR \fIms\fR' \
+    "$(grep -F 'fB\-\-timeout' "$PAGE")"
+check "Subcommands are documented with their usage" \
+    '\fBjcd history list [pattern] [\-\-sort score|recent|visits] [\-\-json]\fR' \
+    "$(grep -F 'fBjcd history list' "$PAGE")"
+check "Config settings list their environment variables" \
+    "Overrides the search.max_matches setting of the config file" \
+    "$(grep -A1 -F 'fBJCD_MAX_MATCHES' "$PAGE" | tail -1)"
+
+mkdir -p "$TEST_DIR/tree/manpage"
+check "A lone manpage is a search" \
+    "$TEST_DIR/tree/manpage" \
+    "$(cd "$TEST_DIR/tree" && JCD_CONFIG="$TEST_DIR/config.toml" XDG_CACHE_HOME="$TEST_DIR/cache" XDG_DATA_HOME="$TEST_DIR/data" "$JCD_BIN" manpage)"
+
+echo -e "\n${BLUE}=== Test 3: Rendering ===${NC}"
+if command -v man >/dev/null 2>&1 && man --help 2>&1 | grep -q -- '--local-file'; then
+    check "man renders the page without warnings" \
+        "" \
+        "$(MANWIDTH=80 man --warnings -l "$PAGE" 2>&1 >/dev/null)"
+else
+    echo "man not installed; skipping"
+fi
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All man page tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some man page tests failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,381 @@
+#!/bin/bash
+
+# Test for how jcd matches directory names
+# Verifies the optional match kinds beyond exact, prefix and substring
+# matches, and where they rank
+
+echo "=== JCD Matching Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_matching_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's settings, cached results and history out of the test
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export XDG_DATA_HOME="$TEST_DIR/data"
+unset JCD_RECENT JCD_TYPOS
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+# Every match of a search from $TEST_DIR/$1, relative to $TEST_DIR, on one line
+matches() {
+    (cd "$TEST_DIR/$1" && "$JCD_BIN" --list "${@:2}" | sed "s|$TEST_DIR/||" | tr '\n' ' ' | sed 's/ $//')
+}
+
+echo -e "\n${BLUE}=== Test 1: Typos ===${NC}"
+mkdir -p "$TEST_DIR"/typos/{services/api,src/servces,docs/lib,docs/libx}
+check "Without --typos a misspelled name matches nothing" \
+    "1" \
+    "$(cd "$TEST_DIR/typos" && "$JCD_BIN" srevices >/dev/null 2>&1; echo $?)"
+check "--typos finds names with swapped, missing or extra letters" \
+    "typos/services typos/src/servces" \
+    "$(matches typos --typos srevices)"
+check "Typo matches rank below real matches" \
+    "typos/src/servces typos/services" \
+    "$(matches typos --typos servces)"
+check "Short patterns allow a single edit" \
+    "typos/docs/lib" \
+    "$(matches typos --typos lbi)"
+check "Patterns of two characters allow none" \
+    "" \
+    "$(matches typos --typos lb)"
+check "Parent directories match with typos too" \
+    "typo_up" \
+    "$(cd "$TEST_DIR/typos/services/api" && "$JCD_BIN" --json --typos srevices | sed 's/.*"quality":"\([a-z_]*\)".*/\1/')"
+check "search.typos turns typos on" \
+    "typos/services typos/src/servces" \
+    "$(JCD_TYPOS=true matches typos srevices)"
+check "--no-typos turns them off again" \
+    "" \
+    "$(JCD_TYPOS=true matches typos --no-typos srevices)"
+check "Globs are matched as written" \
+    "" \
+    "$(matches typos --typos 'srev*')"
+check "--explain shows the typo tier" \
+    "tier 18 (typo down)" \
+    "$(cd "$TEST_DIR/typos" && "$JCD_BIN" --explain --typos srevices | sed -n 2p | sed 's/^ *//; s/, depth.*//')"
+
+echo -e "\n${BLUE}=== Test 2: Acronyms ===${NC}"
+mkdir -p "$TEST_DIR"/acronyms/{tools/foo-bar-tools,tools/foo_bar,more/FooBarTools,more/HTTPServer,old/fbt-old,old/sofbt,old/v1.2}
+check "A pattern matches the initials of hyphen and underscore words" \
+    "acronyms/tools/foo-bar-tools" \
+    "$(matches acronyms fbt | cut -d' ' -f2)"
+check "Acronyms rank below prefix matches and above substring matches" \
+    "acronyms/old/fbt-old acronyms/tools/foo-bar-tools acronyms/old/sofbt" \
+    "$(matches acronyms fbt)"
+check "CamelCase words have initials too" \
+    "acronyms/more/FooBarTools|acronyms/more/HTTPServer" \
+    "$(matches acronyms FBT)|$(matches acronyms HS)"
+check "Case is compared as for other matches" \
+    "|acronyms/more/HTTPServer" \
+    "$(matches acronyms hs)|$(matches acronyms -i hs)"
+check "Every word has to be spelled" \
+    "" \
+    "$(matches acronyms fo_b)"
+check "Digits after a letter stay in its word" \
+    "acronyms/old/v1.2|" \
+    "$(matches acronyms v2)|$(matches acronyms 12)"
+check "Acronym matches are reported as such" \
+    "acronym_down" \
+    "$(cd "$TEST_DIR/acronyms/tools" && "$JCD_BIN" --json fbt | sed 's/.*"quality":"\([a-z_]*\)".*/\1/')"
+check "--explain shows the acronym tier" \
+    "tier 15 (acronym down)" \
+    "$(cd "$TEST_DIR/acronyms" && "$JCD_BIN" --explain fbt | sed -n 5p | sed 's/^ *//; s/, depth.*//')"
+
+echo -e "\n${BLUE}=== Test 3: Word boundaries ===${NC}"
+mkdir -p "$TEST_DIR"/words/{a/capitals,b/my-api-server,c/apiary,d/rapid_api}
+check "A match at a word boundary ranks between prefix and substring matches" \
+    "words/c/apiary words/b/my-api-server words/d/rapid_api words/a/capitals" \
+    "$(matches words api)"
+check "Word matches are reported as such" \
+    "prefix_down word_down word_down partial_down" \
+    "$(cd "$TEST_DIR/words" && "$JCD_BIN" --list --tsv api | cut -f3 | tr '\n' ' ' | sed 's/ $//')"
+check "--explain names the word tier" \
+    "lower tier: partial down after word down" \
+    "$(cd "$TEST_DIR/words" && "$JCD_BIN" --explain api | grep -A2 '/capitals$' | sed -n 3p | sed 's/^ *//')"
+check "The highlight is the match at the word boundary" \
+    "1" \
+    "$(cd "$TEST_DIR/words/d" && "$JCD_BIN" --list --color=always api | cat -v | grep -c 'rapid_^\[\[1;31mapi^\[\[0m$')"
+
+echo -e "\n${BLUE}=== Test 4: CamelCase ===${NC}"
+mkdir -p "$TEST_DIR"/camel/{a/DataServices,b/HTTPServer,c/DataStore,d/foo-bar-tools}
+check "Capitals start words for acronyms" \
+    "camel/a/DataServices camel/c/DataStore" \
+    "$(matches camel DS)"
+check "The start of each word spells a name" \
+    "camel/a/DataServices|camel/a/DataServices|camel/b/HTTPServer" \
+    "$(matches camel --smart-case dserv)|$(matches camel DatServ)|$(matches camel HSe)"
+check "The starts of separated words do too" \
+    "camel/d/foo-bar-tools" \
+    "$(matches camel fobat)"
+check "No word can be skipped" \
+    "|" \
+    "$(matches camel fot)|$(matches camel fob)"
+check "A capital inside a name starts a word match" \
+    "word_down word_down" \
+    "$(cd "$TEST_DIR/camel" && "$JCD_BIN" --list --tsv Serv | cut -f3 | tr '\n' ' ' | sed 's/ $//')"
+check "Word matches rank above acronyms" \
+    "camel/a/DataServices camel/c/DataStore" \
+    "$(matches camel -i dat)"
+
+echo -e "\n${BLUE}=== Test 5: Accents ===${NC}"
+composed="$(printf 'caf\xc3\xa9')"
+decomposed="$(printf 'cafe\xcc\x81')"
+mkdir -p "$TEST_DIR"/accents/{a/"$decomposed",b/Résumé,c/resume-old}
+check "A composed pattern finds a decomposed name" \
+    "accents/a/$decomposed" \
+    "$(matches accents "$composed")"
+check "A decomposed pattern finds it too" \
+    "accents/a/$decomposed" \
+    "$(matches accents "$decomposed")"
+check "Accents count by default" \
+    "accents/c/resume-old" \
+    "$(matches accents -i resume)"
+check "--ignore-accents drops them" \
+    "accents/b/Résumé accents/c/resume-old" \
+    "$(matches accents -i --ignore-accents resume)"
+check "search.ignore_accents drops them, --match-accents keeps them" \
+    "accents/b/Résumé accents/c/resume-old|accents/c/resume-old" \
+    "$(JCD_IGNORE_ACCENTS=true matches accents -i resumé)|$(JCD_IGNORE_ACCENTS=true matches accents -i --match-accents resume)"
+check "Case still counts with accents ignored" \
+    "accents/c/resume-old" \
+    "$(matches accents --ignore-accents resume)"
+check "Globs keep their accents" \
+    "" \
+    "$(matches accents --ignore-accents 'cafe*')"
+check "The accented name is highlighted" \
+    "1" \
+    "$(cd "$TEST_DIR/accents" && "$JCD_BIN" --list --color=always -i --ignore-accents resume | cat -v | grep -c '\[1;31mRM-CM-)sumM-CM-)')"
+check "A regex highlights the decomposed name it matched" \
+    "1" \
+    "$(cd "$TEST_DIR/accents" && "$JCD_BIN" --list --color=always -E "f$(printf '\xc3\xa9')" | cat -v | grep -c '\[1;31mfeM-LM-^A')"
+
+echo -e "\n${BLUE}=== Test 6: Full paths ===${NC}"
+mkdir -p "$TEST_DIR"/full/{backend/src/http,endpoints/src,api/v1/v2}
+check "Path patterns match one name per component" \
+    "full/backend/src full/endpoints/src" \
+    "$(matches full end/src)"
+check "--full-path matches the text across components" \
+    "full/backend/src full/backend/src/http" \
+    "$(matches full --full-path end/src)"
+check "search.full_path does too, --name-only turns it off" \
+    "full/backend/src/http|full/backend/src full/endpoints/src" \
+    "$(JCD_FULL_PATH=true matches full nd/src/ht)|$(JCD_FULL_PATH=true matches full --name-only end/src)"
+check "The path ranks like a name" \
+    "exact_down prefix_down|word_down" \
+    "$(cd "$TEST_DIR/full" && "$JCD_BIN" --list --tsv --full-path backend/src | cut -f3 | tr '\n' ' ' | sed 's/ $//')|$(cd "$TEST_DIR/full" && "$JCD_BIN" --list --tsv --full-path src/http | cut -f3)"
+check "Wildcards see the whole path" \
+    "full/api/v1/v2" \
+    "$(matches full --full-path 'api*2')"
+check "The match is highlighted across the path" \
+    "1" \
+    "$(cd "$TEST_DIR/full" && "$JCD_BIN" --list --color=always --full-path nd/src/ht | cat -v | grep -c 'backe^\[\[0m^\[\[1;31mnd/src/ht^\[\[0mtp$')"
+
+echo -e "\n${BLUE}=== Test 7: Anchors ===${NC}"
+mkdir -p "$TEST_DIR"/anchors/{api,api-server,my-api,rapid,ap}
+check "^ and \$ keep exact matches only" \
+    "anchors/api" \
+    "$(matches anchors '^api$')"
+check "^ keeps exact and prefix matches" \
+    "anchors/api anchors/api-server" \
+    "$(matches anchors '^api')"
+check "\$ keeps names ending with the pattern" \
+    "exact_down word_down" \
+    "$(cd "$TEST_DIR/anchors" && "$JCD_BIN" --list --tsv 'api$' | cut -f3 | tr '\n' ' ' | sed 's/ $//')"
+check "Anchors work with wildcards" \
+    "anchors/ap anchors/api anchors/api-server|anchors/my-api" \
+    "$(matches anchors '^ap*')|$(matches anchors '*-api$')"
+check "Anchored patterns make no typo matches" \
+    "anchors/ap|" \
+    "$(matches anchors --typos 'ape' | cut -d' ' -f1)|$(matches anchors --typos '^ape$')"
+check "A pattern of anchors alone is taken as written" \
+    "" \
+    "$(matches anchors '^')"
+check "Regexes keep their own anchors" \
+    "anchors/api anchors/api-server" \
+    "$(matches anchors -E '^api')"
+check "The anchored end is highlighted" \
+    "1" \
+    "$(cd "$TEST_DIR/anchors" && "$JCD_BIN" --list --color=always 'api$' | cat -v | grep -c 'my-^\[\[1;31mapi^\[\[0m$')"
+
+echo -e "\n${BLUE}=== Test 8: Alternatives ===${NC}"
+mkdir -p "$TEST_DIR"/either/{backend,my-backend,server-old,client}
+check "A name may match either alternative" \
+    "either/backend either/server-old either/my-backend" \
+    "$(matches either 'server|backend')"
+check "Each name gets the best tier of any alternative" \
+    "exact_down prefix_down word_down" \
+    "$(cd "$TEST_DIR/either" && "$JCD_BIN" --list --tsv 'server|backend|end' | cut -f3 | tr '\n' ' ' | sed 's/ $//')"
+check "Alternatives have their own anchors and wildcards" \
+    "either/server-old either/backend" \
+    "$(matches either '^back|*-old')"
+check "Empty alternatives are dropped" \
+    "either/client|" \
+    "$(matches either 'client|')|$(matches either '|')"
+check "Alternatives apply per component of a path pattern" \
+    "either/backend" \
+    "$(matches . 'nothing|either/^back')"
+check "The alternative that matched is highlighted" \
+    "1" \
+    "$(cd "$TEST_DIR/either" && "$JCD_BIN" --list --color=always 'zzz|serv' | cat -v | grep -c '\[1;31mserv^\[\[0mer-old$')"
+
+echo -e "\n${BLUE}=== Test 9: Multiple words ===${NC}"
+mkdir -p "$TEST_DIR"/words/{api-gateway/src/handlers,web/handlers,api/v1/handlers,Api/x/handlers}
+check "Earlier words must match directories above the match" \
+    "words/api/v1/handlers words/api-gateway/src/handlers" \
+    "$(matches words api handlers)"
+check "Words match in order, each a different directory" \
+    "words/api-gateway/src/handlers|" \
+    "$(matches words api src handlers)|$(matches words src api handlers)"
+check "Smart case decides for each word" \
+    "words/Api/x/handlers words/api/v1/handlers words/api-gateway/src/handlers|words/Api/x/handlers" \
+    "$(matches words --smart-case api handlers)|$(matches words --smart-case Api handlers)"
+check "A number after the words is the match index" \
+    "words/api-gateway/src/handlers" \
+    "$(cd "$TEST_DIR/words" && "$JCD_BIN" api handlers 1 | sed "s|$TEST_DIR/||")"
+check "Words above the search directory count too" \
+    "words/api-gateway/src/handlers" \
+    "$(matches words/api-gateway gateway handlers)"
+
+echo -e "\n${BLUE}=== Test 10: Deep path patterns ===${NC}"
+mkdir -p "$TEST_DIR"/deep/proj/{tests,a/b/c/d/e/tests,a/b/misc}
+check "A component after ** may be any number of levels down" \
+    "deep/proj/tests deep/proj/a/b/c/d/e/tests" \
+    "$(matches deep 'proj/**/tests')"
+check "Without ** the component must be near the previous one" \
+    "deep/proj/tests" \
+    "$(matches deep 'proj/tests')"
+check "A trailing ** matches every directory below" \
+    "deep/proj/a/b/c/d/e deep/proj/a/b/c/d/e/tests" \
+    "$(matches deep/proj/a '^d$/**')"
+check "The last component after ** is highlighted" \
+    "1" \
+    "$(cd "$TEST_DIR/deep" && "$JCD_BIN" --list --color=always 'proj/**/tests' | cat -v | grep -c 'e/^\[\[0m^\[\[1;31mtests^\[\[0m$')"
+
+echo -e "\n${BLUE}=== Test 11: Siblings ===${NC}"
+mkdir -p "$TEST_DIR"/next/proj/{src/docs-gen,lib/x/docs,docs,mydocs}
+check "Directories next to this one are not searched by default" \
+    "next/proj/src/docs-gen" \
+    "$(matches next/proj/src docs)"
+check "--siblings matches them after immediate and before deeper matches" \
+    "prefix_down exact_sibling partial_sibling|exact_sibling partial_sibling exact_down" \
+    "$(cd "$TEST_DIR/next/proj/src" && "$JCD_BIN" --list --tsv --siblings docs | cut -f3 | tr '\n' ' ' | sed 's/ $//')|$(cd "$TEST_DIR/next/proj/lib" && "$JCD_BIN" --list --tsv --siblings docs | cut -f3 | tr '\n' ' ' | sed 's/ $//')"
+check "The current directory is not its own sibling" \
+    "next/proj/docs" \
+    "$(matches next/proj/mydocs --siblings '^docs')"
+check "search.siblings turns them on and --no-siblings off" \
+    "next/proj/src/docs-gen next/proj/docs next/proj/mydocs|next/proj/src/docs-gen" \
+    "$(JCD_SIBLINGS=true matches next/proj/src docs)|$(JCD_SIBLINGS=true matches next/proj/src --no-siblings docs)"
+
+echo -e "\n${BLUE}=== Test 12: Cousins ===${NC}"
+mkdir -p "$TEST_DIR"/far/work/{proj/{src/module/docs-gen,docs,tools/docs-site},other/docs,a/b/docs}
+check "--cousins searches a little below each nearby ancestor, nearest first" \
+    "far/work/proj/src/module/docs-gen far/work/proj/docs far/work/other/docs far/work/proj/tools/docs-site" \
+    "$(matches far/work/proj/src/module --cousins docs)"
+check "Cousins rank after siblings and before deeper matches" \
+    "exact_sibling:1 exact_cousin:3 partial_cousin:2 prefix_down:2" \
+    "$(cd "$TEST_DIR/far/work/proj/src" && "$JCD_BIN" --list --tsv --cousins docs | awk -F'\t' '{print $3 ":" $4}' | tr '\n' ' ' | sed 's/ $//')"
+check "Cousins stay within two levels of each ancestor" \
+    "" \
+    "$(matches far/work/proj/src/module --cousins '^docs$' | grep -o 'a/b/docs')"
+check "search.cousins turns them on and --no-cousins off" \
+    "far/work/proj/docs|" \
+    "$(JCD_COUSINS=true matches far/work/proj/src/module '^docs$' | cut -d' ' -f1)|$(JCD_COUSINS=true matches far/work/proj/src/module --no-cousins '^docs$')"
+
+echo -e "\n${BLUE}=== Test 13: Minimum depth ===${NC}"
+mkdir -p "$TEST_DIR"/levels/build/{build-1,sub/build,sub/x/build}
+check "--min-depth skips matches nearer than that below the search directory" \
+    "levels/build/sub/build levels/build/sub/x/build levels/build/build-1" \
+    "$(matches levels --min-depth 2 build)"
+check "Matches up the tree are kept" \
+    "levels/build levels/build/sub/x/build" \
+    "$(matches levels/build/sub --min-depth 2 build)"
+check "--min-depth needs a number" \
+    "2" \
+    "$(cd "$TEST_DIR" && "$JCD_BIN" --min-depth deep build >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test 14: One direction ===${NC}"
+check "--up finds parent directories only" \
+    "levels/build" \
+    "$(matches levels/build/sub --up build)"
+check "--down finds directories below only" \
+    "levels/build/sub/x/build" \
+    "$(matches levels/build/sub/x --down build)"
+check "--up anchors a path pattern at a parent" \
+    "levels/build/sub" \
+    "$(matches levels/build/sub/x --up build/sub)"
+check "--up and --down cannot be combined" \
+    "2" \
+    "$(cd "$TEST_DIR" && "$JCD_BIN" --up --down build >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test 15: Tier limits ===${NC}"
+mkdir -p "$TEST_DIR"/tiers/x/{xapi1,xapi2,xapi3,y/apo}
+check "Without limits one quality fills the match budget" \
+    "tiers/x/xapi1 tiers/x/xapi2 tiers/x/xapi3" \
+    "$(matches tiers --typos --max-matches 3 api)"
+check "--tier-limits leaves room for the next quality" \
+    "tiers/x/xapi1 tiers/x/xapi2 tiers/x/y/apo" \
+    "$(matches tiers --typos --max-matches 3 --tier-limits partial_down=2 api)"
+check "search.tier_limits can be set from the environment" \
+    "tiers/x/xapi1 tiers/x/y/apo" \
+    "$(JCD_TIER_LIMITS=partial_down=1 matches tiers --typos --max-matches 3 api)"
+check "An unknown quality is a usage error" \
+    "2" \
+    "$(cd "$TEST_DIR" && "$JCD_BIN" --tier-limits partial=2 api >/dev/null 2>&1; echo $?)"
+# Each lib holds a partial match the walk may reach before the exact one
+mkdir -p "$TEST_DIR"/tiers/paths/{a,b,c}/lib/{0api,api}
+exact_libs() {
+    (cd "$TEST_DIR/tiers/paths" && "$JCD_BIN" --list --max-matches 2 --tier-limits partial_down=0 "$1" 2>/dev/null | grep -c '/lib/api$')
+}
+check "Path pattern walks stop at the match budget only counting what the limits keep" \
+    "2|2" \
+    "$(exact_libs lib/api)|$(exact_libs '**/lib/api')"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All matching tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some matching tests failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,170 @@
+#!/bin/bash
+
+# Test for the JCD --fzf picker hand-off
+# Uses stand-in pickers so that no terminal or fzf installation is needed
+
+echo "=== JCD Picker Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_picker_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's settings, cached results and history out of the test
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export XDG_DATA_HOME="$TEST_DIR/data"
+
+mkdir -p "$TEST_DIR"/tree/{alpha/app,lib/app,tools/app} "$TEST_DIR/bin"
+
+# Stand-in pickers: each reads the candidates and prints a choice
+cat > "$TEST_DIR/bin/second" <<'EOF'
+#!/bin/sh
+sed -n 2p
+EOF
+cat > "$TEST_DIR/bin/all" <<'EOF'
+#!/bin/sh
+cat
+EOF
+cat > "$TEST_DIR/bin/cancel" <<'EOF'
+#!/bin/sh
+cat >/dev/null
+exit 130
+EOF
+# Stands in for fzf, which is handed colored candidates and prints them plain
+mkdir -p "$TEST_DIR/fzf"
+cat > "$TEST_DIR/fzf/fzf" <<EOF
+#!/bin/sh
+echo "\$*" > "$TEST_DIR/fzf-args"
+tee "$TEST_DIR/fzf-input" | sed -n 2p | sed 's/\x1b\[[0-9;]*m//g'
+EOF
+chmod +x "$TEST_DIR"/bin/* "$TEST_DIR/fzf/fzf"
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test 1: Choosing ===${NC}"
+check "The picker receives every match, best first" \
+    "$("$JCD_BIN" --list app | tr '\n' ' ')" \
+    "$(JCD_PICKER="$TEST_DIR/bin/all" "$JCD_BIN" --fzf app | tr '\n' ' ')"
+check "The chosen match is printed" \
+    "$("$JCD_BIN" app 1)" \
+    "$(JCD_PICKER="$TEST_DIR/bin/second" "$JCD_BIN" --fzf app)"
+check "Picker options are split on whitespace" \
+    "$TEST_DIR/tree/lib/app" \
+    "$(JCD_PICKER="grep lib/" "$JCD_BIN" --fzf app)"
+check "--json prints the chosen matches as an array" \
+    "[{\"path\":\"$TEST_DIR/tree/lib/app\"" \
+    "$(JCD_PICKER="grep lib/" "$JCD_BIN" --fzf --json app | grep -o '^\[{"path":"[^"]*"')"
+
+echo -e "\n${BLUE}=== Test 2: Recording ===${NC}"
+JCD_PICKER="$TEST_DIR/bin/all" "$JCD_BIN" --fzf --record app >/dev/null
+check "--record counts a visit to every chosen directory" \
+    "3" \
+    "$(grep -vc '^#' "$XDG_DATA_HOME/jcd/history.db")"
+
+echo -e "\n${BLUE}=== Test 3: Failures ===${NC}"
+check "Cancelling the picker prints nothing and fails" \
+    "|1" \
+    "$(JCD_PICKER="$TEST_DIR/bin/cancel" "$JCD_BIN" --fzf app)|$(JCD_PICKER="$TEST_DIR/bin/cancel" "$JCD_BIN" --fzf app >/dev/null; echo $?)"
+check "A missing picker is reported" \
+    "yes" \
+    "$(JCD_PICKER="$TEST_DIR/bin/missing" "$JCD_BIN" --fzf app 2>&1 | grep -q "Cannot run picker" && echo yes || echo no)"
+check "No matches fails without starting the picker" \
+    "1" \
+    "$(JCD_PICKER="$TEST_DIR/bin/missing" "$JCD_BIN" --fzf nothing-here 2>/dev/null; echo $?)"
+
+echo -e "\n${BLUE}=== Test 4: Tab-separated output ===${NC}"
+check "--tsv prints path, display form, quality and depth" \
+    "$TEST_DIR/tree/alpha/app	~/tree/alpha/app	exact_down	2" \
+    "$(HOME="$TEST_DIR" "$JCD_BIN" --tsv app)"
+check "--list --tsv prints one line per match" \
+    "3" \
+    "$("$JCD_BIN" --list --tsv app | cut -f3 | grep -c exact_down)"
+check "Outside the home directory the display form is the full path" \
+    "$TEST_DIR/tree/lib/app" \
+    "$(HOME=/nonexistent "$JCD_BIN" --tsv app 1 | cut -f2)"
+
+check "Parent directories have a negative depth" \
+    "-1" \
+    "$(cd "$TEST_DIR/tree/lib/app" && "$JCD_BIN" --tsv lib | cut -f4)"
+check "--complete --tsv describes every candidate" \
+    "3" \
+    "$("$JCD_BIN" --complete --tsv app | awk -F '\t' 'NF == 4' | wc -l | tr -d ' ')"
+
+echo -e "\n${BLUE}=== Test 5: Highlighting ===${NC}"
+ESC=$(printf '\033')
+check "fzf is started with --ansi" \
+    "--ansi" \
+    "$(JCD_PICKER="$TEST_DIR/fzf/fzf" "$JCD_BIN" --fzf pp >/dev/null; cat "$TEST_DIR/fzf-args")"
+check "fzf gets the parent path dimmed and the matched text colored" \
+    "${ESC}[2m$TEST_DIR/tree/alpha/${ESC}[0ma${ESC}[1;31mpp${ESC}[0m" \
+    "$(head -1 "$TEST_DIR/fzf-input")"
+check "The choice comes back without colors" \
+    "$("$JCD_BIN" pp 1)" \
+    "$(JCD_PICKER="$TEST_DIR/fzf/fzf" "$JCD_BIN" --fzf pp)"
+check "Styles come from ui.match_style and ui.path_style" \
+    "$TEST_DIR/tree/alpha/${ESC}[4;32mapp${ESC}[0m" \
+    "$(JCD_PICKER="$TEST_DIR/fzf/fzf" JCD_MATCH_STYLE="underline green" JCD_PATH_STYLE=none "$JCD_BIN" --fzf -i APP >/dev/null; head -1 "$TEST_DIR/fzf-input")"
+check "An unknown style is reported" \
+    "yes" \
+    "$(JCD_MATCH_STYLE=sparkly "$JCD_BIN" app 2>&1 >/dev/null | grep -q "Unknown style 'sparkly'" && echo yes || echo no)"
+check "Other pickers get bare paths" \
+    "$TEST_DIR/tree/alpha/app" \
+    "$(JCD_PICKER="$TEST_DIR/bin/all" "$JCD_BIN" --fzf app | head -1)"
+check "--list piped gets bare paths" \
+    "0" \
+    "$("$JCD_BIN" --list app | grep -c "$ESC")"
+if command -v script >/dev/null 2>&1; then
+    check "--list on a terminal is highlighted" \
+        "${ESC}[2m$TEST_DIR/tree/alpha/${ESC}[0m${ESC}[1;31mapp${ESC}[0m" \
+        "$(script -qc "'$JCD_BIN' --list app" /dev/null | head -1 | tr -d '\r')"
+fi
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All picker tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some picker tests failed.${NC}"
+    exit 1
+fi
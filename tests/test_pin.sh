@@ -0,0 +1,121 @@
+#!/bin/bash
+
+# Test for JCD pinned directories
+# Verifies `jcd pin add/rm/list` and that pinned directories come before every other match
+
+echo "=== JCD Pin Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_pin_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the pin file inside the test directory
+export XDG_DATA_HOME="$TEST_DIR/data"
+export XDG_CONFIG_HOME="$TEST_DIR/config"
+PINS="$XDG_DATA_HOME/jcd/pins"
+
+mkdir -p "$TEST_DIR"/tree/{api,deep/down/below/api,elsewhere/{api,lib},gone}
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/tree"
+
+echo -e "\n${BLUE}=== Test 1: Adding pins ===${NC}"
+check "Without pins the nearest match comes first" \
+    "$TEST_DIR/tree/api" \
+    "$("$JCD_BIN" api 0)"
+(cd deep/down/below/api && "$JCD_BIN" pin add >/dev/null)
+"$JCD_BIN" pin add gone >/dev/null
+check "jcd pin add stores the current directory and explicit paths" \
+    "2" \
+    "$(grep -c "^$TEST_DIR/tree/" "$PINS")"
+check "Pinning a directory twice keeps one entry" \
+    "Already pinned: $TEST_DIR/tree/gone" \
+    "$("$JCD_BIN" pin add "$TEST_DIR/tree/gone")"
+check "Pinning a missing directory fails" \
+    "1" \
+    "$("$JCD_BIN" pin add nowhere >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test 2: Ranking ===${NC}"
+check "A pinned match comes before nearer ones" \
+    "$TEST_DIR/tree/deep/down/below/api" \
+    "$("$JCD_BIN" api 0)"
+check "A pin is marked pinned in JSON output" \
+    '"quality":"pinned"' \
+    "$("$JCD_BIN" --json api | grep -o '"quality":"[a-z_]*"')"
+check "A pin the search would not reach is still found" \
+    "$TEST_DIR/tree/deep/down/below/api" \
+    "$(cd "$TEST_DIR/tree/elsewhere" && JCD_MAX_DEPTH=1 "$JCD_BIN" --down api 0)"
+check "Pins whose name does not match stay out" \
+    "$TEST_DIR/tree/elsewhere/lib" \
+    "$(cd "$TEST_DIR/tree/elsewhere" && "$JCD_BIN" --list --down lib)"
+check "ranking.pins pins directories from config" \
+    "$TEST_DIR/tree/elsewhere/api" \
+    "$(JCD_PINS="$TEST_DIR/tree/elsewhere/api" "$JCD_BIN" api 0)"
+
+echo -e "\n${BLUE}=== Test 3: Listing and removing ===${NC}"
+rmdir gone
+check "jcd pin list marks missing directories" \
+    "$TEST_DIR/tree/gone  (missing)" \
+    "$("$JCD_BIN" pin list | grep gone)"
+check "A missing directory can be unpinned" \
+    "Unpinned $TEST_DIR/tree/gone" \
+    "$("$JCD_BIN" pin rm gone)"
+"$JCD_BIN" pin rm deep/down/below/api >/dev/null
+check "Unpinned directories rank as before" \
+    "$TEST_DIR/tree/api" \
+    "$("$JCD_BIN" api 0)"
+check "Unpinning a directory that is not pinned fails" \
+    "1" \
+    "$("$JCD_BIN" pin rm api >/dev/null 2>&1; echo $?)"
+check "jcd pin list says when nothing is pinned" \
+    "No pinned directories" \
+    "$("$JCD_BIN" pin list)"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All pin tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some pin tests failed.${NC}"
+    exit 1
+fi
@@ -88,6 +88,26 @@ test_jcd "Find all matches for '../ch' pattern" \
          "$JCD_BINARY '../ch' 0" \
          ".*/child[12]$"
 
+# Test 6: Trailing slash lists the children of the best match
+test_jcd "List subdirectories of the best 'subdir' match with 'subdir/'" \
+         "$JCD_BINARY --list --from .. 'subdir/' | tr '\\n' ' '" \
+         "^[^ ]*/parent/subdir/deep1 [^ ]*/parent/subdir/deep2 $"
+
+# Test 7: Trailing slash on a directory without subdirectories
+test_jcd "A match without subdirectories stands for itself with 'deep1/'" \
+         "$JCD_BINARY --list --from .. 'deep1/'" \
+         ".*/parent/subdir/deep1$"
+
+# Test 8: Each dot after the first is one level up
+test_jcd "Navigate two levels up with '...'" \
+         "$JCD_BINARY '...'" \
+         ".*/jcd_test_comprehensive$"
+
+# Test 9: A pattern after the dots is searched from there
+test_jcd "Search for 'foobar' from grandparent level with '.../foobar'" \
+         "$JCD_BINARY '.../foobar'" \
+         ".*/foo/foobar$"
+
 # Test shell function
 echo -e "\n=== Testing JCD Shell Function ==="
 
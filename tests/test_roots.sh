@@ -0,0 +1,152 @@
+#!/bin/bash
+
+# Test for JCD extra search roots
+# Verifies that configured roots are always searched after the local search,
+# and $CDPATH directories when nothing matches nearby
+
+echo "=== JCD Search Roots Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_roots_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's settings and cached results out of the test
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+unset JCD_CDPATH JCD_ROOTS
+
+mkdir -p "$TEST_DIR"/{here/local,here/docs,src/api/v1,src/docs,work/api,work/tools,work/docs}
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/here"
+
+echo -e "\n${BLUE}=== Test 1: CDPATH fallback ===${NC}"
+check "Without CDPATH nothing is found" \
+    "1" \
+    "$(env -u CDPATH "$JCD_BIN" tools 0 >/dev/null 2>&1; echo $?)"
+check "CDPATH entries are searched when nothing matches nearby" \
+    "$TEST_DIR/work/tools" \
+    "$(CDPATH=":$TEST_DIR/src:$TEST_DIR/work" "$JCD_BIN" tools 0)"
+check "Earlier CDPATH entries win" \
+    "$TEST_DIR/src/api" \
+    "$(CDPATH="$TEST_DIR/src:$TEST_DIR/work" "$JCD_BIN" api 0)"
+check "Path patterns are searched below CDPATH entries" \
+    "$TEST_DIR/src/api/v1" \
+    "$(CDPATH="$TEST_DIR/src" "$JCD_BIN" api/v1 0)"
+check "Local matches are preferred over CDPATH" \
+    "$TEST_DIR/here/local" \
+    "$(CDPATH="$TEST_DIR/src" "$JCD_BIN" --list local)"
+
+echo -e "\n${BLUE}=== Test 2: Turning the fallback off ===${NC}"
+check "Relative patterns do not use CDPATH" \
+    "1" \
+    "$(CDPATH="$TEST_DIR/work" "$JCD_BIN" ./tools 0 >/dev/null 2>&1; echo $?)"
+check "JCD_CDPATH=0 disables the fallback" \
+    "1" \
+    "$(CDPATH="$TEST_DIR/work" JCD_CDPATH=0 "$JCD_BIN" tools 0 >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test 3: Configured roots ===${NC}"
+check "JCD_ROOTS matches rank after local ones, in root order" \
+    "$TEST_DIR/here/docs $TEST_DIR/work/docs $TEST_DIR/src/docs" \
+    "$(JCD_ROOTS="$TEST_DIR/work:$TEST_DIR/src" "$JCD_BIN" --list docs | tr '\n' ' ' | sed 's/ $//')"
+cat > "$JCD_CONFIG" << TOML
+[search]
+roots = ["$TEST_DIR/work", "$TEST_DIR/missing"]
+TOML
+check "search.roots is searched even when something matches nearby" \
+    "$TEST_DIR/here/docs $TEST_DIR/work/docs" \
+    "$("$JCD_BIN" --list docs | tr '\n' ' ' | sed 's/ $//')"
+check "A configured root that contains the match counts it once" \
+    "1" \
+    "$(cd "$TEST_DIR/work" && "$JCD_BIN" --list tools | grep -c tools)"
+check "CDPATH is not needed once a root matched" \
+    "$TEST_DIR/work/api" \
+    "$(CDPATH="$TEST_DIR/src" "$JCD_BIN" --list api)"
+rm -f "$JCD_CONFIG"
+
+echo -e "\n${BLUE}=== Test 4: Starting directory ===${NC}"
+check "--from searches from another directory" \
+    "$TEST_DIR/src/api/v1" \
+    "$("$JCD_BIN" --from "$TEST_DIR/src" v1 0)"
+check "A relative --from is taken from the current directory" \
+    "$TEST_DIR/work/api" \
+    "$("$JCD_BIN" --from ../work api 0)"
+check "Relative patterns start from --from" \
+    "$TEST_DIR/src" \
+    "$("$JCD_BIN" --from "$TEST_DIR/src/api" .. 0)"
+check "A missing --from directory is an error" \
+    "Error: --from needs an existing directory: $TEST_DIR/missing|2" \
+    "$("$JCD_BIN" --from "$TEST_DIR/missing" api 0 2>&1)|$("$JCD_BIN" --from "$TEST_DIR/missing" api 0 >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test 5: Deleted current directory ===${NC}"
+# Run jcd from a directory that is removed (and maybe created again) under it
+from_deleted() {
+    local recreate="$1"
+    shift
+    mkdir -p "$TEST_DIR/here/gone"
+    (cd "$TEST_DIR/here/gone" && rmdir "$TEST_DIR/here/gone" &&
+        { [ -z "$recreate" ] || mkdir -p "$TEST_DIR/here/gone/$recreate"; } &&
+        "$@" 2>&1; echo -n "|$?")
+    rm -rf "$TEST_DIR/here/gone"
+}
+check "A deleted directory is searched from its nearest ancestor that exists" \
+    "Warning: the current directory no longer exists; searching from $TEST_DIR/here|$TEST_DIR/here/local||0" \
+    "$(from_deleted "" "$JCD_BIN" local 0 | tr '\n' '|')"
+check "A directory created again is searched from where it now is" \
+    "$TEST_DIR/here/gone/fresh||0" \
+    "$(from_deleted fresh "$JCD_BIN" --quiet fresh 0 | tr '\n' '|')"
+check "--quiet leaves the warning out" \
+    "$TEST_DIR/here/local||0" \
+    "$(from_deleted "" "$JCD_BIN" --quiet local 0 | tr '\n' '|')"
+check "Without \$PWD to go by it is an error" \
+    "|4" \
+    "$(from_deleted "" env -u PWD "$JCD_BIN" local 0 | grep -v '^Error' | tr -d '\n')"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All search root tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some search root tests failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,111 @@
+#!/bin/bash
+
+# Test for the JCD JSON-RPC server
+# Verifies that `jcd serve --stdio` answers search, history and bookmark
+# requests, one JSON message per line, and reports malformed ones
+
+echo "=== JCD Serve Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_serve_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's settings, cached results and history out of the test
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export XDG_DATA_HOME="$TEST_DIR/data"
+
+mkdir -p "$TEST_DIR"/tree/{src/api,work/tools,docs}
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/tree"
+
+# Send request lines to one server and print its responses
+serve() {
+    printf '%s\n' "$@" | "$JCD_BIN" serve --stdio
+}
+
+echo -e "\n${BLUE}=== Test 1: Searching ===${NC}"
+check "search returns the ranked matches" \
+    '{"jsonrpc":"2.0","id":1,"result":[{"path":"'"$TEST_DIR"'/tree/src/api","quality":"exact_down","depth":2,"rank":0}]}' \
+    "$(serve '{"jsonrpc":"2.0","id":1,"method":"search","params":{"query":"api"}}')"
+check "Options apply per request and ids are echoed" \
+    '{"jsonrpc":"2.0","id":"a","result":[{"path":"'"$TEST_DIR"'/tree/src/api","quality":"exact_down","depth":1,"rank":0}]}' \
+    "$(serve '{"jsonrpc":"2.0","id":"a","method":"search","params":{"query":"A.I","regex":true,"ignore_case":true,"from":"src"}}')"
+check "One server answers every request in order" \
+    "1 2 3" \
+    "$(serve '{"id":1,"method":"search","params":{"query":"docs"}}' '{"id":2,"method":"search","params":{"query":"tools"}}' '{"id":3,"method":"search","params":{"query":"nothing_here"}}' | sed 's/.*"id":\([0-9]*\).*/\1/' | paste -sd ' ')"
+
+echo -e "\n${BLUE}=== Test 2: History and bookmarks ===${NC}"
+check "history/record counts a visit that history/list reports" \
+    '{"jsonrpc":"2.0","id":1,"result":null}|"visits":1' \
+    "$(serve '{"id":1,"method":"history/record","params":{"path":"work/tools"}}' '{"id":2,"method":"history/list","params":{"pattern":"tools"}}' | sed -n '1p;2s/.*\("visits":[0-9]*\).*/\1/p' | paste -sd '|')"
+check "bookmark/add and bookmark/list" \
+    '"Added bookmark @d -> '"$TEST_DIR"'/tree/docs"|[{"name":"d","path":"'"$TEST_DIR"'/tree/docs","exists":true}]' \
+    "$(serve '{"id":1,"method":"bookmark/add","params":{"name":"d","path":"docs"}}' '{"id":2,"method":"bookmark/list"}' | sed 's/.*"result":\(.*\)}$/\1/' | paste -sd '|')"
+check "bookmark/remove of a missing bookmark fails" \
+    '{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"No bookmark named @zz"}}' \
+    "$(serve '{"id":1,"method":"bookmark/remove","params":{"name":"zz"}}')"
+
+echo -e "\n${BLUE}=== Test 3: Errors ===${NC}"
+check "Malformed JSON is a parse error" \
+    "-32700" \
+    "$(serve 'not json' | sed 's/.*"code":\(-[0-9]*\).*/\1/')"
+check "Unknown methods are reported" \
+    '{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"Unknown method '"'"'nope'"'"'"}}' \
+    "$(serve '{"id":1,"method":"nope"}')"
+check "Missing parameters are invalid params" \
+    '{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"'"'"'query'"'"' is required"}}' \
+    "$(serve '{"id":1,"method":"search","params":{}}')"
+check "Notifications get no response and exit stops the server" \
+    "1" \
+    "$(serve '{"method":"history/record","params":{"path":"docs"}}' '{"id":1,"method":"bookmark/list"}' '{"method":"exit"}' '{"id":2,"method":"bookmark/list"}' | wc -l | tr -d ' ')"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All serve tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some serve tests failed.${NC}"
+    exit 1
+fi
@@ -0,0 +1,160 @@
+#!/bin/bash
+
+# Test for jcd --stats
+# Verifies that a search reports the directories it visited, the entries it
+# examined, the directories it skipped and the limits it ran into on stderr,
+# as text or as JSON, without changing what it prints on stdout
+
+echo "=== JCD Stats Test ==="
+
+# Color codes for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+PASSED=0
+FAILED=0
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(cd "$SCRIPT_DIR/.." && pwd)"
+JCD_BIN="${JCD_BIN:-$PROJECT_ROOT/target/release/jcd}"
+
+if [ ! -x "$JCD_BIN" ]; then
+    echo -e "${RED}Error: JCD binary not found at $JCD_BIN${NC}"
+    exit 1
+fi
+
+TEST_DIR="/tmp/jcd_stats_test_$$"
+
+cleanup() {
+    rm -rf "$TEST_DIR"
+}
+trap cleanup EXIT
+
+# Keep the user's settings, ignore files, cached results and history out of the test
+export JCD_CONFIG="$TEST_DIR/config.toml"
+export XDG_CONFIG_HOME="$TEST_DIR/xdg"
+export XDG_CACHE_HOME="$TEST_DIR/cache"
+export XDG_DATA_HOME="$TEST_DIR/data"
+
+mkdir -p "$TEST_DIR"/tree/{src/api,lib/api/v1,build/api,docs}
+echo "build" > "$TEST_DIR/tree/.jcdignore"
+
+check() {
+    local description="$1"
+    local expected="$2"
+    local actual="$3"
+
+    echo -e "\nTest: $description"
+    echo "Expected: $expected"
+    echo "Actual:   $actual"
+    if [[ "$actual" == "$expected" ]]; then
+        echo -e "${GREEN}✓ PASSED${NC}"
+        ((PASSED++))
+    else
+        echo -e "${RED}✗ FAILED${NC}"
+        ((FAILED++))
+    fi
+}
+
+cd "$TEST_DIR/tree"
+
+# The value of one line of the --stats report, e.g. `stat "Ignore skips" v1 0`
+stat() {
+    local label="$1"
+    shift
+    "$JCD_BIN" --stats "$@" 2>&1 >/dev/null | grep "^  $label:" | sed 's/^[^:]*: *//'
+}
+
+echo -e "\n${BLUE}=== Test 1: Counts ===${NC}"
+check "--stats leaves stdout alone" \
+    "$("$JCD_BIN" v1 0)" \
+    "$("$JCD_BIN" --stats v1 0 2>/dev/null)"
+check "Nothing is reported without --stats" \
+    "0" \
+    "$("$JCD_BIN" v1 0 2>&1 >/dev/null | wc -l | tr -d ' ')"
+check "Every directory listed is counted" \
+    "7" \
+    "$(stat "Directories visited" v1 0)"
+check "Entries include files and ignored directories" \
+    "8" \
+    "$(stat "Entries examined" v1 0)"
+check "Directories skipped by .jcdignore are counted" \
+    "1" \
+    "$(stat "Ignore skips" v1 0)"
+check "-x skips nothing" \
+    "0" \
+    "$(stat "Ignore skips" -x v1 0)"
+check "The time the walk took is shown" \
+    "yes" \
+    "$(stat "Elapsed" v1 0 | grep -q '^[0-9]*\.[0-9] ms$' && echo yes || echo no)"
+
+echo -e "\n${BLUE}=== Test 2: Limits ===${NC}"
+check "A search within every limit says so" \
+    "not reached|not reached|not reached" \
+    "$(stat "Depth limit" v1 0)|$(stat "Time budget" v1 0)|$(stat "Match limit" v1 0)"
+printf '[search]\nmax_depth = 1\n' > "$JCD_CONFIG"
+check "search.max_depth reports the directories it kept the walk out of" \
+    "reached, 2 directories not entered" \
+    "$(stat "Depth limit" v1 0)"
+rm -f "$JCD_CONFIG"
+check "--max-matches reports the match limit" \
+    "reached" \
+    "$(stat "Match limit" --max-matches 1 api 0)"
+check "A path pattern that stops at the limit stops early" \
+    "reached, the search stopped early" \
+    "$(stat "Match limit" --max-matches 1 l/a 0)"
+
+echo -e "\n${BLUE}=== Test 3: Modes ===${NC}"
+check "--json reports one JSON object on stderr" \
+    "{\"directories_visited\":7,\"entries_examined\":8,\"ignore_skips\":1,\"depth_limited\":0,\"from_index\":false,\"timed_out\":false,\"match_limit_hit\":false,\"partial\":false,\"elapsed_ms\":" \
+    "$("$JCD_BIN" --stats --json v1 0 2>&1 >/dev/null | sed 's/"elapsed_ms":.*/"elapsed_ms":/')"
+check "--list reports the search once" \
+    "1" \
+    "$("$JCD_BIN" --stats --list api 2>&1 >/dev/null | grep -c '^Search statistics:')"
+"$JCD_BIN" api 0 >/dev/null
+check "Later matches are searched again instead of cached" \
+    "7" \
+    "$(stat "Directories visited" api 1)"
+check "--stats cannot be combined with --stdin" \
+    "2" \
+    "$(echo api | "$JCD_BIN" --stats --stdin >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test 4: Unreadable directories ===${NC}"
+mkdir -p "$TEST_DIR"/unreadable/{open/app,loops}
+ln -s self "$TEST_DIR/unreadable/loops/self"
+cd "$TEST_DIR/unreadable"
+check "--report-errors says when every directory could be read" \
+    "Every directory searched could be read" \
+    "$("$JCD_BIN" --report-errors app 2>&1 >/dev/null)"
+check "A symlink loop is reported when symlinks are followed" \
+    "1 directory could not be read (too many levels of symbolic links: 1):|  $TEST_DIR/unreadable/loops/self: too many levels of symbolic links" \
+    "$("$JCD_BIN" --report-errors --follow-symlinks app 2>&1 >/dev/null | tr '\n' '|' | sed 's/|$//')"
+check "Without --report-errors nothing is said" \
+    "" \
+    "$("$JCD_BIN" --follow-symlinks app 2>&1 >/dev/null)"
+# Root reads every directory, so only other users can test this
+if [ "$(id -u)" != 0 ]; then
+    mkdir -p "$TEST_DIR/unreadable/locked/app"
+    chmod 000 "$TEST_DIR/unreadable/locked"
+    check "A directory without read permission is reported" \
+        "yes" \
+        "$("$JCD_BIN" --report-errors app 2>&1 >/dev/null | grep -q "locked: permission denied" && echo yes || echo no)"
+    chmod 755 "$TEST_DIR/unreadable/locked"
+fi
+check "--report-errors cannot be combined with --stdin" \
+    "2" \
+    "$(echo app | "$JCD_BIN" --report-errors --stdin >/dev/null 2>&1; echo $?)"
+
+echo -e "\n${BLUE}=== Test Results Summary ===${NC}"
+echo -e "Tests passed: ${GREEN}$PASSED${NC}"
+echo -e "Tests failed: ${RED}$FAILED${NC}"
+
+if [ $FAILED -eq 0 ]; then
+    echo -e "\n${GREEN}🎉 All stats tests passed!${NC}"
+    exit 0
+else
+    echo -e "\n${RED}❌ Some stats tests failed.${NC}"
+    exit 1
+fi
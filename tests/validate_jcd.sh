@@ -45,6 +45,60 @@ else
     echo "✗ Relative pattern search failed. Got: '$result'"
 fi
 
+# Test home-relative search terms
+result=$(HOME=/tmp/jcd_test "$JCD_BINARY" "~/parent/child2" 2>/dev/null)
+if [[ "$result" == "/tmp/jcd_test/parent/child2" ]]; then
+    echo "✓ Tilde expansion works"
+else
+    echo "✗ Tilde expansion failed. Got: '$result'"
+fi
+
+result=$(HOME=/tmp/jcd_test "$JCD_BINARY" "~/sib" 2>/dev/null)
+if [[ "$result" == "/tmp/jcd_test/sibling" ]]; then
+    echo "✓ Tilde expansion searches below the home directory"
+else
+    echo "✗ Tilde expansion search failed. Got: '$result'"
+fi
+
+# Test listing every match from a single search
+cd /tmp/jcd_test/parent
+result=$("$JCD_BINARY" --list "child" 2>/dev/null | tr '\n' ' ')
+if [[ "$result" == "/tmp/jcd_test/parent/child1 /tmp/jcd_test/parent/child2 " ]]; then
+    echo "✓ Match listing works"
+else
+    echo "✗ Match listing failed. Got: '$result'"
+fi
+
+# Test JSON output
+result=$("$JCD_BINARY" --json "child2" 2>/dev/null)
+if [[ "$result" == '{"path":"/tmp/jcd_test/parent/child2","quality":"exact_down","depth":1,"rank":0}' ]]; then
+    echo "✓ JSON output works"
+else
+    echo "✗ JSON output failed. Got: '$result'"
+fi
+
+# Test completion candidates: all of them in one call, and no match is not an error
+result=$("$JCD_BINARY" --complete "child" 2>/dev/null | tr '\n' ' ')
+if [[ "$result" == "/tmp/jcd_test/parent/child1 /tmp/jcd_test/parent/child2 " ]]; then
+    echo "✓ Completion candidates work"
+else
+    echo "✗ Completion candidates failed. Got: '$result'"
+fi
+if result=$("$JCD_BINARY" --complete "nosuchchild" 2>/dev/null) && [[ -z "$result" ]]; then
+    echo "✓ Completion without candidates succeeds"
+else
+    echo "✗ Completion without candidates failed. Got: '$result'"
+fi
+
+# Test NUL-terminated output, which keeps a newline inside a name intact
+mkdir -p /tmp/jcd_test/parent/$'odd\nchild'
+result=$("$JCD_BINARY" -0 --list "odd" 2>/dev/null | tr '\0\n' '|~')
+if [[ "$result" == "/tmp/jcd_test/parent/odd~child|" ]]; then
+    echo "✓ NUL-terminated output works"
+else
+    echo "✗ NUL-terminated output failed. Got: '$result'"
+fi
+
 # Test shell function
 echo "Testing shell function..."
 source "$SCRIPT_DIR/../src/jcd_function.sh"
@@ -56,6 +110,24 @@ else
     echo "✗ Shell function not loaded"
 fi
 
+# Test that the shell reads every candidate from a single --complete call
+cd /tmp/jcd_test/parent
+result=$(_jcd_complete_candidates "$JCD_BINARY" "child" false | tr '\n' ' ')
+if [[ "$result" == "/tmp/jcd_test/parent/child1 /tmp/jcd_test/parent/child2 " ]]; then
+    echo "✓ Shell completion candidates work"
+else
+    echo "✗ Shell completion candidates failed. Got: '$result'"
+fi
+
+# Test that the shell skips candidates it cannot put on one line
+result=$(_jcd_complete_candidates "$JCD_BINARY" "odd" false)
+if [[ -z "$result" ]]; then
+    echo "✓ Shell completion skips names with newlines"
+else
+    echo "✗ Shell completion offered a name with a newline: '$result'"
+fi
+rm -rf /tmp/jcd_test/parent/$'odd\nchild'
+
 # Test shell function navigation
 cd /tmp/jcd_test/parent/child1
 jcd ".." 2>/dev/null